@@ -0,0 +1,141 @@
+use serde::Serialize;
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Agent {
+    pub id: String,
+    pub hostname: String,
+    pub ip: String,
+    pub os: Option<String>,
+    pub status: String,
+    pub last_seen: Option<String>,
+    pub location: Option<String>,
+    // Hors des exports : un import garde l'intervalle de l'agent existant
+    #[serde(skip)]
+    pub beacon_interval_secs: u64,
+    #[serde(skip)]
+    pub jitter_pct: u8,
+    // Captures d'écran mises en file par le planificateur, None si désactivées
+    #[serde(skip)]
+    pub screenshot_interval_secs: Option<u64>,
+    // Rendus par la dernière fiche :sysinfo, gardés eux aussi par un import
+    #[serde(skip)]
+    pub username: Option<String>,
+    #[serde(skip)]
+    pub is_elevated: bool,
+    #[serde(skip)]
+    pub uptime_secs: Option<u64>,
+    // Agent retiré par self-destruct et gardé en base ; hors de la liste sauf :show-archived
+    #[serde(skip)]
+    pub archived: bool,
+}
+
+pub const AGENT_COLUMNS: &str = "id, hostname, ip, os, status, last_seen, location, beacon_interval_secs, screenshot_interval_secs, username, is_elevated, uptime_secs, jitter_pct, archived";
+
+pub fn agent_from_row(row: &rusqlite::Row) -> rusqlite::Result<Agent> {
+    Ok(Agent {
+        id: row.get(0)?,
+        hostname: row.get(1)?,
+        ip: row.get(2)?,
+        os: row.get(3)?,
+        status: row.get(4)?,
+        last_seen: row.get(5)?,
+        location: row.get(6)?,
+        beacon_interval_secs: row.get(7)?,
+        screenshot_interval_secs: row.get(8)?,
+        username: row.get(9)?,
+        is_elevated: row.get(10)?,
+        uptime_secs: row.get(11)?,
+        jitter_pct: row.get(12)?,
+        archived: row.get(13)?,
+    })
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum AgentField {
+    Id,
+    Hostname,
+    Ip,
+    Os,
+    Status,
+    LastSeen,
+    Location,
+}
+
+impl AgentField {
+    pub const ALL: [AgentField; 7] = [
+        AgentField::Id,
+        AgentField::Hostname,
+        AgentField::Ip,
+        AgentField::Os,
+        AgentField::Status,
+        AgentField::LastSeen,
+        AgentField::Location,
+    ];
+
+    // Champs du formulaire d'édition. L'identifiant n'y est pas : tâches, tags, notes,
+    // groupes et clés y sont rattachés, et il entre dans le chiffrement des tâches.
+    pub const EDITABLE: [AgentField; 6] = [
+        AgentField::Hostname,
+        AgentField::Ip,
+        AgentField::Os,
+        AgentField::Status,
+        AgentField::LastSeen,
+        AgentField::Location,
+    ];
+
+    pub fn column(self) -> &'static str {
+        match self {
+            AgentField::Id => "id",
+            AgentField::Hostname => "hostname",
+            AgentField::Ip => "ip",
+            AgentField::Os => "os",
+            AgentField::Status => "status",
+            AgentField::LastSeen => "last_seen",
+            AgentField::Location => "location",
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            AgentField::Id => "ID",
+            AgentField::Hostname => "Hostname",
+            AgentField::Ip => "IP",
+            AgentField::Os => "OS",
+            AgentField::Status => "Status",
+            AgentField::LastSeen => "Last seen",
+            AgentField::Location => "Location",
+        }
+    }
+
+    // Colonnes NOT NULL de la table agents
+    pub fn is_required(self) -> bool {
+        matches!(self, AgentField::Id | AgentField::Hostname | AgentField::Ip | AgentField::Status)
+    }
+}
+
+impl Agent {
+    pub fn field(&self, field: AgentField) -> Option<&str> {
+        match field {
+            AgentField::Id => Some(&self.id),
+            AgentField::Hostname => Some(&self.hostname),
+            AgentField::Ip => Some(&self.ip),
+            AgentField::Os => self.os.as_deref(),
+            AgentField::Status => Some(&self.status),
+            AgentField::LastSeen => self.last_seen.as_deref(),
+            AgentField::Location => self.location.as_deref(),
+        }
+    }
+
+    pub fn set_field(&mut self, field: AgentField, value: &str) {
+        let optional = (!value.is_empty()).then(|| value.to_string());
+        match field {
+            AgentField::Id => self.id = value.to_string(),
+            AgentField::Hostname => self.hostname = value.to_string(),
+            AgentField::Ip => self.ip = value.to_string(),
+            AgentField::Os => self.os = optional,
+            AgentField::Status => self.status = value.to_string(),
+            AgentField::LastSeen => self.last_seen = optional,
+            AgentField::Location => self.location = optional,
+        }
+    }
+}
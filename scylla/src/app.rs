@@ -0,0 +1,3161 @@
+use ratatui::layout::{Margin, Position, Rect};
+use ratatui::widgets::{ListState, TableState};
+use crossterm::event::KeyCode;
+use chrono::{TimeDelta, Utc};
+use std::cmp::Ordering;
+use std::collections::{BTreeMap, HashMap, HashSet, VecDeque};
+use std::net::{IpAddr, SocketAddr};
+use std::sync::mpsc;
+use std::time::{Duration, Instant};
+use tracing::{debug, error, info, warn};
+use uuid::Uuid;
+
+use crate::agent::{Agent, AgentField};
+use crate::audit::log_action;
+use crate::beacon::{check_beacon, parse_interval, parse_jitter, set_beacon, DEFAULT_BEACON_SECS, DEFAULT_JITTER_PCT};
+use crate::cli::FileFormat;
+use crate::clipboard::{ClipboardCommand, ClipboardPopup, CopyField, COPY_FIELDS};
+use crate::config::ScyllaConfig;
+use crate::db::{Db, Task};
+use crate::editor::NoteEditor;
+use crate::error;
+use crate::eventlog::{log_event, Category, EventEntry, Level};
+use crate::events::AppEvent;
+use crate::form::{Form, TextInput};
+use crate::geoip::{geoip_candidates, open_reader, set_geoip_location, unlocated_agents, GeoLocator};
+use crate::groups::{
+    add_agent_to_group, agents_in_group, create_group, load_groups, load_memberships, remove_agent_from_group, Group,
+};
+use crate::history::{load_history, push_history};
+use crate::import::{import_message, OnConflict};
+use crate::keylog::KeylogPanel;
+use crate::loot::{LootPanel, DOWNLOAD_COMMAND};
+use crate::keymap::{Action, Binding, AGENT_KEYS, AUDIT_KEYS, FILE_KEYS, LISTENER_KEYS, LOG_KEYS, SCHEDULER_KEYS, TASK_KEYS};
+use crate::listeners::{
+    create_listener, delete_listener, load_listeners, set_autostart, start_http_listener, start_listener,
+    start_tcp_listener, stop_listener, Listener, ListenerHandle,
+};
+use crate::logging::{logs_dir, LogViewer};
+use crate::netinfo::{NetworkInterface, NETINFO_COMMAND};
+use crate::notes::{add_note, delete_note, load_notes, notes_for_agent, update_note, Note};
+use crate::pivots::{close_pivot, create_pivot, parse_target, PivotsPanel};
+use crate::processes::{ProcessPanel, PROCESS_LIST_COMMAND};
+use crate::refresh::{RefreshQuery, Refreshed, Strip, StripRows, View, ViewRows};
+use crate::topology::{queue_reachability_scan, TopologyPanel};
+use crate::scheduler::{add_scheduled_task, delete_scheduled_task, set_scheduled_task_enabled};
+use crate::screens::audit::AuditScreen;
+use crate::screens::files::FilesScreen;
+use crate::screens::logs::LogsScreen;
+use crate::screens::scheduler::SchedulerScreen;
+use crate::screens::tasks::TasksScreen;
+use crate::screens::Screen;
+use crate::screenshots::{set_screenshot_interval, GalleryPanel};
+use crate::selfdestruct::{request_self_destruct, settle_self_destructs, SelfDestructConfirm, SelfDestructOutcome};
+use crate::sessions::{sessions_for, OutputBuffer, Sessions};
+use crate::shell::TaskShell;
+use crate::status::{display_status, StalenessThresholds};
+use crate::sysinfo::SYSINFO_COMMAND;
+use crate::tags::{add_tag, agents_with_tag, load_tags, remove_tag, tags_for_agent};
+use crate::theme::{ColorScheme, SCHEME_NAMES};
+use crate::timefmt::parse_timestamp;
+use crate::transfers::{enqueue_upload, initiate_upload, resume_upload, spawn_upload_thread};
+use crate::viewer::OutputViewer;
+use crate::worker::DbRequest;
+
+// Édition successive des champs modifiables de l'agent sélectionné
+pub(crate) struct EditDialog {
+    pub(crate) agent_id: String,
+    pub(crate) field_index: usize,
+    pub(crate) input: TextInput,
+    pub(crate) error: Option<String>,
+}
+
+impl EditDialog {
+    pub fn new(agent: &Agent) -> EditDialog {
+        EditDialog {
+            agent_id: agent.id.clone(),
+            field_index: 0,
+            input: TextInput::new(agent.field(AgentField::EDITABLE[0]).unwrap_or("")),
+            error: None,
+        }
+    }
+
+    pub(crate) fn field(&self) -> AgentField {
+        AgentField::EDITABLE[self.field_index]
+    }
+}
+
+// Hostname et IP sont NOT NULL dans la table agents
+pub(crate) const ADD_AGENT_FIELDS: [(&str, bool); 5] = [
+    ("Hostname", true),
+    ("IP", true),
+    ("OS", false),
+    ("Location", false),
+    ("Note", false),
+];
+
+// Saisie manuelle : id aléatoire, statut "manual" jusqu'au premier contact
+pub(crate) fn agent_from_form(form: &Form) -> Agent {
+    Agent {
+        id: Uuid::new_v4().to_string(),
+        hostname: form.value(0).to_string(),
+        ip: form.value(1).to_string(),
+        os: form.optional(2),
+        status: "manual".to_string(),
+        last_seen: None,
+        location: form.optional(3),
+        beacon_interval_secs: DEFAULT_BEACON_SECS,
+        jitter_pct: DEFAULT_JITTER_PCT,
+        screenshot_interval_secs: None,
+        username: None,
+        is_elevated: false,
+        uptime_secs: None,
+        archived: false,
+    }
+}
+
+pub(crate) const LISTENER_FIELDS: [(&str, bool); 4] = [
+    ("Name", true),
+    ("Protocol", true),
+    ("Bind address", true),
+    ("Port", true),
+];
+
+// Terminal du bas en mode pseudo-shell sur une session TCP
+pub(crate) struct TerminalFocus {
+    pub(crate) session_id: String,
+    pub(crate) agent_id: String,
+    pub(crate) command: String,
+    // Lignes remontées depuis le bas de la sortie
+    pub(crate) scroll: usize,
+}
+
+// Intervalle en 30, 90s, 5m ou 2h ; gigue en pourcentage de l'intervalle, vide pour aucune
+pub(crate) const BEACON_FIELDS: [(&str, bool); 2] = [
+    ("Interval", true),
+    ("Jitter (%)", false),
+];
+
+pub(crate) const UPLOAD_FIELDS: [(&str, bool); 2] = [
+    ("Agent", true),
+    ("Local path", true),
+];
+
+// File de tâches d'un agent ouverte avec v, relue à chaque tick
+pub(crate) struct TaskView {
+    pub(crate) agent_id: String,
+    pub(crate) tasks: Vec<Task>,
+    pub(crate) state: ListState,
+    // Sortie de la tâche ouverte avec Entrée, en plein écran
+    pub(crate) viewer: Option<OutputViewer>,
+    // Octets acquittés et taille des envois, par tâche
+    pub(crate) uploads: HashMap<i64, (u64, u64)>,
+}
+
+// Envoi depuis la liste des tâches : chemin local (Tab complète), puis destination distante
+pub(crate) struct UploadPrompt {
+    pub(crate) agent_id: String,
+    // Renseigné une fois le fichier local vérifié
+    pub(crate) local_path: Option<String>,
+    pub(crate) input: TextInput,
+}
+
+// Événements montrés par la fiche d'un agent
+pub(crate) const DETAIL_EVENTS: usize = 20;
+
+// Fiche plein écran ouverte avec Entrée, relue à chaque tick ; la sélection de la
+// liste n'en bouge pas
+pub(crate) struct DetailView {
+    pub(crate) agent_id: String,
+    pub(crate) events: Vec<EventEntry>,
+    pub(crate) pending_tasks: usize,
+    pub(crate) completed_tasks: usize,
+    pub(crate) scroll: u16,
+    // Hauteur visible et nombre de lignes une fois repliées, relevés au rendu
+    pub(crate) height: u16,
+    pub(crate) content_height: u16,
+}
+
+impl DetailView {
+    pub(crate) fn new(agent_id: &str) -> DetailView {
+        DetailView {
+            agent_id: agent_id.to_string(),
+            events: Vec::new(),
+            pending_tasks: 0,
+            completed_tasks: 0,
+            scroll: 0,
+            height: 0,
+            content_height: 0,
+        }
+    }
+
+    pub(crate) fn max_scroll(&self) -> u16 {
+        self.content_height.saturating_sub(self.height)
+    }
+}
+
+// Écran affiché au centre, dans l'ordre de la barre d'onglets (touches 1 à 7)
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum AppScreen {
+    Agents,
+    Listeners,
+    Tasks,
+    Audit,
+    Files,
+    Logs,
+    Scheduler,
+}
+
+impl AppScreen {
+    pub(crate) const ALL: [AppScreen; 7] = [
+        AppScreen::Agents,
+        AppScreen::Listeners,
+        AppScreen::Tasks,
+        AppScreen::Audit,
+        AppScreen::Files,
+        AppScreen::Logs,
+        AppScreen::Scheduler,
+    ];
+
+    pub(crate) fn position(self) -> usize {
+        AppScreen::ALL.iter().position(|&s| s == self).unwrap_or(0)
+    }
+
+    pub(crate) fn next(self) -> AppScreen {
+        AppScreen::ALL[(self.position() + 1) % AppScreen::ALL.len()]
+    }
+
+    pub(crate) fn previous(self) -> AppScreen {
+        AppScreen::ALL[(self.position() + AppScreen::ALL.len() - 1) % AppScreen::ALL.len()]
+    }
+
+    pub(crate) fn label(self) -> &'static str {
+        match self {
+            AppScreen::Agents => "Agents",
+            AppScreen::Listeners => "Listeners",
+            AppScreen::Tasks => "Tasks",
+            AppScreen::Audit => "Audit",
+            AppScreen::Files => "Files",
+            AppScreen::Logs => "Logs",
+            AppScreen::Scheduler => "Scheduler",
+        }
+    }
+
+    pub(crate) fn bindings(self) -> &'static [Binding] {
+        match self {
+            AppScreen::Agents => AGENT_KEYS,
+            AppScreen::Listeners => LISTENER_KEYS,
+            AppScreen::Tasks => TASK_KEYS,
+            AppScreen::Audit => AUDIT_KEYS,
+            AppScreen::Files => FILE_KEYS,
+            AppScreen::Logs => LOG_KEYS,
+            AppScreen::Scheduler => SCHEDULER_KEYS,
+        }
+    }
+}
+
+// Écrans autonomes ; chacun garde sa sélection quand on passe à un autre
+#[derive(Default)]
+pub(crate) struct Screens {
+    pub(crate) tasks: TasksScreen,
+    pub(crate) audit: AuditScreen,
+    pub(crate) files: FilesScreen,
+    pub(crate) logs: LogsScreen,
+    pub(crate) scheduler: SchedulerScreen,
+}
+
+impl Screens {
+    pub(crate) fn get(&self, screen: AppScreen) -> Option<&dyn Screen> {
+        match screen {
+            AppScreen::Tasks => Some(&self.tasks),
+            AppScreen::Audit => Some(&self.audit),
+            AppScreen::Files => Some(&self.files),
+            AppScreen::Logs => Some(&self.logs),
+            AppScreen::Scheduler => Some(&self.scheduler),
+            AppScreen::Agents | AppScreen::Listeners => None,
+        }
+    }
+
+    pub(crate) fn get_mut(&mut self, screen: AppScreen) -> Option<&mut dyn Screen> {
+        match screen {
+            AppScreen::Tasks => Some(&mut self.tasks),
+            AppScreen::Audit => Some(&mut self.audit),
+            AppScreen::Files => Some(&mut self.files),
+            AppScreen::Logs => Some(&mut self.logs),
+            AppScreen::Scheduler => Some(&mut self.scheduler),
+            AppScreen::Agents | AppScreen::Listeners => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum SortKey {
+    Id,
+    Hostname,
+    Ip,
+    Status,
+    LastSeen,
+}
+
+impl SortKey {
+    pub(crate) const ALL: [SortKey; 5] = [SortKey::Id, SortKey::Hostname, SortKey::Ip, SortKey::Status, SortKey::LastSeen];
+
+    // Pour :sort, sans tenir compte de la casse ni des soulignés (last_seen)
+    pub(crate) fn from_name(name: &str) -> Option<SortKey> {
+        let name = name.replace('_', "");
+        SortKey::ALL.into_iter().find(|key| key.label().eq_ignore_ascii_case(&name))
+    }
+
+    pub(crate) fn label(self) -> &'static str {
+        match self {
+            SortKey::Id => "Id",
+            SortKey::Hostname => "Hostname",
+            SortKey::Ip => "Ip",
+            SortKey::Status => "Status",
+            SortKey::LastSeen => "LastSeen",
+        }
+    }
+
+    // Clé suivante quand on appuie sur 's'
+    pub(crate) fn next(current: Option<SortKey>) -> SortKey {
+        match current {
+            None | Some(SortKey::LastSeen) => SortKey::Id,
+            Some(SortKey::Id) => SortKey::Hostname,
+            Some(SortKey::Hostname) => SortKey::Ip,
+            Some(SortKey::Ip) => SortKey::Status,
+            Some(SortKey::Status) => SortKey::LastSeen,
+        }
+    }
+}
+
+// Les IP sont comparées numériquement, les valeurs non parsables passent après, par ordre alphabétique
+pub(crate) fn compare_ips(a: &str, b: &str) -> Ordering {
+    match (a.parse::<IpAddr>(), b.parse::<IpAddr>()) {
+        (Ok(a), Ok(b)) => a.cmp(&b),
+        (Ok(_), Err(_)) => Ordering::Less,
+        (Err(_), Ok(_)) => Ordering::Greater,
+        (Err(_), Err(_)) => a.cmp(b),
+    }
+}
+
+// Tri stable des indices : à clé égale l'ordre précédent est conservé.
+// Les last_seen absents ou illisibles restent en fin de liste dans les deux sens
+pub(crate) fn sort_indices(indices: &mut [usize], agents: &[Agent], key: SortKey, descending: bool) {
+    let direction = |o: Ordering| if descending { o.reverse() } else { o };
+    indices.sort_by(|&a, &b| {
+        let (a, b) = (&agents[a], &agents[b]);
+        match key {
+            SortKey::Id => direction(a.id.cmp(&b.id)),
+            SortKey::Hostname => direction(a.hostname.to_lowercase().cmp(&b.hostname.to_lowercase())),
+            SortKey::Ip => direction(compare_ips(&a.ip, &b.ip)),
+            SortKey::Status => direction(a.status.to_lowercase().cmp(&b.status.to_lowercase())),
+            SortKey::LastSeen => {
+                let a = a.last_seen.as_deref().and_then(parse_timestamp);
+                let b = b.last_seen.as_deref().and_then(parse_timestamp);
+                match (a, b) {
+                    (Some(a), Some(b)) => direction(a.cmp(&b)),
+                    (Some(_), None) => Ordering::Less,
+                    (None, Some(_)) => Ordering::Greater,
+                    (None, None) => Ordering::Equal,
+                }
+            }
+        }
+    });
+}
+
+// Champ de regroupement de la liste, cyclé avec 'g'
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub(crate) enum GroupBy {
+    Os,
+    Location,
+    Status,
+}
+
+impl GroupBy {
+    pub(crate) fn next(current: Option<GroupBy>) -> Option<GroupBy> {
+        match current {
+            None => Some(GroupBy::Os),
+            Some(GroupBy::Os) => Some(GroupBy::Location),
+            Some(GroupBy::Location) => Some(GroupBy::Status),
+            Some(GroupBy::Status) => None,
+        }
+    }
+
+    pub(crate) fn label(self) -> &'static str {
+        match self {
+            GroupBy::Os => "os",
+            GroupBy::Location => "location",
+            GroupBy::Status => "status",
+        }
+    }
+
+    pub(crate) fn key(self, agent: &Agent) -> String {
+        let value = match self {
+            GroupBy::Os => agent.os.as_deref(),
+            GroupBy::Location => agent.location.as_deref(),
+            GroupBy::Status => Some(agent.status.as_str()),
+        };
+        match value {
+            Some(v) if !v.trim().is_empty() => v.to_string(),
+            _ => "Unknown".to_string(),
+        }
+    }
+}
+
+// Ligne affichée dans la liste : en-tête de groupe ou agent (indice dans agents)
+pub(crate) enum ListRow {
+    Header { name: String, count: usize },
+    Agent(usize),
+}
+
+// Identifie une ligne indépendamment de sa position, pour garder la sélection au recalcul
+pub(crate) enum RowKey {
+    Header(String),
+    Agent(String),
+}
+
+// Recherche insensible à la casse sur les champs d'identification de l'agent
+// Les mots `tag:xxx` exigent l'étiquette (casse ignorée) ; le reste de la requête
+// est cherché tel quel dans les champs texte
+pub(crate) fn matches_filter(agent: &Agent, tags: &[String], groups: &[String], notes: &[Note], query: &str) -> bool {
+    let mut text = Vec::new();
+    for word in query.split_whitespace() {
+        let (wanted, among) = match word.split_once(':') {
+            Some((prefix, wanted)) if prefix.eq_ignore_ascii_case("tag") => (wanted, tags),
+            Some((prefix, wanted)) if prefix.eq_ignore_ascii_case("group") => (wanted, groups),
+            _ => {
+                text.push(word);
+                continue;
+            }
+        };
+        if !wanted.is_empty() && !among.iter().any(|t| t.eq_ignore_ascii_case(wanted)) {
+            return false;
+        }
+    }
+    if text.is_empty() {
+        return true;
+    }
+    let query = text.join(" ").to_lowercase();
+    [agent.id.as_str(), agent.hostname.as_str(), agent.ip.as_str()]
+        .into_iter()
+        .chain(notes.iter().map(|n| n.content.as_str()))
+        .any(|v| v.to_lowercase().contains(&query))
+}
+
+// Ce que l'application laisse à la boucle principale, hors de l'état qu'elle tient ;
+// un test les vérifie sans terminal
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Effect {
+    Quit,
+    // Capture à ouvrir dans la visionneuse du système
+    OpenScreenshot(String),
+    // Texte pour le presse-papiers, écrit par la boucle qui tient le terminal (OSC 52)
+    Copy { label: String, text: String },
+}
+
+// État de l'application : connexion à la base ouverte pour toute la session et état de l'interface
+pub struct App {
+    pub(crate) config: ScyllaConfig,
+    // Palette active, initialisée par la configuration et changée avec :colorscheme ou F2
+    pub(crate) scheme: ColorScheme,
+    // Opérateur connecté, inscrit dans le journal d'audit
+    pub(crate) operator: String,
+    pub(crate) db: Db,
+    // Agents dans l'ordre de chargement, jamais réordonnés ni filtrés sur place
+    pub(crate) agents: Vec<Agent>,
+    // Ordre d'affichage des agents selon le tri actif
+    pub(crate) sorted_indices: Vec<usize>,
+    // Sous-ensemble de sorted_indices qui passe le filtre
+    pub(crate) filtered_indices: Vec<usize>,
+    // Lignes affichées (agents filtrés et en-têtes de groupe), selected_index est une position ici
+    pub(crate) rows: Vec<ListRow>,
+    pub(crate) group_by: Option<GroupBy>,
+    // Groupes repliés, conservés pour la session même après rechargement
+    pub(crate) collapsed_groups: HashSet<(GroupBy, String)>,
+    // Contenu du panneau de droite sur l'écran Agents, changé avec m
+    pub(crate) map_mode: MapMode,
+    // Filtre validé, et saisie en cours quand la barre de recherche est ouverte
+    pub(crate) filter: String,
+    pub(crate) search: Option<TextInput>,
+    pub(crate) sort_key: Option<SortKey>,
+    pub(crate) sort_descending: bool,
+    pub(crate) thresholds: StalenessThresholds,
+    pub(crate) selected_index: usize,
+    pub(crate) list_state: TableState,
+    // Nombre de lignes visibles dans la liste, mis à jour à chaque rendu
+    pub(crate) list_height: usize,
+    pub(crate) add_dialog: Option<Form>,
+    pub(crate) active_screen: AppScreen,
+    pub(crate) listeners: Vec<Listener>,
+    pub(crate) listener_index: usize,
+    pub(crate) listener_state: ListState,
+    pub(crate) listener_dialog: Option<Form>,
+    pub(crate) upload_dialog: Option<Form>,
+    // Dernières entrées du journal d'audit, relues à chaque tick quand le panneau est affiché
+    pub(crate) screens: Screens,
+    // Tâches d'écoute en cours, par identifiant de listener
+    pub(crate) listener_tasks: HashMap<i64, ListenerHandle>,
+    // Check-ins et enregistrements reçus depuis le lancement, affichés dans le menu
+    pub(crate) checkins: u64,
+    pub(crate) events: mpsc::Sender<AppEvent>,
+    // File du worker de base : rechargement périodique, export et import
+    pub(crate) db_requests: mpsc::Sender<DbRequest>,
+    // Incrémentée à chaque relecture de la liste ; une réponse d'une génération
+    // antérieure est écartée
+    pub(crate) agents_generation: u64,
+    pub(crate) agents_loading: bool,
+    // Même principe pour la relecture du tick, qui suit aussi ce qui est affiché
+    pub(crate) refresh_generation: u64,
+    pub(crate) refresh_loading: bool,
+    pub(crate) sessions: Sessions,
+    // Agent dont les sessions sont affichées en détail
+    pub(crate) session_view: Option<String>,
+    // Sortie reçue par session, conservée même quand le terminal n'est pas affiché
+    pub(crate) session_output: HashMap<String, OutputBuffer>,
+    pub(crate) terminal: Option<TerminalFocus>,
+    // Shells sur les tâches par agent, gardés après Échap ; shell est celui affiché
+    pub(crate) shells: HashMap<String, TaskShell>,
+    pub(crate) shell: Option<String>,
+    // Agents apparus depuis le lancement et pas encore surlignés
+    pub(crate) unseen: HashSet<String>,
+    pub(crate) edit_dialog: Option<EditDialog>,
+    // Agents cochés avec Espace, suivis par id pour survivre aux tris et rechargements
+    pub(crate) multi_selection: HashSet<String>,
+    // Ids des agents en attente de confirmation de suppression
+    pub(crate) confirm_delete: Option<Vec<String>>,
+    pub(crate) confirm_self_destruct: Option<SelfDestructConfirm>,
+    // Travaux en cours rappelés par la confirmation de sortie
+    pub(crate) confirm_quit: Option<Vec<String>>,
+    // Exports et imports envoyés au worker, pas encore revenus
+    pub(crate) background_jobs: usize,
+    // Saisie d'une commande pour les agents donnés, dans le panneau terminal
+    pub(crate) command_prompt: Option<CommandPrompt>,
+    // Étiquettes par identifiant d'agent
+    pub(crate) tags: HashMap<String, Vec<String>>,
+    pub(crate) tag_prompt: Option<(Vec<String>, TextInput)>,
+    // Nouvel intervalle de check-in et gigue pour les agents donnés
+    pub(crate) beacon_dialog: Option<(Vec<String>, Form)>,
+    // Notes par identifiant d'agent, la plus récente en premier
+    pub(crate) notes: HashMap<String, Vec<Note>>,
+    // Éditeur plein panneau ouvert avec n ou N
+    pub(crate) note_editor: Option<NoteEditor>,
+    // Noms des groupes de chaque agent, pour le filtre group:
+    pub(crate) memberships: HashMap<String, Vec<String>>,
+    // Panneau ouvert avec p, qui agit sur les agents ciblés à son ouverture
+    pub(crate) groups_panel: Option<GroupsPanel>,
+    // Base geoip_db ouverte au démarrage puis par :geoip reload, consultée à chaque nouvel agent
+    pub(crate) geoip: Option<GeoLocator>,
+    // Palette ouverte avec « : », par exemple :colorscheme nord
+    pub(crate) palette: Option<TextInput>,
+    // Défilement de l'aide, None quand elle est fermée
+    pub(crate) help_scroll: Option<u16>,
+    // Lignes rejetées par le dernier :import, fermé par n'importe quelle touche
+    pub(crate) import_errors: Option<Vec<String>>,
+    // Première touche d'une séquence en cours, comme le premier g de gg
+    pub(crate) pending_key: Option<KeyCode>,
+    pub(crate) context_menu: Option<ContextMenu>,
+    pub(crate) copy_menu: Option<CopyMenu>,
+    // Zones du dernier rendu, pour retrouver la cible d'un clic
+    pub(crate) list_area: Rect,
+    pub(crate) datasheet_area: Rect,
+    pub(crate) datasheet_scroll: u16,
+    pub(crate) task_view: Option<TaskView>,
+    pub(crate) detail_view: Option<DetailView>,
+    // Frappes de l'agent ouvertes avec K, complétées à chaque tick
+    pub(crate) keylog_panel: Option<KeylogPanel>,
+    pub(crate) loot_panel: Option<LootPanel>,
+    pub(crate) gallery_panel: Option<GalleryPanel>,
+    pub(crate) upload_prompt: Option<UploadPrompt>,
+    pub(crate) clipboard_popup: Option<ClipboardPopup>,
+    pub(crate) log_viewer: Option<LogViewer>,
+    pub(crate) pivots_panel: Option<PivotsPanel>,
+    // Processus de l'agent, à la place du terminal connecté
+    pub(crate) process_panel: Option<ProcessPanel>,
+    pub(crate) topology_panel: Option<TopologyPanel>,
+    // Dernières tâches de l'agent sélectionné et nombre en attente, rechargés à chaque tick
+    pub(crate) recent_tasks: Vec<Task>,
+    pub(crate) pending_tasks: usize,
+    // Tâches en attente sur tous les agents, pour le résumé du menu
+    pub(crate) queued_tasks: usize,
+    pub(crate) last_screenshot: Option<String>,
+    // Sous-panneau des interfaces réseau du datasheet, basculé avec W, et dernière réponse
+    // netinfo de l'agent sélectionné
+    pub(crate) show_interfaces: bool,
+    pub(crate) interfaces: Option<(i64, error::Result<Vec<NetworkInterface>>)>,
+    // Agents archivés après self-destruct, masqués de la liste sauf après :show-archived
+    pub(crate) show_archived: bool,
+    // Check-ins de l'agent sélectionné par tranche, les plus anciens en premier
+    pub(crate) checkin_history: Vec<u64>,
+    // Dernier résultat reçu du thread de poll
+    pub(crate) notice: Option<String>,
+    // Retours brefs des commandes, affichés un par un dans la barre d'état à la place des raccourcis
+    pub(crate) status_messages: VecDeque<StatusMessage>,
+    pub(crate) error: Option<String>,
+    // Effets en attente, rendus par handle_action, handle_key et handle_mouse
+    pub(crate) effects: Vec<Effect>,
+}
+
+impl App {
+    // Doit être appelée dans un runtime tokio : les listeners restés actifs sont relancés
+    pub fn new(
+        db: Db,
+        operator: String,
+        config: ScyllaConfig,
+        thresholds: StalenessThresholds,
+        events: mpsc::Sender<AppEvent>,
+        db_requests: mpsc::Sender<DbRequest>,
+    ) -> error::Result<App> {
+        let agents = db.load_agents()?;
+        let listeners = load_listeners(db.conn())?;
+        let tags = load_tags(db.conn())?;
+        let notes = load_notes(db.conn())?;
+        let memberships = load_memberships(db.conn())?;
+        let mut app = App {
+            scheme: config.ui.scheme(),
+            config,
+            operator,
+            db,
+            agents,
+            sorted_indices: Vec::new(),
+            filtered_indices: Vec::new(),
+            rows: Vec::new(),
+            group_by: None,
+            map_mode: MapMode::Datasheet,
+            collapsed_groups: HashSet::new(),
+            filter: String::new(),
+            search: None,
+            sort_key: None,
+            sort_descending: false,
+            thresholds,
+            selected_index: 0,
+            list_state: TableState::default(),
+            list_height: 0,
+            add_dialog: None,
+            active_screen: AppScreen::Agents,
+            listeners,
+            listener_index: 0,
+            listener_state: ListState::default(),
+            listener_dialog: None,
+            upload_dialog: None,
+            screens: Screens::default(),
+            listener_tasks: HashMap::new(),
+            checkins: 0,
+            events,
+            db_requests,
+            agents_generation: 0,
+            agents_loading: false,
+            refresh_generation: 0,
+            refresh_loading: false,
+            sessions: Sessions::default(),
+            session_view: None,
+            session_output: HashMap::new(),
+            terminal: None,
+            shells: HashMap::new(),
+            shell: None,
+            unseen: HashSet::new(),
+            edit_dialog: None,
+            multi_selection: HashSet::new(),
+            confirm_delete: None,
+            confirm_self_destruct: None,
+            confirm_quit: None,
+            background_jobs: 0,
+            command_prompt: None,
+            tags,
+            notes,
+            tag_prompt: None,
+            beacon_dialog: None,
+            note_editor: None,
+            memberships,
+            groups_panel: None,
+            geoip: None,
+            palette: None,
+            help_scroll: None,
+            import_errors: None,
+            pending_key: None,
+            context_menu: None,
+            copy_menu: None,
+            list_area: Rect::default(),
+            datasheet_area: Rect::default(),
+            datasheet_scroll: 0,
+            task_view: None,
+            detail_view: None,
+            keylog_panel: None,
+            loot_panel: None,
+            gallery_panel: None,
+            upload_prompt: None,
+            clipboard_popup: None,
+            pivots_panel: None,
+            process_panel: None,
+            topology_panel: None,
+            log_viewer: None,
+            recent_tasks: Vec::new(),
+            pending_tasks: 0,
+            queued_tasks: 0,
+            last_screenshot: None,
+            show_interfaces: false,
+            interfaces: None,
+            show_archived: false,
+            checkin_history: Vec::new(),
+            notice: None,
+            status_messages: VecDeque::new(),
+            error: None,
+            effects: Vec::new(),
+        };
+        app.prune_labels();
+        app.rebuild_order();
+        app.sync_listener_selection();
+        app.resume_listeners();
+        if app.open_geoip() {
+            app.locate_unlocated();
+        }
+        Ok(app)
+    }
+
+    // Une base GeoIP illisible est signalée sans empêcher le démarrage
+    pub(crate) fn open_geoip(&mut self) -> bool {
+        let Some(path) = &self.config.geoip_db else {
+            return false;
+        };
+        match open_reader(path) {
+            Ok(reader) => self.geoip = Some(GeoLocator::new(reader)),
+            Err(e) => {
+                self.geoip = None;
+                self.show_error(e.to_string());
+            }
+        }
+        self.geoip.is_some()
+    }
+
+    // Renseigne la location d'un agent qui vient de s'enregistrer sans en avoir
+    pub(crate) fn locate_new_agent(&mut self, agent: &mut Agent) {
+        if agent.location.is_some() {
+            return;
+        }
+        let Some(location) = self.geoip.as_mut().and_then(|geoip| geoip.locate(&agent.ip)) else {
+            return;
+        };
+        match set_geoip_location(self.db.conn(), &agent.id, &location) {
+            Ok(()) => agent.location = Some(location),
+            Err(e) => self.show_error(format!("Écriture de la location impossible : {}", e)),
+        }
+    }
+
+    // Au démarrage, les agents restés sans location
+    pub(crate) fn locate_unlocated(&mut self) {
+        match unlocated_agents(self.db.conn()) {
+            Ok(candidates) => {
+                if self.locate_agents(&candidates) > 0 {
+                    self.reload_agents();
+                }
+            }
+            Err(e) => self.show_error(format!("Lecture des agents impossible : {}", e)),
+        }
+    }
+
+    // Écrit la location trouvée pour chaque (id, ip) ; renvoie le nombre d'agents localisés
+    pub(crate) fn locate_agents(&mut self, candidates: &[(String, String)]) -> usize {
+        let Some(geoip) = self.geoip.as_mut() else {
+            return 0;
+        };
+        let found: Vec<(&str, String)> = candidates.iter()
+            .filter_map(|(id, ip)| Some((id.as_str(), geoip.locate(ip)?)))
+            .collect();
+        for (id, location) in &found {
+            if let Err(e) = set_geoip_location(self.db.conn(), id, location) {
+                self.show_error(format!("Écriture de la location impossible : {}", e));
+                return 0;
+            }
+        }
+        found.len()
+    }
+
+    // Ctrl-g : recherche explicite pour l'agent sélectionné, qui remplace sa location
+    pub(crate) fn locate_selected(&mut self) {
+        let Some((id, hostname, ip)) = self.selected_agent().map(|a| (a.id.clone(), a.hostname.clone(), a.ip.clone())) else {
+            return;
+        };
+        if self.geoip.is_none() && !self.open_geoip() {
+            if self.config.geoip_db.is_none() {
+                self.flash("Aucune base GeoIP : renseigner geoip_db dans scylla.toml", true);
+            }
+            return;
+        }
+        if self.locate_agents(&[(id.clone(), ip.clone())]) == 0 {
+            self.flash(&format!("{} : {} absente de la base GeoIP", hostname, ip), true);
+            return;
+        }
+        self.audit("geoip_locate", &id);
+        self.reload_agents();
+        let location = self.agents.iter().find(|a| a.id == id).and_then(|a| a.location.clone());
+        self.flash(&format!("{} : {}", hostname, location.unwrap_or_default()), false);
+    }
+
+    pub fn selected_agent(&self) -> Option<&Agent> {
+        match self.rows.get(self.selected_index)? {
+            ListRow::Agent(i) => self.agents.get(*i),
+            ListRow::Header { .. } => None,
+        }
+    }
+
+    pub(crate) fn selected_header(&self) -> Option<&str> {
+        match self.rows.get(self.selected_index)? {
+            ListRow::Header { name, .. } => Some(name),
+            ListRow::Agent(_) => None,
+        }
+    }
+
+    pub(crate) fn selected_row_key(&self) -> Option<RowKey> {
+        match self.rows.get(self.selected_index)? {
+            ListRow::Header { name, .. } => Some(RowKey::Header(name.clone())),
+            ListRow::Agent(i) => self.agents.get(*i).map(|a| RowKey::Agent(a.id.clone())),
+        }
+    }
+
+    pub fn tags_of(&self, agent_id: &str) -> &[String] {
+        self.tags.get(agent_id).map(Vec::as_slice).unwrap_or(&[])
+    }
+
+    pub fn groups_of(&self, agent_id: &str) -> &[String] {
+        self.memberships.get(agent_id).map(Vec::as_slice).unwrap_or(&[])
+    }
+
+    pub fn notes_of(&self, agent_id: &str) -> &[Note] {
+        self.notes.get(agent_id).map(Vec::as_slice).unwrap_or(&[])
+    }
+
+    pub(crate) fn active_query(&self) -> &str {
+        self.search.as_ref().map(|s| s.content.as_str()).unwrap_or(&self.filter)
+    }
+
+    // Recalcule la vue filtrée en gardant la ligne sélectionnée si elle y figure encore
+    pub(crate) fn refresh_filter(&mut self) {
+        let selected = self.selected_row_key();
+        let query = self.active_query().to_string();
+        self.filtered_indices = self.sorted_indices.iter()
+            .copied()
+            .filter(|&i| {
+                let agent = &self.agents[i];
+                (self.show_archived || !agent.archived) &&
+                matches_filter(agent, self.tags_of(&agent.id), self.groups_of(&agent.id), self.notes_of(&agent.id), &query)
+            })
+            .collect();
+        self.rebuild_rows();
+        self.selected_index = 0;
+        match selected {
+            Some(RowKey::Agent(id)) => self.select_agent_id(&id),
+            Some(RowKey::Header(name)) => self.select_header(&name),
+            None => self.sync_selection(),
+        }
+    }
+
+    // Groupes triés par nom ; dans un groupe, l'ordre de tri courant est conservé
+    pub(crate) fn rebuild_rows(&mut self) {
+        let Some(group_by) = self.group_by else {
+            self.rows = self.filtered_indices.iter().map(|&i| ListRow::Agent(i)).collect();
+            return;
+        };
+        let mut groups: BTreeMap<String, Vec<usize>> = BTreeMap::new();
+        for &i in &self.filtered_indices {
+            groups.entry(group_by.key(&self.agents[i])).or_default().push(i);
+        }
+        self.rows.clear();
+        for (name, members) in groups {
+            let collapsed = self.collapsed_groups.contains(&(group_by, name.clone()));
+            self.rows.push(ListRow::Header { name, count: members.len() });
+            if !collapsed {
+                self.rows.extend(members.into_iter().map(ListRow::Agent));
+            }
+        }
+    }
+
+    pub(crate) fn is_collapsed(&self, name: &str) -> bool {
+        self.group_by
+            .is_some_and(|g| self.collapsed_groups.contains(&(g, name.to_string())))
+    }
+
+    pub(crate) fn set_collapsed(&mut self, name: &str, collapsed: bool) {
+        let Some(group_by) = self.group_by else {
+            return;
+        };
+        let key = (group_by, name.to_string());
+        if collapsed {
+            self.collapsed_groups.insert(key);
+        } else {
+            self.collapsed_groups.remove(&key);
+        }
+        self.rebuild_rows();
+        self.select_header(name);
+    }
+
+    pub(crate) fn cycle_grouping(&mut self) {
+        self.group_by = GroupBy::next(self.group_by);
+        self.refresh_filter();
+    }
+
+    // Gauche replie le groupe de la ligne courante (en-tête ou agent), droite déplie l'en-tête
+    pub(crate) fn collapse_current(&mut self) {
+        let Some(group_by) = self.group_by else {
+            return;
+        };
+        let name = match self.selected_header() {
+            Some(name) => name.to_string(),
+            None => match self.selected_agent() {
+                Some(agent) => group_by.key(agent),
+                None => return,
+            },
+        };
+        self.set_collapsed(&name, true);
+    }
+
+    pub(crate) fn expand_current(&mut self) {
+        if let Some(name) = self.selected_header().map(str::to_string) {
+            self.set_collapsed(&name, false);
+        }
+    }
+
+    pub(crate) fn toggle_current_group(&mut self) {
+        if let Some(name) = self.selected_header().map(str::to_string) {
+            let collapsed = self.is_collapsed(&name);
+            self.set_collapsed(&name, !collapsed);
+        }
+    }
+
+    // Retrie l'ordre d'affichage courant, la sélection suit l'agent par son id
+    pub(crate) fn apply_sort(&mut self) {
+        if let Some(key) = self.sort_key {
+            sort_indices(&mut self.sorted_indices, &self.agents, key, self.sort_descending);
+        }
+        self.refresh_filter();
+    }
+
+    // À appeler quand agents est remplacé : les anciens indices ne sont plus valides
+    pub(crate) fn rebuild_order(&mut self) {
+        self.sorted_indices = (0..self.agents.len()).collect();
+        self.filtered_indices.clear();
+        self.apply_sort();
+    }
+
+    // Revient à l'ordre de chargement
+    pub(crate) fn reset_sort(&mut self) {
+        self.sort_key = None;
+        self.sort_descending = false;
+        self.sorted_indices = (0..self.agents.len()).collect();
+        self.refresh_filter();
+    }
+
+    // Si l'agent est dans un groupe replié, l'en-tête du groupe est sélectionné à sa place
+    pub(crate) fn select_agent_id(&mut self, id: &str) {
+        let found = self.rows.iter().position(|row| matches!(row, ListRow::Agent(i) if self.agents[*i].id == id));
+        match found {
+            Some(pos) => self.selected_index = pos,
+            None => {
+                let header = self.group_by.and_then(|g| {
+                    let agent = self.filtered_indices.iter().map(|&i| &self.agents[i]).find(|a| a.id == id)?;
+                    Some(g.key(agent))
+                });
+                if let Some(name) = header {
+                    self.select_header(&name);
+                    return;
+                }
+            }
+        }
+        self.sync_selection();
+    }
+
+    pub(crate) fn select_header(&mut self, name: &str) {
+        if let Some(pos) = self.rows.iter().position(|row| matches!(row, ListRow::Header { name: n, .. } if n == name)) {
+            self.selected_index = pos;
+        }
+        self.sync_selection();
+    }
+
+    // Garde selected_index dans les bornes et aligne le TableState dessus
+    pub(crate) fn sync_selection(&mut self) {
+        if self.rows.is_empty() {
+            self.selected_index = 0;
+            self.list_state.select(None);
+        } else {
+            self.selected_index = self.selected_index.min(self.rows.len() - 1);
+            self.list_state.select(Some(self.selected_index));
+        }
+        // Le décalage suit la sélection pour qu'elle reste visible
+        if self.list_height > 0 {
+            let offset = self.list_state.offset();
+            if self.selected_index < offset {
+                *self.list_state.offset_mut() = self.selected_index;
+            } else if self.selected_index >= offset + self.list_height {
+                *self.list_state.offset_mut() = self.selected_index + 1 - self.list_height;
+            }
+        }
+        // Surligné une fois : l'agent n'est plus signalé comme nouveau
+        if let Some(id) = self.selected_agent().map(|a| a.id.clone()) {
+            self.unseen.remove(&id);
+        }
+    }
+
+    pub(crate) fn select_next(&mut self) {
+        self.selected_index = self.selected_index.saturating_add(1);
+        self.sync_selection();
+    }
+
+    pub(crate) fn select_previous(&mut self) {
+        self.selected_index = self.selected_index.saturating_sub(1);
+        self.sync_selection();
+    }
+
+    pub(crate) fn page_size(&self) -> usize {
+        self.list_height.max(1)
+    }
+
+    pub(crate) fn page_down(&mut self) {
+        self.selected_index = self.selected_index.saturating_add(self.page_size());
+        self.sync_selection();
+    }
+
+    pub(crate) fn page_up(&mut self) {
+        self.selected_index = self.selected_index.saturating_sub(self.page_size());
+        self.sync_selection();
+    }
+
+    pub(crate) fn half_page_down(&mut self) {
+        self.selected_index = self.selected_index.saturating_add(self.page_size().div_ceil(2));
+        self.sync_selection();
+    }
+
+    pub(crate) fn half_page_up(&mut self) {
+        self.selected_index = self.selected_index.saturating_sub(self.page_size().div_ceil(2));
+        self.sync_selection();
+    }
+
+    pub(crate) fn select_first(&mut self) {
+        self.selected_index = 0;
+        self.sync_selection();
+    }
+
+    pub(crate) fn select_last(&mut self) {
+        self.selected_index = self.rows.len().saturating_sub(1);
+        self.sync_selection();
+    }
+
+    // Action résolue depuis une touche, le menu contextuel ou un test ; renvoie les effets
+    // laissés à la boucle principale
+    pub fn handle_action(&mut self, action: Action) -> Vec<Effect> {
+        self.run_action(action);
+        self.take_effects()
+    }
+
+    pub(crate) fn emit(&mut self, effect: Effect) {
+        self.effects.push(effect);
+    }
+
+    pub(crate) fn take_effects(&mut self) -> Vec<Effect> {
+        std::mem::take(&mut self.effects)
+    }
+
+    // Actions globales d'abord, puis celles de l'écran actif
+    pub(crate) fn run_action(&mut self, action: Action) {
+        match action {
+            Action::Quit => {
+                self.request_quit();
+                return;
+            }
+            Action::NextScreen => {
+                self.active_screen = self.active_screen.next();
+                self.refresh_visible();
+                return;
+            }
+            Action::PreviousScreen => {
+                self.active_screen = self.active_screen.previous();
+                self.refresh_visible();
+                return;
+            }
+            Action::ShowScreen(index) => {
+                self.active_screen = AppScreen::ALL[index.min(AppScreen::ALL.len() - 1)];
+                self.refresh_visible();
+                return;
+            }
+            Action::ToggleFiles => {
+                self.active_screen = if self.active_screen == AppScreen::Files { AppScreen::Agents } else { AppScreen::Files };
+                self.refresh_visible();
+                return;
+            }
+            Action::ToggleAudit => {
+                self.active_screen = if self.active_screen == AppScreen::Audit { AppScreen::Agents } else { AppScreen::Audit };
+                self.refresh_visible();
+                return;
+            }
+            Action::ToggleScheduler => {
+                self.active_screen = if self.active_screen == AppScreen::Scheduler { AppScreen::Agents } else { AppScreen::Scheduler };
+                self.refresh_visible();
+                return;
+            }
+            Action::OpenPalette => {
+                self.palette = Some(TextInput::default());
+                return;
+            }
+            Action::Help => {
+                self.help_scroll = Some(0);
+                return;
+            }
+            Action::CycleTheme => {
+                let name = self.scheme.next_name();
+                self.set_scheme(name);
+                return;
+            }
+            Action::LogViewer => {
+                self.log_viewer = Some(LogViewer::new(&logs_dir(self.db.path())));
+                self.reload_log_viewer();
+                return;
+            }
+            _ => {}
+        }
+        match self.active_screen {
+            AppScreen::Agents => self.run_agent_action(action),
+            AppScreen::Listeners => self.run_listener_action(action),
+            AppScreen::Tasks | AppScreen::Audit | AppScreen::Files | AppScreen::Logs | AppScreen::Scheduler => {
+                self.run_screen_action(action)
+            }
+        }
+    }
+
+    // Actions du panneau Agents, déclenchées au clavier ou depuis le menu contextuel
+    pub(crate) fn run_agent_action(&mut self, action: Action) {
+        match action {
+            Action::Note => {
+                if let Some(agent_id) = self.selected_agent().map(|a| a.id.clone()) {
+                    self.edit_latest_note(&agent_id);
+                }
+            }
+            Action::NewNote => self.note_editor = self.selected_agent().map(|a| NoteEditor::new(&a.id, None, "")),
+            Action::Edit => self.edit_dialog = self.selected_agent().map(EditDialog::new),
+            Action::Search => self.search = Some(TextInput::new(&self.filter)),
+            // e modifie déjà l'agent : E pré-remplit la palette, il reste le chemin à saisir
+            Action::Export => self.palette = Some(TextInput::new("export csv ")),
+            Action::CycleSort => {
+                self.sort_key = Some(SortKey::next(self.sort_key));
+                self.apply_sort();
+            }
+            Action::ToggleSortDirection => {
+                self.sort_descending = !self.sort_descending;
+                self.apply_sort();
+            }
+            Action::Reload => self.reload_agents(),
+            Action::ResetSort => self.reset_sort(),
+            Action::Delete => {
+                let targets = self.delete_targets();
+                if !targets.is_empty() {
+                    self.confirm_delete = Some(targets);
+                }
+            }
+            Action::Open if self.selected_header().is_some() => self.toggle_current_group(),
+            Action::Collapse => self.collapse_current(),
+            Action::Expand => self.expand_current(),
+            Action::CycleGrouping => self.cycle_grouping(),
+            Action::ToggleMap => self.map_mode = self.map_mode.next(),
+            Action::Groups => self.open_groups_panel(),
+            Action::Locate => self.locate_selected(),
+            Action::OpenScreenshot => match self.last_screenshot.clone() {
+                Some(path) => self.emit(Effect::OpenScreenshot(path)),
+                None => self.flash("Aucune capture d'écran pour cet agent", true),
+            },
+            Action::Copy => self.copy_field(CopyField::Ip),
+            Action::Clipboard => self.read_agent_clipboard(),
+            Action::Pivots => {
+                self.pivots_panel = Some(PivotsPanel::default());
+                self.reload_view();
+            }
+            Action::Topology => {
+                self.topology_panel = Some(TopologyPanel::default());
+                self.reload_view();
+            }
+            Action::Processes => {
+                if let Some(agent) = self.selected_agent() {
+                    self.process_panel = Some(ProcessPanel::new(&agent.id));
+                    self.reload_strip();
+                }
+            }
+            Action::Interfaces => {
+                self.show_interfaces = !self.show_interfaces;
+                self.reload_view();
+            }
+            Action::CopyField => {
+                self.copy_menu = self.selected_agent().map(|a| CopyMenu { agent_id: a.id.clone(), selected: 0 });
+            }
+            Action::Keylog => {
+                if let Some(agent) = self.selected_agent() {
+                    self.keylog_panel = Some(KeylogPanel::new(&agent.id));
+                    self.reload_view();
+                }
+            }
+            Action::Gallery => {
+                if let Some(agent) = self.selected_agent() {
+                    self.gallery_panel = Some(GalleryPanel::new(&agent.id));
+                    self.reload_view();
+                }
+            }
+            Action::Loot => {
+                if let Some(agent) = self.selected_agent() {
+                    let agent_id = agent.id.clone();
+                    self.open_loot_panel(&agent_id);
+                }
+            }
+            Action::Sessions => self.session_view = self.selected_agent().map(|a| a.id.clone()),
+            Action::Terminal => self.focus_terminal(),
+            Action::Shell => self.open_shell(),
+            Action::Tags => {
+                let targets = self.action_targets();
+                if !targets.is_empty() {
+                    self.tag_prompt = Some((targets, TextInput::default()));
+                }
+            }
+            // Un agent à la fois, jamais la sélection multiple
+            Action::SelfDestruct => {
+                self.confirm_self_destruct = self.selected_agent().map(|a| SelfDestructConfirm::new(&a.id, &a.hostname));
+            }
+            // Prérempli avec l'intervalle et la gigue de l'agent sélectionné
+            Action::Beacon => {
+                let targets = self.action_targets();
+                let (secs, jitter) = self.selected_agent()
+                    .map_or((DEFAULT_BEACON_SECS, DEFAULT_JITTER_PCT), |a| (a.beacon_interval_secs, a.jitter_pct));
+                if !targets.is_empty() {
+                    let form = Form::new("Beacon", &BEACON_FIELDS).with_values(&[&secs.to_string(), &jitter.to_string()]);
+                    self.beacon_dialog = Some((targets, form));
+                }
+            }
+            Action::Open => {
+                if let Some(agent) = self.selected_agent() {
+                    self.detail_view = Some(DetailView::new(&agent.id));
+                    self.reload_view();
+                }
+            }
+            Action::Tasks => {
+                if let Some(agent) = self.selected_agent() {
+                    self.task_view = Some(TaskView {
+                        agent_id: agent.id.clone(),
+                        tasks: Vec::new(),
+                        state: ListState::default(),
+                        viewer: None,
+                        uploads: HashMap::new(),
+                    });
+                    self.reload_view();
+                }
+            }
+            Action::Command => {
+                let targets = self.action_targets();
+                if !targets.is_empty() {
+                    self.open_command_prompt(targets);
+                }
+            }
+            Action::Mark => self.toggle_mark(),
+            Action::AddAgent => self.add_dialog = Some(Form::new("Nouvel agent", &ADD_AGENT_FIELDS)),
+            Action::SelectAll => {
+                let visible: Vec<String> = self.filtered_indices.iter()
+                    .map(|&i| self.agents[i].id.clone())
+                    .collect();
+                self.multi_selection.extend(visible);
+            }
+            Action::ClearSelection => self.multi_selection.clear(),
+            Action::Down => self.select_next(),
+            Action::Up => self.select_previous(),
+            Action::PageDown => self.page_down(),
+            Action::PageUp => self.page_up(),
+            Action::HalfPageDown => self.half_page_down(),
+            Action::HalfPageUp => self.half_page_up(),
+            Action::First => self.select_first(),
+            Action::Last => self.select_last(),
+            _ => {}
+        }
+    }
+
+    pub(crate) fn run_listener_action(&mut self, action: Action) {
+        match action {
+            Action::Down => {
+                self.listener_index = self.listener_index.saturating_add(1);
+                self.sync_listener_selection();
+            }
+            Action::Up => {
+                self.listener_index = self.listener_index.saturating_sub(1);
+                self.sync_listener_selection();
+            }
+            Action::NewListener => {
+                let port = self.config.default_listener_port.to_string();
+                let dialog = Form::new("Nouveau listener", &LISTENER_FIELDS)
+                    .with_values(&["", "tcp", "0.0.0.0", &port]);
+                self.listener_dialog = Some(dialog);
+            }
+            // Entrée démarre un listener arrêté et arrête un listener démarré
+            Action::Open => {
+                let Some(listener) = self.listeners.get(self.listener_index) else {
+                    return;
+                };
+                let id = listener.id;
+                let running = listener.status == "running";
+                let result = if running {
+                    if let Some(handle) = self.listener_tasks.remove(&id) {
+                        handle.stop();
+                    }
+                    stop_listener(self.db.conn(), id).map_err(|e| e.to_string())
+                } else {
+                    self.spawn_listener_task(self.listener_index)
+                        .and_then(|_| start_listener(self.db.conn(), id).map_err(|e| e.to_string()))
+                };
+                match result {
+                    Ok(()) => {
+                        self.error = None;
+                        let action = if running { "stop_listener" } else { "start_listener" };
+                        self.audit(action, &id.to_string());
+                        let state = if running { "arrêté" } else { "démarré" };
+                        self.record(Level::Info, Category::Listener, None, &format!("Listener {} {}", id, state));
+                    }
+                    Err(e) => self.show_error(format!("Échec du changement d'état : {}", e)),
+                }
+                self.reload_listeners();
+            }
+            Action::Delete => {
+                let Some(listener) = self.listeners.get(self.listener_index) else {
+                    return;
+                };
+                let id = listener.id;
+                match delete_listener(self.db.conn(), id) {
+                    Ok(true) => {
+                        self.error = None;
+                        self.audit("delete_listener", &id.to_string());
+                        self.record(Level::Info, Category::Listener, None, &format!("Listener {} supprimé", id));
+                    }
+                    Ok(false) => self.show_error(format!("Le listener {} doit être arrêté avant d'être supprimé", id)),
+                    Err(e) => self.show_error(format!("Échec de la suppression : {}", e)),
+                }
+                self.reload_listeners();
+            }
+            Action::ToggleAutostart => {
+                let Some(listener) = self.listeners.get(self.listener_index) else {
+                    return;
+                };
+                let (id, autostart) = (listener.id, !listener.autostart);
+                match set_autostart(self.db.conn(), id, autostart) {
+                    Ok(()) => {
+                        self.audit("set_autostart", &format!("{} {}", id, autostart));
+                        let message = format!("Démarrage automatique du listener {} : {}", id, autostart);
+                        self.record(Level::Info, Category::Listener, None, &message);
+                    }
+                    Err(e) => self.show_error(format!("Échec de la mise à jour : {}", e)),
+                }
+                self.reload_listeners();
+            }
+            _ => {}
+        }
+    }
+
+    pub(crate) fn spawn_listener_task(&mut self, index: usize) -> std::result::Result<(), String> {
+        let listener = &self.listeners[index];
+        let ip: IpAddr = listener.bind_addr.parse()
+            .map_err(|_| format!("adresse invalide : {}", listener.bind_addr))?;
+        let addr = SocketAddr::new(ip, listener.port);
+        let handle = match listener.protocol.to_lowercase().as_str() {
+            "tcp" => start_tcp_listener(addr, self.db.path(), self.events.clone(), self.sessions.clone()),
+            "http" => start_http_listener(addr, self.db.path(), self.events.clone()),
+            _ => return Err(format!("protocole {} non pris en charge", listener.protocol)),
+        };
+        self.listener_tasks.insert(listener.id, handle);
+        Ok(())
+    }
+
+    // À l'ouverture, un état running vient d'une session précédente : seuls les
+    // listeners en démarrage automatique sont relancés, les autres repassent à stopped
+    pub(crate) fn resume_listeners(&mut self) {
+        for index in 0..self.listeners.len() {
+            let (id, autostart) = (self.listeners[index].id, self.listeners[index].autostart);
+            let result = if autostart {
+                self.spawn_listener_task(index)
+                    .and_then(|_| start_listener(self.db.conn(), id).map_err(|e| e.to_string()))
+            } else if self.listeners[index].status == "running" {
+                stop_listener(self.db.conn(), id).map_err(|e| e.to_string())
+            } else {
+                Ok(())
+            };
+            if let Err(e) = result {
+                self.show_error(format!("Listener {} non relancé : {}", id, e));
+                if let Err(e) = stop_listener(self.db.conn(), id) {
+                    warn!("listener {} resté running en base : {}", id, e);
+                }
+            }
+        }
+        self.reload_listeners();
+    }
+
+    // Une écoute terminée seule (port pris, erreur fatale) repasse à stopped ;
+    // l'erreur elle-même est déjà remontée par ListenerError
+    pub(crate) fn reap_listeners(&mut self) {
+        let finished: Vec<i64> = self.listener_tasks.iter()
+            .filter(|(_, handle)| handle.is_finished())
+            .map(|(&id, _)| id)
+            .collect();
+        if finished.is_empty() {
+            return;
+        }
+        for id in finished {
+            self.listener_tasks.remove(&id);
+            if let Err(e) = stop_listener(self.db.conn(), id) {
+                self.show_error(format!("Listener {} : {}", id, e));
+            }
+        }
+        self.reload_listeners();
+    }
+
+    pub(crate) fn run_screen_action(&mut self, action: Action) {
+        let Some(screen) = self.screens.get_mut(self.active_screen) else {
+            return;
+        };
+        match screen.handle_action(&self.db, action) {
+            Ok(Some(Action::Upload)) => {
+                let agent_id = self.selected_agent().map(|a| a.id.clone()).unwrap_or_default();
+                self.upload_dialog = Some(Form::new("Envoyer un fichier", &UPLOAD_FIELDS).with_values(&[&agent_id]));
+            }
+            Ok(Some(Action::ToggleScheduledTask)) => self.toggle_scheduled_task(),
+            Ok(Some(Action::Delete)) if self.active_screen == AppScreen::Scheduler => self.delete_scheduled_task(),
+            Ok(_) => {}
+            Err(e) => self.show_error(format!("{} : {}", self.active_screen.label(), e)),
+        }
+    }
+
+    // Erreur affichée jusqu'à la prochaine action, comme une section [keys] invalide au démarrage
+    // Aussi écrite dans le journal de l'application, où elle reste une fois effacée
+    pub fn show_error(&mut self, message: String) {
+        error!("{}", message);
+        self.error = Some(message);
+    }
+
+    pub fn flash(&mut self, message: &str, error: bool) {
+        if error {
+            warn!("{}", message);
+        } else {
+            debug!("{}", message);
+        }
+        if self.status_messages.len() >= MAX_STATUS_MESSAGES {
+            self.status_messages.pop_front();
+        }
+        self.status_messages.push_back(StatusMessage { text: message.to_string(), error, shown_at: None });
+    }
+
+    // Retire le message affiché depuis STATUS_MESSAGE_DURATION et lance le chrono du suivant
+    pub(crate) fn advance_status_messages(&mut self) {
+        if self.status_messages.front()
+            .and_then(|m| m.shown_at)
+            .is_some_and(|at| at.elapsed() >= STATUS_MESSAGE_DURATION)
+        {
+            self.status_messages.pop_front();
+        }
+        if let Some(message) = self.status_messages.front_mut() {
+            message.shown_at.get_or_insert_with(Instant::now);
+        }
+    }
+
+    // Une action non journalisée est signalée sans être annulée
+    pub(crate) fn audit(&mut self, action: &str, detail: &str) {
+        if let Err(e) = log_action(self.db.conn(), &self.operator, action, detail) {
+            self.show_error(format!("Écriture du journal d'audit impossible : {}", e));
+        }
+    }
+
+    // Chaque événement est aussi écrit dans le journal de l'application
+    pub(crate) fn record(&mut self, level: Level, category: Category, agent_id: Option<&str>, message: &str) {
+        let category_name = category.as_str();
+        match level {
+            Level::Info => info!(category = category_name, agent = agent_id, "{}", message),
+            Level::Warn => warn!(category = category_name, agent = agent_id, "{}", message),
+            Level::Error => error!(category = category_name, agent = agent_id, "{}", message),
+        }
+        if let Err(e) = log_event(&self.db, level, category, agent_id, message) {
+            self.show_error(format!("Écriture du journal d'événements impossible : {}", e));
+        }
+    }
+
+    pub(crate) fn reload_scheduler(&mut self) {
+        if let Err(e) = self.screens.scheduler.reload(&self.db) {
+            self.show_error(format!("Scheduler : {}", e));
+        }
+    }
+
+    pub(crate) fn toggle_scheduled_task(&mut self) {
+        let Some(task) = self.screens.scheduler.selected().cloned() else {
+            return;
+        };
+        let enabled = !task.enabled;
+        match set_scheduled_task_enabled(self.db.conn(), &task, enabled) {
+            Ok(()) => {
+                self.audit("set_scheduled_task", &format!("{} {}", task.id, enabled));
+                let state = if enabled { "activée" } else { "suspendue" };
+                self.flash(&format!("Tâche planifiée {} {}", task.id, state), false);
+            }
+            Err(e) => self.show_error(format!("Échec de la mise à jour : {}", e)),
+        }
+        self.reload_scheduler();
+    }
+
+    // Les tâches déjà mises en file restent dans la file
+    pub(crate) fn delete_scheduled_task(&mut self) {
+        let Some(task) = self.screens.scheduler.selected().cloned() else {
+            return;
+        };
+        match delete_scheduled_task(self.db.conn(), task.id) {
+            Ok(()) => {
+                self.audit("delete_scheduled_task", &format!("{} {} [{}] {}", task.id, task.agent_id, task.schedule, task.command));
+                self.flash(&format!("Tâche planifiée {} supprimée", task.id), false);
+            }
+            Err(e) => self.show_error(format!("Échec de la suppression : {}", e)),
+        }
+        self.reload_scheduler();
+    }
+
+    pub(crate) fn confirm_upload(&mut self) {
+        let Some(mut dialog) = self.upload_dialog.take() else {
+            return;
+        };
+        let agent_id = dialog.value(0).to_string();
+        let path = dialog.value(1).to_string();
+        if !self.agents.iter().any(|a| a.id == agent_id) {
+            dialog.reject(0, format!("Agent inconnu : {}", agent_id));
+            self.upload_dialog = Some(dialog);
+            return;
+        }
+        match initiate_upload(self.db.conn(), &agent_id, &path) {
+            Ok(id) => {
+                spawn_upload_thread(self.db.path(), id, &agent_id, &path, self.sessions.clone());
+                self.audit("upload", &format!("{} {} -> {}", id, path, agent_id));
+                self.record(Level::Info, Category::Transfer, Some(&agent_id), &format!("Envoi #{} de {}", id, path));
+                self.screens.files.select_newest();
+                self.refresh_visible();
+            }
+            Err(e) => {
+                dialog.reject(1, format!("Envoi impossible : {}", e));
+                self.upload_dialog = Some(dialog);
+            }
+        }
+    }
+
+    pub(crate) fn sync_listener_selection(&mut self) {
+        if self.listeners.is_empty() {
+            self.listener_index = 0;
+            self.listener_state.select(None);
+        } else {
+            self.listener_index = self.listener_index.min(self.listeners.len() - 1);
+            self.listener_state.select(Some(self.listener_index));
+        }
+    }
+
+    pub(crate) fn reload_listeners(&mut self) {
+        match load_listeners(self.db.conn()) {
+            Ok(listeners) => self.listeners = listeners,
+            Err(e) => self.show_error(format!("Lecture des listeners impossible : {}", e)),
+        }
+        self.sync_listener_selection();
+    }
+
+    pub(crate) fn confirm_create_listener(&mut self) {
+        let Some(mut dialog) = self.listener_dialog.take() else {
+            return;
+        };
+        if dialog.value(2).parse::<IpAddr>().is_err() {
+            let error = format!("Adresse invalide : {}", dialog.value(2));
+            dialog.reject(2, error);
+            self.listener_dialog = Some(dialog);
+            return;
+        }
+        let Ok(port) = dialog.value(3).parse::<u16>() else {
+            let error = format!("Port invalide : {}", dialog.value(3));
+            dialog.reject(3, error);
+            self.listener_dialog = Some(dialog);
+            return;
+        };
+        let (name, protocol, bind_addr) = (dialog.value(0), dialog.value(1), dialog.value(2));
+        match create_listener(self.db.conn(), name, protocol, bind_addr, port) {
+            Ok(id) => {
+                let detail = format!("{} {} {} {}:{}", id, name, protocol, bind_addr, port);
+                self.audit("create_listener", &detail);
+                self.record(Level::Info, Category::Listener, None, &format!("Listener créé : {}", detail));
+                self.reload_listeners();
+                if let Some(pos) = self.listeners.iter().position(|l| l.id == id) {
+                    self.listener_index = pos;
+                    self.sync_listener_selection();
+                }
+            }
+            Err(e) => {
+                dialog.error = Some(format!("Échec de la création : {}", e));
+                self.listener_dialog = Some(dialog);
+            }
+        }
+    }
+
+    // Une fenêtre ouverte garde la main : la souris n'agit que sur l'écran principal
+    pub(crate) fn modal_open(&self) -> bool {
+        self.add_dialog.is_some() || self.terminal.is_some() || self.shell.is_some() || self.session_view.is_some()
+            || self.upload_dialog.is_some() || self.listener_dialog.is_some()
+            || self.confirm_delete.is_some() || self.confirm_self_destruct.is_some() || self.edit_dialog.is_some() || self.search.is_some()
+            || self.command_prompt.is_some() || self.task_view.is_some() || self.tag_prompt.is_some()
+            || self.beacon_dialog.is_some() || self.upload_prompt.is_some()
+            || self.note_editor.is_some() || self.palette.is_some() || self.import_errors.is_some()
+            || self.groups_panel.is_some() || self.keylog_panel.is_some() || self.loot_panel.is_some() || self.gallery_panel.is_some() || self.clipboard_popup.is_some() || self.log_viewer.is_some()
+            || self.detail_view.is_some() || self.copy_menu.is_some() || self.pivots_panel.is_some()
+            || self.process_panel.is_some() || self.topology_panel.is_some() || self.confirm_quit.is_some()
+    }
+
+    // Ce que la sortie interromprait : exports et imports du worker, listeners, sessions
+    pub(crate) fn in_flight_work(&self) -> Vec<String> {
+        let mut work = Vec::new();
+        if self.background_jobs > 0 {
+            work.push(format!("{} export(s)/import(s) en cours", self.background_jobs));
+        }
+        if !self.listener_tasks.is_empty() {
+            work.push(format!("{} listener(s) actif(s)", self.listener_tasks.len()));
+        }
+        if !self.sessions.is_empty() {
+            work.push(format!("{} session(s) TCP ouverte(s)", self.sessions.len()));
+        }
+        work
+    }
+
+    // q hors de toute fenêtre, :quit ou Ctrl-C : confirmation seulement s'il reste du
+    // travail en cours, et un second Ctrl-C sort sans attendre la réponse
+    pub(crate) fn request_quit(&mut self) {
+        let work = self.in_flight_work();
+        if work.is_empty() || self.confirm_quit.take().is_some() {
+            self.emit(Effect::Quit);
+        } else {
+            self.confirm_quit = Some(work);
+        }
+    }
+
+    // Dernier pas de la sortie, une fois la boucle terminée : les listeners sont arrêtés et
+    // marqués comme tels, la connexion est rendue pour le checkpoint final. Abandonner le
+    // reste de l'application ferme la file du worker.
+    pub fn shutdown(mut self) -> Db {
+        for (id, handle) in std::mem::take(&mut self.listener_tasks) {
+            handle.stop();
+            if let Err(e) = stop_listener(self.db.conn(), id) {
+                error!("listener {} : {}", id, e);
+            }
+        }
+        info!("listeners arrêtés");
+        self.db
+    }
+
+    pub(crate) fn copy_field(&mut self, field: CopyField) {
+        if let Some(agent) = self.selected_agent() {
+            let label = format!("{} de {}", field.label(), agent.id);
+            let text = field.value(agent);
+            self.emit(Effect::Copy { label, text });
+        }
+    }
+
+    // clipboard_read part dans la file de l'agent, la fenêtre attend sa sortie
+    pub(crate) fn read_agent_clipboard(&mut self) {
+        let Some(agent_id) = self.selected_agent().map(|a| a.id.clone()) else {
+            return;
+        };
+        match ClipboardCommand::read().enqueue(self.db.conn(), &agent_id) {
+            Ok(task_id) => {
+                self.audit("clipboard_read", &format!("{} {}", task_id, agent_id));
+                self.clipboard_popup = Some(ClipboardPopup::new(&agent_id, task_id));
+                self.reload_view();
+            }
+            Err(e) => self.show_error(format!("Lecture du presse-papiers de {} impossible : {}", agent_id, e)),
+        }
+    }
+
+    pub(crate) fn open_command_prompt(&mut self, agent_ids: Vec<String>) {
+        // Un historique illisible n'empêche pas de taper une commande
+        let history = match load_history(self.db.conn()) {
+            Ok(history) => history,
+            Err(e) => {
+                self.show_error(format!("Historique indisponible : {}", e));
+                Vec::new()
+            }
+        };
+        self.command_prompt = Some(CommandPrompt {
+            agent_ids,
+            input: TextInput::default(),
+            history,
+            recall: None,
+            draft: String::new(),
+        });
+    }
+
+    // Prompt de commande prérempli : le chemin distant suit download
+    pub(crate) fn open_download_prompt(&mut self, agent_id: &str) {
+        self.open_command_prompt(vec![agent_id.to_string()]);
+        if let Some(prompt) = self.command_prompt.as_mut() {
+            prompt.input = TextInput::new(&format!("{} ", DOWNLOAD_COMMAND));
+        }
+    }
+
+    pub(crate) fn open_loot_panel(&mut self, agent_id: &str) {
+        self.loot_panel = Some(LootPanel::new(agent_id));
+        self.reload_view();
+    }
+
+    // Une sonde reach par agent en ligne, vers tous les autres agents connus
+    pub(crate) fn scan_reachability(&mut self) {
+        let now = Utc::now();
+        let online: Vec<&Agent> = self.agents.iter().filter(|a| display_status(a, now, &self.thresholds) == "online").collect();
+        if online.is_empty() {
+            self.flash("Aucun agent en ligne à sonder", true);
+            return;
+        }
+        match queue_reachability_scan(self.db.conn(), &online, &self.agents) {
+            Ok(queued) => {
+                let ids: Vec<&str> = online.iter().map(|a| a.id.as_str()).collect();
+                self.audit("scan_reachability", &ids.join(", "));
+                self.record(Level::Info, Category::Task, None, &format!("Sonde de joignabilité envoyée à {} agent(s)", queued));
+                self.flash(&format!("Sonde envoyée à {} agent(s)", queued), false);
+            }
+            Err(e) => self.flash(&format!("Échec de l'envoi : {}", e), true),
+        }
+    }
+
+    // Nouvelle tâche ps ; le panneau affiche la réponse dès qu'elle arrive
+    pub(crate) fn request_process_list(&mut self) {
+        let Some(agent_id) = self.process_panel.as_ref().map(|p| p.agent_id.clone()) else {
+            return;
+        };
+        match self.db.enqueue_task(&agent_id, PROCESS_LIST_COMMAND) {
+            Ok(_) => {
+                self.audit("enqueue_task", &format!("{}: {}", agent_id, PROCESS_LIST_COMMAND));
+                self.record(Level::Info, Category::Task, Some(&agent_id), &format!("Tâche en file : {}", PROCESS_LIST_COMMAND));
+                self.flash(&format!("Liste des processus demandée à {}", agent_id), false);
+            }
+            Err(e) => self.flash(&format!("Échec de l'envoi : {}", e), true),
+        }
+    }
+
+    pub(crate) fn close_pivot(&mut self, id: i64) {
+        match close_pivot(self.db.conn(), id) {
+            Ok(()) => {
+                self.audit("close_pivot", &id.to_string());
+                self.flash(&format!("Pivot {} fermé", id), false);
+                self.reload_view();
+            }
+            Err(e) => self.flash(&format!("Fermeture du pivot {} impossible : {}", id, e), true),
+        }
+    }
+
+    // Schéma intégré, toujours retouché par [ui.colors]
+    pub(crate) fn set_scheme(&mut self, name: &str) {
+        let Some(scheme) = ColorScheme::from_name(name) else {
+            return;
+        };
+        self.scheme = self.config.ui.colors.apply(scheme);
+        self.flash(&format!("Schéma de couleurs : {}", self.scheme.name), false);
+    }
+
+    pub(crate) fn reload_log_viewer(&mut self) {
+        let Some(viewer) = self.log_viewer.as_mut() else {
+            return;
+        };
+        if let Err(e) = viewer.reload() {
+            self.show_error(format!("Lecture du journal impossible : {}", e));
+        }
+    }
+
+    // Reprend la note la plus récente, ou en commence une s'il n'y en a pas
+    pub(crate) fn edit_latest_note(&mut self, agent_id: &str) {
+        self.note_editor = Some(match self.notes_of(agent_id).first() {
+            Some(note) => NoteEditor::new(agent_id, Some(note.id), &note.content),
+            None => NoteEditor::new(agent_id, None, ""),
+        });
+    }
+
+    // La session la plus récente de l'agent sélectionné
+    pub(crate) fn focus_terminal(&mut self) {
+        let Some(agent) = self.selected_agent() else {
+            return;
+        };
+        let agent_id = agent.id.clone();
+        match sessions_for(&self.sessions, &agent_id).pop() {
+            Some(session) => {
+                self.terminal = Some(TerminalFocus {
+                    session_id: session.id.to_string(),
+                    agent_id,
+                    command: String::new(),
+                    scroll: 0,
+                });
+            }
+            None => self.show_error(format!("{} n'a pas de session active", agent_id)),
+        }
+    }
+
+    // Shell de l'agent sélectionné, créé au premier passage puis repris tel quel
+    pub(crate) fn open_shell(&mut self) {
+        let Some(agent) = self.selected_agent() else {
+            return;
+        };
+        let (agent_id, hostname) = (agent.id.clone(), agent.hostname.clone());
+        self.shells.entry(agent_id.clone()).or_insert_with(|| TaskShell::new(&agent_id, &hostname));
+        self.shell = Some(agent_id);
+        // Les réponses arrivées pendant qu'il était masqué
+        self.reload_strip();
+    }
+
+    // La ligne part comme une tâche ordinaire ; un agent muet est signalé sans bloquer l'envoi
+    pub(crate) fn send_shell_line(&mut self) {
+        let Some(shell) = self.shell.as_ref().and_then(|id| self.shells.get_mut(id)) else {
+            return;
+        };
+        let command = shell.input.content.trim().to_string();
+        if command.is_empty() {
+            return;
+        }
+        let agent_id = shell.agent_id.clone();
+        let task_id = match self.db.enqueue_task(&agent_id, &command) {
+            Ok(task_id) => task_id,
+            Err(e) => {
+                self.show_error(format!("Échec de l'envoi : {}", e));
+                return;
+            }
+        };
+        shell.input = TextInput::default();
+        shell.sent(task_id, &command);
+        self.error = None;
+        self.audit("enqueue_task", &format!("{}: {}", agent_id, command));
+        self.record(Level::Info, Category::Task, Some(&agent_id), &format!("Shell : {}", command));
+        if let Err(e) = push_history(self.db.conn(), &command) {
+            self.show_error(format!("Historique non enregistré : {}", e));
+        }
+        let status = self.agents.iter()
+            .find(|a| a.id == agent_id)
+            .map(|a| display_status(a, Utc::now(), &self.thresholds).to_string());
+        if let Some(status) = status.filter(|s| matches!(s.as_str(), "stale" | "dead" | "offline")) {
+            self.flash(&format!("{} est {} : la commande attendra son prochain check-in", agent_id, status), true);
+        }
+    }
+
+    pub(crate) fn open_groups_panel(&mut self) {
+        match load_groups(self.db.conn()) {
+            Ok(groups) => {
+                let mut panel = GroupsPanel { groups, index: 0, members: Vec::new(), targets: self.action_targets(), input: None };
+                self.load_group_members(&mut panel);
+                self.groups_panel = Some(panel);
+            }
+            Err(e) => self.flash(&format!("Lecture des groupes impossible : {}", e), true),
+        }
+    }
+
+    pub(crate) fn load_group_members(&mut self, panel: &mut GroupsPanel) {
+        let members = match panel.groups.get(panel.index) {
+            Some(group) => agents_in_group(self.db.conn(), group.id),
+            None => Ok(Vec::new()),
+        };
+        match members {
+            Ok(members) => panel.members = members,
+            Err(e) => self.flash(&format!("Lecture des membres impossible : {}", e), true),
+        }
+    }
+
+    // Relit les groupes et les appartenances après une modification, en gardant la position
+    pub(crate) fn refresh_groups(&mut self, panel: &mut GroupsPanel) {
+        let loaded = load_groups(self.db.conn())
+            .and_then(|groups| Ok((groups, load_memberships(self.db.conn())?)));
+        match loaded {
+            Ok((groups, memberships)) => {
+                panel.groups = groups;
+                panel.index = panel.index.min(panel.groups.len().saturating_sub(1));
+                self.memberships = memberships;
+                self.prune_labels();
+                self.refresh_filter();
+            }
+            Err(e) => self.flash(&format!("Lecture des groupes impossible : {}", e), true),
+        }
+        self.load_group_members(panel);
+    }
+
+    // "nom description libre" : le nom s'écrit d'un seul mot pour rester utilisable dans le filtre
+    pub(crate) fn create_group(&mut self, panel: &mut GroupsPanel, line: &str) {
+        let line = line.trim();
+        let (name, description) = match line.split_once(char::is_whitespace) {
+            Some((name, description)) => (name, Some(description.trim())),
+            None => (line, None),
+        };
+        if name.is_empty() {
+            return;
+        }
+        match create_group(self.db.conn(), name, description) {
+            Ok(id) => {
+                self.audit("create_group", &format!("{} #{}", name, id));
+                self.flash(&format!("Groupe {} créé", name), false);
+                self.refresh_groups(panel);
+                if let Some(index) = panel.groups.iter().position(|g| g.id == id) {
+                    panel.index = index;
+                    self.load_group_members(panel);
+                }
+            }
+            Err(e) => self.flash(&format!("Création du groupe {} impossible : {}", name, e), true),
+        }
+    }
+
+    pub(crate) fn edit_group_members(&mut self, panel: &mut GroupsPanel, add: bool) {
+        let Some(group) = panel.groups.get(panel.index).cloned() else {
+            return;
+        };
+        if panel.targets.is_empty() {
+            self.flash("Aucun agent sélectionné dans la liste", true);
+            return;
+        }
+        let result = panel.targets.iter().try_for_each(|id| {
+            if add {
+                add_agent_to_group(self.db.conn(), group.id, id)
+            } else {
+                remove_agent_from_group(self.db.conn(), group.id, id)
+            }
+        });
+        match result {
+            Ok(()) => {
+                let (action, verb) = if add { ("add_to_group", "ajouté(s) à") } else { ("remove_from_group", "retiré(s) de") };
+                self.audit(action, &format!("{} {}", group.name, panel.targets.join(",")));
+                self.flash(&format!("{} agent(s) {} {}", panel.targets.len(), verb, group.name), false);
+            }
+            Err(e) => self.flash(&format!("Mise à jour du groupe {} impossible : {}", group.name, e), true),
+        }
+        self.refresh_groups(panel);
+    }
+
+    pub(crate) fn run_palette_command(&mut self, line: &str) {
+        let line = line.trim();
+        let (name, args) = line.split_once(char::is_whitespace).unwrap_or((line, ""));
+        if name.is_empty() {
+            return;
+        }
+        // Nom exact, sinon préfixe sans ambiguïté (:q, :rel…)
+        let exact = PALETTE_COMMANDS.iter().find(|(command, _)| *command == name);
+        let command = exact.or(match palette_matches(name).as_slice() {
+            [only] => Some(*only),
+            _ => None,
+        });
+        match command {
+            Some((_, run)) => run(self, args.trim()),
+            None => self.show_error(format!("Commande inconnue : {}", name)),
+        }
+    }
+
+    // Une note vidée est supprimée ; une nouvelle note vide n'est pas créée
+    pub(crate) fn save_note(&mut self, editor: &NoteEditor) {
+        let agent_id = editor.agent_id.as_str();
+        if !self.agents.iter().any(|a| a.id == agent_id) {
+            self.show_error(format!("{} n'existe plus, note non enregistrée", agent_id));
+            return;
+        }
+        let content = editor.content();
+        let saved = match (editor.note_id, content.is_empty()) {
+            (None, true) => return,
+            (None, false) => add_note(self.db.conn(), agent_id, &content).map(|id| ("add_note", id)),
+            (Some(id), false) => update_note(self.db.conn(), id, &content).map(|_| ("update_note", id)),
+            (Some(id), true) => delete_note(self.db.conn(), id).map(|_| ("delete_note", id)),
+        };
+        let result = saved.and_then(|saved| Ok((saved, notes_for_agent(self.db.conn(), agent_id)?)));
+        match result {
+            Ok(((action, id), notes)) => {
+                self.error = None;
+                self.audit(action, &format!("{} #{}", agent_id, id));
+                let message = if content.is_empty() { "Note supprimée" } else { "Note enregistrée" };
+                self.record(Level::Info, Category::Agent, Some(agent_id), &format!("{} (#{})", message, id));
+                self.flash(message, false);
+                self.notes.insert(agent_id.to_string(), notes);
+                // Les notes font partie des champs filtrés
+                self.refresh_filter();
+            }
+            Err(e) => self.show_error(format!("Échec de l'enregistrement de la note : {}", e)),
+        }
+    }
+
+    pub(crate) fn apply_tag_edits(&mut self, agent_ids: &[String], added: &[String], removed: &[String]) {
+        self.error = None;
+        let edits: Vec<String> = added.iter().map(|t| format!("+{}", t))
+            .chain(removed.iter().map(|t| format!("-{}", t)))
+            .collect();
+        for id in agent_ids {
+            let result = added.iter()
+                .try_for_each(|tag| add_tag(self.db.conn(), id, tag))
+                .and_then(|_| removed.iter().try_for_each(|tag| remove_tag(self.db.conn(), id, tag)))
+                .and_then(|_| tags_for_agent(self.db.conn(), id));
+            match result {
+                Ok(tags) if tags.is_empty() => {
+                    self.tags.remove(id);
+                }
+                Ok(tags) => {
+                    self.tags.insert(id.clone(), tags);
+                }
+                Err(e) => {
+                    self.show_error(format!("Échec de la mise à jour des tags de {} : {}", id, e));
+                    break;
+                }
+            }
+            self.audit("edit_tags", &format!("{} {}", id, edits.join(" ")));
+            self.record(Level::Info, Category::Agent, Some(id), &format!("Tags : {}", edits.join(" ")));
+        }
+        // Un filtre tag: en cours peut faire entrer ou sortir des agents
+        self.refresh_filter();
+    }
+
+    // Le fichier est découpé en tranches à la mise en file ; le panneau des tâches suit l'envoi
+    pub(crate) fn start_task_upload(&mut self, agent_id: &str, local_path: &str, remote_path: &str) {
+        let (chunk_bytes, max_bytes) = (self.config.upload_chunk_bytes, self.config.max_upload_bytes);
+        match enqueue_upload(self.db.conn(), agent_id, local_path, remote_path, chunk_bytes, max_bytes) {
+            Ok(id) => {
+                self.audit("upload_task", &format!("{} {} -> {}:{}", id, local_path, agent_id, remote_path));
+                self.record(Level::Info, Category::Task, Some(agent_id), &format!("Envoi #{} de {} vers {}", id, local_path, remote_path));
+                self.flash(&format!("Envoi #{} mis en file pour {}", id, agent_id), false);
+                self.reload_view();
+            }
+            Err(e) => self.show_error(e.to_string()),
+        }
+    }
+
+    pub(crate) fn resume_task_upload(&mut self, task_id: i64) {
+        match resume_upload(self.db.conn(), task_id) {
+            Ok(()) => {
+                self.audit("resume_upload", &task_id.to_string());
+                self.flash(&format!("Envoi #{} remis en file", task_id), false);
+                self.reload_view();
+            }
+            Err(e) => self.flash(&e.to_string(), true),
+        }
+    }
+
+    // La base d'abord, puis une tâche set_beacon par agent : l'implant règle sa boucle dessus.
+    // Les valeurs sont vérifiées avant la première tâche : aucun agent de la sélection
+    // ne reçoit de réglage refusé
+    pub(crate) fn confirm_beacon(&mut self) {
+        let Some((agent_ids, mut dialog)) = self.beacon_dialog.take() else {
+            return;
+        };
+        let Some(secs) = parse_interval(dialog.value(0)) else {
+            let error = format!("Intervalle invalide : {} (par exemple 30, 90s, 5m ou 2h)", dialog.value(0));
+            dialog.reject(0, error);
+            self.beacon_dialog = Some((agent_ids, dialog));
+            return;
+        };
+        let Some(jitter) = parse_jitter(dialog.value(1)) else {
+            let error = format!("Gigue invalide : {} (un pourcentage, par exemple 0, 20 ou 50%)", dialog.value(1));
+            dialog.reject(1, error);
+            self.beacon_dialog = Some((agent_ids, dialog));
+            return;
+        };
+        if let Err(e) = check_beacon(secs, jitter) {
+            dialog.reject(1, e.to_string());
+            self.beacon_dialog = Some((agent_ids, dialog));
+            return;
+        }
+        // Les agents suivant un échec ne sont pas réglés : le bilan ne compte que ceux qui l'ont été
+        let mut updated = 0;
+        for id in &agent_ids {
+            if let Err(e) = set_beacon(self.db.conn(), id, secs, jitter) {
+                self.show_error(format!("Beacon de {} inchangé : {}", id, e));
+                break;
+            }
+            updated += 1;
+            self.audit("set_beacon", &format!("{} {} {}", id, secs, jitter));
+            self.record(Level::Info, Category::Agent, Some(id), &format!("Beacon toutes les {}s ±{}%", secs, jitter));
+        }
+        let message = match updated == agent_ids.len() {
+            true => format!("Beacon : toutes les {}s ±{}% pour {} agent(s)", secs, jitter, updated),
+            false => format!("Beacon : toutes les {}s ±{}% pour {} agent(s) sur {}", secs, jitter, updated, agent_ids.len()),
+        };
+        self.flash(&message, updated < agent_ids.len());
+        self.reload_agents();
+    }
+
+    pub fn handle_event(&mut self, event: AppEvent) {
+        if matches!(event, AppEvent::ExportFinished { .. } | AppEvent::ImportFinished { .. }) {
+            self.background_jobs = self.background_jobs.saturating_sub(1);
+        }
+        match event {
+            AppEvent::CommandResult(result) => {
+                let first_line = result.output.as_deref()
+                    .and_then(|o| o.lines().next())
+                    .unwrap_or("");
+                self.notice = Some(format!(
+                    "{} #{} ({}) terminé : {}",
+                    result.agent_id, result.id, result.command, first_line,
+                ));
+                let message = format!("Tâche #{} ({}) terminée", result.id, result.command);
+                self.record(Level::Info, Category::Task, Some(&result.agent_id), &message);
+            }
+            AppEvent::AgentConnected(mut agent) => {
+                self.checkins += 1;
+                self.locate_new_agent(&mut agent);
+                self.notice = Some(format!("{} ({}) connecté depuis {}", agent.id, agent.hostname, agent.ip));
+                let message = format!("Connecté depuis {} ({})", agent.ip, agent.hostname);
+                self.record(Level::Info, Category::Agent, Some(&agent.id), &message);
+                self.upsert_agent(agent);
+            }
+            AppEvent::AgentCheckin(agent) => {
+                self.checkins += 1;
+                self.upsert_agent(agent);
+            }
+            AppEvent::AgentsUpdated(ids) => {
+                for id in &ids {
+                    self.record(Level::Warn, Category::Agent, Some(id), "Passé offline faute de check-in");
+                }
+                self.request_agents();
+            }
+            AppEvent::ListenerError(error) => {
+                // Déjà journalisée par record
+                self.record(Level::Error, Category::Listener, None, &error);
+                self.error = Some(format!("Listener : {}", error));
+            }
+            AppEvent::SessionOutput { session_id, data } => {
+                self.session_output.entry(session_id).or_default().push_bytes(&data);
+            }
+            AppEvent::ScheduledTasksRun(runs) => {
+                for run in &runs {
+                    let message = match run.schedule_id {
+                        Some(schedule_id) => format!("Tâche planifiée {} : #{} {}", schedule_id, run.task_id, run.command),
+                        None => format!("Capture périodique : #{} {}", run.task_id, run.command),
+                    };
+                    self.record(Level::Info, Category::Task, Some(&run.agent_id), &message);
+                }
+                self.flash(&format!("{} tâche(s) planifiée(s) mise(s) en file", runs.len()), false);
+                self.reload_scheduler();
+            }
+            AppEvent::AgentsLoaded(generation, result) => {
+                self.agents_loading = false;
+                if generation != self.agents_generation {
+                    return;
+                }
+                match result {
+                    Ok(agents) => self.apply_agents(agents),
+                    Err(e) => self.show_error(format!("Rechargement des agents impossible : {}", e)),
+                }
+            }
+            AppEvent::Refreshed(generation, refreshed) => {
+                self.refresh_loading = false;
+                // Relecture périmée ou affichage changé depuis la demande : le prochain tick relit
+                if generation != self.refresh_generation || refreshed.query != self.visible_query() {
+                    return;
+                }
+                self.apply_refresh(*refreshed);
+            }
+            AppEvent::ExportFinished { format, path, result } => match result {
+                Ok(count) => {
+                    self.audit("export_agents", &format!("{} {} {}", format.as_str(), count, path.display()));
+                    self.flash(&format!("{} agent(s) exporté(s) vers {}", count, path.display()), false);
+                }
+                Err(e) => self.flash(&format!("Export impossible : {}", e), true),
+            },
+            AppEvent::ImportFinished { path, result } => match result {
+                Ok(summary) => {
+                    let message = import_message(&path, &summary);
+                    self.audit("import_agents", &message);
+                    self.record(Level::Info, Category::Agent, None, &message);
+                    self.flash(&message, !summary.errors.is_empty());
+                    if !summary.errors.is_empty() {
+                        self.import_errors = Some(summary.errors);
+                    }
+                    self.reload_agents();
+                }
+                Err(e) => self.flash(&format!("Import impossible : {}", e), true),
+            },
+            // Traités par la boucle principale avant d'arriver ici
+            AppEvent::Input(_) | AppEvent::Tick | AppEvent::Terminate => {}
+        }
+    }
+
+    // Relecture périodique confiée au worker ; une seule à la fois, le worker pouvant être
+    // occupé par un export
+    pub fn request_agents(&mut self) {
+        if self.agents_loading {
+            return;
+        }
+        self.agents_generation += 1;
+        if self.db_requests.send(DbRequest::LoadAgents(self.agents_generation)).is_ok() {
+            self.agents_loading = true;
+        }
+    }
+
+    // Relecture immédiate après une modification faite depuis l'interface ; une relecture
+    // du worker encore en cours devient périmée
+    pub(crate) fn reload_agents(&mut self) {
+        self.agents_generation += 1;
+        match self.db.load_agents() {
+            Ok(agents) => self.apply_agents(agents),
+            Err(e) => self.show_error(format!("Rechargement des agents impossible : {}", e)),
+        }
+    }
+
+    // Remplace la liste sans perdre la sélection ni le défilement ; rien ne bouge si elle n'a pas changé
+    pub(crate) fn apply_agents(&mut self, agents: Vec<Agent>) {
+        if agents == self.agents {
+            return;
+        }
+        let known: HashSet<&str> = self.agents.iter().map(|a| a.id.as_str()).collect();
+        let new_ids: Vec<String> = agents.iter()
+            .filter(|a| !known.contains(a.id.as_str()))
+            .map(|a| a.id.clone())
+            .collect();
+        self.unseen.extend(new_ids);
+
+        // Un agent archivé revenu en check-in retrouve ses étiquettes, notes et groupes
+        let revived = agents.iter().any(|a| !a.archived && self.agents.iter().any(|old| old.id == a.id && old.archived));
+
+        let selected = self.selected_row_key();
+        let offset = self.list_state.offset();
+        self.agents = agents;
+        let remaining: HashSet<&str> = self.agents.iter().map(|a| a.id.as_str()).collect();
+        self.multi_selection.retain(|id| remaining.contains(id.as_str()));
+        self.unseen.retain(|id| remaining.contains(id.as_str()));
+        if revived {
+            self.reload_labels();
+        } else {
+            self.prune_labels();
+        }
+        self.rebuild_order();
+        *self.list_state.offset_mut() = offset;
+        match selected {
+            Some(RowKey::Agent(id)) => self.select_agent_id(&id),
+            Some(RowKey::Header(name)) => self.select_header(&name),
+            None => self.sync_selection(),
+        }
+    }
+
+    // Remplace ou ajoute l'agent reçu d'un listener en gardant la ligne sélectionnée
+    pub(crate) fn upsert_agent(&mut self, agent: Agent) {
+        // Une liste lue avant ce check-in l'effacerait
+        self.agents_generation += 1;
+        let selected = self.selected_row_key();
+        let mut revived = false;
+        match self.agents.iter_mut().find(|a| a.id == agent.id) {
+            Some(existing) => {
+                revived = existing.archived && !agent.archived;
+                *existing = agent;
+            }
+            None => {
+                self.unseen.insert(agent.id.clone());
+                self.agents.push(agent);
+            }
+        }
+        if revived {
+            self.reload_labels();
+        }
+        self.rebuild_order();
+        match selected {
+            Some(RowKey::Agent(id)) => self.select_agent_id(&id),
+            Some(RowKey::Header(name)) => self.select_header(&name),
+            None => self.sync_selection(),
+        }
+    }
+
+    // Les lectures de ce qui est affiché partent au worker ; les self-destruct restent
+    // ici, leur suivi modifiant la liste des agents
+    pub fn on_tick(&mut self) {
+        self.advance_status_messages();
+        self.reap_listeners();
+        self.settle_self_destructs();
+        self.reload_log_viewer();
+        self.request_refresh();
+    }
+
+    // Ce qui est à l'écran, dans l'ordre du dessin : le dernier panneau dessiné recouvre
+    // les précédents et l'écran actif
+    pub(crate) fn visible_query(&self) -> RefreshQuery {
+        let view = if self.log_viewer.is_some() {
+            View::None
+        } else if self.topology_panel.is_some() {
+            View::Topology
+        } else if self.pivots_panel.is_some() {
+            View::Pivots
+        } else if let Some(panel) = &self.keylog_panel {
+            View::Keylog { agent_id: panel.agent_id.clone(), after_id: panel.last_id() }
+        } else if let Some(panel) = &self.loot_panel {
+            View::Loot(panel.agent_id.clone())
+        } else if let Some(panel) = &self.gallery_panel {
+            View::Gallery(panel.agent_id.clone())
+        } else if let Some(popup) = &self.clipboard_popup {
+            match popup.waiting() {
+                true => View::Clipboard { agent_id: popup.agent_id.clone(), task_id: popup.task_id },
+                false => View::None,
+            }
+        } else if let Some(view) = &self.task_view {
+            View::Tasks(view.agent_id.clone())
+        } else if let Some(view) = &self.detail_view {
+            View::Detail(view.agent_id.clone())
+        } else {
+            match self.active_screen {
+                AppScreen::Agents => View::Agent {
+                    agent_id: self.selected_agent().map(|a| a.id.clone()),
+                    interfaces: self.show_interfaces,
+                    window: TimeDelta::seconds(self.config.checkin_window_secs),
+                    bucket: TimeDelta::seconds(self.config.checkin_bucket_secs),
+                },
+                screen => self.screens.get(screen).map_or(View::None, |s| View::Screen(s.query())),
+            }
+        };
+        let shell = self.shell.as_ref().and_then(|id| self.shells.get(id));
+        let strip = if let Some(panel) = &self.process_panel {
+            Strip::Processes(panel.agent_id.clone())
+        } else if self.terminal.is_some() {
+            Strip::None
+        } else if let Some(shell) = shell {
+            match shell.pending().is_empty() {
+                true => Strip::None,
+                false => Strip::Shell { agent_id: shell.agent_id.clone(), pending: shell.pending().to_vec() },
+            }
+        } else {
+            Strip::RecentTasks(self.selected_agent().map(|a| a.id.clone()))
+        };
+        RefreshQuery { view, strip }
+    }
+
+    // Relecture du tick confiée au worker ; une seule à la fois, comme celle des agents
+    pub fn request_refresh(&mut self) {
+        if self.refresh_loading {
+            return;
+        }
+        self.refresh_generation += 1;
+        if self.db_requests.send(DbRequest::Refresh(self.refresh_generation, self.visible_query())).is_ok() {
+            self.refresh_loading = true;
+        }
+    }
+
+    // Relecture immédiate de tout ce qui est affiché, après un changement d'écran ou une
+    // action ; une relecture du worker encore en cours devient périmée
+    pub fn refresh_visible(&mut self) {
+        self.refresh_generation += 1;
+        let refreshed = self.visible_query().load(&self.db);
+        self.apply_refresh(refreshed);
+    }
+
+    // Comme refresh_visible, pour le seul panneau du milieu, par exemple juste après son ouverture
+    pub(crate) fn reload_view(&mut self) {
+        self.refresh_generation += 1;
+        let view = self.visible_query().view;
+        let rows = view.load(&self.db);
+        self.apply_view(&view, rows);
+    }
+
+    // Et pour le seul terminal du bas
+    pub(crate) fn reload_strip(&mut self) {
+        self.refresh_generation += 1;
+        let strip = self.visible_query().strip;
+        let rows = strip.load(&self.db);
+        self.apply_strip(&strip, rows);
+    }
+
+    pub(crate) fn apply_refresh(&mut self, refreshed: Refreshed) {
+        let Refreshed { query, view, strip, queued } = refreshed;
+        self.apply_view(&query.view, view);
+        self.apply_strip(&query.strip, strip);
+        match queued {
+            Ok(count) => self.queued_tasks = count,
+            Err(e) => self.show_error(format!("Lecture des tâches impossible : {}", e)),
+        }
+    }
+
+    fn apply_view(&mut self, view: &View, rows: error::Result<ViewRows>) {
+        let rows = match rows {
+            Ok(rows) => rows,
+            Err(e) => {
+                let subject = match view {
+                    View::None | View::Agent { .. } | View::Tasks(_) => "des tâches".to_string(),
+                    View::Screen(_) => format!("de l'écran {}", self.active_screen.label()),
+                    View::Detail(_) => "de la fiche".to_string(),
+                    View::Clipboard { .. } => "du presse-papiers".to_string(),
+                    View::Gallery(_) => "des captures".to_string(),
+                    View::Loot(_) => "des fichiers rapatriés".to_string(),
+                    View::Keylog { .. } => "du keylog".to_string(),
+                    View::Pivots => "des pivots".to_string(),
+                    View::Topology => "de la topologie".to_string(),
+                };
+                self.show_error(format!("Lecture {} impossible : {}", subject, e));
+                return;
+            }
+        };
+        match rows {
+            ViewRows::None => {}
+            ViewRows::Agent { pending, screenshot, history, interfaces } => {
+                self.pending_tasks = pending;
+                self.last_screenshot = screenshot;
+                self.checkin_history = history;
+                self.interfaces = interfaces.flatten();
+            }
+            ViewRows::Screen(rows) => {
+                if let Some(screen) = self.screens.get_mut(self.active_screen) {
+                    screen.apply(rows);
+                }
+            }
+            // Une fiche dont l'agent a disparu, supprimé depuis la ligne de commande par exemple, se ferme
+            ViewRows::Detail { pending, completed, events } => {
+                let Some(view) = self.detail_view.as_mut() else {
+                    return;
+                };
+                if !self.agents.iter().any(|a| a.id == view.agent_id) {
+                    self.detail_view = None;
+                    return;
+                }
+                view.pending_tasks = pending;
+                view.completed_tasks = completed;
+                view.events = events;
+            }
+            // La sélection reste sur la même ligne ; sans sélection, la tâche la plus récente
+            ViewRows::Tasks { tasks, uploads } => {
+                let Some(view) = self.task_view.as_mut() else {
+                    return;
+                };
+                view.tasks = tasks;
+                view.uploads = uploads;
+                let last = view.tasks.len().checked_sub(1);
+                let selected = view.state.selected().or(last).zip(last).map(|(s, l)| s.min(l));
+                view.state.select(selected);
+            }
+            ViewRows::Clipboard(task) => {
+                if let Some(popup) = self.clipboard_popup.as_mut() {
+                    popup.apply(task);
+                }
+            }
+            ViewRows::Gallery(shots) => {
+                if let Some(panel) = self.gallery_panel.as_mut() {
+                    panel.set_shots(shots);
+                }
+            }
+            ViewRows::Loot(files) => {
+                if let Some(panel) = self.loot_panel.as_mut() {
+                    panel.set_files(files);
+                }
+            }
+            ViewRows::Keylog(chunks) => {
+                if let Some(panel) = self.keylog_panel.as_mut() {
+                    panel.append(chunks);
+                }
+            }
+            ViewRows::Pivots(pivots) => {
+                if let Some(panel) = self.pivots_panel.as_mut() {
+                    panel.set_pivots(pivots, &self.agents);
+                }
+            }
+            ViewRows::Topology(edges) => {
+                if let Some(panel) = self.topology_panel.as_mut() {
+                    panel.set_edges(edges, &self.agents);
+                }
+            }
+        }
+    }
+
+    fn apply_strip(&mut self, strip: &Strip, rows: error::Result<StripRows>) {
+        match rows {
+            Ok(StripRows::None) => {}
+            Ok(StripRows::Processes(found)) => {
+                if let Some(panel) = self.process_panel.as_mut() {
+                    panel.set_list(found);
+                }
+            }
+            Ok(StripRows::Shell(replies)) => {
+                let Strip::Shell { agent_id, .. } = strip else {
+                    return;
+                };
+                if let Some(shell) = self.shells.get_mut(agent_id) {
+                    shell.apply(replies);
+                }
+            }
+            Ok(StripRows::RecentTasks(tasks)) => self.recent_tasks = tasks,
+            Err(e) => {
+                let subject = match strip {
+                    Strip::Processes(_) => "des processus",
+                    Strip::Shell { .. } => "des réponses du shell",
+                    Strip::None | Strip::RecentTasks(_) => "des tâches",
+                };
+                self.show_error(format!("Lecture {} impossible : {}", subject, e));
+            }
+        }
+    }
+
+    // Écrit le champ courant dans la base puis passe au suivant
+    pub(crate) fn commit_edit_field(&mut self) {
+        let Some(mut dialog) = self.edit_dialog.take() else {
+            return;
+        };
+        let field = dialog.field();
+        let value = dialog.input.content.trim().to_string();
+        let Some(index) = self.agents.iter().position(|a| a.id == dialog.agent_id) else {
+            return;
+        };
+        if field.is_required() && value.is_empty() {
+            dialog.error = Some(format!("{} est obligatoire", field.label()));
+            self.edit_dialog = Some(dialog);
+            return;
+        }
+        if self.agents[index].field(field).unwrap_or("") != value {
+            if let Err(e) = self.db.update_agent_field(&dialog.agent_id, field, &value) {
+                dialog.error = Some(format!("Échec de la mise à jour : {}", e));
+                self.edit_dialog = Some(dialog);
+                return;
+            }
+            self.audit("update_agent", &format!("{} {}={}", dialog.agent_id, field.label(), value));
+            let message = format!("{} modifié : {}", field.label(), value);
+            self.record(Level::Info, Category::Agent, Some(&dialog.agent_id), &message);
+            self.agents[index].set_field(field, &value);
+        }
+        dialog.error = None;
+        dialog.field_index += 1;
+        if let Some(&next) = AgentField::EDITABLE.get(dialog.field_index) {
+            dialog.input = TextInput::new(self.agents[index].field(next).unwrap_or(""));
+            self.edit_dialog = Some(dialog);
+        }
+    }
+
+    // Agents visés par une action : la sélection multiple si elle existe, sinon l'agent surligné
+    pub(crate) fn action_targets(&self) -> Vec<String> {
+        if self.multi_selection.is_empty() {
+            return self.selected_agent().map(|a| vec![a.id.clone()]).unwrap_or_default();
+        }
+        self.sorted_indices.iter()
+            .map(|&i| &self.agents[i].id)
+            .filter(|id| self.multi_selection.contains(*id))
+            .cloned()
+            .collect()
+    }
+
+    // Comme action_targets, mais un agent marqué puis masqué par le filtre n'est jamais supprimé
+    pub(crate) fn delete_targets(&self) -> Vec<String> {
+        let visible: HashSet<&str> = self.filtered_indices.iter().map(|&i| self.agents[i].id.as_str()).collect();
+        self.action_targets()
+            .into_iter()
+            .filter(|id| visible.contains(id.as_str()))
+            .collect()
+    }
+
+    pub(crate) fn toggle_mark(&mut self) {
+        if let Some(id) = self.selected_agent().map(|a| a.id.clone())
+            && !self.multi_selection.remove(&id)
+        {
+            self.multi_selection.insert(id);
+        }
+    }
+
+    pub(crate) fn self_destruct(&mut self, agent_id: &str) {
+        let grace = TimeDelta::seconds(self.config.self_destruct_grace_secs);
+        match request_self_destruct(self.db.conn(), agent_id, &self.operator, grace) {
+            Ok(_) => {
+                let message = format!("Self-destruct en file par {}, {}s pour confirmer", self.operator, grace.num_seconds());
+                self.record(Level::Warn, Category::Agent, Some(agent_id), &message);
+                self.flash(&format!("{} : {}", agent_id, message), false);
+                self.reload_agents();
+            }
+            Err(e) => self.show_error(format!("Self-destruct de {} impossible : {}", agent_id, e)),
+        }
+    }
+
+    // Réponses et échéances des self-destruct : l'agent passe dead, archivé ou supprimé
+    // s'il a répondu, selon archive_destroyed_agents
+    pub(crate) fn settle_self_destructs(&mut self) {
+        let archive = self.config.archive_destroyed_agents;
+        let outcomes = match settle_self_destructs(self.db.conn(), Utc::now(), archive) {
+            Ok(outcomes) => outcomes,
+            Err(e) => {
+                self.show_error(format!("Suivi des self-destruct impossible : {}", e));
+                return;
+            }
+        };
+        if outcomes.is_empty() {
+            return;
+        }
+        let kept = if archive { ", archivé" } else { "" };
+        let mut confirmed = Vec::new();
+        for outcome in outcomes {
+            match outcome {
+                SelfDestructOutcome::Confirmed(id) if !archive => confirmed.push(id),
+                SelfDestructOutcome::Confirmed(id) => {
+                    self.record(Level::Warn, Category::Agent, Some(&id), "Self-destruct confirmé, agent dead, archivé");
+                    self.flash(&format!("{} a confirmé le self-destruct : archivé", id), false);
+                }
+                SelfDestructOutcome::TimedOut(id) => {
+                    let message = format!("Self-destruct sans réponse, agent dead{}", kept);
+                    self.record(Level::Warn, Category::Agent, Some(&id), &message);
+                    self.flash(&format!("{} n'a pas confirmé le self-destruct : dead{}", id, kept), true);
+                }
+            }
+        }
+        if !confirmed.is_empty() {
+            self.remove_agents(&confirmed);
+        } else {
+            self.reload_agents();
+        }
+    }
+
+    // Étiquettes, notes et groupes gardés pour les seuls agents de la liste : un agent
+    // supprimé ou archivé (masqué) n'est plus ciblé par les filtres tag:, group: et note:
+    pub(crate) fn prune_labels(&mut self) {
+        let kept: HashSet<&str> = self.agents.iter()
+            .filter(|a| self.show_archived || !a.archived)
+            .map(|a| a.id.as_str())
+            .collect();
+        self.tags.retain(|id, _| kept.contains(id.as_str()));
+        self.notes.retain(|id, _| kept.contains(id.as_str()));
+        self.memberships.retain(|id, _| kept.contains(id.as_str()));
+    }
+
+    // Relues en base quand des agents archivés réapparaissent ; les archivés, eux, les gardent en base
+    pub(crate) fn reload_labels(&mut self) {
+        let loaded = load_tags(self.db.conn()).and_then(|tags| {
+            Ok((tags, load_notes(self.db.conn())?, load_memberships(self.db.conn())?))
+        });
+        match loaded {
+            Ok((tags, notes, memberships)) => {
+                self.tags = tags;
+                self.notes = notes;
+                self.memberships = memberships;
+            }
+            Err(e) => self.show_error(format!("Lecture des étiquettes impossible : {}", e)),
+        }
+        self.prune_labels();
+    }
+
+    pub(crate) fn remove_agents(&mut self, ids: &[String]) {
+        if let Err(e) = self.db.delete_agents(ids, &self.operator) {
+            self.show_error(format!("Échec de la suppression : {}", e));
+            return;
+        }
+        self.error = None;
+        self.agents.retain(|a| !ids.contains(&a.id));
+        self.prune_labels();
+        for id in ids {
+            self.record(Level::Warn, Category::Agent, Some(id), "Agent supprimé");
+            self.multi_selection.remove(id);
+            self.shells.remove(id);
+            if self.shell.as_ref() == Some(id) {
+                self.shell = None;
+            }
+        }
+        let position = self.selected_index;
+        self.rebuild_order();
+        self.selected_index = position;
+        self.sync_selection();
+    }
+
+    pub(crate) fn confirm_add_agent(&mut self) {
+        let Some(mut dialog) = self.add_dialog.take() else {
+            return;
+        };
+        if dialog.value(1).parse::<IpAddr>().is_err() {
+            let error = format!("IP invalide : {}", dialog.value(1));
+            dialog.reject(1, error);
+            self.add_dialog = Some(dialog);
+            return;
+        }
+        let agent = agent_from_form(&dialog);
+        // Relue après l'insertion pour garder en mémoire exactement la ligne stockée
+        let result = self.db.save_agent(&agent).and_then(|_| self.db.get_agent(&agent.id));
+        match result {
+            Ok(stored) => {
+                // Le champ Note du formulaire devient la première note de l'agent
+                if let Some(content) = dialog.optional(4) {
+                    match add_note(self.db.conn(), &agent.id, &content).and_then(|_| notes_for_agent(self.db.conn(), &agent.id)) {
+                        Ok(notes) => {
+                            self.notes.insert(agent.id.clone(), notes);
+                        }
+                        Err(e) => self.show_error(format!("Échec de l'enregistrement de la note : {}", e)),
+                    }
+                }
+                self.audit("add_agent", &format!("{} {} {}", agent.id, agent.hostname, agent.ip));
+                self.record(Level::Info, Category::Agent, Some(&agent.id), &format!("Agent ajouté : {} {}", agent.hostname, agent.ip));
+                self.agents.push(stored.unwrap_or(agent));
+                let id = self.agents[self.agents.len() - 1].id.clone();
+                self.rebuild_order();
+                self.select_agent_id(&id);
+            }
+            Err(e) => {
+                dialog.error = Some(format!("Échec de l'enregistrement : {}", e));
+                self.add_dialog = Some(dialog);
+            }
+        }
+    }
+}
+
+// Entrées du menu ouvert par un clic droit sur un agent
+pub(crate) const CONTEXT_ACTIONS: &[(&str, Action)] = &[
+    ("Edit", Action::Edit),
+    ("Delete", Action::Delete),
+    ("Connect", Action::Terminal),
+    ("Shell", Action::Shell),
+    ("Clipboard", Action::Clipboard),
+];
+
+// Menu posé au point du clic ; l'agent visé est déjà la sélection courante
+pub(crate) struct ContextMenu {
+    pub(crate) agent_id: String,
+    pub(crate) position: Position,
+    pub(crate) selected: usize,
+    // Zone du dernier rendu, recalée pour ne pas déborder de l'écran
+    pub(crate) area: Rect,
+}
+
+impl ContextMenu {
+    pub(crate) fn place(&mut self, frame: Rect) -> Rect {
+        let width = 14.min(frame.width);
+        let height = (CONTEXT_ACTIONS.len() as u16 + 2).min(frame.height);
+        let x = self.position.x.min(frame.right().saturating_sub(width));
+        let y = self.position.y.min(frame.bottom().saturating_sub(height));
+        self.area = Rect::new(x, y, width, height);
+        self.area
+    }
+
+    pub(crate) fn item_at(&self, column: u16, row: u16) -> Option<(&'static str, Action)> {
+        let inner = self.area.inner(Margin::new(1, 1));
+        if !inner.contains(Position::new(column, row)) {
+            return None;
+        }
+        CONTEXT_ACTIONS.get((row - inner.y) as usize).copied()
+    }
+}
+
+// Choix du champ à copier, ouvert avec Y sur l'agent sélectionné
+pub(crate) struct CopyMenu {
+    pub(crate) agent_id: String,
+    pub(crate) selected: usize,
+}
+
+impl CopyMenu {
+    pub(crate) fn field(&self) -> CopyField {
+        COPY_FIELDS[self.selected]
+    }
+}
+
+// Saisie du terminal : agents visés et rappel de l'historique avec Haut/Bas
+pub(crate) struct CommandPrompt {
+    pub(crate) agent_ids: Vec<String>,
+    pub(crate) input: TextInput,
+    pub(crate) history: Vec<String>,
+    // Position dans history pendant un rappel, None sur la ligne en cours
+    pub(crate) recall: Option<usize>,
+    // Ligne en cours de saisie, rendue quand on redescend au-delà de l'historique
+    pub(crate) draft: String,
+}
+
+impl CommandPrompt {
+    pub(crate) fn recall_previous(&mut self) {
+        let index = match self.recall {
+            None if self.history.is_empty() => return,
+            None => {
+                self.draft = self.input.content.clone();
+                self.history.len() - 1
+            }
+            Some(index) => index.saturating_sub(1),
+        };
+        self.recall = Some(index);
+        self.input = TextInput::new(&self.history[index]);
+    }
+
+    pub(crate) fn recall_next(&mut self) {
+        let Some(index) = self.recall else {
+            return;
+        };
+        if index + 1 < self.history.len() {
+            self.recall = Some(index + 1);
+            self.input = TextInput::new(&self.history[index + 1]);
+        } else {
+            self.recall = None;
+            self.input = TextInput::new(&self.draft);
+        }
+    }
+}
+
+// Commande de la palette, appelée avec le reste de la ligne après son nom
+pub(crate) type CommandFn = fn(&mut App, &str);
+
+pub(crate) const PALETTE_COMMANDS: &[(&str, CommandFn)] = &[
+    ("colorscheme", palette_colorscheme),
+    ("clipboard-write", palette_clipboard_write),
+    ("delete", palette_delete),
+    ("export", palette_export),
+    ("filter", palette_filter),
+    ("geoip", palette_geoip),
+    ("import", palette_import),
+    ("mark", palette_mark),
+    ("netinfo", palette_netinfo),
+    ("pivot", palette_pivot),
+    ("quit", palette_quit),
+    ("reload", palette_reload),
+    ("scan-reachability", palette_scan_reachability),
+    ("schedule", palette_schedule),
+    ("screenshot-interval", palette_screenshot_interval),
+    ("show-archived", palette_show_archived),
+    ("sort", palette_sort),
+    ("sysinfo", palette_sysinfo),
+];
+
+// Suggestions tant que le nom de commande n'est pas terminé par une espace
+pub(crate) fn palette_matches(input: &str) -> Vec<&'static (&'static str, CommandFn)> {
+    if input.contains(char::is_whitespace) {
+        return Vec::new();
+    }
+    PALETTE_COMMANDS.iter().filter(|(name, _)| name.starts_with(input)).collect()
+}
+
+pub(crate) fn palette_quit(app: &mut App, _: &str) {
+    app.request_quit();
+}
+
+pub(crate) fn palette_scan_reachability(app: &mut App, _: &str) {
+    app.scan_reachability();
+}
+
+pub(crate) fn palette_reload(app: &mut App, _: &str) {
+    app.reload_agents();
+    app.reload_listeners();
+    app.refresh_visible();
+}
+
+// :sort <champ> [desc] ; sans argument, retour à l'ordre de la base
+pub(crate) fn palette_sort(app: &mut App, args: &str) {
+    let mut words = args.split_whitespace();
+    let Some(field) = words.next() else {
+        app.reset_sort();
+        return;
+    };
+    let Some(key) = SortKey::from_name(field) else {
+        let names: Vec<&str> = SortKey::ALL.iter().map(|k| k.label()).collect();
+        app.error = Some(format!("Champ de tri inconnu : {} ({})", field, names.join(", ")));
+        return;
+    };
+    app.sort_key = Some(key);
+    app.sort_descending = words.next().is_some_and(|w| w.eq_ignore_ascii_case("desc"));
+    app.apply_sort();
+}
+
+// :netinfo demande les interfaces réseau des agents visés ; W les affiche dans le datasheet
+pub(crate) fn palette_netinfo(app: &mut App, _: &str) {
+    let targets = app.action_targets();
+    if targets.is_empty() {
+        app.flash("Aucun agent sélectionné", true);
+        return;
+    }
+    for agent_id in &targets {
+        match app.db.enqueue_task(agent_id, NETINFO_COMMAND) {
+            Ok(task_id) => app.audit("enqueue_task", &format!("{}: {} #{}", agent_id, NETINFO_COMMAND, task_id)),
+            Err(e) => {
+                app.show_error(format!("Échec de l'envoi : {}", e));
+                return;
+            }
+        }
+    }
+    app.flash(&format!("{} mis en file pour {} agent(s)", NETINFO_COMMAND, targets.len()), false);
+}
+
+// :sysinfo redemande la fiche système des agents visés ; la ligne de chaque agent est
+// réécrite quand sa réponse arrive
+pub(crate) fn palette_sysinfo(app: &mut App, _: &str) {
+    let targets = app.action_targets();
+    if targets.is_empty() {
+        app.flash("Aucun agent sélectionné", true);
+        return;
+    }
+    for agent_id in &targets {
+        match app.db.enqueue_task(agent_id, SYSINFO_COMMAND) {
+            Ok(task_id) => app.audit("enqueue_task", &format!("{}: {} #{}", agent_id, SYSINFO_COMMAND, task_id)),
+            Err(e) => {
+                app.show_error(format!("Échec de l'envoi : {}", e));
+                return;
+            }
+        }
+    }
+    app.flash(&format!("{} mis en file pour {} agent(s)", SYSINFO_COMMAND, targets.len()), false);
+}
+
+// Même effet qu'une recherche validée avec /
+pub(crate) fn palette_filter(app: &mut App, args: &str) {
+    app.filter = args.to_string();
+    app.refresh_filter();
+}
+
+// geoip reload : relit geoip_db, qui a pu être mise à jour, puis refait la recherche pour
+// les agents sans location ou localisés par une recherche précédente
+pub(crate) fn palette_geoip(app: &mut App, args: &str) {
+    if args != "reload" {
+        app.flash("Usage : geoip reload", true);
+        return;
+    }
+    if app.config.geoip_db.is_none() {
+        app.flash("Aucune base GeoIP : renseigner geoip_db dans scylla.toml", true);
+        return;
+    }
+    if !app.open_geoip() {
+        return;
+    }
+    let candidates = match geoip_candidates(app.db.conn()) {
+        Ok(candidates) => candidates,
+        Err(e) => {
+            app.flash(&format!("Lecture des agents impossible : {}", e), true);
+            return;
+        }
+    };
+    let found = app.locate_agents(&candidates);
+    let message = format!("GeoIP : {} agent(s) localisé(s) sur {}", found, candidates.len());
+    app.audit("geoip_reload", &message);
+    app.flash(&message, false);
+    app.reload_agents();
+}
+
+// Ajoute à la sélection multiple tous les agents portant l'étiquette, visibles ou non
+pub(crate) fn palette_mark(app: &mut App, args: &str) {
+    if args.is_empty() {
+        app.error = Some("Usage : mark <étiquette>".to_string());
+        return;
+    }
+    match agents_with_tag(app.db.conn(), args) {
+        Ok(agents) if agents.is_empty() => app.error = Some(format!("Aucun agent avec l'étiquette {}", args)),
+        Ok(agents) => {
+            app.error = None;
+            app.notice = Some(format!("{} agent(s) marqué(s) avec l'étiquette {}", agents.len(), args));
+            app.multi_selection.extend(agents.into_iter().map(|a| a.id));
+        }
+        Err(e) => app.error = Some(format!("Lecture des étiquettes impossible : {}", e)),
+    }
+}
+
+// Bascule l'affichage des agents archivés après un self-destruct
+pub(crate) fn palette_show_archived(app: &mut App, _: &str) {
+    app.show_archived = !app.show_archived;
+    if app.show_archived {
+        app.reload_labels();
+    } else {
+        app.prune_labels();
+    }
+    app.refresh_filter();
+    let shown = if app.show_archived { "affichés" } else { "masqués" };
+    app.flash(&format!("Agents archivés {}", shown), false);
+}
+
+pub(crate) fn palette_colorscheme(app: &mut App, args: &str) {
+    if args.is_empty() {
+        app.notice = Some(format!("Schémas disponibles : {}", SCHEME_NAMES.join(", ")));
+        return;
+    }
+    if ColorScheme::from_name(args).is_none() {
+        app.error = Some(format!("Schéma inconnu : {} ({})", args, SCHEME_NAMES.join(", ")));
+        return;
+    }
+    app.set_scheme(args);
+}
+
+pub(crate) fn palette_format(name: &str) -> Option<FileFormat> {
+    match name {
+        "csv" => Some(FileFormat::Csv),
+        "json" => Some(FileFormat::Json),
+        _ => None,
+    }
+}
+
+// Agents de la vue courante, filtre et tri compris : export csv|json <chemin>. L'écriture
+// se fait dans le worker, le résultat revient en ExportFinished.
+pub(crate) fn palette_export(app: &mut App, args: &str) {
+    let Some((format, path)) = args.split_once(char::is_whitespace).map(|(f, p)| (f, p.trim())) else {
+        app.flash("Usage : export csv|json <chemin>", true);
+        return;
+    };
+    let Some(format) = palette_format(format) else {
+        app.flash(&format!("Format d'export inconnu : {} (csv ou json)", format), true);
+        return;
+    };
+    // La sélection multiple l'emporte sur le filtre, sinon la vue filtrée et triée
+    let agents: Vec<Agent> = if app.multi_selection.is_empty() {
+        app.filtered_indices.iter().map(|&i| app.agents[i].clone()).collect()
+    } else {
+        app.agents.iter().filter(|a| app.multi_selection.contains(&a.id)).cloned().collect()
+    };
+    let path = std::path::PathBuf::from(path);
+    let message = format!("Export vers {} en cours…", path.display());
+    let request = DbRequest::Export { agents, notes: app.notes.clone(), format, path };
+    match app.db_requests.send(request) {
+        Ok(()) => {
+            app.background_jobs += 1;
+            app.flash(&message, false);
+        }
+        Err(_) => app.flash("Export impossible : worker de base arrêté", true),
+    }
+}
+
+// import csv|json <chemin> [--on-conflict skip|update|fail] : par défaut, une ligne déjà connue est ignorée
+pub(crate) fn palette_import(app: &mut App, args: &str) {
+    const USAGE: &str = "Usage : import csv|json <chemin> [--on-conflict skip|update|fail]";
+    let words: Vec<&str> = args.split_whitespace().collect();
+    let (format, path, on_conflict) = match words[..] {
+        [format, path] => (format, path, OnConflict::Skip),
+        [format, path, "--on-conflict", strategy] => match <OnConflict as clap::ValueEnum>::from_str(strategy, true) {
+            Ok(on_conflict) => (format, path, on_conflict),
+            Err(_) => {
+                app.flash(USAGE, true);
+                return;
+            }
+        },
+        _ => {
+            app.flash(USAGE, true);
+            return;
+        }
+    };
+    let Some(format) = palette_format(format) else {
+        app.flash(&format!("Format d'import inconnu : {} (csv ou json)", format), true);
+        return;
+    };
+    let path = std::path::PathBuf::from(path);
+    let message = format!("Import de {} en cours…", path.display());
+    match app.db_requests.send(DbRequest::Import { format, path, on_conflict }) {
+        Ok(()) => {
+            app.background_jobs += 1;
+            app.flash(&message, false);
+        }
+        Err(_) => app.flash("Import impossible : worker de base arrêté", true),
+    }
+}
+
+// schedule <agent> <cron> -- <commande> : cron à cinq champs (crontab), six ou sept avec
+// les secondes et l'année, ou un raccourci comme @hourly
+pub(crate) fn palette_schedule(app: &mut App, args: &str) {
+    const USAGE: &str = "Usage : schedule <agent> <cron> -- <commande>";
+    let Some((head, command)) = args.split_once(" -- ").map(|(h, c)| (h.trim(), c.trim())) else {
+        app.flash(USAGE, true);
+        return;
+    };
+    let Some((agent_id, schedule)) = head.split_once(char::is_whitespace).map(|(a, s)| (a, s.trim())) else {
+        app.flash(USAGE, true);
+        return;
+    };
+    if command.is_empty() {
+        app.flash(USAGE, true);
+        return;
+    }
+    if !app.agents.iter().any(|a| a.id == agent_id) {
+        app.error = Some(format!("Agent inconnu : {}", agent_id));
+        return;
+    }
+    match add_scheduled_task(app.db.conn(), agent_id, command, schedule) {
+        Ok(id) => {
+            app.audit("add_scheduled_task", &format!("{} {} [{}] {}", id, agent_id, schedule, command));
+            app.flash(&format!("Tâche planifiée {} créée pour {}", id, agent_id), false);
+            app.reload_scheduler();
+        }
+        Err(e) => app.flash(&e.to_string(), true),
+    }
+}
+
+// screenshot-interval <agent> <intervalle|off> : le planificateur met en file les captures
+pub(crate) fn palette_screenshot_interval(app: &mut App, args: &str) {
+    const USAGE: &str = "Usage : screenshot-interval <agent> <intervalle|off>";
+    let Some((agent_id, interval)) = args.split_once(char::is_whitespace).map(|(a, i)| (a, i.trim())) else {
+        app.flash(USAGE, true);
+        return;
+    };
+    let secs = if interval == "off" {
+        None
+    } else {
+        match parse_interval(interval) {
+            Some(secs) => Some(secs),
+            None => {
+                app.flash(&format!("Intervalle invalide : {} (par exemple 30, 90s, 5m ou 2h)", interval), true);
+                return;
+            }
+        }
+    };
+    match set_screenshot_interval(app.db.conn(), agent_id, secs) {
+        Ok(()) => {
+            let detail = secs.map_or("off".to_string(), |s| format!("{}s", s));
+            app.audit("set_screenshot_interval", &format!("{} {}", agent_id, detail));
+            app.record(Level::Info, Category::Agent, Some(agent_id), &format!("Captures périodiques : {}", detail));
+            app.flash(&format!("Captures de {} : {}", agent_id, detail), false);
+            app.reload_agents();
+        }
+        Err(e) => app.flash(&e.to_string(), true),
+    }
+}
+
+// pivot <relais> <adresse>:<port> : la commande part dans la file du relais
+pub(crate) fn palette_pivot(app: &mut App, args: &str) {
+    const USAGE: &str = "Usage : pivot <relais> <adresse>:<port>";
+    let Some((relay, target)) = args.split_once(char::is_whitespace) else {
+        app.flash(USAGE, true);
+        return;
+    };
+    let Some((addr, port)) = parse_target(target) else {
+        app.flash(USAGE, true);
+        return;
+    };
+    match create_pivot(app.db.conn(), relay, &addr, port) {
+        Ok(id) => {
+            app.audit("create_pivot", &format!("{} {} {}:{}", id, relay, addr, port));
+            app.record(Level::Info, Category::Agent, Some(relay), &format!("Pivot {} vers {}:{} demandé", id, addr, port));
+            app.flash(&format!("Pivot {} créé via {}", id, relay), false);
+            app.reload_view();
+        }
+        Err(e) => app.flash(&e.to_string(), true),
+    }
+}
+
+// clipboard-write <texte> : remplace le presse-papiers de l'agent sélectionné ou des agents marqués
+pub(crate) fn palette_clipboard_write(app: &mut App, args: &str) {
+    if args.is_empty() {
+        app.flash("Usage : clipboard-write <texte>", true);
+        return;
+    }
+    let targets = app.action_targets();
+    if targets.is_empty() {
+        app.flash("Aucun agent sélectionné", true);
+        return;
+    }
+    let command = ClipboardCommand::write(args);
+    for agent_id in &targets {
+        match command.enqueue(app.db.conn(), agent_id) {
+            Ok(task_id) => app.audit("clipboard_write", &format!("{} {}", task_id, agent_id)),
+            Err(e) => {
+                app.show_error(format!("Écriture du presse-papiers de {} impossible : {}", agent_id, e));
+                return;
+            }
+        }
+    }
+    app.flash(&format!("clipboard_write mis en file pour {} agent(s)", targets.len()), false);
+}
+
+// Passe par la même confirmation que d ; l'agent n'a pas besoin d'être visible
+pub(crate) fn palette_delete(app: &mut App, args: &str) {
+    if app.agents.iter().any(|a| a.id == args) {
+        app.confirm_delete = Some(vec![args.to_string()]);
+    } else {
+        app.error = Some(format!("Agent inconnu : {}", args));
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub(crate) enum MapMode {
+    Datasheet,
+    // Fiche à gauche, position de l'agent sélectionné à droite
+    Split,
+    // Tous les agents filtrés, le sélectionné en évidence
+    Map,
+}
+
+impl MapMode {
+    pub(crate) fn next(self) -> MapMode {
+        match self {
+            MapMode::Datasheet => MapMode::Split,
+            MapMode::Split => MapMode::Map,
+            MapMode::Map => MapMode::Datasheet,
+        }
+    }
+}
+
+pub(crate) struct GroupsPanel {
+    pub(crate) groups: Vec<Group>,
+    pub(crate) index: usize,
+    // Membres du groupe en surbrillance
+    pub(crate) members: Vec<String>,
+    // Agents visés par a et x : la sélection multiple ou l'agent courant à l'ouverture
+    pub(crate) targets: Vec<String>,
+    // Saisie ouverte par + pour un nouveau groupe
+    pub(crate) input: Option<TextInput>,
+}
+
+// Dernière ligne : compteurs d'agents, heure UTC, opérateur puis tri et filtre en cours
+pub(crate) const STATUS_MESSAGE_DURATION: Duration = Duration::from_secs(3);
+// Au-delà, les messages les plus anciens sont abandonnés plutôt que d'attendre leur tour
+pub(crate) const MAX_STATUS_MESSAGES: usize = 4;
+
+pub(crate) struct StatusMessage {
+    pub(crate) text: String,
+    pub(crate) error: bool,
+    // Posé quand le message arrive en tête de file
+    pub(crate) shown_at: Option<Instant>,
+}
@@ -0,0 +1,73 @@
+use rusqlite::{Connection, Result};
+
+use crate::timefmt::now_timestamp;
+
+// Opérateur attribué aux entrées reprises de operator_log, antérieures aux comptes
+fn system_user() -> String {
+    std::env::var("USER")
+        .or_else(|_| std::env::var("USERNAME"))
+        .unwrap_or_else(|_| "operator".to_string())
+}
+
+// Entrée du journal d'audit, la plus récente en premier à l'affichage
+#[derive(Debug)]
+pub struct AuditEntry {
+    pub id: i64,
+    pub timestamp: String,
+    pub operator: String,
+    pub action: String,
+    pub detail: String,
+}
+
+// Journal des actions de l'opérateur, horodaté, jamais purgé depuis l'interface.
+// Reprend les lignes de l'ancienne table operator_log si elle existe encore.
+pub fn ensure_audit_log_table(conn: &Connection) -> Result<()> {
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS audit_log (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            timestamp TEXT NOT NULL,
+            operator TEXT NOT NULL,
+            action TEXT NOT NULL,
+            detail TEXT NOT NULL DEFAULT ''
+        )",
+        [],
+    )?;
+    let legacy: i64 = conn.query_row(
+        "SELECT COUNT(*) FROM sqlite_master WHERE type = 'table' AND name = 'operator_log'",
+        [],
+        |row| row.get(0),
+    )?;
+    if legacy > 0 {
+        conn.execute(
+            "INSERT INTO audit_log (timestamp, operator, action, detail)
+             SELECT timestamp, ?1, action, COALESCE(details, '') FROM operator_log ORDER BY id",
+            [system_user()],
+        )?;
+        conn.execute("DROP TABLE operator_log", [])?;
+    }
+    Ok(())
+}
+
+pub fn log_action(conn: &Connection, operator: &str, action: &str, detail: &str) -> Result<()> {
+    conn.execute(
+        "INSERT INTO audit_log (timestamp, operator, action, detail) VALUES (?1, ?2, ?3, ?4)",
+        (now_timestamp(), operator, action, detail),
+    )?;
+    Ok(())
+}
+
+pub fn recent_audit_entries(conn: &Connection, limit: usize) -> Result<Vec<AuditEntry>> {
+    let mut stmt = conn.prepare_cached(
+        "SELECT id, timestamp, operator, action, detail FROM audit_log ORDER BY id DESC LIMIT ?1"
+    )?;
+    let rows = stmt.query_map([limit as i64], |row| {
+        Ok(AuditEntry {
+            id: row.get(0)?,
+            timestamp: row.get(1)?,
+            operator: row.get(2)?,
+            action: row.get(3)?,
+            detail: row.get(4)?,
+        })
+    })?;
+    rows.collect()
+}
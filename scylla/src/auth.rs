@@ -0,0 +1,80 @@
+use chrono::{TimeDelta, Utc};
+use rusqlite::{Connection, OptionalExtension};
+
+use crate::error::{Error, Result};
+use crate::timefmt::{format_timestamp, now_timestamp, parse_timestamp};
+
+// Échecs consécutifs tolérés avant verrouillage, et durée du verrouillage
+const MAX_FAILED_ATTEMPTS: i64 = 5;
+const LOCKOUT: TimeDelta = TimeDelta::minutes(15);
+
+// Comptes opérateur ; seul le hash bcrypt du mot de passe est stocké
+pub fn ensure_users_table(conn: &Connection) -> rusqlite::Result<()> {
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS users (
+            username TEXT PRIMARY KEY,
+            password_hash TEXT NOT NULL,
+            failed_attempts INTEGER NOT NULL DEFAULT 0,
+            locked_until TEXT,
+            created_at TEXT NOT NULL
+        )",
+        [],
+    )?;
+    Ok(())
+}
+
+pub fn has_users(conn: &Connection) -> Result<bool> {
+    let count: i64 = conn.query_row("SELECT COUNT(*) FROM users", [], |row| row.get(0))?;
+    Ok(count > 0)
+}
+
+pub fn create_user(conn: &Connection, username: &str, password: &str) -> Result<()> {
+    let hash = bcrypt::hash(password, bcrypt::DEFAULT_COST)?;
+    conn.execute(
+        "INSERT INTO users (username, password_hash, created_at) VALUES (?1, ?2, ?3)",
+        (username, hash, now_timestamp()),
+    )?;
+    Ok(())
+}
+
+// Un utilisateur inconnu est un simple refus ; un compte verrouillé est une erreur
+// pour que l'écran de connexion puisse afficher l'échéance
+pub fn authenticate(conn: &Connection, username: &str, password: &str) -> Result<bool> {
+    let row = conn
+        .query_row(
+            "SELECT password_hash, failed_attempts, locked_until FROM users WHERE username = ?1",
+            [username],
+            |row| Ok((row.get::<_, String>(0)?, row.get::<_, i64>(1)?, row.get::<_, Option<String>>(2)?)),
+        )
+        .optional()?;
+    let Some((hash, failed_attempts, locked_until)) = row else {
+        return Ok(false);
+    };
+    if let Some(until) = locked_until.as_deref().and_then(parse_timestamp)
+        && until > Utc::now()
+    {
+        return Err(Error::AccountLocked { username: username.to_string(), until: format_timestamp(until) });
+    }
+    if bcrypt::verify(password, &hash)? {
+        conn.execute(
+            "UPDATE users SET failed_attempts = 0, locked_until = NULL WHERE username = ?1",
+            [username],
+        )?;
+        return Ok(true);
+    }
+    let failed_attempts = failed_attempts + 1;
+    // Le compteur repart de zéro une fois le verrou posé
+    if failed_attempts >= MAX_FAILED_ATTEMPTS {
+        let until = format_timestamp(Utc::now() + LOCKOUT);
+        conn.execute(
+            "UPDATE users SET failed_attempts = 0, locked_until = ?2 WHERE username = ?1",
+            (username, until),
+        )?;
+    } else {
+        conn.execute(
+            "UPDATE users SET failed_attempts = ?2 WHERE username = ?1",
+            (username, failed_attempts),
+        )?;
+    }
+    Ok(false)
+}
@@ -0,0 +1,136 @@
+use chrono::{DateTime, TimeDelta, Utc};
+use rusqlite::{Connection, OptionalExtension};
+
+use crate::agent::Agent;
+use crate::db::{insert_task, write_transaction};
+use crate::error::{Error, Result};
+use crate::timefmt::parse_timestamp;
+
+// Fréquence de check-in d'un agent et gigue autour d'elle, en pourcentage de l'intervalle,
+// réglées depuis l'interface et transmises à l'implant par une tâche
+// set_beacon {"interval":<secondes>,"jitter":<pourcentage>}. Les seuils stale et dead comptent
+// des beacons manqués, chacun long de l'intervalle majoré de la gigue.
+pub const DEFAULT_BEACON_SECS: u64 = 60;
+pub const DEFAULT_JITTER_PCT: u8 = 20;
+pub const MAX_JITTER_PCT: u8 = 100;
+pub const BEACON_COMMAND: &str = "set_beacon";
+// Une semaine au plus : au-delà, un agent offline ne se distingue plus d'un agent perdu
+pub const MAX_BEACON_SECS: u64 = 7 * 86400;
+
+pub fn add_beacon_column(conn: &Connection) -> rusqlite::Result<()> {
+    conn.execute(
+        &format!("ALTER TABLE agents ADD COLUMN beacon_interval_secs INTEGER NOT NULL DEFAULT {}", DEFAULT_BEACON_SECS),
+        [],
+    )?;
+    Ok(())
+}
+
+pub fn add_beacon_jitter_column(conn: &Connection) -> rusqlite::Result<()> {
+    conn.execute("ALTER TABLE agents ADD COLUMN beacon_jitter_secs INTEGER NOT NULL DEFAULT 0", [])?;
+    Ok(())
+}
+
+// La gigue passe en pourcentage de l'intervalle : une gigue déjà réglée en secondes est
+// convertie, arrondie et plafonnée à 100 %, les autres agents prennent la valeur par défaut
+pub fn move_jitter_to_pct(conn: &Connection) -> rusqlite::Result<()> {
+    conn.execute(
+        &format!(
+            "ALTER TABLE agents ADD COLUMN jitter_pct INTEGER NOT NULL DEFAULT {} CHECK (jitter_pct BETWEEN 0 AND {})",
+            DEFAULT_JITTER_PCT, MAX_JITTER_PCT
+        ),
+        [],
+    )?;
+    conn.execute(
+        &format!(
+            "UPDATE agents SET jitter_pct = MIN({}, (beacon_jitter_secs * 100 + beacon_interval_secs / 2) / beacon_interval_secs)
+             WHERE beacon_jitter_secs > 0 AND beacon_interval_secs > 0",
+            MAX_JITTER_PCT
+        ),
+        [],
+    )?;
+    conn.execute("ALTER TABLE agents DROP COLUMN beacon_jitter_secs", [])?;
+    Ok(())
+}
+
+// "45", "45s", "5m" ou "2h" ; None pour zéro, une unité inconnue ou plus d'une semaine
+pub fn parse_interval(text: &str) -> Option<u64> {
+    let text = text.trim();
+    let (digits, unit) = match text.find(|c: char| !c.is_ascii_digit()) {
+        Some(at) => text.split_at(at),
+        None => (text, "s"),
+    };
+    let factor = match unit.trim() {
+        "s" => 1,
+        "m" => 60,
+        "h" => 3600,
+        _ => return None,
+    };
+    let secs = digits.parse::<u64>().ok()?.checked_mul(factor)?;
+    (1..=MAX_BEACON_SECS).contains(&secs).then_some(secs)
+}
+
+// "20" ou "20%" ; vide pour aucune gigue. Le plafond est vérifié par check_beacon
+pub fn parse_jitter(text: &str) -> Option<u8> {
+    match text.trim().trim_end_matches('%').trim() {
+        "" => Some(0),
+        digits => digits.parse().ok(),
+    }
+}
+
+// La gigue ne dépasse jamais l'intervalle : l'implant n'attend jamais moins de zéro seconde
+pub fn check_beacon(secs: u64, jitter_pct: u8) -> Result<()> {
+    if !(1..=MAX_BEACON_SECS).contains(&secs) {
+        return Err(Error::Beacon(format!("intervalle de {}s hors de 1s..{}s", secs, MAX_BEACON_SECS)));
+    }
+    if jitter_pct > MAX_JITTER_PCT {
+        return Err(Error::Beacon(format!("gigue de {}% hors de 0..{}%", jitter_pct, MAX_JITTER_PCT)));
+    }
+    Ok(())
+}
+
+// Tâche lue par l'implant : set_beacon {"interval":300,"jitter":20}
+pub fn beacon_task(secs: u64, jitter_pct: u8) -> String {
+    format!("{} {}", BEACON_COMMAND, serde_json::json!({ "interval": secs, "jitter": jitter_pct }))
+}
+
+// Les valeurs sont enregistrées tout de suite : ce sont elles qui fixent les seuils stale et
+// dead, même si l'implant ne reçoit la tâche qu'à son prochain check-in
+pub fn set_beacon(conn: &Connection, agent_id: &str, secs: u64, jitter_pct: u8) -> Result<i64> {
+    check_beacon(secs, jitter_pct)?;
+    // Lecture sous le verrou d'écriture : l'agent ne peut pas être supprimé entre les deux
+    let tx = write_transaction(conn)?;
+    let known: Option<String> = tx
+        .query_row("SELECT id FROM agents WHERE id = ?1", [agent_id], |row| row.get(0))
+        .optional()?;
+    if known.is_none() {
+        return Err(Error::AgentNotFound(agent_id.to_string()));
+    }
+    tx.execute(
+        "UPDATE agents SET beacon_interval_secs = ?1, jitter_pct = ?2 WHERE id = ?3",
+        (secs, jitter_pct, agent_id),
+    )?;
+    let task_id = insert_task(&tx, agent_id, &beacon_task(secs, jitter_pct))?;
+    tx.commit()?;
+    Ok(task_id)
+}
+
+// Silence au bout duquel `beacons` check-ins d'affilée sont manqués : chaque beacon dure
+// au plus interval * (1 + gigue / 100), arrondi à la seconde supérieure
+pub fn missed_after(interval_secs: u64, jitter_pct: u8, beacons: u32) -> TimeDelta {
+    let window = interval_secs.saturating_mul(100 + jitter_pct as u64).div_ceil(100);
+    TimeDelta::seconds(window.saturating_mul(beacons as u64).min(i64::MAX as u64) as i64)
+}
+
+// Un last_seen absent ou illisible ne compte pas
+pub fn missed_check_ins(agent: &Agent, beacons: u32, now: DateTime<Utc>) -> bool {
+    agent.last_seen
+        .as_deref()
+        .and_then(parse_timestamp)
+        .is_some_and(|at| now - at >= missed_after(agent.beacon_interval_secs, agent.jitter_pct, beacons))
+}
+
+// Temps restant avant le prochain check-in attendu, négatif une fois l'heure passée
+pub fn next_check_in(agent: &Agent, now: DateTime<Utc>) -> Option<TimeDelta> {
+    let at = agent.last_seen.as_deref().and_then(parse_timestamp)?;
+    Some(at + TimeDelta::seconds(agent.beacon_interval_secs as i64) - now)
+}
@@ -0,0 +1,71 @@
+use chrono::{DateTime, TimeDelta, Utc};
+use rusqlite::Connection;
+
+use crate::timefmt::{format_timestamp, now_timestamp, parse_timestamp};
+
+// Un check-in par ligne, écrit par les listeners à chaque contact d'un implant : le
+// datasheet en tire la régularité du beacon. Purgé par le thread offline.
+pub fn ensure_checkins_table(conn: &Connection) -> rusqlite::Result<()> {
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS checkins (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            agent_id TEXT NOT NULL,
+            at TEXT NOT NULL,
+            FOREIGN KEY(agent_id) REFERENCES agents(id)
+        );
+        CREATE INDEX IF NOT EXISTS checkins_by_agent ON checkins (agent_id, at);",
+    )
+}
+
+pub fn record_checkin(conn: &Connection, agent_id: &str) -> rusqlite::Result<()> {
+    conn.execute("INSERT INTO checkins (agent_id, at) VALUES (?1, ?2)", (agent_id, now_timestamp()))?;
+    Ok(())
+}
+
+// Renvoie le nombre de lignes supprimées
+pub fn prune_checkins(conn: &Connection, retention: TimeDelta) -> rusqlite::Result<usize> {
+    let cutoff = format_timestamp(Utc::now() - retention);
+    conn.execute("DELETE FROM checkins WHERE at < ?1", [cutoff])
+}
+
+pub fn remove_all_checkins(conn: &Connection, agent_id: &str) -> rusqlite::Result<()> {
+    conn.execute("DELETE FROM checkins WHERE agent_id = ?1", [agent_id])?;
+    Ok(())
+}
+
+// Nombre de check-ins par tranche de bucket sur window, la plus ancienne en premier ; la
+// dernière tranche se termine à now. Un horodatage hors de la fenêtre est ignoré.
+pub fn bucket_checkins(times: &[DateTime<Utc>], now: DateTime<Utc>, window: TimeDelta, bucket: TimeDelta) -> Vec<u64> {
+    let bucket_secs = bucket.num_seconds().max(1);
+    let window_secs = window.num_seconds().max(0);
+    let count = ((window_secs + bucket_secs - 1) / bucket_secs) as usize;
+    let mut buckets = vec![0u64; count];
+    for &at in times {
+        let age = (now - at).num_seconds();
+        if !(0..window_secs).contains(&age) {
+            continue;
+        }
+        let back = (age / bucket_secs) as usize;
+        if let Some(slot) = count.checked_sub(back + 1).and_then(|i| buckets.get_mut(i)) {
+            *slot += 1;
+        }
+    }
+    buckets
+}
+
+// Horodatages écrits par record_checkin, tous au même format : l'ordre des chaînes suit celui du temps
+pub fn checkin_history(
+    conn: &Connection,
+    agent_id: &str,
+    now: DateTime<Utc>,
+    window: TimeDelta,
+    bucket: TimeDelta,
+) -> rusqlite::Result<Vec<u64>> {
+    let mut stmt = conn.prepare_cached("SELECT at FROM checkins WHERE agent_id = ?1 AND at >= ?2")?;
+    let rows = stmt.query_map((agent_id, format_timestamp(now - window)), |row| row.get::<_, String>(0))?;
+    let mut times = Vec::new();
+    for at in rows {
+        times.extend(parse_timestamp(&at?));
+    }
+    Ok(bucket_checkins(&times, now, window, bucket))
+}
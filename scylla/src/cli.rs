@@ -0,0 +1,211 @@
+use std::path::{Path, PathBuf};
+
+use clap::{Parser, Subcommand, ValueEnum};
+
+use crate::import::OnConflict;
+
+// Rechargement périodique de la liste, en ticks de la boucle principale (tick_rate_ms, 200 ms par défaut)
+pub const DEFAULT_REFRESH_TICKS: u32 = 25;
+
+#[derive(Debug, Parser)]
+#[command(name = "scylla", about = "Interface de suivi des agents")]
+pub struct Cli {
+    /// Base SQLite à utiliser (sinon $SCYLLA_DB, db_path de la configuration, puis ~/.local/share/scylla/c2.db)
+    #[arg(long, value_name = "PATH")]
+    pub db: Option<PathBuf>,
+    /// Fichier de configuration (par défaut ~/.config/scylla/scylla.toml)
+    #[arg(long, value_name = "PATH")]
+    pub config: Option<PathBuf>,
+    /// Crée la base avec le schéma attendu si elle n'existe pas
+    #[arg(long)]
+    pub init: bool,
+    /// Beacons manqués avant qu'un agent en ligne soit affiché stale (sinon stale_after_beacons, 3)
+    #[arg(long, value_name = "BEACONS")]
+    pub stale_after: Option<u32>,
+    /// Beacons manqués avant qu'un agent en ligne soit affiché dead puis passé offline (sinon dead_after_beacons, 6)
+    #[arg(long, value_name = "BEACONS")]
+    pub dead_after: Option<u32>,
+    /// Ticks entre deux rechargements de la liste ; 0 désactive le rechargement
+    #[arg(long, value_name = "TICKS", default_value_t = DEFAULT_REFRESH_TICKS)]
+    pub refresh_ticks: u32,
+    /// Affiche la configuration effective au format TOML et quitte
+    #[arg(long)]
+    pub dump_config: bool,
+    /// Sorties sans couleurs ANSI, comme lorsque stdout n'est pas un terminal ou que NO_COLOR est défini
+    #[arg(long, global = true)]
+    pub no_color: bool,
+    #[command(subcommand)]
+    pub command: Option<Command>,
+}
+
+// Sans sous-commande, scylla ouvre l'interface
+#[derive(Debug, Subcommand)]
+pub enum Command {
+    /// Ouvre l'interface (comportement par défaut)
+    Tui,
+    /// Consulte ou supprime les agents directement dans la base
+    Agents {
+        #[command(subcommand)]
+        action: AgentsCommand,
+    },
+    /// Exporte tous les agents de la base sans ouvrir l'interface
+    Export {
+        #[arg(long, value_enum, default_value_t = FileFormat::Csv)]
+        format: FileFormat,
+        /// Fichier de sortie, écrasé s'il existe
+        #[arg(long, value_name = "PATH")]
+        out: PathBuf,
+    },
+    /// Ajoute à la base les agents d'un fichier CSV ou JSON
+    Import {
+        #[arg(long, value_name = "PATH")]
+        file: PathBuf,
+        /// Sinon déduit de l'extension : .json, tout le reste est lu comme du CSV
+        #[arg(long, value_enum)]
+        format: Option<FileFormat>,
+        /// Ligne dont l'id ou le couple hostname + ip existe déjà
+        #[arg(long, value_enum, default_value_t = OnConflict::Skip)]
+        on_conflict: OnConflict,
+    },
+}
+
+#[derive(Debug, Subcommand)]
+pub enum AgentsCommand {
+    /// Tableau des agents, ou tableau JSON avec --json
+    List {
+        /// Statut affiché, après déclassement en stale ou dead (online, offline, stale…)
+        #[arg(long, value_name = "STATUS")]
+        status: Option<String>,
+        #[arg(long)]
+        json: bool,
+    },
+    /// Fiche d'un agent avec ses étiquettes et ses notes
+    Show { id: String },
+    /// Supprime un agent, ses commandes, étiquettes et notes
+    Delete { id: String },
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum FileFormat {
+    Csv,
+    Json,
+}
+
+impl FileFormat {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            FileFormat::Csv => "csv",
+            FileFormat::Json => "json",
+        }
+    }
+
+    pub fn of_path(path: &Path) -> FileFormat {
+        match path.extension().and_then(|e| e.to_str()) {
+            Some(ext) if ext.eq_ignore_ascii_case("json") => FileFormat::Json,
+            _ => FileFormat::Csv,
+        }
+    }
+}
+
+// Répertoire XDG (données ou configuration) : la variable si elle est absolue,
+// sinon le sous-répertoire conventionnel de HOME
+fn xdg_dir(xdg_var: Option<&str>, home: Option<&str>, home_subdir: &str) -> Option<PathBuf> {
+    // La spécification XDG ignore une valeur relative
+    xdg_var
+        .map(PathBuf::from)
+        .filter(|p| p.is_absolute())
+        .or_else(|| home.filter(|h| !h.is_empty()).map(|h| Path::new(h).join(home_subdir)))
+}
+
+// Ordre de priorité : --db, puis SCYLLA_DB, puis db_path de la configuration, puis le
+// répertoire de données XDG. Sans HOME ni XDG_DATA_HOME, on retombe sur c2.db dans le
+// répertoire courant.
+pub fn resolve_db_path(
+    flag: Option<&Path>,
+    env_db: Option<&str>,
+    config_db: Option<&Path>,
+    xdg_data_home: Option<&str>,
+    home: Option<&str>,
+) -> PathBuf {
+    if let Some(path) = flag {
+        return path.to_path_buf();
+    }
+    if let Some(path) = env_db.filter(|p| !p.is_empty()) {
+        return PathBuf::from(path);
+    }
+    if let Some(path) = config_db {
+        return path.to_path_buf();
+    }
+    match xdg_dir(xdg_data_home, home, ".local/share") {
+        Some(dir) => dir.join("scylla").join("c2.db"),
+        None => PathBuf::from("c2.db"),
+    }
+}
+
+// --config, sinon scylla.toml dans le répertoire de configuration XDG ou le répertoire courant
+pub fn resolve_config_path(flag: Option<&Path>, xdg_config_home: Option<&str>, home: Option<&str>) -> PathBuf {
+    if let Some(path) = flag {
+        return path.to_path_buf();
+    }
+    match xdg_dir(xdg_config_home, home, ".config") {
+        Some(dir) => dir.join("scylla").join("scylla.toml"),
+        None => PathBuf::from("scylla.toml"),
+    }
+}
+
+fn var(name: &str) -> Option<String> {
+    std::env::var(name).ok()
+}
+
+impl Cli {
+    pub fn config_path(&self) -> PathBuf {
+        resolve_config_path(self.config.as_deref(), var("XDG_CONFIG_HOME").as_deref(), var("HOME").as_deref())
+    }
+
+    pub fn db_path(&self, config_db: Option<&Path>) -> PathBuf {
+        resolve_db_path(
+            self.db.as_deref(),
+            var("SCYLLA_DB").as_deref(),
+            config_db,
+            var("XDG_DATA_HOME").as_deref(),
+            var("HOME").as_deref(),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn db_path_precedence_is_flag_env_config_default() {
+        let flag = Path::new("/srv/flag.db");
+        let config = Path::new("/srv/config.db");
+        let resolve = |flag, env, config| resolve_db_path(flag, env, config, None, Some("/home/alice"));
+        assert_eq!(resolve(Some(flag), Some("/srv/env.db"), Some(config)), PathBuf::from("/srv/flag.db"));
+        assert_eq!(resolve(None, Some("/srv/env.db"), Some(config)), PathBuf::from("/srv/env.db"));
+        assert_eq!(resolve(None, None, Some(config)), PathBuf::from("/srv/config.db"));
+        assert_eq!(resolve(None, None, None), PathBuf::from("/home/alice/.local/share/scylla/c2.db"));
+        // SCYLLA_DB= vide compte comme absente
+        assert_eq!(resolve(None, Some(""), Some(config)), PathBuf::from("/srv/config.db"));
+    }
+
+    #[test]
+    fn default_db_path_follows_xdg() {
+        let default = |xdg, home| resolve_db_path(None, None, None, xdg, home);
+        assert_eq!(default(Some("/data"), Some("/home/alice")), PathBuf::from("/data/scylla/c2.db"));
+        // Une valeur relative est ignorée, comme le veut la spécification
+        assert_eq!(default(Some("data"), Some("/home/alice")), PathBuf::from("/home/alice/.local/share/scylla/c2.db"));
+        assert_eq!(default(None, Some("")), PathBuf::from("c2.db"));
+        assert_eq!(default(None, None), PathBuf::from("c2.db"));
+    }
+
+    #[test]
+    fn config_path_falls_back_to_xdg_then_the_working_directory() {
+        let flag = Path::new("/etc/scylla.toml");
+        assert_eq!(resolve_config_path(Some(flag), Some("/cfg"), Some("/home/alice")), PathBuf::from("/etc/scylla.toml"));
+        assert_eq!(resolve_config_path(None, Some("/cfg"), Some("/home/alice")), PathBuf::from("/cfg/scylla/scylla.toml"));
+        assert_eq!(resolve_config_path(None, None, Some("/home/alice")), PathBuf::from("/home/alice/.config/scylla/scylla.toml"));
+        assert_eq!(resolve_config_path(None, None, None), PathBuf::from("scylla.toml"));
+    }
+}
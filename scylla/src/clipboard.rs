@@ -0,0 +1,275 @@
+use std::fmt;
+use std::io::{self, IsTerminal, Write};
+use std::process::{Command, Stdio};
+
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use crossterm::event::{KeyCode, KeyEvent};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Clear, Paragraph, Wrap};
+use ratatui::Frame;
+use rusqlite::{Connection, OptionalExtension};
+
+use crate::agent::Agent;
+use crate::crypto::open_payload_text;
+use crate::db::insert_task;
+use crate::error::{Error, Result};
+use crate::theme::ColorScheme;
+use crate::ui::centered_rect;
+
+// Champ de l'agent sélectionné copié avec y (l'IP) ou choisi avec Y
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CopyField {
+    Id,
+    Hostname,
+    Ip,
+    // Ligne complète en TSV, dans l'ordre des colonnes de scylla agents list
+    Row,
+}
+
+pub const COPY_FIELDS: &[CopyField] = &[CopyField::Id, CopyField::Hostname, CopyField::Ip, CopyField::Row];
+
+impl CopyField {
+    pub fn label(self) -> &'static str {
+        match self {
+            CopyField::Id => "ID",
+            CopyField::Hostname => "Hostname",
+            CopyField::Ip => "IP",
+            CopyField::Row => "Ligne (TSV)",
+        }
+    }
+
+    pub fn value(self, agent: &Agent) -> String {
+        match self {
+            CopyField::Id => agent.id.clone(),
+            CopyField::Hostname => agent.hostname.clone(),
+            CopyField::Ip => agent.ip.clone(),
+            CopyField::Row => {
+                let cells = [
+                    agent.id.as_str(),
+                    agent.hostname.as_str(),
+                    agent.ip.as_str(),
+                    agent.os.as_deref().unwrap_or(""),
+                    agent.status.as_str(),
+                    agent.last_seen.as_deref().unwrap_or(""),
+                    agent.location.as_deref().unwrap_or(""),
+                ];
+                // Une tabulation ou un retour dans un champ décalerait les colonnes
+                let cells: Vec<String> = cells.iter().map(|c| c.replace(['\t', '\n', '\r'], " ")).collect();
+                cells.join("\t")
+            }
+        }
+    }
+}
+
+// Moyen qui a reçu le texte, rappelé dans la confirmation
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CopyMethod {
+    Tool(&'static str),
+    Osc52,
+}
+
+impl fmt::Display for CopyMethod {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CopyMethod::Tool(name) => f.write_str(name),
+            CopyMethod::Osc52 => f.write_str("OSC 52"),
+        }
+    }
+}
+
+// Outils du presse-papiers système, essayés selon l'affichage présent
+fn clipboard_tools() -> Vec<(&'static str, &'static [&'static str])> {
+    let has_env = |name: &str| std::env::var_os(name).is_some_and(|v| !v.is_empty());
+    let mut tools: Vec<(&'static str, &'static [&'static str])> = Vec::new();
+    if cfg!(target_os = "macos") {
+        tools.push(("pbcopy", &[]));
+    }
+    if has_env("WAYLAND_DISPLAY") {
+        tools.push(("wl-copy", &[]));
+    }
+    if has_env("DISPLAY") {
+        tools.push(("xclip", &["-selection", "clipboard"]));
+        tools.push(("xsel", &["--clipboard", "--input"]));
+    }
+    tools
+}
+
+// Outil absent ou en échec : on passe au suivant
+fn copy_with_tool(program: &str, args: &[&str], text: &str) -> io::Result<bool> {
+    let mut child = Command::new(program)
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()?;
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin.write_all(text.as_bytes())?;
+    }
+    Ok(child.wait()?.success())
+}
+
+// Séquence OSC 52, que le terminal de l'opérateur copie même à travers SSH ; sous tmux,
+// elle doit traverser le multiplexeur
+fn osc52(text: &str) -> String {
+    let sequence = format!("\x1b]52;c;{}\x07", STANDARD.encode(text));
+    if std::env::var_os("TMUX").is_some() {
+        format!("\x1bPtmux;{}\x1b\\", sequence.replace('\x1b', "\x1b\x1b"))
+    } else {
+        sequence
+    }
+}
+
+// Presse-papiers du système d'abord, sinon OSC 52 écrit sur terminal : la sortie de
+// l'interface elle-même, entre deux rendus, pour ne pas se mêler à un dessin en cours
+pub fn copy_to_clipboard(text: &str, terminal: &mut impl Write) -> Result<CopyMethod> {
+    for (program, args) in clipboard_tools() {
+        if let Ok(true) = copy_with_tool(program, args, text) {
+            return Ok(CopyMethod::Tool(program));
+        }
+    }
+    let dumb = std::env::var("TERM").map_or(true, |term| term.is_empty() || term == "dumb");
+    if dumb || !io::stdout().is_terminal() {
+        return Err(Error::Clipboard("ni outil système (wl-copy, xclip, xsel) ni terminal pour OSC 52".to_string()));
+    }
+    terminal.write_all(osc52(text).as_bytes())?;
+    terminal.flush()?;
+    Ok(CopyMethod::Osc52)
+}
+
+// Presse-papiers de l'agent, par une tâche comme une autre : clipboard_read rend le
+// texte dans la sortie, clipboard_write <texte> le remplace
+pub const CLIPBOARD_READ_COMMAND: &str = "clipboard_read";
+pub const CLIPBOARD_WRITE_COMMAND: &str = "clipboard_write";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ClipboardAction {
+    Read,
+    Write(String),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ClipboardCommand {
+    pub action: ClipboardAction,
+    // Texte rendu par l'agent pour une lecture, une fois la tâche terminée
+    pub data: Option<String>,
+}
+
+impl ClipboardCommand {
+    pub fn read() -> ClipboardCommand {
+        ClipboardCommand { action: ClipboardAction::Read, data: None }
+    }
+
+    pub fn write(text: &str) -> ClipboardCommand {
+        ClipboardCommand { action: ClipboardAction::Write(text.to_string()), data: None }
+    }
+
+    pub fn command_line(&self) -> String {
+        match &self.action {
+            ClipboardAction::Read => CLIPBOARD_READ_COMMAND.to_string(),
+            ClipboardAction::Write(text) => format!("{} {}", CLIPBOARD_WRITE_COMMAND, text),
+        }
+    }
+
+    pub fn enqueue(&self, conn: &Connection, agent_id: &str) -> rusqlite::Result<i64> {
+        insert_task(conn, agent_id, &self.command_line())
+    }
+}
+
+pub enum ClipboardOutcome {
+    Pending,
+    Closed,
+    // Texte reçu, à copier dans le presse-papiers local
+    Copy(String),
+}
+
+// Statut de la tâche clipboard_read, avec le texte déchiffré une fois qu'elle est terminée
+pub fn clipboard_task(conn: &Connection, agent_id: &str, task_id: i64) -> rusqlite::Result<Option<(String, Option<String>)>> {
+    let task: Option<(String, Option<String>)> = conn
+        .query_row("SELECT status, output FROM tasks WHERE id = ?1", [task_id], |row| Ok((row.get(0)?, row.get(1)?)))
+        .optional()?;
+    let Some((status, output)) = task else {
+        return Ok(None);
+    };
+    if !matches!(status.as_str(), "done" | "failed") {
+        return Ok(Some((status, None)));
+    }
+    let text = open_payload_text(conn, agent_id, output.unwrap_or_default())?;
+    Ok(Some((status, Some(text))))
+}
+
+// Fenêtre ouverte par l'action Clipboard : suit la tâche clipboard_read, relue à chaque
+// tick, jusqu'à ce que l'agent rende le texte
+pub struct ClipboardPopup {
+    pub agent_id: String,
+    pub task_id: i64,
+    command: ClipboardCommand,
+    status: String,
+    scroll: u16,
+}
+
+impl ClipboardPopup {
+    pub fn new(agent_id: &str, task_id: i64) -> ClipboardPopup {
+        ClipboardPopup {
+            agent_id: agent_id.to_string(),
+            task_id,
+            command: ClipboardCommand::read(),
+            status: "pending".to_string(),
+            scroll: 0,
+        }
+    }
+
+    pub fn text(&self) -> Option<&str> {
+        self.command.data.as_deref()
+    }
+
+    // Plus rien à relire une fois le texte reçu
+    pub fn waiting(&self) -> bool {
+        self.command.data.is_none()
+    }
+
+    pub fn apply(&mut self, task: Option<(String, Option<String>)>) {
+        let Some((status, text)) = task.filter(|_| self.waiting()) else {
+            return;
+        };
+        self.command.data = text;
+        self.status = status;
+    }
+
+    pub fn handle_key(&mut self, key: KeyEvent) -> ClipboardOutcome {
+        match key.code {
+            KeyCode::Esc | KeyCode::Char('q') | KeyCode::Enter => return ClipboardOutcome::Closed,
+            KeyCode::Down | KeyCode::Char('j') => self.scroll = self.scroll.saturating_add(1),
+            KeyCode::Up | KeyCode::Char('k') => self.scroll = self.scroll.saturating_sub(1),
+            KeyCode::Char('y') if self.status == "done" => {
+                if let Some(text) = self.text() {
+                    return ClipboardOutcome::Copy(text.to_string());
+                }
+            }
+            _ => {}
+        }
+        ClipboardOutcome::Pending
+    }
+}
+
+pub fn draw_clipboard_popup(f: &mut Frame, popup: &ClipboardPopup, scheme: &ColorScheme) {
+    let area = centered_rect(60, 40, f.area());
+    f.render_widget(Clear, area);
+    let block = scheme.block(format!(
+        "Presse-papiers de {} (#{}) — y: copier, Échap",
+        popup.agent_id,
+        popup.task_id,
+    ));
+    let paragraph = match popup.text() {
+        Some(text) if popup.status == "failed" => {
+            Paragraph::new(Line::from(Span::styled(format!("Échec : {}", text), scheme.error())))
+        }
+        Some("") => Paragraph::new(Line::from(Span::styled("Presse-papiers vide", scheme.dim()))),
+        Some(text) => Paragraph::new(text.to_string()).scroll((popup.scroll, 0)),
+        None => Paragraph::new(Line::from(Span::styled(
+            format!("En attente de l'agent ({})…", popup.status),
+            scheme.dim(),
+        ))),
+    };
+    f.render_widget(paragraph.wrap(Wrap { trim: false }).block(block), area);
+}
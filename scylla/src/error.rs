@@ -0,0 +1,70 @@
+use thiserror::Error;
+
+// Erreurs de la couche base et des transferts, affichées telles quelles dans l'interface
+#[derive(Debug, Error)]
+pub enum Error {
+    #[error("impossible d'ouvrir {path} : {source}")]
+    Open {
+        path: String,
+        #[source]
+        source: rusqlite::Error,
+    },
+    #[error("{0} introuvable (relancer avec --init pour la créer)")]
+    DatabaseMissing(String),
+    #[error("base au schéma v{found}, cette version de scylla ne connaît que v{supported}")]
+    SchemaTooNew { found: i64, supported: i64 },
+    #[error("configuration {path} invalide : {source}")]
+    Config {
+        path: String,
+        #[source]
+        source: toml::de::Error,
+    },
+    #[error("{0}")]
+    Sqlite(#[from] rusqlite::Error),
+    #[error("{0}")]
+    Io(#[from] std::io::Error),
+    #[error("agent {0} introuvable")]
+    AgentNotFound(String),
+    #[error("{0}")]
+    Session(String),
+    #[error("{0}")]
+    Bcrypt(#[from] bcrypt::BcryptError),
+    #[cfg(feature = "geoip")]
+    #[error("base GeoIP {path} illisible : {source}")]
+    GeoIp {
+        path: String,
+        #[source]
+        source: maxminddb::MaxMindDbError,
+    },
+    #[cfg(not(feature = "geoip"))]
+    #[error("base GeoIP {0} ignorée : Scylla est compilé sans la feature geoip")]
+    GeoIpDisabled(String),
+    #[error("tâche {0} introuvable")]
+    TaskNotFound(i64),
+    #[error("capture refusée : {0}")]
+    Screenshot(String),
+    #[error("téléchargement refusé : {0}")]
+    Download(String),
+    #[error("envoi refusé : {0}")]
+    Upload(String),
+    #[error("presse-papiers indisponible : {0}")]
+    Clipboard(String),
+    #[error("planification « {schedule} » invalide : {reason}")]
+    Schedule { schedule: String, reason: String },
+    #[error("planification « {0} » sans échéance à venir")]
+    ScheduleExhausted(String),
+    #[error("{0}")]
+    Crypto(String),
+    #[error("liste de processus illisible : {0}")]
+    ProcessList(String),
+    #[error("beacon refusé : {0}")]
+    Beacon(String),
+    #[error("interfaces réseau illisibles : {0}")]
+    NetInfo(String),
+    #[error("fiche système illisible : {0}")]
+    SystemInfo(String),
+    #[error("compte {username} verrouillé jusqu'à {until}")]
+    AccountLocked { username: String, until: String },
+}
+
+pub type Result<T> = std::result::Result<T, Error>;
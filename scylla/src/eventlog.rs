@@ -0,0 +1,137 @@
+use rusqlite::{Connection, Result, Row};
+
+use crate::db::Db;
+use crate::timefmt::now_timestamp;
+
+// Gravité d'un événement ; le filtre de l'écran Logs garde un niveau et les suivants
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Level {
+    Info,
+    Warn,
+    Error,
+}
+
+impl Level {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Level::Info => "info",
+            Level::Warn => "warn",
+            Level::Error => "error",
+        }
+    }
+
+    // Une valeur inconnue en base est lue comme info plutôt que de masquer la ligne
+    fn parse(value: &str) -> Level {
+        match value {
+            "warn" => Level::Warn,
+            "error" => Level::Error,
+            _ => Level::Info,
+        }
+    }
+
+    fn severity(self) -> i64 {
+        self as i64
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Category {
+    Agent,
+    Task,
+    Listener,
+    Session,
+    Transfer,
+}
+
+impl Category {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Category::Agent => "agent",
+            Category::Task => "task",
+            Category::Listener => "listener",
+            Category::Session => "session",
+            Category::Transfer => "transfer",
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct EventEntry {
+    pub id: i64,
+    pub timestamp: String,
+    pub level: Level,
+    pub category: String,
+    pub agent_id: Option<String>,
+    pub message: String,
+}
+
+// Ce qui s'est passé, là où audit_log garde qui l'a fait
+pub fn ensure_events_table(conn: &Connection) -> Result<()> {
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS events (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            timestamp TEXT NOT NULL,
+            level TEXT NOT NULL,
+            category TEXT NOT NULL,
+            agent_id TEXT,
+            message TEXT NOT NULL
+        )",
+        [],
+    )?;
+    Ok(())
+}
+
+// Seul point d'écriture du journal, pour que toutes les lignes aient la même forme
+pub fn log_event(db: &Db, level: Level, category: Category, agent_id: Option<&str>, message: &str) -> Result<()> {
+    db.conn().execute(
+        "INSERT INTO events (timestamp, level, category, agent_id, message) VALUES (?1, ?2, ?3, ?4, ?5)",
+        (now_timestamp(), level.as_str(), category.as_str(), agent_id, message),
+    )?;
+    Ok(())
+}
+
+const EVENT_COLUMNS: &str = "id, timestamp, level, category, agent_id, message";
+
+// Rang SQL du niveau, pour filtrer sans dépendre de l'ordre alphabétique
+const SEVERITY: &str = "CASE level WHEN 'error' THEN 2 WHEN 'warn' THEN 1 ELSE 0 END";
+
+fn event_from_row(row: &Row) -> Result<EventEntry> {
+    Ok(EventEntry {
+        id: row.get(0)?,
+        timestamp: row.get(1)?,
+        level: Level::parse(&row.get::<_, String>(2)?),
+        category: row.get(3)?,
+        agent_id: row.get(4)?,
+        message: row.get(5)?,
+    })
+}
+
+// Les plus récents en premier
+pub fn recent_events(conn: &Connection, min_level: Level, limit: usize) -> Result<Vec<EventEntry>> {
+    let mut stmt = conn.prepare_cached(&format!(
+        "SELECT {} FROM events WHERE {} >= ?1 ORDER BY id DESC LIMIT ?2",
+        EVENT_COLUMNS, SEVERITY,
+    ))?;
+    let rows = stmt.query_map((min_level.severity(), limit as i64), event_from_row)?;
+    rows.collect()
+}
+
+// Derniers événements d'un agent, les plus récents en premier
+pub fn events_for_agent(conn: &Connection, agent_id: &str, limit: usize) -> Result<Vec<EventEntry>> {
+    let mut stmt = conn.prepare_cached(&format!(
+        "SELECT {} FROM events WHERE agent_id = ?1 ORDER BY id DESC LIMIT ?2",
+        EVENT_COLUMNS,
+    ))?;
+    let rows = stmt.query_map((agent_id, limit as i64), event_from_row)?;
+    rows.collect()
+}
+
+// Suite du journal après after_id, la plus ancienne en premier
+pub fn events_after(conn: &Connection, after_id: i64, min_level: Level, limit: usize) -> Result<Vec<EventEntry>> {
+    let mut stmt = conn.prepare_cached(&format!(
+        "SELECT {} FROM events WHERE id > ?1 AND {} >= ?2 ORDER BY id LIMIT ?3",
+        EVENT_COLUMNS, SEVERITY,
+    ))?;
+    let rows = stmt.query_map((after_id, min_level.severity(), limit as i64), event_from_row)?;
+    rows.collect()
+}
@@ -0,0 +1,295 @@
+use std::path::PathBuf;
+use std::sync::mpsc::Sender;
+use std::sync::{Arc, Condvar, Mutex, PoisonError};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+use chrono::{TimeDelta, Utc};
+use crossterm::event::{self, Event};
+use rusqlite::{Connection, Result};
+use tracing::{error, warn};
+
+use crate::cli::FileFormat;
+use crate::agent::{agent_from_row, Agent, AGENT_COLUMNS};
+use crate::beacon::missed_check_ins;
+use crate::checkins::prune_checkins;
+use crate::crypto::open_payload_text;
+use crate::db::connect;
+use crate::error;
+use crate::import::ImportSummary;
+use crate::refresh::Refreshed;
+use crate::scheduler::{run_auto_screenshots, run_due_tasks, ScheduledRun};
+
+const POLL_INTERVAL: Duration = Duration::from_secs(2);
+// Le délai avant offline suit le beacon de chaque agent, souvent de quelques minutes
+const OFFLINE_SWEEP_INTERVAL: Duration = Duration::from_secs(15);
+const SCHEDULER_INTERVAL: Duration = Duration::from_secs(30);
+// Délai maximal avant que le thread de saisie remarque l'arrêt
+const INPUT_POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+// Signal d'arrêt partagé par les threads d'arrière-plan : leur attente entre deux cycles
+// est interrompue dès qu'il est déclenché
+#[derive(Clone, Default)]
+pub struct Shutdown(Arc<(Mutex<bool>, Condvar)>);
+
+impl Shutdown {
+    pub fn trigger(&self) {
+        let (stopped, condvar) = &*self.0;
+        *stopped.lock().unwrap_or_else(PoisonError::into_inner) = true;
+        condvar.notify_all();
+    }
+
+    // Attend au plus timeout ; vrai si l'arrêt est demandé
+    pub fn wait(&self, timeout: Duration) -> bool {
+        let (stopped, condvar) = &*self.0;
+        let guard = stopped.lock().unwrap_or_else(PoisonError::into_inner);
+        let (guard, _) = condvar
+            .wait_timeout_while(guard, timeout, |stopped| !*stopped)
+            .unwrap_or_else(PoisonError::into_inner);
+        *guard
+    }
+}
+
+// Résultat d'une commande terminée, remonté par le thread de poll
+#[derive(Debug)]
+pub struct CommandResult {
+    pub id: i64,
+    pub agent_id: String,
+    pub command: String,
+    pub output: Option<String>,
+}
+
+// Événements envoyés à la boucle principale depuis les threads d'arrière-plan
+#[derive(Debug)]
+pub enum AppEvent {
+    CommandResult(CommandResult),
+    // Agent enregistré ou reconnecté via un listener TCP
+    AgentConnected(Agent),
+    // Check-in HTTP : seul last_seen change, pas de notification
+    AgentCheckin(Agent),
+    ListenerError(String),
+    // Agents passés offline faute de check-in ; la liste doit être relue
+    AgentsUpdated(Vec<String>),
+    // Octets reçus sur une session TCP
+    SessionOutput { session_id: String, data: Vec<u8> },
+    // Tâches mises en file par le planificateur
+    ScheduledTasksRun(Vec<ScheduledRun>),
+    // Touche, souris ou redimensionnement lu par le thread de saisie
+    Input(Event),
+    // Cadence de l'interface, indépendante de la durée des requêtes en cours
+    Tick,
+    // SIGTERM ou SIGINT : sortie propre sans confirmation, pour un arrêt par systemd
+    Terminate,
+    // Réponses du worker de base, la génération écartant les listes périmées
+    AgentsLoaded(u64, error::Result<Vec<Agent>>),
+    Refreshed(u64, Box<Refreshed>),
+    ExportFinished { format: FileFormat, path: PathBuf, result: std::io::Result<usize> },
+    ImportFinished { path: PathBuf, result: error::Result<ImportSummary> },
+}
+
+// Connexion propre à un thread d'arrière-plan ; sans elle le thread s'arrête aussitôt
+fn open_thread_connection(conn_path: &str, thread: &str) -> Option<Connection> {
+    match connect(conn_path) {
+        Ok(conn) => Some(conn),
+        Err(e) => {
+            error!("thread {} arrêté : ouverture de {} impossible : {}", thread, conn_path, e);
+            None
+        }
+    }
+}
+
+// Une tâche indéchiffrable est journalisée et marquée vue : elle ne bloque pas les suivantes
+// et n'est pas relue à chaque passage
+fn take_completed(conn: &Connection) -> Result<Vec<CommandResult>> {
+    let mut stmt = conn.prepare(
+        "SELECT id, agent_id, command, output FROM tasks WHERE status = 'done' AND seen = 0"
+    )?;
+    let rows = stmt
+        .query_map([], |row| Ok((row.get::<_, i64>(0)?, row.get::<_, String>(1)?, row.get(2)?, row.get(3)?)))?
+        .collect::<Result<Vec<(i64, String, String, Option<String>)>>>()?;
+    let mut results = Vec::new();
+    for (id, agent_id, command, output) in rows {
+        let opened = open_payload_text(conn, &agent_id, command)
+            .and_then(|command| Ok((command, output.map(|o| open_payload_text(conn, &agent_id, o)).transpose()?)));
+        match opened {
+            Ok((command, output)) => results.push(CommandResult { id, agent_id, command, output }),
+            Err(e) => {
+                warn!("tâche {} de {} illisible, ignorée : {}", id, agent_id, e);
+                conn.execute("UPDATE tasks SET seen = 1 WHERE id = ?1", [id])?;
+            }
+        }
+    }
+    Ok(results)
+}
+
+// Ouvre sa propre connexion et remonte les commandes terminées non encore vues.
+// Le thread s'arrête sur shutdown ou quand le récepteur est abandonné.
+pub fn spawn_poll_thread(conn_path: &str, tx: Sender<AppEvent>, shutdown: Shutdown) -> JoinHandle<()> {
+    let conn_path = conn_path.to_string();
+    thread::spawn(move || {
+        let Some(conn) = open_thread_connection(&conn_path, "de poll") else {
+            return;
+        };
+        loop {
+            match take_completed(&conn) {
+                Ok(results) => {
+                    for result in results {
+                        let id = result.id;
+                        if tx.send(AppEvent::CommandResult(result)).is_err() {
+                            return;
+                        }
+                        if let Err(e) = conn.execute("UPDATE tasks SET seen = 1 WHERE id = ?1", [id]) {
+                            warn!("tâche {} non marquée comme vue : {}", id, e);
+                        }
+                    }
+                }
+                Err(e) => warn!("lecture des tâches terminées impossible : {}", e),
+            }
+            if shutdown.wait(POLL_INTERVAL) {
+                return;
+            }
+        }
+    })
+}
+
+// Passe offline les agents online muets depuis dead_after_beacons check-ins, chacun selon
+// son beacon. last_seen garde plusieurs formats : la comparaison se fait ici plutôt qu'en SQL.
+fn mark_offline(conn: &Connection, dead_after_beacons: u32) -> Result<Vec<String>> {
+    let now = Utc::now();
+    let mut stmt = conn.prepare(&format!("SELECT {} FROM agents WHERE status = 'online'", AGENT_COLUMNS))?;
+    let mut overdue = Vec::new();
+    for agent in stmt.query_map([], agent_from_row)? {
+        let agent = agent?;
+        if missed_check_ins(&agent, dead_after_beacons, now) {
+            overdue.push(agent.id);
+        }
+    }
+    let tx = conn.unchecked_transaction()?;
+    for id in &overdue {
+        tx.execute("UPDATE agents SET status = 'offline' WHERE id = ?1 AND status = 'online'", [id])?;
+    }
+    tx.commit()?;
+    Ok(overdue)
+}
+
+// Même cycle de vie que le thread de poll, à un rythme plus lent. Purge au passage les
+// check-ins plus anciens que retention.
+pub fn spawn_offline_thread(
+    conn_path: &str,
+    dead_after_beacons: u32,
+    retention: Duration,
+    tx: Sender<AppEvent>,
+    shutdown: Shutdown,
+) -> JoinHandle<()> {
+    let conn_path = conn_path.to_string();
+    let retention = TimeDelta::from_std(retention).unwrap_or(TimeDelta::MAX);
+    thread::spawn(move || {
+        let Some(conn) = open_thread_connection(&conn_path, "offline") else {
+            return;
+        };
+        loop {
+            match mark_offline(&conn, dead_after_beacons) {
+                Ok(ids) if ids.is_empty() => {}
+                Ok(ids) => {
+                    if tx.send(AppEvent::AgentsUpdated(ids)).is_err() {
+                        return;
+                    }
+                }
+                Err(e) => warn!("passage des agents offline impossible : {}", e),
+            }
+            if let Err(e) = prune_checkins(&conn, retention) {
+                warn!("purge des check-ins impossible : {}", e);
+            }
+            if shutdown.wait(OFFLINE_SWEEP_INTERVAL) {
+                return;
+            }
+        }
+    })
+}
+
+// Échéances vérifiées toutes les 30 secondes : une tâche part donc jusqu'à 30 s après
+// l'heure prévue, et seulement tant que l'interface est ouverte
+pub fn spawn_scheduler_thread(conn_path: &str, tx: Sender<AppEvent>, shutdown: Shutdown) -> JoinHandle<()> {
+    let conn_path = conn_path.to_string();
+    thread::spawn(move || {
+        let Some(conn) = open_thread_connection(&conn_path, "du planificateur") else {
+            return;
+        };
+        loop {
+            let now = Utc::now();
+            let runs = run_due_tasks(&conn, now).and_then(|mut runs| {
+                runs.extend(run_auto_screenshots(&conn, now)?);
+                Ok(runs)
+            });
+            match runs {
+                Ok(runs) if runs.is_empty() => {}
+                Ok(runs) => {
+                    if tx.send(AppEvent::ScheduledTasksRun(runs)).is_err() {
+                        return;
+                    }
+                }
+                Err(e) => warn!("exécution des tâches planifiées impossible : {}", e),
+            }
+            if shutdown.wait(SCHEDULER_INTERVAL) {
+                return;
+            }
+        }
+    })
+}
+
+// Seul lecteur du terminal une fois l'interface lancée : la connexion et l'écran
+// d'erreur de démarrage lisent encore directement avant son lancement
+pub fn spawn_input_thread(tx: Sender<AppEvent>, shutdown: Shutdown) -> JoinHandle<()> {
+    thread::spawn(move || {
+        while !shutdown.wait(Duration::ZERO) {
+            let event = match event::poll(INPUT_POLL_INTERVAL) {
+                Ok(false) => continue,
+                Ok(true) => event::read(),
+                Err(e) => Err(e),
+            };
+            let event = match event {
+                Ok(event) => event,
+                Err(e) => {
+                    error!("lecture du terminal impossible : {}", e);
+                    return;
+                }
+            };
+            if tx.send(AppEvent::Input(event)).is_err() {
+                return;
+            }
+        }
+    })
+}
+
+// Sur le runtime des listeners ; en mode brut, Ctrl-C arrive comme une touche et ne
+// passe pas par ici
+#[cfg(unix)]
+pub fn spawn_signal_task(tx: Sender<AppEvent>) {
+    use tokio::signal::unix::{signal, SignalKind};
+    tokio::spawn(async move {
+        let (Ok(mut term), Ok(mut int)) = (signal(SignalKind::terminate()), signal(SignalKind::interrupt())) else {
+            warn!("signaux d'arrêt non interceptés");
+            return;
+        };
+        tokio::select! {
+            _ = term.recv() => {}
+            _ = int.recv() => {}
+        }
+        let _ = tx.send(AppEvent::Terminate);
+    });
+}
+
+#[cfg(not(unix))]
+pub fn spawn_signal_task(_tx: Sender<AppEvent>) {}
+
+// Échéances fixes plutôt qu'une attente après chaque envoi, pour ne pas dériver
+pub fn spawn_tick_thread(tick_rate: Duration, tx: Sender<AppEvent>, shutdown: Shutdown) -> JoinHandle<()> {
+    thread::spawn(move || {
+        let mut next_tick = Instant::now() + tick_rate;
+        while !shutdown.wait(next_tick.saturating_duration_since(Instant::now())) {
+            if tx.send(AppEvent::Tick).is_err() {
+                return;
+            }
+            next_tick = (next_tick + tick_rate).max(Instant::now());
+        }
+    })
+}
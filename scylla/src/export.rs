@@ -0,0 +1,66 @@
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+
+use serde::Serialize;
+
+use crate::agent::Agent;
+use crate::notes::Note;
+
+const CSV_HEADER: &str = "id,hostname,ip,os,status,last_seen,location,note";
+
+// Les notes vivent dans leur propre table : l'export garde la plus récente comme huitième champ
+#[derive(Serialize)]
+struct ExportedAgent<'a> {
+    #[serde(flatten)]
+    agent: &'a Agent,
+    note: Option<&'a str>,
+}
+
+fn latest_note<'a>(notes: &'a HashMap<String, Vec<Note>>, agent_id: &str) -> Option<&'a str> {
+    notes.get(agent_id).and_then(|n| n.first()).map(|n| n.content.as_str())
+}
+
+// Entre guillemets seulement si nécessaire, les guillemets internes doublés
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+// Dans l'ordre reçu : l'appelant passe la vue filtrée et triée. Renvoie le nombre de lignes écrites
+pub fn export_agents_csv(agents: &[&Agent], notes: &HashMap<String, Vec<Note>>, path: &Path) -> io::Result<usize> {
+    let mut out = BufWriter::new(File::create(path)?);
+    writeln!(out, "{}", CSV_HEADER)?;
+    for agent in agents {
+        let fields = [
+            Some(agent.id.as_str()),
+            Some(agent.hostname.as_str()),
+            Some(agent.ip.as_str()),
+            agent.os.as_deref(),
+            Some(agent.status.as_str()),
+            agent.last_seen.as_deref(),
+            agent.location.as_deref(),
+            latest_note(notes, &agent.id),
+        ];
+        let row: Vec<String> = fields.iter().map(|f| csv_field(f.unwrap_or(""))).collect();
+        writeln!(out, "{}", row.join(","))?;
+    }
+    out.flush()?;
+    Ok(agents.len())
+}
+
+// Tableau d'objets dont les clés sont les noms des champs d'Agent plus note ; un champ absent vaut null
+pub fn export_agents_json(agents: &[&Agent], notes: &HashMap<String, Vec<Note>>, path: &Path) -> io::Result<usize> {
+    let rows: Vec<ExportedAgent> = agents.iter()
+        .map(|agent| ExportedAgent { agent, note: latest_note(notes, &agent.id) })
+        .collect();
+    let mut out = BufWriter::new(File::create(path)?);
+    serde_json::to_writer_pretty(&mut out, &rows)?;
+    writeln!(out)?;
+    out.flush()?;
+    Ok(rows.len())
+}
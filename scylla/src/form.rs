@@ -0,0 +1,180 @@
+use crossterm::event::{KeyCode, KeyEvent};
+use ratatui::layout::{Position, Rect};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Clear, Paragraph};
+use ratatui::Frame;
+
+use crate::theme::ColorScheme;
+
+// Ligne de saisie avec curseur, alimentée par les événements clavier bruts
+#[derive(Default)]
+pub struct TextInput {
+    pub content: String,
+    // Position du curseur en caractères, pas en octets
+    pub cursor: usize,
+}
+
+impl TextInput {
+    pub fn new(content: &str) -> TextInput {
+        TextInput {
+            content: content.to_string(),
+            cursor: content.chars().count(),
+        }
+    }
+
+    fn byte_index(&self) -> usize {
+        self.content.char_indices()
+            .nth(self.cursor)
+            .map(|(i, _)| i)
+            .unwrap_or(self.content.len())
+    }
+
+    // Renvoie true si la touche a été consommée par la saisie
+    pub fn handle_key(&mut self, key: KeyEvent) -> bool {
+        match key.code {
+            KeyCode::Char(c) => {
+                let i = self.byte_index();
+                self.content.insert(i, c);
+                self.cursor += 1;
+            }
+            KeyCode::Backspace if self.cursor > 0 => {
+                self.cursor -= 1;
+                let i = self.byte_index();
+                self.content.remove(i);
+            }
+            KeyCode::Delete if self.cursor < self.content.chars().count() => {
+                let i = self.byte_index();
+                self.content.remove(i);
+            }
+            KeyCode::Left => self.cursor = self.cursor.saturating_sub(1),
+            KeyCode::Right => self.cursor = (self.cursor + 1).min(self.content.chars().count()),
+            KeyCode::Home => self.cursor = 0,
+            KeyCode::End => self.cursor = self.content.chars().count(),
+            KeyCode::Backspace | KeyCode::Delete => {}
+            _ => return false,
+        }
+        true
+    }
+}
+
+// Formulaire en fenêtre : Tab et Maj-Tab changent de champ, Entrée valide depuis n'importe lequel.
+// La validation propre à chaque formulaire est faite par l'appelant sur Confirmed.
+pub struct Form {
+    pub title: &'static str,
+    // Libellé de chaque champ et s'il est obligatoire
+    fields: &'static [(&'static str, bool)],
+    focus: usize,
+    inputs: Vec<TextInput>,
+    // Champ affiché masqué, pour un mot de passe
+    secret: Option<usize>,
+    pub error: Option<String>,
+}
+
+pub enum FormOutcome {
+    Pending,
+    Cancelled,
+    Confirmed,
+}
+
+impl Form {
+    pub fn new(title: &'static str, fields: &'static [(&'static str, bool)]) -> Form {
+        Form {
+            title,
+            fields,
+            focus: 0,
+            inputs: fields.iter().map(|_| TextInput::default()).collect(),
+            secret: None,
+            error: None,
+        }
+    }
+
+    pub fn with_values(mut self, values: &[&str]) -> Form {
+        for (input, value) in self.inputs.iter_mut().zip(values) {
+            *input = TextInput::new(value);
+        }
+        self
+    }
+
+    pub fn with_secret(mut self, index: usize) -> Form {
+        self.secret = Some(index);
+        self
+    }
+
+    pub fn clear(&mut self, index: usize) {
+        self.inputs[index] = TextInput::default();
+    }
+
+    pub fn value(&self, index: usize) -> &str {
+        self.inputs[index].content.trim()
+    }
+
+    pub fn optional(&self, index: usize) -> Option<String> {
+        let value = self.value(index);
+        (!value.is_empty()).then(|| value.to_string())
+    }
+
+    // Place le curseur sur le champ fautif avec son message
+    pub fn reject(&mut self, index: usize, error: String) {
+        self.focus = index;
+        self.error = Some(error);
+    }
+
+    pub fn handle_key(&mut self, key: KeyEvent) -> FormOutcome {
+        match key.code {
+            KeyCode::Esc => return FormOutcome::Cancelled,
+            KeyCode::Tab | KeyCode::Down => self.focus = (self.focus + 1) % self.fields.len(),
+            KeyCode::BackTab | KeyCode::Up => self.focus = (self.focus + self.fields.len() - 1) % self.fields.len(),
+            KeyCode::Enter => {
+                let missing = self.fields.iter()
+                    .enumerate()
+                    .find(|&(i, &(_, required))| required && self.value(i).is_empty());
+                if let Some((i, (label, _))) = missing {
+                    self.reject(i, format!("{} est obligatoire", label));
+                    return FormOutcome::Pending;
+                }
+                self.error = None;
+                return FormOutcome::Confirmed;
+            }
+            _ => {
+                self.inputs[self.focus].handle_key(key);
+            }
+        }
+        FormOutcome::Pending
+    }
+}
+
+pub fn draw_form(f: &mut Frame, form: &Form, area: Rect, scheme: &ColorScheme) {
+    let mut lines: Vec<Line> = form.fields.iter()
+        .zip(form.inputs.iter())
+        .enumerate()
+        .map(|(i, ((label, required), input))| {
+            let marker = if *required { "*" } else { " " };
+            let content = if form.secret == Some(i) {
+                "*".repeat(input.content.chars().count())
+            } else {
+                input.content.clone()
+            };
+            let text = format!("{}{}: {}", marker, label, content);
+            if i == form.focus {
+                Line::from(Span::styled(text, scheme.accent()))
+            } else {
+                Line::from(text)
+            }
+        })
+        .collect();
+    lines.push(Line::from(""));
+    match &form.error {
+        Some(e) => lines.push(Line::from(Span::styled(e.clone(), scheme.error()))),
+        None => lines.push(Line::from("Tab/Maj-Tab: champ, Entrée: valider, Échap: annuler")),
+    }
+
+    f.render_widget(Clear, area);
+    let paragraph = Paragraph::new(lines)
+        .block(scheme.block(form.title));
+    f.render_widget(paragraph, area);
+
+    // Curseur dans le champ actif, à la position d'édition
+    let (label, _) = form.fields[form.focus];
+    let col = (1 + label.chars().count() + 2 + form.inputs[form.focus].cursor) as u16;
+    f.set_cursor_position(Position::new(area.x + 1 + col, area.y + 1 + form.focus as u16));
+}
@@ -0,0 +1,134 @@
+use std::collections::HashMap;
+use std::net::IpAddr;
+use std::path::Path;
+
+#[cfg(feature = "geoip")]
+use maxminddb::{geoip2, Reader};
+use rusqlite::Connection;
+
+use crate::error::{Error, Result};
+
+// Location donnée aux adresses privées, de bouclage ou de lien local, sans consulter la base
+pub const INTERNAL: &str = "internal";
+
+// Base GeoLite2/GeoIP2 City ou Country chargée en mémoire, ouverte une fois par session
+#[cfg(feature = "geoip")]
+pub type GeoReader = Reader<Vec<u8>>;
+// Sans la feature geoip, aucune base ne s'ouvre : seules les adresses internes sont nommées
+#[cfg(not(feature = "geoip"))]
+pub struct GeoReader;
+
+// Distingue une location trouvée par GeoIP d'une saisie : seule la première est refaite
+// par :geoip reload. save_agent et update_agent_field remettent l'indicateur à 0.
+pub fn add_geoip_column(conn: &Connection) -> rusqlite::Result<()> {
+    conn.execute("ALTER TABLE agents ADD COLUMN location_from_geoip INTEGER NOT NULL DEFAULT 0", [])?;
+    Ok(())
+}
+
+#[cfg(feature = "geoip")]
+pub fn open_reader(path: &Path) -> Result<GeoReader> {
+    Reader::open_readfile(path).map_err(|source| Error::GeoIp { path: path.display().to_string(), source })
+}
+
+#[cfg(not(feature = "geoip"))]
+pub fn open_reader(path: &Path) -> Result<GeoReader> {
+    Err(Error::GeoIpDisabled(path.display().to_string()))
+}
+
+// RFC 1918, bouclage, lien local et adresses IPv6 locales uniques
+pub fn is_internal(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(ip) => ip.is_private() || ip.is_loopback() || ip.is_link_local() || ip.is_unspecified(),
+        IpAddr::V6(ip) => ip.is_loopback() || ip.is_unique_local() || ip.is_unicast_link_local() || ip.is_unspecified(),
+    }
+}
+
+// "Ville, CC" quand la base connaît la ville, sinon le code pays seul ; "internal" pour une
+// adresse privée, None pour une adresse illisible ou absente de la base
+pub fn lookup(reader: &GeoReader, ip: &str) -> Option<String> {
+    let ip: IpAddr = ip.trim().parse().ok()?;
+    if is_internal(ip) {
+        return Some(INTERNAL.to_string());
+    }
+    lookup_public(reader, ip)
+}
+
+#[cfg(feature = "geoip")]
+fn lookup_public(reader: &GeoReader, ip: IpAddr) -> Option<String> {
+    let city = reader.lookup(ip).ok()?.decode::<geoip2::City>().ok()??;
+    let country = city.country.iso_code.map(str::to_string)
+        .or_else(|| city.country.names.english.map(str::to_string));
+    match (city.city.names.english, country) {
+        (Some(name), Some(country)) => Some(format!("{}, {}", name, country)),
+        (Some(name), None) => Some(name.to_string()),
+        (None, country) => country,
+    }
+}
+
+#[cfg(not(feature = "geoip"))]
+fn lookup_public(_: &GeoReader, _: IpAddr) -> Option<String> {
+    None
+}
+
+// Base ouverte et recherches déjà faites, par IP : un rechargement des agents ne relit pas
+// la base pour une adresse connue. :geoip reload repart d'un cache vide.
+pub struct GeoLocator {
+    reader: GeoReader,
+    cache: HashMap<String, Option<String>>,
+}
+
+impl GeoLocator {
+    pub fn new(reader: GeoReader) -> GeoLocator {
+        GeoLocator { reader, cache: HashMap::new() }
+    }
+
+    pub fn locate(&mut self, ip: &str) -> Option<String> {
+        if let Some(found) = self.cache.get(ip) {
+            return found.clone();
+        }
+        let found = lookup(&self.reader, ip);
+        self.cache.insert(ip.to_string(), found.clone());
+        found
+    }
+}
+
+pub fn set_geoip_location(conn: &Connection, agent_id: &str, location: &str) -> rusqlite::Result<()> {
+    conn.execute(
+        "UPDATE agents SET location = ?1, location_from_geoip = 1 WHERE id = ?2",
+        (location, agent_id),
+    )?;
+    Ok(())
+}
+
+// Agents sans location ou dont la location vient d'une précédente recherche
+pub fn geoip_candidates(conn: &Connection) -> rusqlite::Result<Vec<(String, String)>> {
+    let mut stmt = conn.prepare("SELECT id, ip FROM agents WHERE location IS NULL OR location_from_geoip = 1")?;
+    let rows = stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?;
+    rows.collect()
+}
+
+// Agents sans location, localisés au démarrage
+pub fn unlocated_agents(conn: &Connection) -> rusqlite::Result<Vec<(String, String)>> {
+    let mut stmt = conn.prepare("SELECT id, ip FROM agents WHERE location IS NULL OR location = ''")?;
+    let rows = stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?;
+    rows.collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ip(text: &str) -> IpAddr {
+        text.parse().expect("adresse valide")
+    }
+
+    #[test]
+    fn private_addresses_are_internal() {
+        for internal in ["10.0.0.5", "172.16.8.2", "192.168.1.20", "127.0.0.1", "169.254.3.4", "::1", "fd12:3456::1", "fe80::1"] {
+            assert!(is_internal(ip(internal)), "{} devrait être interne", internal);
+        }
+        for public in ["8.8.8.8", "172.32.0.1", "193.0.14.129", "2001:4860:4860::8888"] {
+            assert!(!is_internal(ip(public)), "{} devrait être publique", public);
+        }
+    }
+}
@@ -0,0 +1,104 @@
+use std::collections::HashMap;
+
+use rusqlite::{Connection, Result};
+
+// Couleurs attribuées à tour de rôle à la création, sous un nom que ratatui sait relire
+pub const GROUP_COLORS: &[&str] = &["cyan", "magenta", "green", "yellow", "lightblue", "lightred"];
+
+#[derive(Debug, Clone)]
+pub struct Group {
+    pub id: i64,
+    pub name: String,
+    pub description: Option<String>,
+    pub color: Option<String>,
+    pub members: usize,
+}
+
+// Contrairement aux étiquettes, libres, un groupe existe avant ses membres : il a une
+// description et une couleur, et le filtre group: le retrouve par son nom
+pub fn ensure_groups_tables(conn: &Connection) -> Result<()> {
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS groups (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            name TEXT NOT NULL UNIQUE COLLATE NOCASE,
+            description TEXT,
+            color TEXT
+        )",
+        [],
+    )?;
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS agent_groups (
+            group_id INTEGER NOT NULL,
+            agent_id TEXT NOT NULL,
+            PRIMARY KEY (group_id, agent_id),
+            FOREIGN KEY(group_id) REFERENCES groups(id)
+        )",
+        [],
+    )?;
+    Ok(())
+}
+
+pub fn create_group(conn: &Connection, name: &str, description: Option<&str>) -> Result<i64> {
+    let count: i64 = conn.query_row("SELECT COUNT(*) FROM groups", [], |row| row.get(0))?;
+    let color = GROUP_COLORS[count as usize % GROUP_COLORS.len()];
+    conn.execute(
+        "INSERT INTO groups (name, description, color) VALUES (?1, ?2, ?3)",
+        (name, description, color),
+    )?;
+    Ok(conn.last_insert_rowid())
+}
+
+pub fn add_agent_to_group(conn: &Connection, group_id: i64, agent_id: &str) -> Result<()> {
+    conn.execute("INSERT OR IGNORE INTO agent_groups (group_id, agent_id) VALUES (?1, ?2)", (group_id, agent_id))?;
+    Ok(())
+}
+
+pub fn remove_agent_from_group(conn: &Connection, group_id: i64, agent_id: &str) -> Result<()> {
+    conn.execute("DELETE FROM agent_groups WHERE group_id = ?1 AND agent_id = ?2", (group_id, agent_id))?;
+    Ok(())
+}
+
+pub fn remove_from_all_groups(conn: &Connection, agent_id: &str) -> Result<()> {
+    conn.execute("DELETE FROM agent_groups WHERE agent_id = ?1", [agent_id])?;
+    Ok(())
+}
+
+pub fn agents_in_group(conn: &Connection, group_id: i64) -> Result<Vec<String>> {
+    let mut stmt = conn.prepare("SELECT agent_id FROM agent_groups WHERE group_id = ?1 ORDER BY agent_id")?;
+    let rows = stmt.query_map([group_id], |row| row.get(0))?;
+    rows.collect()
+}
+
+// Par nom, avec le nombre de membres
+pub fn load_groups(conn: &Connection) -> Result<Vec<Group>> {
+    let mut stmt = conn.prepare(
+        "SELECT groups.id, name, description, color, COUNT(agent_groups.agent_id)
+         FROM groups LEFT JOIN agent_groups ON agent_groups.group_id = groups.id
+         GROUP BY groups.id ORDER BY name",
+    )?;
+    let rows = stmt.query_map([], |row| {
+        Ok(Group {
+            id: row.get(0)?,
+            name: row.get(1)?,
+            description: row.get(2)?,
+            color: row.get(3)?,
+            members: row.get::<_, i64>(4)? as usize,
+        })
+    })?;
+    rows.collect()
+}
+
+// Noms des groupes de chaque agent, pour le filtre group:
+pub fn load_memberships(conn: &Connection) -> Result<HashMap<String, Vec<String>>> {
+    let mut stmt = conn.prepare(
+        "SELECT agent_id, name FROM agent_groups JOIN groups ON groups.id = agent_groups.group_id
+         ORDER BY agent_id, name",
+    )?;
+    let mut memberships: HashMap<String, Vec<String>> = HashMap::new();
+    let rows = stmt.query_map([], |row| Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?)))?;
+    for row in rows {
+        let (agent_id, name) = row?;
+        memberships.entry(agent_id).or_default().push(name);
+    }
+    Ok(memberships)
+}
@@ -0,0 +1,154 @@
+use std::io::{self, IsTerminal, Write};
+
+use chrono::Utc;
+use crossterm::style::Stylize;
+use serde::Serialize;
+
+use crate::cli::AgentsCommand;
+use crate::agent::Agent;
+use crate::db::Db;
+use crate::error::{Error, Result};
+use crate::notes::notes_for_agent;
+use crate::status::{display_status, StalenessThresholds};
+use crate::tags::tags_for_agent;
+use crate::timefmt::format_relative;
+
+// scylla agents … : mêmes fonctions de base que l'interface, résultat sur stdout et
+// messages d'erreur sur stderr
+pub struct Output {
+    color: bool,
+}
+
+impl Output {
+    // Couleurs seulement vers un terminal, sans --no-color ni NO_COLOR (https://no-color.org)
+    pub fn detect(no_color: bool) -> Output {
+        let env_no_color = std::env::var_os("NO_COLOR").is_some_and(|v| !v.is_empty());
+        Output { color: !no_color && !env_no_color && io::stdout().is_terminal() }
+    }
+
+    // Couleur appliquée après le remplissage, pour ne pas compter les séquences ANSI dans la largeur
+    fn status(&self, status: &str, width: usize) -> String {
+        let padded = format!("{:<width$}", status, width = width);
+        if !self.color {
+            return padded;
+        }
+        match status.to_lowercase().as_str() {
+            "online" | "idle" => padded.green().to_string(),
+            "stale" => padded.yellow().to_string(),
+            "offline" | "dead" => padded.red().to_string(),
+            _ => padded,
+        }
+    }
+
+    fn header(&self, text: String) -> String {
+        if self.color { text.bold().to_string() } else { text }
+    }
+}
+
+#[derive(Serialize)]
+struct ListedAgent<'a> {
+    #[serde(flatten)]
+    agent: &'a Agent,
+    displayed_status: &'a str,
+}
+
+pub fn run_agents(db_path: &str, thresholds: &StalenessThresholds, command: &AgentsCommand, out: &Output) -> Result<()> {
+    let db = Db::open(db_path)?;
+    match command {
+        AgentsCommand::List { status, json } => list_agents(&db, thresholds, status.as_deref(), *json, out),
+        AgentsCommand::Show { id } => show_agent(&db, thresholds, id, out),
+        AgentsCommand::Delete { id } => {
+            if db.get_agent(id)?.is_none() {
+                return Err(Error::AgentNotFound(id.clone()));
+            }
+            db.delete_agents(std::slice::from_ref(id), "cli")?;
+            eprintln!("Agent {} supprimé", id);
+            Ok(())
+        }
+    }
+}
+
+fn list_agents(db: &Db, thresholds: &StalenessThresholds, status: Option<&str>, json: bool, out: &Output) -> Result<()> {
+    let now = Utc::now();
+    let agents = db.load_agents()?;
+    let listed: Vec<ListedAgent> = agents
+        .iter()
+        .map(|agent| ListedAgent { agent, displayed_status: display_status(agent, now, thresholds) })
+        .filter(|listed| status.is_none_or(|s| listed.displayed_status.eq_ignore_ascii_case(s)))
+        .collect();
+    let mut stdout = io::stdout().lock();
+    if json {
+        serde_json::to_writer_pretty(&mut stdout, &listed).map_err(io::Error::from)?;
+        writeln!(stdout)?;
+        return Ok(());
+    }
+    let rows: Vec<[String; 7]> = listed
+        .iter()
+        .map(|l| {
+            [
+                l.agent.id.clone(),
+                l.agent.hostname.clone(),
+                l.agent.ip.clone(),
+                l.agent.os.clone().unwrap_or_else(|| "-".to_string()),
+                l.displayed_status.to_string(),
+                format_relative(l.agent.last_seen.as_deref(), now),
+                l.agent.location.clone().unwrap_or_else(|| "-".to_string()),
+            ]
+        })
+        .collect();
+    let headers = ["ID", "HOSTNAME", "IP", "OS", "STATUS", "LAST SEEN", "LOCATION"];
+    let mut widths = headers.map(|h| h.chars().count());
+    for row in &rows {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.chars().count());
+        }
+    }
+    let header: Vec<String> = headers.iter().zip(widths).map(|(h, w)| format!("{:<w$}", h, w = w)).collect();
+    writeln!(stdout, "{}", out.header(header.join("  ").trim_end().to_string()))?;
+    for row in &rows {
+        let cells: Vec<String> = row
+            .iter()
+            .zip(widths)
+            .enumerate()
+            .map(|(i, (cell, w))| if i == 4 { out.status(cell, w) } else { format!("{:<w$}", cell, w = w) })
+            .collect();
+        writeln!(stdout, "{}", cells.join("  ").trim_end())?;
+    }
+    Ok(())
+}
+
+fn show_agent(db: &Db, thresholds: &StalenessThresholds, id: &str, out: &Output) -> Result<()> {
+    let agent = db.get_agent(id)?.ok_or_else(|| Error::AgentNotFound(id.to_string()))?;
+    let now = Utc::now();
+    let status = display_status(&agent, now, thresholds);
+    let tags = tags_for_agent(db.conn(), id)?;
+    let notes = notes_for_agent(db.conn(), id)?;
+    let fields = [
+        ("ID", agent.id.clone()),
+        ("Hostname", agent.hostname.clone()),
+        ("IP", agent.ip.clone()),
+        ("OS", agent.os.clone().unwrap_or_else(|| "-".to_string())),
+        ("Status", status.to_string()),
+        (
+            "Last seen",
+            match &agent.last_seen {
+                Some(at) => format!("{} ({})", at, format_relative(Some(at), now)),
+                None => "-".to_string(),
+            },
+        ),
+        ("Location", agent.location.clone().unwrap_or_else(|| "-".to_string())),
+        ("Tags", if tags.is_empty() { "-".to_string() } else { tags.join(", ") }),
+    ];
+    let width = fields.iter().map(|(label, _)| label.len()).max().unwrap_or(0);
+    let mut stdout = io::stdout().lock();
+    for (label, value) in &fields {
+        let value = if *label == "Status" { out.status(value, 0) } else { value.clone() };
+        writeln!(stdout, "{} {}", out.header(format!("{:<width$}", label, width = width)), value)?;
+    }
+    for note in &notes {
+        writeln!(stdout)?;
+        writeln!(stdout, "{}", out.header(format!("Note du {}", note.updated_at)))?;
+        writeln!(stdout, "{}", note.content)?;
+    }
+    Ok(())
+}
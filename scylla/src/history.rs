@@ -0,0 +1,45 @@
+use rusqlite::{Connection, Result};
+
+use crate::timefmt::now_timestamp;
+
+// Au-delà, les plus anciennes commandes sont oubliées à chaque ajout
+const HISTORY_SIZE: i64 = 500;
+
+// Historique partagé par tous les agents, comme celui d'un shell
+pub fn ensure_history_table(conn: &Connection) -> Result<()> {
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS command_history (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            command TEXT NOT NULL,
+            created_at TEXT NOT NULL
+        )",
+        [],
+    )?;
+    Ok(())
+}
+
+// Une commande identique à la précédente n'est pas répétée
+pub fn push_history(conn: &Connection, command: &str) -> Result<()> {
+    let last: Option<String> = conn
+        .query_row("SELECT command FROM command_history ORDER BY id DESC LIMIT 1", [], |row| row.get(0))
+        .ok();
+    if last.as_deref() == Some(command) {
+        return Ok(());
+    }
+    conn.execute(
+        "INSERT INTO command_history (command, created_at) VALUES (?1, ?2)",
+        (command, now_timestamp()),
+    )?;
+    conn.execute(
+        "DELETE FROM command_history WHERE id <= (SELECT MAX(id) FROM command_history) - ?1",
+        [HISTORY_SIZE],
+    )?;
+    Ok(())
+}
+
+// Les plus anciennes en premier, Haut remonte depuis la fin
+pub fn load_history(conn: &Connection) -> Result<Vec<String>> {
+    let mut stmt = conn.prepare("SELECT command FROM command_history ORDER BY id")?;
+    let rows = stmt.query_map([], |row| row.get(0))?;
+    rows.collect()
+}
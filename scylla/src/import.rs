@@ -0,0 +1,294 @@
+use std::net::IpAddr;
+use std::path::Path;
+
+use clap::ValueEnum;
+use rusqlite::{Connection, OptionalExtension};
+use serde::Deserialize;
+
+use crate::agent::Agent;
+use crate::beacon::{DEFAULT_BEACON_SECS, DEFAULT_JITTER_PCT};
+use crate::db::Db;
+use crate::error::Result;
+use crate::notes::{add_note, notes_for_agent};
+
+// Ligne dont l'id, ou le couple hostname + ip, existe déjà dans la base
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum OnConflict {
+    Skip,
+    Update,
+    // Le moindre conflit annule tout l'import
+    Fail,
+}
+
+// Bilan d'un import : une ligne invalide est notée dans errors sans interrompre les suivantes
+#[derive(Debug, Default)]
+pub struct ImportSummary {
+    pub inserted: usize,
+    pub updated: usize,
+    pub skipped: usize,
+    pub errors: Vec<String>,
+}
+
+pub fn import_message(path: &std::path::Path, summary: &ImportSummary) -> String {
+    format!(
+        "Import de {} : {} ajouté(s), {} mis à jour, {} ignoré(s), {} erreur(s)",
+        path.display(), summary.inserted, summary.updated, summary.skipped, summary.errors.len(),
+    )
+}
+
+// Une ligne du fichier, avec les mêmes noms de champs que l'export. Sans id, un
+// identifiant agent-NNN est attribué ; les champs inconnus sont ignorés
+#[derive(Debug, Deserialize)]
+struct AgentRecord {
+    #[serde(default)]
+    id: Option<String>,
+    hostname: String,
+    ip: String,
+    #[serde(default)]
+    os: Option<String>,
+    #[serde(default)]
+    status: Option<String>,
+    #[serde(default)]
+    last_seen: Option<String>,
+    #[serde(default)]
+    location: Option<String>,
+    #[serde(default)]
+    note: Option<String>,
+}
+
+fn non_empty(value: Option<String>) -> Option<String> {
+    value.map(|v| v.trim().to_string()).filter(|v| !v.is_empty())
+}
+
+impl AgentRecord {
+    // L'id reste vide s'il est à générer : il ne l'est qu'une fois la ligne acceptée
+    fn into_agent(self) -> std::result::Result<(Agent, Option<String>), String> {
+        let (hostname, ip) = (self.hostname.trim(), self.ip.trim());
+        if hostname.is_empty() {
+            return Err("hostname vide".to_string());
+        }
+        if ip.parse::<IpAddr>().is_err() {
+            return Err(format!("IP invalide : {}", ip));
+        }
+        let agent = Agent {
+            id: non_empty(self.id).unwrap_or_default(),
+            hostname: hostname.to_string(),
+            ip: ip.to_string(),
+            os: non_empty(self.os),
+            // Comme une saisie manuelle tant que l'agent ne s'est pas manifesté
+            status: non_empty(self.status).unwrap_or_else(|| "manual".to_string()),
+            last_seen: non_empty(self.last_seen),
+            location: non_empty(self.location),
+            beacon_interval_secs: DEFAULT_BEACON_SECS,
+            jitter_pct: DEFAULT_JITTER_PCT,
+            screenshot_interval_secs: None,
+            username: None,
+            is_elevated: false,
+            uptime_secs: None,
+            archived: false,
+        };
+        Ok((agent, non_empty(self.note)))
+    }
+}
+
+// Découpe un CSV en enregistrements : champs entre guillemets (virgules, guillemets doublés
+// et retours à la ligne compris) et fins de ligne CRLF. Chaque enregistrement garde le
+// numéro de sa première ligne, pour les messages d'erreur.
+fn parse_csv(text: &str) -> Vec<(usize, Vec<String>)> {
+    // Excel ajoute un BOM qui collerait au nom de la première colonne
+    let text = text.strip_prefix('\u{feff}').unwrap_or(text);
+    let mut records = Vec::new();
+    let mut record = Vec::new();
+    let mut field = String::new();
+    let mut quoted = false;
+    let mut line = 1;
+    let mut start = 1;
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if quoted && chars.peek() == Some(&'"') => {
+                field.push('"');
+                chars.next();
+            }
+            '"' if quoted => quoted = false,
+            '"' if field.is_empty() => quoted = true,
+            ',' if !quoted => record.push(std::mem::take(&mut field)),
+            '\r' if !quoted && chars.peek() == Some(&'\n') => {}
+            '\n' if !quoted => {
+                record.push(std::mem::take(&mut field));
+                records.push((start, std::mem::take(&mut record)));
+                line += 1;
+                start = line;
+            }
+            c => {
+                if c == '\n' {
+                    line += 1;
+                }
+                field.push(c);
+            }
+        }
+    }
+    if !field.is_empty() || !record.is_empty() {
+        record.push(field);
+        records.push((start, record));
+    }
+    // Les lignes vides ne sont pas des enregistrements
+    records.retain(|(_, r)| !(r.len() == 1 && r[0].trim().is_empty()));
+    records
+}
+
+// Numéro suivant le plus grand agent-NNN existant
+fn next_agent_id(conn: &Connection) -> Result<String> {
+    let mut stmt = conn.prepare("SELECT id FROM agents WHERE id LIKE 'agent-%'")?;
+    let ids = stmt.query_map([], |row| row.get::<_, String>(0))?.collect::<rusqlite::Result<Vec<_>>>()?;
+    let last = ids.iter().filter_map(|id| id.strip_prefix("agent-")?.parse::<u32>().ok()).max().unwrap_or(0);
+    Ok(format!("agent-{:03}", last + 1))
+}
+
+// Agent déjà en base qui entre en conflit avec la ligne : même id, sinon même hostname + ip
+fn find_conflict(conn: &Connection, agent: &Agent) -> Result<Option<String>> {
+    let existing = conn
+        .query_row(
+            "SELECT id FROM agents WHERE id = ?1 OR (hostname = ?2 AND ip = ?3) ORDER BY id = ?1 DESC LIMIT 1",
+            (&agent.id, &agent.hostname, &agent.ip),
+            |row| row.get(0),
+        )
+        .optional()?;
+    Ok(existing)
+}
+
+struct Importer<'a> {
+    db: &'a Db,
+    on_conflict: OnConflict,
+    summary: ImportSummary,
+    // Conflits rencontrés avec OnConflict::Fail, rapportés même si rien n'est écrit
+    conflicts: usize,
+}
+
+impl Importer<'_> {
+    fn store(&mut self, origin: &str, mut agent: Agent, note: Option<String>) -> Result<()> {
+        let conn = self.db.conn();
+        match find_conflict(conn, &agent)? {
+            Some(existing) => match self.on_conflict {
+                OnConflict::Skip => self.summary.skipped += 1,
+                OnConflict::Fail => {
+                    self.summary.errors.push(format!("{} : conflit avec l'agent {}", origin, existing));
+                    self.conflicts += 1;
+                }
+                OnConflict::Update => {
+                    // Un conflit sur hostname + ip met à jour l'agent existant sous son propre id
+                    if let Some(current) = self.db.get_agent(&existing)? {
+                        agent.beacon_interval_secs = current.beacon_interval_secs;
+                        agent.jitter_pct = current.jitter_pct;
+                        agent.screenshot_interval_secs = current.screenshot_interval_secs;
+                        agent.username = current.username;
+                        agent.is_elevated = current.is_elevated;
+                        agent.uptime_secs = current.uptime_secs;
+                        agent.archived = current.archived;
+                    }
+                    agent.id = existing;
+                    self.db.save_agent(&agent)?;
+                    // Pas de doublon si la note est déjà la plus récente, comme après un export
+                    let latest = notes_for_agent(conn, &agent.id)?.into_iter().next().map(|n| n.content);
+                    if let Some(note) = note.filter(|n| latest.as_ref() != Some(n)) {
+                        add_note(conn, &agent.id, &note)?;
+                    }
+                    self.summary.updated += 1;
+                }
+            },
+            None => {
+                if agent.id.is_empty() {
+                    agent.id = next_agent_id(conn)?;
+                }
+                self.db.save_agent(&agent)?;
+                if let Some(note) = note {
+                    add_note(conn, &agent.id, &note)?;
+                }
+                self.summary.inserted += 1;
+            }
+        }
+        Ok(())
+    }
+}
+
+// Tout l'import dans une transaction : avec OnConflict::Fail, un seul conflit laisse la base
+// intacte et le bilan ne compte plus que les erreurs
+fn run_import<F>(db: &Db, on_conflict: OnConflict, rows: F) -> Result<ImportSummary>
+where
+    F: FnOnce(&mut Importer) -> Result<()>,
+{
+    let tx = db.conn().unchecked_transaction()?;
+    let mut importer = Importer { db, on_conflict, summary: ImportSummary::default(), conflicts: 0 };
+    rows(&mut importer)?;
+    let mut summary = importer.summary;
+    if importer.conflicts > 0 {
+        drop(tx);
+        summary.errors.push(format!("{} conflit(s) : import annulé, aucune ligne écrite", importer.conflicts));
+        summary.inserted = 0;
+        summary.updated = 0;
+        summary.skipped = 0;
+    } else {
+        tx.commit()?;
+    }
+    Ok(summary)
+}
+
+// Colonnes repérées par leur nom dans la première ligne, dans n'importe quel ordre
+pub fn import_agents_csv(db: &Db, path: &Path, on_conflict: OnConflict) -> Result<ImportSummary> {
+    let text = std::fs::read_to_string(path)?;
+    let mut records = parse_csv(&text).into_iter();
+    let Some((_, header)) = records.next() else {
+        return Ok(ImportSummary { errors: vec!["fichier vide".to_string()], ..Default::default() });
+    };
+    let header: Vec<String> = header.iter().map(|h| h.trim().to_lowercase()).collect();
+    let column = |name: &str| header.iter().position(|h| h == name);
+    let missing: Vec<&str> = ["hostname", "ip"].into_iter().filter(|c| column(c).is_none()).collect();
+    if !missing.is_empty() {
+        let error = format!("ligne 1 : colonnes manquantes : {}", missing.join(", "));
+        return Ok(ImportSummary { errors: vec![error], ..Default::default() });
+    }
+    run_import(db, on_conflict, |importer| {
+        for (line, fields) in records {
+            let get = |name: &str| column(name).and_then(|i| fields.get(i)).cloned();
+            let record = AgentRecord {
+                id: get("id"),
+                hostname: get("hostname").unwrap_or_default(),
+                ip: get("ip").unwrap_or_default(),
+                os: get("os"),
+                status: get("status"),
+                last_seen: get("last_seen"),
+                location: get("location"),
+                note: get("note"),
+            };
+            let origin = format!("ligne {}", line);
+            match record.into_agent() {
+                Ok((agent, note)) => importer.store(&origin, agent, note)?,
+                Err(e) => importer.summary.errors.push(format!("{} : {}", origin, e)),
+            }
+        }
+        Ok(())
+    })
+}
+
+// Un tableau d'objets comme celui de l'export ; un élément invalide est signalé par sa position
+pub fn import_agents_json(db: &Db, path: &Path, on_conflict: OnConflict) -> Result<ImportSummary> {
+    let text = std::fs::read_to_string(path)?;
+    let text = text.strip_prefix('\u{feff}').unwrap_or(&text);
+    let items: Vec<serde_json::Value> = match serde_json::from_str(text) {
+        Ok(items) => items,
+        Err(e) => return Ok(ImportSummary { errors: vec![format!("JSON invalide : {}", e)], ..Default::default() }),
+    };
+    run_import(db, on_conflict, |importer| {
+        for (index, item) in items.into_iter().enumerate() {
+            let record = serde_json::from_value::<AgentRecord>(item)
+                .map_err(|e| e.to_string())
+                .and_then(AgentRecord::into_agent);
+            let origin = format!("élément {}", index + 1);
+            match record {
+                Ok((agent, note)) => importer.store(&origin, agent, note)?,
+                Err(e) => importer.summary.errors.push(format!("{} : {}", origin, e)),
+            }
+        }
+        Ok(())
+    })
+}
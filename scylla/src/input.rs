@@ -0,0 +1,758 @@
+use ratatui::layout::{Position, Rect};
+use crossterm::event::{KeyCode, KeyEvent, KeyEventKind, KeyModifiers, MouseButton, MouseEvent, MouseEventKind};
+
+use crate::app::{palette_matches, App, AppScreen, ContextMenu, Effect, UploadPrompt, CONTEXT_ACTIONS};
+use crate::clipboard::{ClipboardOutcome, COPY_FIELDS};
+use crate::editor::EditorOutcome;
+use crate::eventlog::{Category, Level};
+use crate::form::{FormOutcome, TextInput};
+use crate::history::push_history;
+use crate::keylog::{export_keylog, KeylogOutcome};
+use crate::loot::LootOutcome;
+use crate::screenshots::GalleryOutcome;
+use crate::pivots::PivotOutcome;
+use crate::processes::ProcessOutcome;
+use crate::topology::TopologyOutcome;
+use crate::selfdestruct::ConfirmOutcome;
+use crate::keymap::{resolve, GLOBAL_KEYS};
+use crate::tags::parse_tag_edits;
+use crate::transfers::{check_upload, complete_path};
+use crate::viewer::OutputViewer;
+
+// Clavier et souris : une fenêtre ouverte prend la main, sinon la touche se résout en
+// Action pour App::handle_action
+impl App {
+    pub fn handle_key(&mut self, key: KeyEvent) -> Vec<Effect> {
+        self.dispatch_key(key);
+        self.take_effects()
+    }
+
+    pub(crate) fn dispatch_key(&mut self, key: KeyEvent) {
+        // Certains terminaux envoient aussi Release : seul Press déplace le curseur,
+        // la répétition du terminal arrivant elle-même comme une suite de Press
+        if key.kind != KeyEventKind::Press {
+            return;
+        }
+        // Ctrl-C quitte de partout, fenêtres et saisies comprises
+        if key.code == KeyCode::Char('c') && key.modifiers.contains(KeyModifiers::CONTROL) {
+            self.request_quit();
+            return;
+        }
+        // Toute autre touche que 'y' annule
+        if self.confirm_quit.take().is_some() {
+            if key.code == KeyCode::Char('y') {
+                self.emit(Effect::Quit);
+            }
+            return;
+        }
+        // Vérifie à la main que le terminal est restauré après une panique
+        #[cfg(feature = "debug-panic")]
+        if key.code == KeyCode::F(12) {
+            panic!("panique volontaire (F12, feature debug-panic)");
+        }
+        // L'aide garde toutes les touches : seules Échap, ?, q et F1 la referment
+        if let Some(scroll) = self.help_scroll.as_mut() {
+            match key.code {
+                KeyCode::Esc | KeyCode::Char('?') | KeyCode::Char('q') | KeyCode::F(1) => self.help_scroll = None,
+                KeyCode::Down | KeyCode::Char('j') => *scroll = scroll.saturating_add(1),
+                KeyCode::Up | KeyCode::Char('k') => *scroll = scroll.saturating_sub(1),
+                KeyCode::PageDown | KeyCode::Char(' ') => *scroll = scroll.saturating_add(10),
+                KeyCode::PageUp => *scroll = scroll.saturating_sub(10),
+                KeyCode::Home | KeyCode::Char('g') => *scroll = 0,
+                // Borné au dessin
+                KeyCode::End | KeyCode::Char('G') => *scroll = u16::MAX,
+                _ => {}
+            }
+            return;
+        }
+        // N'importe quelle touche referme le rapport d'import
+        if self.import_errors.take().is_some() {
+            return;
+        }
+        if self.context_menu.is_some() {
+            self.handle_context_menu_key(key);
+            return;
+        }
+        if self.copy_menu.is_some() {
+            self.handle_copy_menu_key(key);
+            return;
+        }
+        if let Some(dialog) = self.add_dialog.as_mut() {
+            match dialog.handle_key(key) {
+                FormOutcome::Pending => {}
+                FormOutcome::Cancelled => self.add_dialog = None,
+                FormOutcome::Confirmed => self.confirm_add_agent(),
+            }
+            return;
+        }
+        if self.terminal.is_some() {
+            self.handle_terminal_key(key);
+            return;
+        }
+        if self.shell.is_some() {
+            self.handle_shell_key(key);
+            return;
+        }
+        if self.session_view.is_some() {
+            if matches!(key.code, KeyCode::Esc | KeyCode::Char('o') | KeyCode::Char('q')) {
+                self.session_view = None;
+            }
+            return;
+        }
+        if let Some(dialog) = self.upload_dialog.as_mut() {
+            match dialog.handle_key(key) {
+                FormOutcome::Pending => {}
+                FormOutcome::Cancelled => self.upload_dialog = None,
+                FormOutcome::Confirmed => self.confirm_upload(),
+            }
+            return;
+        }
+        if let Some(dialog) = self.listener_dialog.as_mut() {
+            match dialog.handle_key(key) {
+                FormOutcome::Pending => {}
+                FormOutcome::Cancelled => self.listener_dialog = None,
+                FormOutcome::Confirmed => self.confirm_create_listener(),
+            }
+            return;
+        }
+        if let Some(ids) = self.confirm_delete.take() {
+            // Toute autre touche que 'y' annule
+            if key.code == KeyCode::Char('y') {
+                self.remove_agents(&ids);
+            }
+            return;
+        }
+        if let Some(confirm) = self.confirm_self_destruct.as_mut() {
+            match confirm.handle_key(key) {
+                ConfirmOutcome::Pending => {}
+                ConfirmOutcome::Closed => self.confirm_self_destruct = None,
+                ConfirmOutcome::Confirmed(agent_id) => {
+                    self.confirm_self_destruct = None;
+                    self.self_destruct(&agent_id);
+                }
+            }
+            return;
+        }
+        if self.edit_dialog.is_some() {
+            self.handle_edit_key(key);
+            return;
+        }
+        if self.search.is_some() {
+            self.handle_search_key(key);
+            return;
+        }
+        if self.command_prompt.is_some() {
+            self.handle_command_key(key);
+            return;
+        }
+        if self.upload_prompt.is_some() {
+            self.handle_upload_key(key);
+            return;
+        }
+        // Ouvert depuis la liste des tâches, il passe devant elle
+        if self.loot_panel.is_some() {
+            self.handle_loot_key(key);
+            return;
+        }
+        if self.task_view.is_some() {
+            self.handle_task_view_key(key);
+            return;
+        }
+        if self.keylog_panel.is_some() {
+            self.handle_keylog_key(key);
+            return;
+        }
+        if let Some(popup) = self.clipboard_popup.as_mut() {
+            match popup.handle_key(key) {
+                ClipboardOutcome::Pending => {}
+                ClipboardOutcome::Closed => self.clipboard_popup = None,
+                ClipboardOutcome::Copy(text) => {
+                    let label = format!("Presse-papiers de {}", popup.agent_id);
+                    self.emit(Effect::Copy { label, text });
+                }
+            }
+            return;
+        }
+        if let Some(panel) = self.gallery_panel.as_mut() {
+            match panel.handle_key(key) {
+                GalleryOutcome::Pending => {}
+                GalleryOutcome::Closed => self.gallery_panel = None,
+                GalleryOutcome::Open(path) => self.emit(Effect::OpenScreenshot(path)),
+            }
+            return;
+        }
+        if let Some(panel) = self.pivots_panel.as_mut() {
+            match panel.handle_key(key) {
+                PivotOutcome::Pending => {}
+                PivotOutcome::Closed => self.pivots_panel = None,
+                PivotOutcome::ClosePivot(id) => self.close_pivot(id),
+            }
+            return;
+        }
+        if let Some(panel) = self.topology_panel.as_mut() {
+            match panel.handle_key(key) {
+                TopologyOutcome::Pending => {}
+                TopologyOutcome::Closed => self.topology_panel = None,
+                TopologyOutcome::Scan => self.scan_reachability(),
+            }
+            return;
+        }
+        if let Some(panel) = self.process_panel.as_mut() {
+            match panel.handle_key(key) {
+                ProcessOutcome::Pending => {}
+                ProcessOutcome::Closed => self.process_panel = None,
+                ProcessOutcome::Refresh => self.request_process_list(),
+            }
+            return;
+        }
+        if let Some(viewer) = self.log_viewer.as_mut() {
+            if viewer.handle_key(key) {
+                self.log_viewer = None;
+            }
+            return;
+        }
+        if self.tag_prompt.is_some() {
+            self.handle_tag_key(key);
+            return;
+        }
+        if let Some((_, dialog)) = self.beacon_dialog.as_mut() {
+            match dialog.handle_key(key) {
+                FormOutcome::Pending => {}
+                FormOutcome::Cancelled => self.beacon_dialog = None,
+                FormOutcome::Confirmed => self.confirm_beacon(),
+            }
+            return;
+        }
+        if self.note_editor.is_some() {
+            self.handle_note_key(key);
+            return;
+        }
+        if self.detail_view.is_some() {
+            self.handle_detail_key(key);
+            return;
+        }
+        if self.groups_panel.is_some() {
+            self.handle_groups_key(key);
+            return;
+        }
+        if self.palette.is_some() {
+            self.handle_palette_key(key);
+            return;
+        }
+        // Une vue ouverte dans l'écran, comme la sortie d'une tâche, prend toutes les touches
+        if self.screens.get(self.active_screen).is_some_and(|s| s.captures_input()) {
+            self.handle_screen_key(key);
+            return;
+        }
+        // Une touche de l'écran actif l'emporte sur la touche globale (a dans Listeners)
+        let tables = [self.active_screen.bindings(), GLOBAL_KEYS];
+        let Some(action) = resolve(&tables, &mut self.pending_key, key) else {
+            return;
+        };
+        self.run_action(action);
+    }
+
+    // Seul l'envoi de fichier remonte des écrans autonomes, pour le formulaire d'App
+    pub(crate) fn handle_screen_key(&mut self, key: KeyEvent) {
+        let Some(screen) = self.screens.get_mut(self.active_screen) else {
+            return;
+        };
+        if let Err(e) = screen.handle_key(&self.db, key) {
+            self.show_error(format!("{} : {}", self.active_screen.label(), e));
+        }
+    }
+
+    // Ligne de la liste sous la souris, compte tenu du défilement
+    pub(crate) fn row_at(&self, column: u16, row: u16) -> Option<usize> {
+        if !self.list_area.contains(Position::new(column, row)) {
+            return None;
+        }
+        let index = self.list_state.offset() + (row - self.list_area.y) as usize;
+        (index < self.rows.len()).then_some(index)
+    }
+
+    pub fn handle_mouse(&mut self, mouse: MouseEvent) -> Vec<Effect> {
+        self.dispatch_mouse(mouse);
+        self.take_effects()
+    }
+
+    pub(crate) fn dispatch_mouse(&mut self, mouse: MouseEvent) {
+        if let Some(scroll) = self.help_scroll.as_mut() {
+            match mouse.kind {
+                MouseEventKind::ScrollDown => *scroll = scroll.saturating_add(3),
+                MouseEventKind::ScrollUp => *scroll = scroll.saturating_sub(3),
+                MouseEventKind::Down(_) => self.help_scroll = None,
+                _ => {}
+            }
+            return;
+        }
+        if let Some(menu) = &self.context_menu {
+            if let MouseEventKind::Down(_) = mouse.kind {
+                let clicked = menu.item_at(mouse.column, mouse.row);
+                self.context_menu = None;
+                if let Some((_, action)) = clicked {
+                    self.run_agent_action(action);
+                }
+            }
+            return;
+        }
+        if self.modal_open() || self.active_screen != AppScreen::Agents {
+            return;
+        }
+        let here = Position::new(mouse.column, mouse.row);
+        match mouse.kind {
+            MouseEventKind::Down(button) => {
+                let Some(index) = self.row_at(mouse.column, mouse.row) else {
+                    return;
+                };
+                self.selected_index = index;
+                self.sync_selection();
+                if button == MouseButton::Right && let Some(agent) = self.selected_agent() {
+                    self.context_menu = Some(ContextMenu {
+                        agent_id: agent.id.clone(),
+                        position: here,
+                        selected: 0,
+                        area: Rect::default(),
+                    });
+                }
+            }
+            MouseEventKind::ScrollDown if self.datasheet_area.contains(here) => {
+                self.datasheet_scroll = self.datasheet_scroll.saturating_add(1);
+            }
+            MouseEventKind::ScrollUp if self.datasheet_area.contains(here) => {
+                self.datasheet_scroll = self.datasheet_scroll.saturating_sub(1);
+            }
+            MouseEventKind::ScrollDown if self.list_area.contains(here) => self.select_next(),
+            MouseEventKind::ScrollUp if self.list_area.contains(here) => self.select_previous(),
+            _ => {}
+        }
+    }
+
+    pub(crate) fn handle_context_menu_key(&mut self, key: KeyEvent) {
+        let Some(menu) = self.context_menu.as_mut() else {
+            return;
+        };
+        match key.code {
+            KeyCode::Esc => self.context_menu = None,
+            KeyCode::Up => menu.selected = menu.selected.saturating_sub(1),
+            KeyCode::Down => menu.selected = (menu.selected + 1).min(CONTEXT_ACTIONS.len() - 1),
+            KeyCode::Enter => {
+                let (_, action) = CONTEXT_ACTIONS[menu.selected];
+                self.context_menu = None;
+                self.run_agent_action(action);
+            }
+            _ => {}
+        }
+    }
+
+    pub(crate) fn handle_copy_menu_key(&mut self, key: KeyEvent) {
+        let Some(menu) = self.copy_menu.as_mut() else {
+            return;
+        };
+        match key.code {
+            KeyCode::Esc | KeyCode::Char('q') => self.copy_menu = None,
+            KeyCode::Up | KeyCode::Char('k') => menu.selected = menu.selected.saturating_sub(1),
+            KeyCode::Down | KeyCode::Char('j') => menu.selected = (menu.selected + 1).min(COPY_FIELDS.len() - 1),
+            KeyCode::Enter | KeyCode::Char('y') => {
+                let field = menu.field();
+                self.copy_menu = None;
+                self.copy_field(field);
+            }
+            _ => {}
+        }
+    }
+
+    pub(crate) fn handle_command_key(&mut self, key: KeyEvent) {
+        let Some(prompt) = self.command_prompt.as_mut() else {
+            return;
+        };
+        match key.code {
+            KeyCode::Esc => self.command_prompt = None,
+            KeyCode::Up => prompt.recall_previous(),
+            KeyCode::Down => prompt.recall_next(),
+            KeyCode::Enter => {
+                let command = prompt.input.content.trim().to_string();
+                let agent_ids = std::mem::take(&mut prompt.agent_ids);
+                if !command.is_empty() {
+                    let result = agent_ids.iter()
+                        .try_for_each(|id| self.db.enqueue_task(id, &command).map(|_| ()));
+                    match result {
+                        Ok(()) => {
+                            self.error = None;
+                            let detail = format!("{}: {}", agent_ids.join(","), command);
+                            self.audit("enqueue_task", &detail);
+                            for id in &agent_ids {
+                                self.record(Level::Info, Category::Task, Some(id), &format!("Tâche en file : {}", command));
+                            }
+                            if let Err(e) = push_history(self.db.conn(), &command) {
+                                self.show_error(format!("Historique non enregistré : {}", e));
+                            }
+                        }
+                        Err(e) => self.show_error(format!("Échec de l'envoi : {}", e)),
+                    }
+                }
+                // Le terminal reste ouvert pour enchaîner les commandes, Échap le quitte
+                self.open_command_prompt(agent_ids);
+                self.refresh_visible();
+            }
+            _ => {
+                prompt.input.handle_key(key);
+            }
+        }
+    }
+
+    pub(crate) fn handle_task_view_key(&mut self, key: KeyEvent) {
+        let Some(view) = self.task_view.as_mut() else {
+            return;
+        };
+        if let Some(viewer) = view.viewer.as_mut() {
+            match viewer.handle_key(&self.db, key) {
+                Ok(true) => {}
+                Ok(false) => view.viewer = None,
+                Err(e) => self.show_error(format!("Lecture de la sortie impossible : {}", e)),
+            }
+            return;
+        }
+        let last = view.tasks.len().saturating_sub(1);
+        let selected = view.state.selected().unwrap_or(last);
+        match key.code {
+            KeyCode::Esc | KeyCode::Char('q') => self.task_view = None,
+            KeyCode::Char('c') => {
+                let agent_ids = vec![view.agent_id.clone()];
+                self.open_command_prompt(agent_ids);
+            }
+            KeyCode::Char('f') => {
+                let agent_id = view.agent_id.clone();
+                self.open_download_prompt(&agent_id);
+            }
+            KeyCode::Char('F') => {
+                let agent_id = view.agent_id.clone();
+                self.open_loot_panel(&agent_id);
+            }
+            KeyCode::Char('u') => {
+                self.upload_prompt = Some(UploadPrompt {
+                    agent_id: view.agent_id.clone(),
+                    local_path: None,
+                    input: TextInput::default(),
+                });
+            }
+            KeyCode::Char('r') => {
+                if let Some(id) = view.tasks.get(selected).map(|t| t.id) {
+                    self.resume_task_upload(id);
+                }
+            }
+            KeyCode::Enter => {
+                let Some(task) = view.tasks.get(selected) else {
+                    return;
+                };
+                if !matches!(task.status.as_str(), "done" | "failed") {
+                    self.notice = Some(format!("La tâche #{} n'est pas terminée", task.id));
+                    return;
+                }
+                match OutputViewer::open(&self.db, task) {
+                    Ok(viewer) => view.viewer = Some(viewer),
+                    Err(e) => self.show_error(format!("Lecture de la sortie impossible : {}", e)),
+                }
+            }
+            KeyCode::Down => view.state.select(Some((selected + 1).min(last))),
+            KeyCode::Up => view.state.select(Some(selected.saturating_sub(1))),
+            _ => {}
+        }
+    }
+
+    // Défilement de la fiche ; e ouvre la note par-dessus, Échap revient à la liste
+    pub(crate) fn handle_detail_key(&mut self, key: KeyEvent) {
+        let Some(view) = self.detail_view.as_mut() else {
+            return;
+        };
+        let page = view.height.max(1);
+        let scroll = match key.code {
+            KeyCode::Esc | KeyCode::Char('q') => {
+                self.detail_view = None;
+                return;
+            }
+            KeyCode::Char('e') => {
+                let agent_id = view.agent_id.clone();
+                self.edit_latest_note(&agent_id);
+                return;
+            }
+            KeyCode::Down | KeyCode::Char('j') => view.scroll.saturating_add(1),
+            KeyCode::Up | KeyCode::Char('k') => view.scroll.saturating_sub(1),
+            KeyCode::PageDown => view.scroll.saturating_add(page),
+            KeyCode::PageUp => view.scroll.saturating_sub(page),
+            KeyCode::Char('g') | KeyCode::Home => 0,
+            KeyCode::Char('G') | KeyCode::End => u16::MAX,
+            _ => return,
+        };
+        view.scroll = scroll.min(view.max_scroll());
+    }
+
+    pub(crate) fn handle_loot_key(&mut self, key: KeyEvent) {
+        let Some(panel) = self.loot_panel.as_mut() else {
+            return;
+        };
+        match panel.handle_key(key) {
+            LootOutcome::Pending => {}
+            LootOutcome::Closed => self.loot_panel = None,
+            LootOutcome::Reveal(path) => self.flash(&path.display().to_string(), false),
+        }
+    }
+
+    pub(crate) fn handle_keylog_key(&mut self, key: KeyEvent) {
+        let Some(panel) = self.keylog_panel.as_mut() else {
+            return;
+        };
+        match panel.handle_key(key) {
+            KeylogOutcome::Pending => {}
+            KeylogOutcome::Closed => self.keylog_panel = None,
+            KeylogOutcome::Export => {
+                let agent_id = panel.agent_id.clone();
+                match export_keylog(self.db.conn(), &agent_id) {
+                    Ok(path) => {
+                        self.audit("export_keylog", &format!("{} {}", agent_id, path.display()));
+                        self.flash(&format!("Keylog de {} exporté vers {}", agent_id, path.display()), false);
+                    }
+                    Err(e) => self.flash(&format!("Export du keylog impossible : {}", e), true),
+                }
+            }
+        }
+    }
+
+    pub(crate) fn handle_terminal_key(&mut self, key: KeyEvent) {
+        let Some(terminal) = self.terminal.as_mut() else {
+            return;
+        };
+        match key.code {
+            KeyCode::Esc => self.terminal = None,
+            KeyCode::Char(c) => terminal.command.push(c),
+            KeyCode::Backspace => {
+                terminal.command.pop();
+            }
+            KeyCode::PageUp => terminal.scroll = terminal.scroll.saturating_add(5),
+            KeyCode::PageDown => terminal.scroll = terminal.scroll.saturating_sub(5),
+            KeyCode::Enter => {
+                let command = std::mem::take(&mut terminal.command);
+                terminal.scroll = 0;
+                let Some(session) = self.sessions.get(&terminal.session_id) else {
+                    let message = format!("Session de {} fermée", terminal.agent_id);
+                    self.show_error(message);
+                    return;
+                };
+                let mut bytes = command.clone().into_bytes();
+                bytes.push(b'\n');
+                match session.tx.try_send(bytes) {
+                    Ok(()) => {
+                        self.error = None;
+                        self.session_output.entry(terminal.session_id.clone())
+                            .or_default()
+                            .push_line(format!("$ {}", command));
+                        let detail = format!("{} {}: {}", terminal.agent_id, terminal.session_id, command);
+                        let message = format!("Commande sur {} : {}", terminal.session_id, command);
+                        let agent_id = terminal.agent_id.clone();
+                        drop(session);
+                        self.audit("session_command", &detail);
+                        self.record(Level::Info, Category::Session, Some(&agent_id), &message);
+                    }
+                    Err(e) => {
+                        drop(session);
+                        self.show_error(format!("Envoi impossible : {}", e));
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    pub(crate) fn handle_shell_key(&mut self, key: KeyEvent) {
+        let Some(shell) = self.shell.as_ref().and_then(|id| self.shells.get_mut(id)) else {
+            self.shell = None;
+            return;
+        };
+        match key.code {
+            // La ligne en cours reste dans le shell pour le prochain passage
+            KeyCode::Esc => self.shell = None,
+            KeyCode::PageUp => shell.scroll = shell.scroll.saturating_add(5),
+            KeyCode::PageDown => shell.scroll = shell.scroll.saturating_sub(5),
+            KeyCode::Enter => self.send_shell_line(),
+            _ => {
+                shell.input.handle_key(key);
+            }
+        }
+    }
+
+    pub(crate) fn handle_tag_key(&mut self, key: KeyEvent) {
+        let Some((agent_ids, input)) = self.tag_prompt.as_mut() else {
+            return;
+        };
+        match key.code {
+            KeyCode::Esc => self.tag_prompt = None,
+            KeyCode::Enter => {
+                let (added, removed) = parse_tag_edits(&input.content);
+                let agent_ids = std::mem::take(agent_ids);
+                self.tag_prompt = None;
+                self.apply_tag_edits(&agent_ids, &added, &removed);
+            }
+            _ => {
+                input.handle_key(key);
+            }
+        }
+    }
+
+    // Le fichier local est vérifié dès la première Entrée, avant de demander la destination
+    pub(crate) fn handle_upload_key(&mut self, key: KeyEvent) {
+        let Some(prompt) = self.upload_prompt.as_mut() else {
+            return;
+        };
+        match key.code {
+            KeyCode::Esc => self.upload_prompt = None,
+            KeyCode::Tab if prompt.local_path.is_none() => {
+                prompt.input = TextInput::new(&complete_path(&prompt.input.content));
+            }
+            KeyCode::Enter if prompt.local_path.is_none() => {
+                let path = prompt.input.content.trim().to_string();
+                if path.is_empty() {
+                    return;
+                }
+                match check_upload(&path, self.config.max_upload_bytes) {
+                    Ok(_) => {
+                        prompt.local_path = Some(path);
+                        prompt.input = TextInput::default();
+                    }
+                    Err(e) => self.show_error(e.to_string()),
+                }
+            }
+            KeyCode::Enter => {
+                let remote = prompt.input.content.trim().to_string();
+                if remote.is_empty() {
+                    return;
+                }
+                let Some(prompt) = self.upload_prompt.take() else {
+                    return;
+                };
+                let local = prompt.local_path.unwrap_or_default();
+                self.start_task_upload(&prompt.agent_id, &local, &remote);
+            }
+            _ => {
+                prompt.input.handle_key(key);
+            }
+        }
+    }
+
+    pub(crate) fn handle_groups_key(&mut self, key: KeyEvent) {
+        let Some(mut panel) = self.groups_panel.take() else {
+            return;
+        };
+        if let Some(input) = panel.input.as_mut() {
+            match key.code {
+                KeyCode::Esc => panel.input = None,
+                KeyCode::Enter => {
+                    let line = std::mem::take(&mut input.content);
+                    panel.input = None;
+                    self.create_group(&mut panel, &line);
+                }
+                _ => {
+                    input.handle_key(key);
+                }
+            }
+            self.groups_panel = Some(panel);
+            return;
+        }
+        match key.code {
+            KeyCode::Esc | KeyCode::Char('q') | KeyCode::Char('p') => return,
+            KeyCode::Up | KeyCode::Char('k') => {
+                panel.index = panel.index.saturating_sub(1);
+                self.load_group_members(&mut panel);
+            }
+            KeyCode::Down | KeyCode::Char('j') => {
+                panel.index = (panel.index + 1).min(panel.groups.len().saturating_sub(1));
+                self.load_group_members(&mut panel);
+            }
+            KeyCode::Char('+') => panel.input = Some(TextInput::default()),
+            KeyCode::Char('a') => self.edit_group_members(&mut panel, true),
+            KeyCode::Char('x') => self.edit_group_members(&mut panel, false),
+            // Filtre la liste sur les membres et referme le panneau
+            KeyCode::Enter => {
+                if let Some(group) = panel.groups.get(panel.index) {
+                    self.filter = format!("group:{}", group.name);
+                    self.refresh_filter();
+                }
+                return;
+            }
+            _ => {}
+        }
+        self.groups_panel = Some(panel);
+    }
+
+    pub(crate) fn handle_palette_key(&mut self, key: KeyEvent) {
+        let Some(input) = self.palette.as_mut() else {
+            return;
+        };
+        match key.code {
+            KeyCode::Esc => self.palette = None,
+            // Complète le nom de commande avec la première suggestion
+            KeyCode::Tab => {
+                if let Some((name, _)) = palette_matches(&input.content).first() {
+                    *input = TextInput::new(&format!("{} ", name));
+                }
+            }
+            KeyCode::Enter => {
+                let line = std::mem::take(&mut input.content);
+                self.palette = None;
+                self.run_palette_command(&line);
+            }
+            _ => {
+                input.handle_key(key);
+            }
+        }
+    }
+
+    pub(crate) fn handle_note_key(&mut self, key: KeyEvent) {
+        let Some(editor) = self.note_editor.as_mut() else {
+            return;
+        };
+        match editor.handle_key(key) {
+            EditorOutcome::Pending => {}
+            EditorOutcome::Cancelled => self.note_editor = None,
+            EditorOutcome::Saved => {
+                if let Some(editor) = self.note_editor.take() {
+                    self.save_note(&editor);
+                }
+            }
+        }
+    }
+
+    pub(crate) fn handle_search_key(&mut self, key: KeyEvent) {
+        let Some(search) = self.search.as_mut() else {
+            return;
+        };
+        match key.code {
+            KeyCode::Enter => {
+                self.filter = search.content.clone();
+                self.search = None;
+            }
+            KeyCode::Esc => {
+                self.filter.clear();
+                self.search = None;
+                self.refresh_filter();
+            }
+            _ => {
+                if search.handle_key(key) {
+                    self.refresh_filter();
+                }
+            }
+        }
+    }
+
+    pub(crate) fn handle_edit_key(&mut self, key: KeyEvent) {
+        let Some(dialog) = self.edit_dialog.as_mut() else {
+            return;
+        };
+        match key.code {
+            KeyCode::Esc => self.edit_dialog = None,
+            KeyCode::Enter => self.commit_edit_field(),
+            _ => {
+                dialog.input.handle_key(key);
+            }
+        }
+    }
+}
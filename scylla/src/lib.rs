@@ -0,0 +1,53 @@
+pub mod agent;
+pub mod app;
+pub mod audit;
+pub mod auth;
+pub mod beacon;
+pub mod checkins;
+pub mod cli;
+pub mod clipboard;
+pub mod config;
+pub mod crypto;
+pub mod db;
+pub mod editor;
+pub mod error;
+pub mod eventlog;
+pub mod events;
+pub mod export;
+pub mod form;
+pub mod geoip;
+pub mod groups;
+pub mod headless;
+pub mod history;
+pub mod import;
+pub mod input;
+pub mod keylog;
+pub mod keymap;
+pub mod listeners;
+pub mod logging;
+pub mod loot;
+pub mod map;
+pub mod netinfo;
+pub mod notes;
+pub mod pivots;
+pub mod processes;
+pub mod refresh;
+pub mod scheduler;
+pub mod schema;
+pub mod screens;
+pub mod screenshots;
+pub mod selfdestruct;
+pub mod sessions;
+pub mod shell;
+pub mod status;
+pub mod sysinfo;
+pub mod tags;
+pub mod theme;
+pub mod timefmt;
+pub mod topology;
+pub mod transfers;
+pub mod transport;
+pub mod tui;
+pub mod ui;
+pub mod viewer;
+pub mod worker;
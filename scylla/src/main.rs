@@ -1,183 +1,359 @@
 use ratatui::backend::CrosstermBackend;
 use ratatui::Terminal;
-use ratatui::widgets::{Block, List, ListItem, ListState, Paragraph};
-use ratatui::text::{Span, Line};
-use ratatui::style::{Style, Color};
-use crossterm::{
-    execute,
-    terminal::{enable_raw_mode, disable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
-    event::{self, Event, KeyCode},
-};
+use ratatui::widgets::Block;
+use crossterm::event::{self, Event, KeyCode, KeyEventKind};
 use std::io;
-use std::time::{Duration, Instant};
-use rusqlite::{Connection, Result};
-
-#[derive(Debug)]
-struct Agent {
-    id: String,
-    hostname: String,
-    ip: String,
-    os: Option<String>,
-    status: String,
-    last_seen: Option<String>,
-    location: Option<String>,
-    note: Option<String>,
-}
-
-fn load_agents() -> Result<Vec<Agent>> {
-    let conn = Connection::open("c2.db")?;
-    let mut stmt = conn.prepare(
-        "SELECT id, hostname, ip, os, status, last_seen, location, note FROM agents"
-    )?;
-    let agent_iter = stmt.query_map([], |row| {
-        Ok(Agent {
-            id: row.get(0)?,
-            hostname: row.get(1)?,
-            ip: row.get(2)?,
-            os: row.get(3).ok(),
-            status: row.get(4)?,
-            last_seen: row.get(5).ok(),
-            location: row.get(6).ok(),
-            note: row.get(7).ok(),
-        })
-    })?;
-    Ok(agent_iter.filter_map(Result::ok).collect())
-}
+use std::sync::mpsc;
+use std::time::Duration;
 
-fn main() -> Result<(), Box<dyn std::error::Error>> {
-    enable_raw_mode()?;
-    let mut stdout = io::stdout();
-    execute!(stdout, EnterAlternateScreen)?;
-    let backend = CrosstermBackend::new(stdout);
-    let mut terminal = Terminal::new(backend)?;
+use clap::Parser;
+use tracing::{error, info, warn};
+use scylla::agent::Agent;
+use scylla::app::{App, Effect};
+use scylla::audit::log_action;
+use scylla::auth::{authenticate, create_user, has_users};
+use scylla::cli::{Cli, Command, FileFormat};
+use scylla::clipboard::copy_to_clipboard;
+use scylla::config::ScyllaConfig;
+use scylla::crypto::{set_payload_key, EncryptionKey};
+use scylla::db::Db;
+use scylla::error::{self, Error};
+use scylla::events::{
+    spawn_input_thread, spawn_offline_thread, spawn_poll_thread, spawn_scheduler_thread, spawn_signal_task,
+    spawn_tick_thread, AppEvent, Shutdown,
+};
+use scylla::export::{export_agents_csv, export_agents_json};
+use scylla::form::{draw_form, Form, FormOutcome};
+use scylla::headless::{run_agents, Output};
+use scylla::import::{import_agents_csv, import_agents_json, import_message, ImportSummary, OnConflict};
+use scylla::keymap::set_overrides;
+use scylla::logging::{init_logging, logs_dir};
+use scylla::notes::load_notes;
+use scylla::screenshots::{beside_db_path, open_screenshot};
+use scylla::status::StalenessThresholds;
+use scylla::theme::ColorScheme;
+use scylla::tui::TerminalGuard;
+use scylla::ui::{centered_rect, draw, draw_startup_error};
+use scylla::worker::spawn_db_worker;
 
-    let agents = load_agents().unwrap_or_default();
-    let mut selected_index = 0usize;
-    let mut list_state = ListState::default();
-    list_state.select(Some(selected_index));
+const LOGIN_FIELDS: [(&str, bool); 2] = [
+    ("Username", true),
+    ("Password", true),
+];
 
-    let tick_rate = Duration::from_millis(200);
-    let mut last_tick = Instant::now();
-    let mut last_pressed: Option<KeyCode> = None; // mémorise la dernière touche pressée
+// Délai laissé aux requêtes et sessions des listeners à l'arrêt
+const LISTENER_GRACE: Duration = Duration::from_secs(2);
 
+// Écran de connexion avant la boucle principale ; None si l'opérateur abandonne.
+// Sur une base sans compte, les identifiants saisis créent le premier opérateur.
+fn run_login(
+    terminal: &mut Terminal<CrosstermBackend<io::Stdout>>,
+    db: &Db,
+    scheme: &ColorScheme,
+) -> error::Result<Option<String>> {
+    let first_operator = !has_users(db.conn())?;
+    let title = if first_operator { "Créer le premier opérateur" } else { "Connexion" };
+    let mut form = Form::new(title, &LOGIN_FIELDS).with_secret(1);
     loop {
         terminal.draw(|f| {
-            let size = f.area();
+            f.render_widget(Block::default().style(scheme.base()), f.area());
+            draw_form(f, &form, centered_rect(50, 25, f.area()), scheme);
+        })?;
+        let Event::Key(key) = event::read()? else {
+            continue;
+        };
+        if key.kind != KeyEventKind::Press {
+            continue;
+        }
+        match form.handle_key(key) {
+            FormOutcome::Pending => {}
+            FormOutcome::Cancelled => return Ok(None),
+            FormOutcome::Confirmed => {
+                let username = form.value(0).to_string();
+                if first_operator {
+                    create_user(db.conn(), &username, form.value(1))?;
+                    log_action(db.conn(), &username, "create_user", &username)?;
+                    info!("premier opérateur créé : {}", username);
+                    return Ok(Some(username));
+                }
+                match authenticate(db.conn(), &username, form.value(1)) {
+                    Ok(true) => {
+                        log_action(db.conn(), &username, "login", "")?;
+                        info!("connexion de {}", username);
+                        return Ok(Some(username));
+                    }
+                    Ok(false) => {
+                        log_action(db.conn(), &username, "login_failed", "")?;
+                        warn!("échec de connexion pour {}", username);
+                        form.clear(1);
+                        form.reject(1, "Identifiants invalides".to_string());
+                    }
+                    Err(e @ Error::AccountLocked { .. }) => {
+                        warn!("{}", e);
+                        form.clear(1);
+                        form.reject(0, e.to_string());
+                    }
+                    Err(e) => return Err(e),
+                }
+            }
+        }
+    }
+}
 
-            let vertical_chunks = ratatui::layout::Layout::default()
-                .direction(ratatui::layout::Direction::Vertical)
-                .margin(1)
-                .constraints([
-                    ratatui::layout::Constraint::Length(3),
-                    ratatui::layout::Constraint::Min(10),
-                    ratatui::layout::Constraint::Length(7),
-                ])
-                .split(size);
+// scylla export : tous les agents dans l'ordre de la base, sans connexion opérateur
+fn run_export(db_path: &str, format: FileFormat, out: &std::path::Path) -> Result<usize, Box<dyn std::error::Error>> {
+    let db = Db::open(db_path)?;
+    let agents = db.load_agents()?;
+    let notes = load_notes(db.conn())?;
+    let agents: Vec<&Agent> = agents.iter().collect();
+    let count = match format {
+        FileFormat::Csv => export_agents_csv(&agents, &notes, out)?,
+        FileFormat::Json => export_agents_json(&agents, &notes, out)?,
+    };
+    Ok(count)
+}
 
-            let top_chunks = ratatui::layout::Layout::default()
-                .direction(ratatui::layout::Direction::Horizontal)
-                .constraints([
-                    ratatui::layout::Constraint::Length(20),
-                    ratatui::layout::Constraint::Min(10),
-                ])
-                .split(vertical_chunks[0]);
+// scylla import : journalisé dans l'audit comme depuis la palette, au nom de l'opérateur « cli »
+fn run_import(db_path: &str, format: FileFormat, file: &std::path::Path, on_conflict: OnConflict) -> Result<ImportSummary, Box<dyn std::error::Error>> {
+    let db = Db::open(db_path)?;
+    let summary = match format {
+        FileFormat::Csv => import_agents_csv(&db, file, on_conflict)?,
+        FileFormat::Json => import_agents_json(&db, file, on_conflict)?,
+    };
+    log_action(db.conn(), "cli", "import_agents", &import_message(file, &summary))?;
+    Ok(summary)
+}
 
-            let middle_chunks = ratatui::layout::Layout::default()
-                .direction(ratatui::layout::Direction::Horizontal)
-                .constraints([
-                    ratatui::layout::Constraint::Length(30),
-                    ratatui::layout::Constraint::Min(10),
-                ])
-                .split(vertical_chunks[1]);
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let cli = Cli::parse();
+    let refresh_ticks = cli.refresh_ticks;
+    // Le message de toml indique la ligne et le champ fautifs, affiché tel quel plutôt qu'en Debug
+    let mut config = match ScyllaConfig::load(&cli.config_path()) {
+        Ok(config) => config,
+        Err(e) => {
+            eprintln!("{}", e);
+            std::process::exit(1);
+        }
+    };
+    config.stale_after_beacons = cli.stale_after.unwrap_or(config.stale_after_beacons);
+    config.dead_after_beacons = cli.dead_after.unwrap_or(config.dead_after_beacons);
+    let thresholds = StalenessThresholds::from_config(&config)?;
+    let key_warnings = set_overrides(&config.keys);
+    let db_path = cli.db_path(config.db_path.as_deref()).into_os_string().into_string()
+        .map_err(|path| format!("chemin de base non UTF-8 : {}", path.to_string_lossy()))?;
+    if cli.dump_config {
+        config.db_path = Some(db_path.into());
+        print!("{}", config.dump());
+        for warning in key_warnings {
+            eprintln!("{}", warning);
+        }
+        return Ok(());
+    }
+    // Une clé configurée mais illisible arrête tout : des tâches écrites en clair à sa place
+    // passeraient inaperçues
+    if let Some(path) = &config.operator_key {
+        match EncryptionKey::load(path) {
+            Ok(key) => set_payload_key(key),
+            Err(e) => {
+                eprintln!("{}", e);
+                std::process::exit(1);
+            }
+        }
+    }
+    // 0 en cas de succès, 3 pour un agent inconnu, 1 pour toute autre erreur ; un tube
+    // refermé par le lecteur (| head) n'est pas une erreur
+    if let Some(Command::Agents { action }) = &cli.command {
+        match run_agents(&db_path, &thresholds, action, &Output::detect(cli.no_color)) {
+            Ok(()) => {}
+            Err(Error::Io(e)) if e.kind() == io::ErrorKind::BrokenPipe => {}
+            Err(e @ Error::AgentNotFound(_)) => {
+                eprintln!("{}", e);
+                std::process::exit(3);
+            }
+            Err(e) => {
+                eprintln!("{}", e);
+                std::process::exit(1);
+            }
+        }
+        return Ok(());
+    }
+    if let Some(Command::Export { format, out }) = &cli.command {
+        match run_export(&db_path, *format, out) {
+            Ok(count) => eprintln!("{} agent(s) exporté(s) vers {}", count, out.display()),
+            Err(e) => {
+                eprintln!("Export impossible : {}", e);
+                std::process::exit(1);
+            }
+        }
+        return Ok(());
+    }
+    if let Some(Command::Import { file, format, on_conflict }) = &cli.command {
+        let format = format.unwrap_or_else(|| FileFormat::of_path(file));
+        let summary = match run_import(&db_path, format, file, *on_conflict) {
+            Ok(summary) => summary,
+            Err(e) => {
+                eprintln!("Import impossible : {}", e);
+                std::process::exit(1);
+            }
+        };
+        for error in &summary.errors {
+            eprintln!("{}", error);
+        }
+        eprintln!("{}", import_message(file, &summary));
+        if !summary.errors.is_empty() {
+            std::process::exit(1);
+        }
+        return Ok(());
+    }
+    // Runtime pour les listeners réseau, arrêté en dernier avec un court délai pour les
+    // tâches en cours. L'interface reste synchrone, nourrie par des threads.
+    let runtime = tokio::runtime::Runtime::new()?;
+    let _runtime_guard = runtime.enter();
+    let (tx, rx) = mpsc::channel();
+    let (db_requests, db_queue) = mpsc::channel();
 
-            let logo_block = Block::default().title("Logo RAT").borders(ratatui::widgets::Borders::ALL);
-            f.render_widget(logo_block, top_chunks[0]);
+    // Avant le mode brut : une fois l'interface lancée, plus rien ne s'écrit sur le
+    // terminal. Un journal impossible à ouvrir est signalé sans bloquer le démarrage.
+    let mut warnings = key_warnings;
+    if let Err(e) = init_logging(&logs_dir(&db_path), config.log_level) {
+        warnings.push(format!("Journal de l'application désactivé : {}", e));
+    }
+    info!("démarrage sur {}", db_path);
 
-            let menu_block = Block::default().title("Menu").borders(ratatui::widgets::Borders::ALL);
-            f.render_widget(menu_block, top_chunks[1]);
+    let mut terminal = TerminalGuard::enter(&config.ui.title)?;
 
-            let agent_items: Vec<ListItem> = agents.iter()
-                .enumerate()
-                .map(|(i, a)| {
-                    let text = format!("{} | {} | {} | {}", a.id, a.hostname, a.ip, a.status);
-                    if i == selected_index {
-                        ListItem::new(Span::styled(text, Style::default().bg(Color::Blue).fg(Color::White)))
-                    } else {
-                        ListItem::new(Span::raw(text))
-                    }
-                })
-                .collect();
+    let opened = if cli.init && !std::path::Path::new(&db_path).exists() {
+        Db::create(&db_path)
+    } else {
+        Db::open(&db_path)
+    };
+    let scheme = config.ui.scheme();
+    let tick_rate = Duration::from_millis(config.tick_rate_ms);
+    let checkin_retention = Duration::from_secs(config.checkin_retention_days.max(0) as u64 * 86400);
+    let started = opened
+        .and_then(|db| Ok(run_login(&mut terminal, &db, &scheme)?.map(|operator| (db, operator))))
+        .and_then(|logged| {
+            logged
+                .map(|(db, operator)| App::new(db, operator, config, thresholds, tx.clone(), db_requests))
+                .transpose()
+        });
+    let mut app = match started {
+        Ok(Some(app)) => app,
+        Ok(None) => return Ok(()),
+        Err(e) => {
+            let message = e.to_string();
+            error!("démarrage impossible : {}", message);
+            let shown: io::Result<()> = (|| loop {
+                terminal.draw(|f| draw_startup_error(f, &db_path, &message, &scheme))?;
+                if let Event::Key(key) = event::read()?
+                    && matches!(key.code, KeyCode::Char('q') | KeyCode::Esc | KeyCode::Enter)
+                {
+                    return Ok(());
+                }
+            })();
+            // Le message d'erreur s'affiche sur le terminal restauré
+            drop(terminal);
+            shown?;
+            return Err(e.into());
+        }
+    };
+    // Une section [keys] erronée ne bloque pas le démarrage
+    if !warnings.is_empty() {
+        app.show_error(warnings.join(" ; "));
+    }
 
-            let agents_list = List::new(agent_items)
-                .block(Block::default().title("Agent list").borders(ratatui::widgets::Borders::ALL));
+    let shutdown = Shutdown::default();
+    let workers = [
+        spawn_db_worker(Db::open(&db_path)?, db_queue, tx.clone()),
+        spawn_offline_thread(&db_path, thresholds.dead_after_beacons, checkin_retention, tx.clone(), shutdown.clone()),
+        spawn_scheduler_thread(&db_path, tx.clone(), shutdown.clone()),
+        spawn_poll_thread(&db_path, tx.clone(), shutdown.clone()),
+        spawn_tick_thread(tick_rate, tx.clone(), shutdown.clone()),
+        spawn_input_thread(tx.clone(), shutdown.clone()),
+    ];
+    spawn_signal_task(tx);
+    let mut ticks: u32 = 0;
+    let mut quit = false;
 
-            f.render_stateful_widget(agents_list, middle_chunks[0], &mut list_state);
+    // Un dessin par lot d'événements : tout ce qui est arrivé pendant le rendu est traité
+    // avant le suivant, et les ticks accumulés n'en font qu'un
+    while !quit {
+        terminal.draw(|f| draw(f, &mut app))?;
 
-            let datasheet_text = if let Some(agent) = agents.get(selected_index) {
-                vec![
-                    Line::from(format!("ID: {}", agent.id)),
-                    Line::from(format!("Hostname: {}", agent.hostname)),
-                    Line::from(format!("IP: {}", agent.ip)),
-                    Line::from(format!("OS: {}", agent.os.as_deref().unwrap_or("-"))),
-                    Line::from(format!("Status: {}", agent.status)),
-                    Line::from(format!("Last seen: {}", agent.last_seen.as_deref().unwrap_or("-"))),
-                    Line::from(format!("Location: {}", agent.location.as_deref().unwrap_or("-"))),
-                    Line::from(format!("Note: {}", agent.note.as_deref().unwrap_or("-"))),
-                ]
-            } else {
-                vec![Line::from("Aucun agent sélectionné")]
+        let Ok(first) = rx.recv() else {
+            break;
+        };
+        let mut ticked = false;
+        let mut next = Some(first);
+        while let Some(event) = next.take() {
+            let effects = match event {
+                AppEvent::Input(Event::Key(key_event)) => app.handle_key(key_event),
+                AppEvent::Input(Event::Mouse(mouse_event)) => app.handle_mouse(mouse_event),
+                // Le prochain tour redessine à la nouvelle taille
+                AppEvent::Input(Event::Resize(_, _)) => {
+                    terminal.autoresize()?;
+                    Vec::new()
+                }
+                AppEvent::Input(_) => Vec::new(),
+                AppEvent::Tick => {
+                    ticked = true;
+                    Vec::new()
+                }
+                AppEvent::Terminate => vec![Effect::Quit],
+                event => {
+                    app.handle_event(event);
+                    Vec::new()
+                }
             };
-
-            let datasheet = Paragraph::new(datasheet_text)
-                .block(Block::default().title("Datasheet / Map").borders(ratatui::widgets::Borders::ALL));
-            f.render_widget(datasheet, middle_chunks[1]);
-
-            let terminal_block = Block::default().title("Terminal connecté").borders(ratatui::widgets::Borders::ALL);
-            f.render_widget(terminal_block, vertical_chunks[2]);
-        })?;
-
-        let timeout = tick_rate.checked_sub(last_tick.elapsed()).unwrap_or(Duration::from_secs(0));
-        if event::poll(timeout)? {
-            if let Event::Key(key_event) = event::read()? {
-                match key_event.code {
-                    KeyCode::Char('q') => break,
-                    KeyCode::Down | KeyCode::Up => {
-                        // Si c'est la même touche que la dernière, on ignore
-                        if last_pressed != Some(key_event.code) {
-                            match key_event.code {
-                                KeyCode::Down => {
-                                    if selected_index + 1 < agents.len() {
-                                        selected_index += 1;
-                                    }
-                                }
-                                KeyCode::Up => {
-                                    if selected_index > 0 {
-                                        selected_index -= 1;
-                                    }
-                                }
-                                _ => {}
-                            }
-                            list_state.select(Some(selected_index));
-                            last_pressed = Some(key_event.code); // on note la touche
+            for effect in effects {
+                match effect {
+                    Effect::Quit => quit = true,
+                    Effect::OpenScreenshot(path) => {
+                        if let Err(e) = open_screenshot(&beside_db_path(&db_path, "screenshots"), &path) {
+                            app.flash(&format!("Ouverture de {} impossible : {}", path, e), true);
                         }
                     }
-                    _ => {
-                        last_pressed = None; // autre touche => reset
-                    }
+                    Effect::Copy { label, text } => match copy_to_clipboard(&text, terminal.backend_mut()) {
+                        Ok(method) => {
+                            app.flash(&format!("{} copié ({}) : {}", label, method, text.replace('\t', " ")), false);
+                        }
+                        Err(e) => app.flash(&format!("Copie impossible : {}", e), true),
+                    },
                 }
             }
-        } else {
-            last_pressed = None; // pas d'événement => reset
+            if !quit {
+                next = rx.try_recv().ok();
+            }
         }
-
-        if last_tick.elapsed() >= tick_rate {
-            last_tick = Instant::now();
+        if ticked {
+            app.on_tick();
+            ticks = ticks.wrapping_add(1);
+            if refresh_ticks > 0 && ticks.is_multiple_of(refresh_ticks) {
+                app.request_agents();
+            }
         }
     }
 
-    disable_raw_mode()?;
-    execute!(terminal.backend_mut(), LeaveAlternateScreen)?;
-    terminal.show_cursor()?;
-
+    // Le terminal reste en place, avec ce message, jusqu'à ce que tout soit écrit : un
+    // export en cours retarde le retour au shell plutôt que d'être perdu
+    info!("arrêt demandé");
+    app.flash("Arrêt : listeners, threads et base…", false);
+    terminal.draw(|f| draw(f, &mut app))?;
+    shutdown.trigger();
+    let db = app.shutdown();
+    for worker in workers {
+        if worker.join().is_err() {
+            error!("un thread d'arrière-plan s'est arrêté sur une panique");
+        }
+    }
+    // Requêtes HTTP et sessions TCP encore ouvertes : un délai borné, puis abandon
+    drop(_runtime_guard);
+    runtime.shutdown_timeout(LISTENER_GRACE);
+    if let Err(e) = db.checkpoint() {
+        error!("checkpoint du WAL impossible : {}", e);
+    }
+    drop(db);
+    drop(terminal);
+    info!("arrêt");
     Ok(())
 }
@@ -0,0 +1,135 @@
+use ratatui::layout::Rect;
+use ratatui::style::Style;
+use ratatui::symbols::Marker;
+use ratatui::text::Span;
+use ratatui::widgets::canvas::{Canvas, Map, MapResolution};
+use ratatui::Frame;
+
+use crate::theme::ColorScheme;
+
+// Projection équirectangulaire de 85° N à 60° S : les pôles ne portent aucun agent et
+// écraseraient les continents
+const LAT_TOP: f64 = 85.0;
+const LAT_BOTTOM: f64 = -60.0;
+
+// Pas de base geoip embarquée : les noms courants de pays et de villes suffisent pour
+// placer un agent, le champ location étant saisi à la main ou importé
+const PLACES: &[(&str, f64, f64)] = &[
+    ("paris", 48.86, 2.35),
+    ("lyon", 45.76, 4.84),
+    ("marseille", 43.30, 5.37),
+    ("london", 51.51, -0.13),
+    ("londres", 51.51, -0.13),
+    ("berlin", 52.52, 13.40),
+    ("madrid", 40.42, -3.70),
+    ("rome", 41.90, 12.50),
+    ("amsterdam", 52.37, 4.90),
+    ("brussels", 50.85, 4.35),
+    ("bruxelles", 50.85, 4.35),
+    ("geneva", 46.20, 6.14),
+    ("genève", 46.20, 6.14),
+    ("moscow", 55.76, 37.62),
+    ("new york", 40.71, -74.01),
+    ("san francisco", 37.77, -122.42),
+    ("los angeles", 34.05, -118.24),
+    ("chicago", 41.88, -87.63),
+    ("montreal", 45.50, -73.57),
+    ("montréal", 45.50, -73.57),
+    ("toronto", 43.65, -79.38),
+    ("são paulo", -23.55, -46.63),
+    ("sao paulo", -23.55, -46.63),
+    ("tokyo", 35.68, 139.69),
+    ("beijing", 39.90, 116.41),
+    ("shanghai", 31.23, 121.47),
+    ("singapore", 1.35, 103.82),
+    ("sydney", -33.87, 151.21),
+    ("mumbai", 19.08, 72.88),
+    ("dubai", 25.20, 55.27),
+    ("cairo", 30.04, 31.24),
+    ("johannesburg", -26.20, 28.05),
+    ("france", 46.60, 2.40),
+    ("fr", 46.60, 2.40),
+    ("germany", 51.16, 10.45),
+    ("allemagne", 51.16, 10.45),
+    ("de", 51.16, 10.45),
+    ("united kingdom", 54.0, -2.0),
+    ("royaume-uni", 54.0, -2.0),
+    ("uk", 54.0, -2.0),
+    ("gb", 54.0, -2.0),
+    ("spain", 40.0, -4.0),
+    ("espagne", 40.0, -4.0),
+    ("es", 40.0, -4.0),
+    ("italy", 42.8, 12.8),
+    ("italie", 42.8, 12.8),
+    ("it", 42.8, 12.8),
+    ("netherlands", 52.1, 5.3),
+    ("pays-bas", 52.1, 5.3),
+    ("nl", 52.1, 5.3),
+    ("belgium", 50.5, 4.5),
+    ("belgique", 50.5, 4.5),
+    ("be", 50.5, 4.5),
+    ("switzerland", 46.8, 8.2),
+    ("suisse", 46.8, 8.2),
+    ("ch", 46.8, 8.2),
+    ("russia", 61.5, 90.0),
+    ("russie", 61.5, 90.0),
+    ("ru", 61.5, 90.0),
+    ("united states", 39.8, -98.6),
+    ("usa", 39.8, -98.6),
+    ("états-unis", 39.8, -98.6),
+    ("us", 39.8, -98.6),
+    ("canada", 56.1, -106.3),
+    ("ca", 56.1, -106.3),
+    ("brazil", -14.2, -51.9),
+    ("brésil", -14.2, -51.9),
+    ("br", -14.2, -51.9),
+    ("japan", 36.2, 138.3),
+    ("japon", 36.2, 138.3),
+    ("jp", 36.2, 138.3),
+    ("china", 35.9, 104.2),
+    ("chine", 35.9, 104.2),
+    ("cn", 35.9, 104.2),
+    ("india", 20.6, 79.0),
+    ("inde", 20.6, 79.0),
+    ("in", 20.6, 79.0),
+    ("australia", -25.3, 133.8),
+    ("australie", -25.3, 133.8),
+    ("au", -25.3, 133.8),
+    ("south africa", -30.6, 22.9),
+    ("afrique du sud", -30.6, 22.9),
+    ("za", -30.6, 22.9),
+];
+
+// "48.85,2.35" en degrés décimaux, sinon le premier élément reconnu de "Paris, FR"
+pub fn parse_location(location: &str) -> Option<(f64, f64)> {
+    if let Some((lat, lon)) = location.split_once(',')
+        && let (Ok(lat), Ok(lon)) = (lat.trim().parse::<f64>(), lon.trim().parse::<f64>())
+    {
+        return ((-90.0..=90.0).contains(&lat) && (-180.0..=180.0).contains(&lon)).then_some((lat, lon));
+    }
+    location.split(',').find_map(|part| {
+        let part = part.trim().to_lowercase();
+        PLACES.iter().find(|(name, _, _)| *name == part).map(|&(_, lat, lon)| (lat, lon))
+    })
+}
+
+// Tracé du monde de ratatui en braille ; un point tracé plus tard recouvre ceux de la même
+// case, l'appelant passe donc l'agent sélectionné en dernier
+pub fn draw_map(f: &mut Frame, area: Rect, points: &[((f64, f64), Style)], title: String, scheme: &ColorScheme) {
+    let canvas = Canvas::default()
+        .block(scheme.block(title))
+        .background_color(scheme.background)
+        .marker(Marker::Braille)
+        .x_bounds([-180.0, 180.0])
+        .y_bounds([LAT_BOTTOM, LAT_TOP])
+        .paint(|ctx| {
+            ctx.draw(&Map { resolution: MapResolution::High, color: scheme.border });
+            ctx.layer();
+            for &((lat, lon), style) in points {
+                if (LAT_BOTTOM..=LAT_TOP).contains(&lat) {
+                    ctx.print(lon, lat, Span::styled("•", style));
+                }
+            }
+        });
+    f.render_widget(canvas, area);
+}
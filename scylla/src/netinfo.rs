@@ -0,0 +1,119 @@
+use ratatui::layout::{Constraint, Rect};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Paragraph, Row, Table};
+use ratatui::Frame;
+use rusqlite::{Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+
+use crate::crypto::open_payload_text;
+use crate::error::{Error, Result};
+use crate::theme::ColorScheme;
+
+// Commande qui demande à l'agent ses interfaces réseau ; il répond en JSON dans la sortie
+// de la tâche, une liste de NetworkInterface. Plusieurs réseaux sur un même agent sont
+// autant de pivots possibles.
+pub const NETINFO_COMMAND: &str = "netinfo";
+
+// [{"name": "eth0", "mac": "AA-BB-CC-DD-EE-FF", "ipv4": ["10.0.0.5/24"], "ipv6": [], "is_up": true}]
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct NetworkInterface {
+    pub name: String,
+    pub mac: String,
+    #[serde(default)]
+    pub ipv4: Vec<String>,
+    #[serde(default)]
+    pub ipv6: Vec<String>,
+    pub is_up: bool,
+}
+
+// Adresses MAC rendues en aa:bb:cc:dd:ee:ff quel que soit le format de l'agent ; une
+// valeur qui n'a pas six octets est reprise telle quelle
+pub fn parse_netinfo(output: &str) -> Result<Vec<NetworkInterface>> {
+    let mut interfaces: Vec<NetworkInterface> =
+        serde_json::from_str(output).map_err(|e| Error::NetInfo(e.to_string()))?;
+    for interface in &mut interfaces {
+        interface.mac = format_mac(&interface.mac);
+    }
+    Ok(interfaces)
+}
+
+// "AA-BB-CC-DD-EE-FF", "aabb.ccdd.eeff" ou "AABBCCDDEEFF" donnent "aa:bb:cc:dd:ee:ff"
+pub fn format_mac(raw: &str) -> String {
+    let digits: String = raw.chars().filter(|c| !matches!(c, ':' | '-' | '.')).collect();
+    if digits.len() != 12 || !digits.chars().all(|c| c.is_ascii_hexdigit()) {
+        return raw.trim().to_string();
+    }
+    let digits = digits.to_ascii_lowercase();
+    (0..6).map(|i| &digits[i * 2..i * 2 + 2]).collect::<Vec<_>>().join(":")
+}
+
+// Dernière réponse de l'agent, avec l'id de sa tâche
+pub fn last_netinfo(conn: &Connection, agent_id: &str) -> Result<Option<(i64, Result<Vec<NetworkInterface>>)>> {
+    let found: Option<(i64, Option<String>)> = conn
+        .query_row(
+            "SELECT id, output FROM tasks WHERE agent_id = ?1 AND payload_type = 'netinfo' AND status = 'done'
+             ORDER BY id DESC LIMIT 1",
+            [agent_id],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )
+        .optional()?;
+    let Some((id, output)) = found else {
+        return Ok(None);
+    };
+    let output = open_payload_text(conn, agent_id, output.unwrap_or_default())?;
+    Ok(Some((id, parse_netinfo(&output))))
+}
+
+// Lignes utiles au sous-panneau, cadre et en-tête compris
+pub fn netinfo_height(found: &Option<(i64, Result<Vec<NetworkInterface>>)>) -> u16 {
+    match found {
+        Some((_, Ok(interfaces))) if !interfaces.is_empty() => interfaces.len() as u16 + 3,
+        _ => 3,
+    }
+}
+
+// Sous-panneau du datasheet ouvert avec W : une ligne par interface, adresses séparées par des virgules
+pub fn draw_netinfo_panel(
+    f: &mut Frame,
+    found: &Option<(i64, Result<Vec<NetworkInterface>>)>,
+    area: Rect,
+    scheme: &ColorScheme,
+) {
+    let interfaces = match found {
+        None => {
+            let hint = Span::styled("Aucune réponse : :netinfo pour la demander", scheme.dim());
+            f.render_widget(Paragraph::new(Line::from(hint)).block(scheme.block("Interfaces")), area);
+            return;
+        }
+        Some((id, Err(e))) => {
+            let error = Span::styled(format!("Réponse #{} illisible : {}", id, e), scheme.error());
+            f.render_widget(Paragraph::new(Line::from(error)).block(scheme.block("Interfaces")), area);
+            return;
+        }
+        Some((_, Ok(interfaces))) => interfaces,
+    };
+    let header = Row::new(["Name", "MAC", "State", "IPv4", "IPv6"]).style(scheme.accent());
+    let rows: Vec<Row> = interfaces.iter()
+        .map(|interface| {
+            let state = if interface.is_up { "up" } else { "down" };
+            let row = Row::new([
+                interface.name.clone(),
+                interface.mac.clone(),
+                state.to_string(),
+                interface.ipv4.join(", "),
+                interface.ipv6.join(", "),
+            ]);
+            if interface.is_up { row } else { row.style(scheme.dim()) }
+        })
+        .collect();
+    let constraints = [
+        Constraint::Length(10),
+        Constraint::Length(17),
+        Constraint::Length(5),
+        Constraint::Fill(1),
+        Constraint::Fill(1),
+    ];
+    let title = format!("Interfaces ({})", interfaces.len());
+    let table = Table::new(rows, constraints).header(header).column_spacing(1).block(scheme.block(title));
+    f.render_widget(table, area);
+}
@@ -0,0 +1,97 @@
+use std::collections::HashMap;
+
+use rusqlite::{Connection, Result, Row};
+
+use crate::timefmt::now_timestamp;
+
+// Plusieurs notes horodatées par agent, à la place de l'ancienne colonne agents.note
+#[derive(Debug, Clone, PartialEq)]
+pub struct Note {
+    pub id: i64,
+    pub agent_id: String,
+    pub content: String,
+    pub created_at: String,
+    pub updated_at: String,
+}
+
+// Reprend les notes existantes avant de supprimer la colonne
+pub fn move_notes_to_table(conn: &Connection) -> Result<()> {
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS notes (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            agent_id TEXT NOT NULL,
+            content TEXT NOT NULL,
+            created_at TEXT NOT NULL,
+            updated_at TEXT NOT NULL
+        )",
+        [],
+    )?;
+    let now = now_timestamp();
+    conn.execute(
+        "INSERT INTO notes (agent_id, content, created_at, updated_at)
+         SELECT id, note, ?1, ?1 FROM agents WHERE note IS NOT NULL AND note != ''",
+        [&now],
+    )?;
+    conn.execute("ALTER TABLE agents DROP COLUMN note", [])?;
+    Ok(())
+}
+
+const NOTE_COLUMNS: &str = "id, agent_id, content, created_at, updated_at";
+
+fn note_from_row(row: &Row) -> Result<Note> {
+    Ok(Note {
+        id: row.get(0)?,
+        agent_id: row.get(1)?,
+        content: row.get(2)?,
+        created_at: row.get(3)?,
+        updated_at: row.get(4)?,
+    })
+}
+
+pub fn add_note(conn: &Connection, agent_id: &str, content: &str) -> Result<i64> {
+    let now = now_timestamp();
+    conn.execute(
+        "INSERT INTO notes (agent_id, content, created_at, updated_at) VALUES (?1, ?2, ?3, ?3)",
+        (agent_id, content, &now),
+    )?;
+    Ok(conn.last_insert_rowid())
+}
+
+pub fn update_note(conn: &Connection, id: i64, content: &str) -> Result<()> {
+    conn.execute("UPDATE notes SET content = ?1, updated_at = ?2 WHERE id = ?3", (content, now_timestamp(), id))?;
+    Ok(())
+}
+
+pub fn delete_note(conn: &Connection, id: i64) -> Result<()> {
+    conn.execute("DELETE FROM notes WHERE id = ?1", [id])?;
+    Ok(())
+}
+
+pub fn remove_all_notes(conn: &Connection, agent_id: &str) -> Result<()> {
+    conn.execute("DELETE FROM notes WHERE agent_id = ?1", [agent_id])?;
+    Ok(())
+}
+
+// La plus récemment modifiée en premier
+pub fn notes_for_agent(conn: &Connection, agent_id: &str) -> Result<Vec<Note>> {
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {} FROM notes WHERE agent_id = ?1 ORDER BY updated_at DESC, id DESC",
+        NOTE_COLUMNS,
+    ))?;
+    let rows = stmt.query_map([agent_id], note_from_row)?;
+    rows.collect()
+}
+
+// Toutes les notes d'un coup, pour le filtre et la fiche, dans le même ordre que notes_for_agent
+pub fn load_notes(conn: &Connection) -> Result<HashMap<String, Vec<Note>>> {
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {} FROM notes ORDER BY agent_id, updated_at DESC, id DESC",
+        NOTE_COLUMNS,
+    ))?;
+    let mut notes: HashMap<String, Vec<Note>> = HashMap::new();
+    for note in stmt.query_map([], note_from_row)? {
+        let note = note?;
+        notes.entry(note.agent_id.clone()).or_default().push(note);
+    }
+    Ok(notes)
+}
@@ -0,0 +1,284 @@
+use std::collections::HashSet;
+
+use crossterm::event::{KeyCode, KeyEvent};
+use ratatui::layout::Rect;
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Clear, Paragraph};
+use ratatui::Frame;
+use rusqlite::{Connection, OptionalExtension, Row};
+
+use crate::agent::Agent;
+use crate::db::insert_task;
+use crate::error::{Error, Result};
+use crate::theme::ColorScheme;
+use crate::timefmt::now_timestamp;
+
+// Relais ouvert par un agent vers une adresse de son réseau. Statuts : pending tant que
+// l'agent n'a pas répondu, active, failed, ou closed une fois fermé depuis l'interface
+#[derive(Debug, Clone)]
+pub struct Pivot {
+    pub id: i64,
+    pub relay_agent_id: String,
+    pub target_addr: String,
+    pub target_port: u16,
+    pub status: String,
+    pub created_at: String,
+}
+
+pub fn ensure_pivots_table(conn: &Connection) -> rusqlite::Result<()> {
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS pivots (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            relay_agent_id TEXT NOT NULL,
+            target_addr TEXT NOT NULL,
+            target_port INTEGER NOT NULL,
+            status TEXT NOT NULL DEFAULT 'pending',
+            created_at TEXT NOT NULL,
+            task_id INTEGER,
+            FOREIGN KEY(relay_agent_id) REFERENCES agents(id)
+        )",
+        [],
+    )?;
+    Ok(())
+}
+
+// "10.0.0.5:445" ou "[fe80::1]:22"
+pub fn parse_target(spec: &str) -> Option<(String, u16)> {
+    let (addr, port) = spec.trim().rsplit_once(':')?;
+    let addr = addr.strip_prefix('[').and_then(|a| a.strip_suffix(']')).unwrap_or(addr);
+    let port: u16 = port.parse().ok().filter(|&p| p != 0)?;
+    if addr.is_empty() || addr.contains(char::is_whitespace) {
+        return None;
+    }
+    Some((addr.to_string(), port))
+}
+
+// Enregistre le relais et met en file la commande pivot pour l'agent : c'est lui qui
+// ouvre la connexion vers la cible et fait passer les octets par son canal
+pub fn create_pivot(conn: &Connection, relay_agent_id: &str, target_addr: &str, target_port: u16) -> Result<i64> {
+    let known: Option<String> = conn
+        .query_row("SELECT id FROM agents WHERE id = ?1", [relay_agent_id], |row| row.get(0))
+        .optional()?;
+    if known.is_none() {
+        return Err(Error::AgentNotFound(relay_agent_id.to_string()));
+    }
+    let tx = conn.unchecked_transaction()?;
+    tx.execute(
+        "INSERT INTO pivots (relay_agent_id, target_addr, target_port, status, created_at) VALUES (?1, ?2, ?3, 'pending', ?4)",
+        (relay_agent_id, target_addr, target_port, now_timestamp()),
+    )?;
+    let id = tx.last_insert_rowid();
+    let task_id = insert_task(&tx, relay_agent_id, &format!("pivot {} {}:{}", id, target_addr, target_port))?;
+    tx.execute("UPDATE pivots SET task_id = ?1 WHERE id = ?2", (task_id, id))?;
+    tx.commit()?;
+    Ok(id)
+}
+
+// Un relais fermé n'est plus utilisé par l'interface ; l'agent reçoit l'ordre de le couper
+pub fn close_pivot(conn: &Connection, id: i64) -> Result<()> {
+    let relay: Option<String> = conn
+        .query_row("SELECT relay_agent_id FROM pivots WHERE id = ?1 AND status != 'closed'", [id], |row| row.get(0))
+        .optional()?;
+    let Some(relay) = relay else {
+        return Err(Error::TaskNotFound(id));
+    };
+    let tx = conn.unchecked_transaction()?;
+    tx.execute("UPDATE pivots SET status = 'closed' WHERE id = ?1", [id])?;
+    insert_task(&tx, &relay, &format!("pivot-close {}", id))?;
+    tx.commit()?;
+    Ok(())
+}
+
+// Le statut suit la réponse de l'agent à la commande pivot
+fn sync_pivot_status(conn: &Connection) -> rusqlite::Result<()> {
+    conn.execute(
+        "UPDATE pivots SET status = CASE (SELECT status FROM tasks WHERE tasks.id = pivots.task_id)
+             WHEN 'done' THEN 'active' WHEN 'failed' THEN 'failed' ELSE status END
+         WHERE status = 'pending'",
+        [],
+    )?;
+    Ok(())
+}
+
+fn pivot_from_row(row: &Row) -> rusqlite::Result<Pivot> {
+    Ok(Pivot {
+        id: row.get(0)?,
+        relay_agent_id: row.get(1)?,
+        target_addr: row.get(2)?,
+        target_port: row.get(3)?,
+        status: row.get(4)?,
+        created_at: row.get(5)?,
+    })
+}
+
+pub fn load_pivots(conn: &Connection) -> rusqlite::Result<Vec<Pivot>> {
+    sync_pivot_status(conn)?;
+    let mut stmt = conn.prepare_cached(
+        "SELECT id, relay_agent_id, target_addr, target_port, status, created_at FROM pivots ORDER BY id",
+    )?;
+    let rows = stmt.query_map([], pivot_from_row)?;
+    rows.collect()
+}
+
+pub fn remove_all_pivots(conn: &Connection, agent_id: &str) -> rusqlite::Result<()> {
+    conn.execute("DELETE FROM pivots WHERE relay_agent_id = ?1", [agent_id])?;
+    Ok(())
+}
+
+// Une ligne du graphe : un agent, ou un relais sélectionnable
+struct GraphLine {
+    prefix: String,
+    agent: Option<String>,
+    pivot: Option<usize>,
+}
+
+// Arbre des relais depuis le serveur. Un agent que l'on joint par un relais (son IP est
+// la cible) est rattaché sous ce relais, avec ses propres relais ; les autres agents
+// relais partent de la racine.
+fn build_graph(pivots: &[Pivot], agents: &[Agent]) -> Vec<GraphLine> {
+    let reached_by = |pivot: &Pivot| agents.iter().find(|a| a.ip == pivot.target_addr).map(|a| a.id.clone());
+    let reached: HashSet<String> = pivots.iter().filter(|p| p.status != "closed").filter_map(reached_by).collect();
+    let mut roots: Vec<&str> = Vec::new();
+    for pivot in pivots {
+        let relay = pivot.relay_agent_id.as_str();
+        if !reached.contains(relay) && !roots.contains(&relay) {
+            roots.push(relay);
+        }
+    }
+
+    fn walk(
+        relay: &str,
+        indent: &str,
+        pivots: &[Pivot],
+        reached_by: &dyn Fn(&Pivot) -> Option<String>,
+        seen: &mut HashSet<String>,
+        lines: &mut Vec<GraphLine>,
+    ) {
+        // Une boucle entre agents s'arrête au premier retour
+        if !seen.insert(relay.to_string()) {
+            return;
+        }
+        let children: Vec<usize> = (0..pivots.len()).filter(|&i| pivots[i].relay_agent_id == relay).collect();
+        for (n, &i) in children.iter().enumerate() {
+            let last = n + 1 == children.len();
+            let branch = if last { "└─ " } else { "├─ " };
+            let reached = if pivots[i].status == "closed" { None } else { reached_by(&pivots[i]) };
+            lines.push(GraphLine { prefix: format!("{}{}", indent, branch), agent: reached.clone(), pivot: Some(i) });
+            if let Some(agent) = reached {
+                let deeper = format!("{}{}", indent, if last { "   " } else { "│  " });
+                walk(&agent, &deeper, pivots, reached_by, seen, lines);
+            }
+        }
+    }
+
+    let mut lines = vec![GraphLine { prefix: "C2".to_string(), agent: None, pivot: None }];
+    let mut seen = HashSet::new();
+    for (n, root) in roots.iter().enumerate() {
+        let last = n + 1 == roots.len();
+        lines.push(GraphLine { prefix: if last { "└─ " } else { "├─ " }.to_string(), agent: Some(root.to_string()), pivot: None });
+        walk(root, if last { "   " } else { "│  " }, pivots, &reached_by, &mut seen, &mut lines);
+    }
+    lines
+}
+
+pub enum PivotOutcome {
+    Pending,
+    Closed,
+    // Fermeture du relais sélectionné
+    ClosePivot(i64),
+}
+
+// Panneau ouvert avec V : tous les relais, en arbre depuis le serveur, rechargés à chaque tick
+#[derive(Default)]
+pub struct PivotsPanel {
+    pivots: Vec<Pivot>,
+    lines: Vec<GraphLine>,
+    // Position dans lines, toujours sur un relais quand il y en a
+    selected: usize,
+}
+
+impl PivotsPanel {
+    pub fn set_pivots(&mut self, pivots: Vec<Pivot>, agents: &[Agent]) {
+        let current = self.selected_pivot().map(|p| p.id);
+        self.pivots = pivots;
+        self.lines = build_graph(&self.pivots, agents);
+        let position = |id| self.lines.iter().position(|l| l.pivot.is_some_and(|i| self.pivots[i].id == id));
+        self.selected = current
+            .and_then(position)
+            .or_else(|| self.lines.iter().position(|l| l.pivot.is_some()))
+            .unwrap_or(0);
+    }
+
+    fn selected_pivot(&self) -> Option<&Pivot> {
+        self.lines.get(self.selected).and_then(|l| l.pivot).map(|i| &self.pivots[i])
+    }
+
+    // Saute les lignes d'agents : seuls les relais se sélectionnent
+    fn step(&mut self, forward: bool) {
+        let found = if forward {
+            (self.selected + 1..self.lines.len()).find(|&i| self.lines[i].pivot.is_some())
+        } else {
+            (0..self.selected).rev().find(|&i| self.lines[i].pivot.is_some())
+        };
+        if let Some(i) = found {
+            self.selected = i;
+        }
+    }
+
+    pub fn handle_key(&mut self, key: KeyEvent) -> PivotOutcome {
+        match key.code {
+            KeyCode::Esc | KeyCode::Char('q') | KeyCode::Char('V') => return PivotOutcome::Closed,
+            KeyCode::Down | KeyCode::Char('j') => self.step(true),
+            KeyCode::Up | KeyCode::Char('k') => self.step(false),
+            KeyCode::Char('x') => {
+                if let Some(pivot) = self.selected_pivot().filter(|p| p.status != "closed") {
+                    return PivotOutcome::ClosePivot(pivot.id);
+                }
+            }
+            _ => {}
+        }
+        PivotOutcome::Pending
+    }
+}
+
+pub fn draw_pivots_panel(f: &mut Frame, panel: &PivotsPanel, area: Rect, scheme: &ColorScheme) {
+    f.render_widget(Clear, area);
+    let block = scheme.block(format!(
+        "Pivots ({}) — j/k, x: fermer, :pivot <relais> <adresse>:<port>, Échap",
+        panel.pivots.len(),
+    ));
+    if panel.pivots.is_empty() {
+        let empty = Line::from(Span::styled("Aucun relais : :pivot <relais> <adresse>:<port>", scheme.dim()));
+        f.render_widget(Paragraph::new(empty).block(block), area);
+        return;
+    }
+    let mut lines: Vec<Line> = Vec::new();
+    for (i, line) in panel.lines.iter().enumerate() {
+        let mut spans = vec![Span::styled(line.prefix.clone(), scheme.dim())];
+        if let Some(index) = line.pivot {
+            let pivot = &panel.pivots[index];
+            let style = match pivot.status.as_str() {
+                "active" => scheme.online(),
+                "failed" => scheme.error(),
+                "closed" => scheme.dim(),
+                _ => scheme.accent(),
+            };
+            let target = if pivot.target_addr.contains(':') {
+                format!("[{}]:{}", pivot.target_addr, pivot.target_port)
+            } else {
+                format!("{}:{}", pivot.target_addr, pivot.target_port)
+            };
+            let base = if i == panel.selected { scheme.selection() } else { scheme.base() };
+            spans.push(Span::styled(format!("#{} {} ", pivot.id, target), base));
+            spans.push(Span::styled(format!("[{}]", pivot.status), style));
+            spans.push(Span::styled(format!(" depuis {}", pivot.created_at), scheme.dim()));
+            if let Some(agent) = &line.agent {
+                spans.push(Span::raw(format!(" → {}", agent)));
+            }
+        } else if let Some(agent) = &line.agent {
+            spans.push(Span::styled(agent.clone(), scheme.accent()));
+        }
+        lines.push(Line::from(spans));
+    }
+    f.render_widget(Paragraph::new(lines).block(block), area);
+}
@@ -0,0 +1,262 @@
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use ratatui::layout::{Constraint, Rect};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Paragraph, Row, Table, TableState};
+use ratatui::Frame;
+use rusqlite::{Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+
+use crate::crypto::open_payload_text;
+use crate::error::{Error, Result};
+use crate::theme::ColorScheme;
+
+// Commande qui demande à l'agent la liste de ses processus ; il répond en JSON dans la
+// sortie de la tâche, voir ProcessListResponse
+pub const PROCESS_LIST_COMMAND: &str = "ps";
+
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct ProcessEntry {
+    pub pid: u32,
+    pub name: String,
+    pub user: String,
+    pub cpu_pct: f32,
+    pub mem_kb: u64,
+}
+
+// {"agent_id": "...", "entries": [{"pid": 1, "name": "init", "user": "root", "cpu_pct": 0.1, "mem_kb": 1024}]}
+// agent_id peut manquer : c'est alors celui de la tâche
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct ProcessListResponse {
+    #[serde(default)]
+    pub agent_id: String,
+    pub entries: Vec<ProcessEntry>,
+}
+
+pub fn parse_process_list(output: &str) -> Result<ProcessListResponse> {
+    serde_json::from_str(output).map_err(|e| Error::ProcessList(e.to_string()))
+}
+
+// Dernière liste reçue de l'agent, avec l'id de sa tâche
+pub fn last_process_list(conn: &Connection, agent_id: &str) -> Result<Option<(i64, Result<ProcessListResponse>)>> {
+    let found: Option<(i64, Option<String>)> = conn
+        .query_row(
+            "SELECT id, output FROM tasks WHERE agent_id = ?1 AND payload_type = 'process_list' AND status = 'done'
+             ORDER BY id DESC LIMIT 1",
+            [agent_id],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )
+        .optional()?;
+    let Some((id, output)) = found else {
+        return Ok(None);
+    };
+    let output = open_payload_text(conn, agent_id, output.unwrap_or_default())?;
+    let parsed = parse_process_list(&output).map(|mut list| {
+        if list.agent_id.is_empty() {
+            list.agent_id = agent_id.to_string();
+        }
+        list
+    });
+    Ok(Some((id, parsed)))
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProcessColumn {
+    Pid,
+    Name,
+    User,
+    Cpu,
+    Mem,
+}
+
+pub const PROCESS_COLUMNS: [ProcessColumn; 5] =
+    [ProcessColumn::Pid, ProcessColumn::Name, ProcessColumn::User, ProcessColumn::Cpu, ProcessColumn::Mem];
+
+impl ProcessColumn {
+    pub fn label(self) -> &'static str {
+        match self {
+            ProcessColumn::Pid => "PID",
+            ProcessColumn::Name => "Nom",
+            ProcessColumn::User => "Utilisateur",
+            ProcessColumn::Cpu => "CPU %",
+            ProcessColumn::Mem => "Mémoire",
+        }
+    }
+
+    fn next(self) -> ProcessColumn {
+        let index = PROCESS_COLUMNS.iter().position(|&c| c == self).unwrap_or(0);
+        PROCESS_COLUMNS[(index + 1) % PROCESS_COLUMNS.len()]
+    }
+}
+
+pub enum ProcessOutcome {
+    Pending,
+    Closed,
+    // Nouvelle tâche ps pour l'agent du panneau
+    Refresh,
+}
+
+// Sous-panneau du terminal ouvert avec Ctrl+P : la dernière liste de processus de
+// l'agent, relue à chaque tick pour suivre la réponse à un ps
+pub struct ProcessPanel {
+    pub agent_id: String,
+    task_id: Option<i64>,
+    entries: Vec<ProcessEntry>,
+    // Réponse illisible, affichée à la place du tableau
+    error: Option<String>,
+    sort: ProcessColumn,
+    descending: bool,
+    state: TableState,
+}
+
+impl ProcessPanel {
+    // Les plus gourmands d'abord
+    pub fn new(agent_id: &str) -> ProcessPanel {
+        ProcessPanel {
+            agent_id: agent_id.to_string(),
+            task_id: None,
+            entries: Vec::new(),
+            error: None,
+            sort: ProcessColumn::Cpu,
+            descending: true,
+            state: TableState::default(),
+        }
+    }
+
+    pub fn entries(&self) -> &[ProcessEntry] {
+        &self.entries
+    }
+
+    // Dernière réponse lue par last_process_list ; le tri et la sélection ne changent
+    // qu'à l'arrivée d'une nouvelle liste
+    pub fn set_list(&mut self, found: Option<(i64, Result<ProcessListResponse>)>) {
+        let Some((id, parsed)) = found else {
+            return;
+        };
+        if self.task_id == Some(id) {
+            return;
+        }
+        self.task_id = Some(id);
+        match parsed {
+            Ok(list) => {
+                self.entries = list.entries;
+                self.error = None;
+                self.sort_entries();
+                self.state.select((!self.entries.is_empty()).then_some(0));
+            }
+            Err(e) => {
+                self.entries.clear();
+                self.error = Some(e.to_string());
+                self.state.select(None);
+            }
+        }
+    }
+
+    // La sélection suit le processus, pas la ligne
+    fn sort_entries(&mut self) {
+        let selected = self.state.selected().and_then(|i| self.entries.get(i)).map(|p| p.pid);
+        let sort = self.sort;
+        self.entries.sort_by(|a, b| {
+            let order = match sort {
+                ProcessColumn::Pid => a.pid.cmp(&b.pid),
+                ProcessColumn::Name => a.name.to_lowercase().cmp(&b.name.to_lowercase()),
+                ProcessColumn::User => a.user.cmp(&b.user),
+                ProcessColumn::Cpu => a.cpu_pct.total_cmp(&b.cpu_pct),
+                ProcessColumn::Mem => a.mem_kb.cmp(&b.mem_kb),
+            };
+            order.then(a.pid.cmp(&b.pid))
+        });
+        if self.descending {
+            self.entries.reverse();
+        }
+        if let Some(pid) = selected {
+            self.state.select(self.entries.iter().position(|p| p.pid == pid));
+        }
+    }
+
+    // Processus le plus gourmand en CPU, surligné quel que soit le tri
+    fn top_cpu(&self) -> Option<u32> {
+        self.entries.iter().max_by(|a, b| a.cpu_pct.total_cmp(&b.cpu_pct)).map(|p| p.pid)
+    }
+
+    pub fn handle_key(&mut self, key: KeyEvent) -> ProcessOutcome {
+        let last = self.entries.len().saturating_sub(1);
+        let selected = self.state.selected().unwrap_or(0);
+        match key.code {
+            KeyCode::Char('p') if key.modifiers.contains(KeyModifiers::CONTROL) => return ProcessOutcome::Closed,
+            KeyCode::Esc | KeyCode::Char('q') => return ProcessOutcome::Closed,
+            KeyCode::Char('r') => return ProcessOutcome::Refresh,
+            KeyCode::Char('s') => {
+                self.sort = self.sort.next();
+                // Texte croissant, nombres décroissants
+                self.descending = matches!(self.sort, ProcessColumn::Cpu | ProcessColumn::Mem);
+                self.sort_entries();
+            }
+            KeyCode::Char('I') => {
+                self.descending = !self.descending;
+                self.sort_entries();
+            }
+            KeyCode::Down | KeyCode::Char('j') => self.state.select(Some((selected + 1).min(last))),
+            KeyCode::Up | KeyCode::Char('k') => self.state.select(Some(selected.saturating_sub(1))),
+            KeyCode::Home | KeyCode::Char('g') => self.state.select(Some(0)),
+            KeyCode::End | KeyCode::Char('G') => self.state.select(Some(last)),
+            _ => {}
+        }
+        if self.entries.is_empty() {
+            self.state.select(None);
+        }
+        ProcessOutcome::Pending
+    }
+}
+
+pub fn draw_process_panel(f: &mut Frame, panel: &mut ProcessPanel, area: Rect, scheme: &ColorScheme) {
+    let arrow = if panel.descending { "▼" } else { "▲" };
+    let block = scheme.block(format!(
+        "Processus de {} ({}) — s: tri, I: inverser, r: relancer ps, Échap",
+        panel.agent_id,
+        panel.entries.len(),
+    ));
+    if let Some(error) = &panel.error {
+        f.render_widget(Paragraph::new(Span::styled(error.clone(), scheme.error())).block(block), area);
+        return;
+    }
+    if panel.entries.is_empty() {
+        let hint = match panel.task_id {
+            Some(_) => "Aucun processus dans la réponse",
+            None => "Aucune liste reçue : r envoie la commande ps",
+        };
+        f.render_widget(Paragraph::new(Line::from(Span::styled(hint, scheme.dim()))).block(block), area);
+        return;
+    }
+    let header = Row::new(PROCESS_COLUMNS.iter().map(|&column| {
+        if column == panel.sort {
+            Span::styled(format!("{} {}", column.label(), arrow), scheme.accent())
+        } else {
+            Span::raw(column.label())
+        }
+    }));
+    let top = panel.top_cpu();
+    let rows: Vec<Row> = panel.entries.iter()
+        .map(|p| {
+            let row = Row::new(vec![
+                p.pid.to_string(),
+                p.name.clone(),
+                p.user.clone(),
+                format!("{:.1}", p.cpu_pct),
+                format!("{} Ko", p.mem_kb),
+            ]);
+            if Some(p.pid) == top { row.style(scheme.warning()) } else { row }
+        })
+        .collect();
+    let widths = [
+        Constraint::Length(8),
+        Constraint::Min(16),
+        Constraint::Length(14),
+        Constraint::Length(8),
+        Constraint::Length(12),
+    ];
+    let table = Table::new(rows, widths)
+        .header(header)
+        .row_highlight_style(scheme.selection())
+        .block(block);
+    f.render_stateful_widget(table, area, &mut panel.state);
+}
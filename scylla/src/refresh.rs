@@ -0,0 +1,159 @@
+use std::collections::HashMap;
+
+use chrono::{TimeDelta, Utc};
+
+use crate::app::DETAIL_EVENTS;
+use crate::checkins::checkin_history;
+use crate::clipboard::clipboard_task;
+use crate::db::{Db, Task};
+use crate::error::Result;
+use crate::eventlog::{events_for_agent, EventEntry};
+use crate::keylog::{keylog_since, KeylogChunk};
+use crate::loot::{load_files, LootFile};
+use crate::netinfo::{last_netinfo, NetworkInterface};
+use crate::pivots::{load_pivots, Pivot};
+use crate::processes::{last_process_list, ProcessListResponse};
+use crate::screens::{ScreenQuery, ScreenRows};
+use crate::screenshots::{last_screenshot, load_screenshots, Screenshot};
+use crate::shell::{shell_replies, ShellReply};
+use crate::topology::{load_reachability, Edge};
+use crate::transfers::upload_progress;
+
+// Dernières tâches de l'agent sélectionné, dans le terminal du bas
+const RECENT_TASKS: usize = 5;
+
+// Relecture du tick : l'interface décrit ce qui est à l'écran, le worker le relit sur sa
+// propre connexion, puis l'interface applique les lignes si l'affichage n'a pas changé
+// entre-temps. Ce qui est masqué n'est pas relu.
+#[derive(Clone, Debug, PartialEq)]
+pub struct RefreshQuery {
+    pub view: View,
+    pub strip: Strip,
+}
+
+// Vue du milieu de l'écran : le panneau ouvert par-dessus les autres, sinon l'écran actif
+#[derive(Clone, Debug, PartialEq)]
+pub enum View {
+    // Écran Listeners, visionneuse de journal, presse-papiers déjà reçu : rien à relire
+    None,
+    // Datasheet de l'écran Agents ; sans agent sélectionné, elle se vide
+    Agent { agent_id: Option<String>, interfaces: bool, window: TimeDelta, bucket: TimeDelta },
+    Screen(ScreenQuery),
+    Detail(String),
+    Tasks(String),
+    Clipboard { agent_id: String, task_id: i64 },
+    Gallery(String),
+    Loot(String),
+    Keylog { agent_id: String, after_id: i64 },
+    Pivots,
+    Topology,
+}
+
+// Terminal du bas
+#[derive(Clone, Debug, PartialEq)]
+pub enum Strip {
+    // Session TCP, alimentée par les listeners, ou shell sans tâche en attente
+    None,
+    Processes(String),
+    Shell { agent_id: String, pending: Vec<i64> },
+    RecentTasks(Option<String>),
+}
+
+#[derive(Debug)]
+pub enum ViewRows {
+    None,
+    Agent {
+        pending: usize,
+        screenshot: Option<String>,
+        history: Vec<u64>,
+        // Lues seulement si le sous-panneau est ouvert
+        interfaces: Option<Option<(i64, Result<Vec<NetworkInterface>>)>>,
+    },
+    Screen(ScreenRows),
+    Detail { pending: usize, completed: usize, events: Vec<EventEntry> },
+    Tasks { tasks: Vec<Task>, uploads: HashMap<i64, (u64, u64)> },
+    Clipboard(Option<(String, Option<String>)>),
+    Gallery(Vec<Screenshot>),
+    Loot(Vec<LootFile>),
+    Keylog(Vec<KeylogChunk>),
+    Pivots(Vec<Pivot>),
+    Topology(HashMap<(String, String), Edge>),
+}
+
+#[derive(Debug)]
+pub enum StripRows {
+    None,
+    Processes(Option<(i64, Result<ProcessListResponse>)>),
+    Shell(Vec<ShellReply>),
+    RecentTasks(Vec<Task>),
+}
+
+// Réponse à une RefreshQuery, qu'elle accompagne : chaque partie échoue séparément
+#[derive(Debug)]
+pub struct Refreshed {
+    pub query: RefreshQuery,
+    pub view: Result<ViewRows>,
+    pub strip: Result<StripRows>,
+    // Tâches en attente sur tous les agents, pour la barre d'état
+    pub queued: Result<usize>,
+}
+
+impl RefreshQuery {
+    pub fn load(self, db: &Db) -> Refreshed {
+        Refreshed {
+            view: self.view.load(db),
+            strip: self.strip.load(db),
+            queued: db.total_pending_task_count(),
+            query: self,
+        }
+    }
+}
+
+impl View {
+    pub fn load(&self, db: &Db) -> Result<ViewRows> {
+        let conn = db.conn();
+        Ok(match self {
+            View::None => ViewRows::None,
+            View::Agent { agent_id: None, interfaces, .. } => ViewRows::Agent {
+                pending: 0,
+                screenshot: None,
+                history: Vec::new(),
+                interfaces: interfaces.then_some(None),
+            },
+            View::Agent { agent_id: Some(agent_id), interfaces, window, bucket } => ViewRows::Agent {
+                pending: db.pending_task_count(agent_id)?,
+                screenshot: last_screenshot(conn, agent_id)?,
+                history: checkin_history(conn, agent_id, Utc::now(), *window, *bucket)?,
+                interfaces: if *interfaces { Some(last_netinfo(conn, agent_id)?) } else { None },
+            },
+            View::Screen(query) => ViewRows::Screen(query.load(db)?),
+            View::Detail(agent_id) => ViewRows::Detail {
+                pending: db.pending_task_count(agent_id)?,
+                completed: db.completed_task_count(agent_id)?,
+                events: events_for_agent(conn, agent_id, DETAIL_EVENTS)?,
+            },
+            View::Tasks(agent_id) => ViewRows::Tasks {
+                tasks: db.tasks_for_agent(agent_id)?,
+                uploads: upload_progress(conn, agent_id)?,
+            },
+            View::Clipboard { agent_id, task_id } => ViewRows::Clipboard(clipboard_task(conn, agent_id, *task_id)?),
+            View::Gallery(agent_id) => ViewRows::Gallery(load_screenshots(conn, agent_id)?),
+            View::Loot(agent_id) => ViewRows::Loot(load_files(conn, agent_id)?),
+            View::Keylog { agent_id, after_id } => ViewRows::Keylog(keylog_since(conn, agent_id, *after_id)?),
+            View::Pivots => ViewRows::Pivots(load_pivots(conn)?),
+            View::Topology => ViewRows::Topology(load_reachability(conn)?),
+        })
+    }
+}
+
+impl Strip {
+    pub fn load(&self, db: &Db) -> Result<StripRows> {
+        Ok(match self {
+            Strip::None => StripRows::None,
+            Strip::RecentTasks(None) => StripRows::RecentTasks(Vec::new()),
+            Strip::Processes(agent_id) => StripRows::Processes(last_process_list(db.conn(), agent_id)?),
+            Strip::Shell { agent_id, pending } => StripRows::Shell(shell_replies(db.conn(), agent_id, pending)?),
+            Strip::RecentTasks(Some(agent_id)) => StripRows::RecentTasks(db.recent_tasks(agent_id, RECENT_TASKS)?),
+        })
+    }
+}
@@ -0,0 +1,188 @@
+use std::str::FromStr;
+
+use chrono::{DateTime, TimeDelta, Utc};
+use cron::Schedule;
+use rusqlite::{Connection, Row};
+
+use crate::db::insert_task;
+use crate::error::{Error, Result};
+use crate::screenshots::SCREENSHOT_COMMAND;
+use crate::timefmt::{format_timestamp, parse_timestamp};
+
+// Commande renvoyée à un agent à chaque échéance de son expression cron
+#[derive(Debug, Clone)]
+pub struct ScheduledTask {
+    pub id: i64,
+    pub agent_id: String,
+    pub command: String,
+    pub schedule: String,
+    pub last_run: Option<String>,
+    pub next_run: Option<String>,
+    pub enabled: bool,
+}
+
+// Tâche mise en file par le thread du planificateur ; sans schedule_id pour une capture
+// périodique, réglée sur l'agent plutôt que par une expression cron
+#[derive(Debug)]
+pub struct ScheduledRun {
+    pub schedule_id: Option<i64>,
+    pub task_id: i64,
+    pub agent_id: String,
+    pub command: String,
+}
+
+pub fn ensure_scheduled_tasks_table(conn: &Connection) -> rusqlite::Result<()> {
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS scheduled_tasks (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            agent_id TEXT NOT NULL,
+            command TEXT NOT NULL,
+            schedule TEXT NOT NULL,
+            last_run TEXT,
+            next_run TEXT,
+            enabled INTEGER NOT NULL DEFAULT 1,
+            FOREIGN KEY(agent_id) REFERENCES agents(id)
+        )",
+        [],
+    )?;
+    Ok(())
+}
+
+// Le crate cron attend les secondes en tête : une expression crontab classique à cinq
+// champs part à la seconde 0. Les raccourcis comme @hourly passent tels quels.
+pub fn parse_schedule(schedule: &str) -> Result<Schedule> {
+    let schedule = schedule.trim();
+    let expression = if !schedule.starts_with('@') && schedule.split_whitespace().count() == 5 {
+        format!("0 {}", schedule)
+    } else {
+        schedule.to_string()
+    };
+    // L'erreur du crate répète l'expression et un curseur sur plusieurs lignes : seule
+    // la dernière, l'explication, tient dans la barre d'état
+    Schedule::from_str(&expression).map_err(|e| Error::Schedule {
+        schedule: schedule.to_string(),
+        reason: e.to_string().lines().last().unwrap_or_default().to_string(),
+    })
+}
+
+// Première échéance strictement après from ; une expression sans échéance à venir
+// (une année passée, par exemple) est refusée à la création
+pub fn next_run_from_cron(schedule: &str, from: DateTime<Utc>) -> Result<DateTime<Utc>> {
+    parse_schedule(schedule)?
+        .after(&from)
+        .next()
+        .ok_or_else(|| Error::ScheduleExhausted(schedule.to_string()))
+}
+
+fn scheduled_task_from_row(row: &Row) -> rusqlite::Result<ScheduledTask> {
+    Ok(ScheduledTask {
+        id: row.get(0)?,
+        agent_id: row.get(1)?,
+        command: row.get(2)?,
+        schedule: row.get(3)?,
+        last_run: row.get(4)?,
+        next_run: row.get(5)?,
+        enabled: row.get(6)?,
+    })
+}
+
+const SCHEDULED_TASK_COLUMNS: &str = "id, agent_id, command, schedule, last_run, next_run, enabled";
+
+pub fn add_scheduled_task(conn: &Connection, agent_id: &str, command: &str, schedule: &str) -> Result<i64> {
+    let next_run = next_run_from_cron(schedule, Utc::now())?;
+    conn.execute(
+        "INSERT INTO scheduled_tasks (agent_id, command, schedule, next_run) VALUES (?1, ?2, ?3, ?4)",
+        (agent_id, command, schedule.trim(), format_timestamp(next_run)),
+    )?;
+    Ok(conn.last_insert_rowid())
+}
+
+pub fn load_scheduled_tasks(conn: &Connection) -> rusqlite::Result<Vec<ScheduledTask>> {
+    let mut stmt = conn.prepare_cached(&format!(
+        "SELECT {} FROM scheduled_tasks ORDER BY next_run IS NULL, next_run, id",
+        SCHEDULED_TASK_COLUMNS,
+    ))?;
+    let rows = stmt.query_map([], scheduled_task_from_row)?;
+    rows.collect()
+}
+
+// Une tâche réactivée repart de maintenant, sans rattraper les échéances manquées
+pub fn set_scheduled_task_enabled(conn: &Connection, task: &ScheduledTask, enabled: bool) -> Result<()> {
+    let next_run = if enabled { Some(format_timestamp(next_run_from_cron(&task.schedule, Utc::now())?)) } else { None };
+    conn.execute(
+        "UPDATE scheduled_tasks SET enabled = ?1, next_run = ?2 WHERE id = ?3",
+        (enabled, next_run, task.id),
+    )?;
+    Ok(())
+}
+
+pub fn delete_scheduled_task(conn: &Connection, id: i64) -> rusqlite::Result<()> {
+    conn.execute("DELETE FROM scheduled_tasks WHERE id = ?1", [id])?;
+    Ok(())
+}
+
+pub fn remove_all_scheduled_tasks(conn: &Connection, agent_id: &str) -> rusqlite::Result<()> {
+    conn.execute("DELETE FROM scheduled_tasks WHERE agent_id = ?1", [agent_id])?;
+    Ok(())
+}
+
+// Met en file chaque tâche échue, une seule fois même si plusieurs échéances sont passées
+// pendant que l'interface était fermée. Une expression devenue illisible désactive la tâche.
+pub fn run_due_tasks(conn: &Connection, now: DateTime<Utc>) -> Result<Vec<ScheduledRun>> {
+    let due: Vec<ScheduledTask> = {
+        let mut stmt = conn.prepare_cached(&format!(
+            "SELECT {} FROM scheduled_tasks WHERE enabled = 1 AND next_run <= ?1",
+            SCHEDULED_TASK_COLUMNS,
+        ))?;
+        let rows = stmt.query_map([format_timestamp(now)], scheduled_task_from_row)?;
+        rows.collect::<rusqlite::Result<_>>()?
+    };
+    let mut runs = Vec::new();
+    for task in due {
+        let tx = conn.unchecked_transaction()?;
+        let task_id = insert_task(&tx, &task.agent_id, &task.command)?;
+        match next_run_from_cron(&task.schedule, now) {
+            Ok(next_run) => tx.execute(
+                "UPDATE scheduled_tasks SET last_run = ?1, next_run = ?2 WHERE id = ?3",
+                (format_timestamp(now), format_timestamp(next_run), task.id),
+            )?,
+            Err(_) => tx.execute(
+                "UPDATE scheduled_tasks SET last_run = ?1, next_run = NULL, enabled = 0 WHERE id = ?2",
+                (format_timestamp(now), task.id),
+            )?,
+        };
+        tx.commit()?;
+        runs.push(ScheduledRun { schedule_id: Some(task.id), task_id, agent_id: task.agent_id, command: task.command });
+    }
+    Ok(runs)
+}
+
+// Met en file screenshot pour chaque agent dont la dernière capture demandée date d'au
+// moins son intervalle. Rien tant qu'une capture attend encore l'agent : un agent
+// injoignable n'accumule pas de tâches.
+pub fn run_auto_screenshots(conn: &Connection, now: DateTime<Utc>) -> rusqlite::Result<Vec<ScheduledRun>> {
+    let agents: Vec<(String, u64, Option<String>, bool)> = {
+        let mut stmt = conn.prepare_cached(
+            "SELECT a.id, a.screenshot_interval_secs,
+                (SELECT MAX(created_at) FROM tasks t WHERE t.agent_id = a.id AND t.payload_type = 'screenshot'),
+                EXISTS (SELECT 1 FROM tasks t WHERE t.agent_id = a.id AND t.payload_type = 'screenshot'
+                        AND t.status IN ('pending', 'sent'))
+             FROM agents a WHERE a.screenshot_interval_secs IS NOT NULL ORDER BY a.id",
+        )?;
+        let rows = stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?)))?;
+        rows.collect::<rusqlite::Result<_>>()?
+    };
+    let mut runs = Vec::new();
+    for (agent_id, interval, last, waiting) in agents {
+        let due = last
+            .as_deref()
+            .and_then(parse_timestamp)
+            .is_none_or(|at| now - at >= TimeDelta::seconds(interval.min(i64::MAX as u64) as i64));
+        if waiting || !due {
+            continue;
+        }
+        let task_id = insert_task(conn, &agent_id, SCREENSHOT_COMMAND)?;
+        runs.push(ScheduledRun { schedule_id: None, task_id, agent_id, command: SCREENSHOT_COMMAND.to_string() });
+    }
+    Ok(runs)
+}
@@ -0,0 +1,134 @@
+use rusqlite::{Connection, TransactionBehavior};
+
+use crate::audit::ensure_audit_log_table;
+use crate::auth::ensure_users_table;
+use crate::beacon::{add_beacon_column, add_beacon_jitter_column, move_jitter_to_pct};
+use crate::checkins::ensure_checkins_table;
+use crate::crypto::{add_session_key_column, ensure_keys_table};
+use crate::db::ensure_commands_table;
+use crate::error::{Error, Result};
+use crate::eventlog::ensure_events_table;
+use crate::geoip::add_geoip_column;
+use crate::groups::ensure_groups_tables;
+use crate::history::ensure_history_table;
+use crate::keylog::ensure_keylogs_table;
+use crate::listeners::{add_listener_profile_columns, ensure_listeners_table};
+use crate::loot::ensure_files_tables;
+use crate::notes::move_notes_to_table;
+use crate::pivots::ensure_pivots_table;
+use crate::scheduler::ensure_scheduled_tasks_table;
+use crate::screenshots::{add_payload_type_column, add_screenshot_interval_column};
+use crate::selfdestruct::{add_archived_column, ensure_self_destructs_table};
+use crate::sysinfo::add_sysinfo_columns;
+use crate::tags::ensure_tags_table;
+use crate::timefmt::now_timestamp;
+use crate::topology::ensure_reachability_table;
+use crate::transfers::{ensure_task_uploads_tables, ensure_transfers_table};
+
+type Migration = fn(&Connection) -> rusqlite::Result<()>;
+
+// Migrations dans l'ordre : la version d'une base est le nombre de migrations appliquées.
+// Chacune doit rester idempotente, les bases d'avant schema_version les rejouent toutes.
+const MIGRATIONS: &[(&str, Migration)] = &[
+    ("agents, commandes et résultats", create_core_tables),
+    ("listeners", ensure_listeners_table),
+    ("tags", ensure_tags_table),
+    ("journal d'audit", ensure_audit_log_table),
+    ("transferts", ensure_transfers_table),
+    ("opérateurs", ensure_users_table),
+    ("file de tâches", rename_commands_to_tasks),
+    ("historique des commandes", ensure_history_table),
+    ("profils de listeners", add_listener_profile_columns),
+    ("journal d'événements", ensure_events_table),
+    ("notes par agent", move_notes_to_table),
+    ("localisation GeoIP", add_geoip_column),
+    ("groupes d'agents", ensure_groups_tables),
+    ("tâches planifiées", ensure_scheduled_tasks_table),
+    ("type de sortie des tâches", add_payload_type_column),
+    ("keylogs", ensure_keylogs_table),
+    ("clés des agents", ensure_keys_table),
+    ("clés de session TCP", add_session_key_column),
+    ("pivots", ensure_pivots_table),
+    ("joignabilité entre agents", ensure_reachability_table),
+    ("intervalle de beacon", add_beacon_column),
+    ("historique des check-ins", ensure_checkins_table),
+    ("self-destruct en attente", ensure_self_destructs_table),
+    ("fichiers rapatriés", ensure_files_tables),
+    ("captures périodiques", add_screenshot_interval_column),
+    ("envois par tâche", ensure_task_uploads_tables),
+    ("fiche système des agents", add_sysinfo_columns),
+    ("gigue du beacon", add_beacon_jitter_column),
+    ("agents archivés", add_archived_column),
+    ("gigue en pourcentage", move_jitter_to_pct),
+];
+
+pub const SCHEMA_VERSION: i64 = MIGRATIONS.len() as i64;
+
+fn create_core_tables(conn: &Connection) -> rusqlite::Result<()> {
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS agents (
+            id TEXT PRIMARY KEY,
+            hostname TEXT NOT NULL,
+            ip TEXT NOT NULL,
+            os TEXT,
+            status TEXT NOT NULL DEFAULT 'offline',
+            last_seen TEXT,
+            location TEXT,
+            note TEXT
+        )",
+        [],
+    )?;
+    ensure_commands_table(conn)?;
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS results (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            command_id INTEGER NOT NULL,
+            output TEXT NOT NULL,
+            timestamp TEXT NOT NULL,
+            FOREIGN KEY(command_id) REFERENCES commands(id)
+        )",
+        [],
+    )?;
+    Ok(())
+}
+
+// La file de commandes devient la table tasks, terminée se disant désormais done.
+// Les références de results suivent le renommage.
+fn rename_commands_to_tasks(conn: &Connection) -> rusqlite::Result<()> {
+    conn.execute("ALTER TABLE commands RENAME TO tasks", [])?;
+    conn.execute("UPDATE tasks SET status = 'done' WHERE status = 'completed'", [])?;
+    Ok(())
+}
+
+pub fn schema_version(conn: &Connection) -> rusqlite::Result<i64> {
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS schema_version (
+            version INTEGER PRIMARY KEY,
+            description TEXT NOT NULL,
+            applied_at TEXT NOT NULL
+        )",
+        [],
+    )?;
+    conn.query_row("SELECT COALESCE(MAX(version), 0) FROM schema_version", [], |row| row.get(0))
+}
+
+// Applique les migrations manquantes dans une seule transaction : une base n'est jamais
+// laissée à mi-chemin. Une base écrite par une version plus récente n'est pas touchée.
+// Verrou d'écriture dès le début : deux processus ouverts en même temps sur une base à
+// migrer ne lisent pas tous deux l'ancienne version
+pub fn migrate(conn: &mut Connection) -> Result<()> {
+    let tx = conn.transaction_with_behavior(TransactionBehavior::Immediate)?;
+    let current = schema_version(&tx)?;
+    if current > SCHEMA_VERSION {
+        return Err(Error::SchemaTooNew { found: current, supported: SCHEMA_VERSION });
+    }
+    for (version, (description, migration)) in (1..).zip(MIGRATIONS).skip(current as usize) {
+        migration(&tx)?;
+        tx.execute(
+            "INSERT INTO schema_version (version, description, applied_at) VALUES (?1, ?2, ?3)",
+            (version, description, now_timestamp()),
+        )?;
+    }
+    tx.commit()?;
+    Ok(())
+}
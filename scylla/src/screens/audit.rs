@@ -0,0 +1,81 @@
+use ratatui::layout::Rect;
+use ratatui::style::Modifier;
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{List, ListItem, ListState};
+use ratatui::Frame;
+
+use crate::audit::AuditEntry;
+use crate::db::Db;
+use crate::error::Result;
+use crate::keymap::Action;
+use crate::screens::{Screen, ScreenQuery, ScreenRows};
+use crate::theme::ColorScheme;
+
+pub(crate) const AUDIT_LIMIT: usize = 100;
+
+#[derive(Default)]
+pub struct AuditScreen {
+    entries: Vec<AuditEntry>,
+    state: ListState,
+}
+
+impl Screen for AuditScreen {
+    fn query(&self) -> ScreenQuery {
+        ScreenQuery::Audit
+    }
+
+    fn apply(&mut self, rows: ScreenRows) {
+        let ScreenRows::Audit(entries) = rows else {
+            return;
+        };
+        self.entries = entries;
+        let selected = self.state.selected().unwrap_or(0);
+        self.state.select((!self.entries.is_empty()).then(|| selected.min(self.entries.len() - 1)));
+    }
+
+    fn draw(&mut self, f: &mut Frame, area: Rect, scheme: &ColorScheme) {
+        let items: Vec<ListItem> = self.entries.iter()
+            .map(|e| ListItem::new(Line::from(vec![
+                Span::styled(format!("{} ", e.timestamp), scheme.dim()),
+                Span::styled(format!("{} ", e.operator), scheme.accent()),
+                Span::styled(e.action.clone(), scheme.base().add_modifier(Modifier::BOLD)),
+                Span::raw(format!(" {}", e.detail)),
+            ])))
+            .collect();
+        let list = List::new(items)
+            .block(scheme.block("Audit"))
+            .highlight_style(scheme.selection());
+        f.render_stateful_widget(list, area, &mut self.state);
+    }
+
+    fn datasheet(&self, _scheme: &ColorScheme) -> Vec<Line<'static>> {
+        let Some(e) = self.state.selected().and_then(|i| self.entries.get(i)) else {
+            return vec![Line::from("Aucune entrée sélectionnée")];
+        };
+        vec![
+            Line::from(format!("ID: {}", e.id)),
+            Line::from(format!("Timestamp: {}", e.timestamp)),
+            Line::from(format!("Operator: {}", e.operator)),
+            Line::from(format!("Action: {}", e.action)),
+            Line::from(format!("Detail: {}", e.detail)),
+        ]
+    }
+
+    fn handle_action(&mut self, _db: &Db, action: Action) -> Result<Option<Action>> {
+        let last = self.entries.len().saturating_sub(1);
+        let selected = self.state.selected().unwrap_or(0);
+        let selected = match action {
+            Action::Down => selected.saturating_add(1),
+            Action::Up => selected.saturating_sub(1),
+            Action::PageDown => selected.saturating_add(10),
+            Action::PageUp => selected.saturating_sub(10),
+            Action::HalfPageDown => selected.saturating_add(5),
+            Action::HalfPageUp => selected.saturating_sub(5),
+            Action::First => 0,
+            Action::Last => last,
+            action => return Ok(Some(action)),
+        };
+        self.state.select(Some(selected.min(last)));
+        Ok(None)
+    }
+}
@@ -0,0 +1,128 @@
+use chrono::{DateTime, TimeDelta, Utc};
+use ratatui::layout::Rect;
+use ratatui::style::{Modifier, Style};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Gauge, Paragraph};
+use ratatui::Frame;
+
+use crate::db::Db;
+use crate::error::Result;
+use crate::keymap::Action;
+use crate::screens::{Screen, ScreenQuery, ScreenRows};
+use crate::theme::ColorScheme;
+use crate::timefmt::{format_age, parse_timestamp};
+use crate::transfers::FileTransfer;
+
+#[derive(Default)]
+pub struct FilesScreen {
+    transfers: Vec<FileTransfer>,
+    index: usize,
+}
+
+impl FilesScreen {
+    // Après un envoi, le nouveau transfert est en tête de liste
+    pub fn select_newest(&mut self) {
+        self.index = 0;
+    }
+}
+
+fn transfer_style(status: &str, scheme: &ColorScheme) -> Style {
+    match status {
+        "completed" => scheme.online(),
+        "running" => scheme.info(),
+        "failed" => scheme.error(),
+        _ => scheme.warning(),
+    }
+}
+
+// Estimation au débit moyen depuis le début du transfert
+fn transfer_eta(transfer: &FileTransfer, now: DateTime<Utc>) -> Option<TimeDelta> {
+    if transfer.status != "running" || transfer.transferred_bytes <= 0 {
+        return None;
+    }
+    let elapsed = now - transfer.started_at.as_deref().and_then(parse_timestamp)?;
+    let remaining = (transfer.size_bytes - transfer.transferred_bytes).max(0);
+    let millis = elapsed.num_milliseconds() as f64 * remaining as f64 / transfer.transferred_bytes as f64;
+    Some(TimeDelta::milliseconds(millis as i64))
+}
+
+impl Screen for FilesScreen {
+    // La progression des transferts n'est relue que si l'écran est affiché
+    fn query(&self) -> ScreenQuery {
+        ScreenQuery::Files
+    }
+
+    fn apply(&mut self, rows: ScreenRows) {
+        let ScreenRows::Files(transfers) = rows else {
+            return;
+        };
+        self.transfers = transfers;
+        self.index = self.index.min(self.transfers.len().saturating_sub(1));
+    }
+
+    // Deux lignes par transfert : le fichier, puis sa jauge
+    fn draw(&mut self, f: &mut Frame, area: Rect, scheme: &ColorScheme) {
+        let block = scheme.block("Files");
+        let inner = block.inner(area);
+        f.render_widget(block, area);
+        if self.transfers.is_empty() {
+            f.render_widget(Paragraph::new("Aucun transfert (u: envoyer)"), inner);
+            return;
+        }
+        let now = Utc::now();
+        let per_page = (inner.height as usize / 2).max(1);
+        let first = self.index.saturating_sub(per_page - 1);
+        for (row, (i, t)) in self.transfers.iter().enumerate().skip(first).take(per_page).enumerate() {
+            let y = inner.y + 2 * row as u16;
+            if y + 1 >= inner.y + inner.height {
+                break;
+            }
+            let name = std::path::Path::new(&t.filename)
+                .file_name()
+                .map(|n| n.to_string_lossy().into_owned())
+                .unwrap_or_else(|| t.filename.clone());
+            let style = if i == self.index { scheme.selection().add_modifier(Modifier::BOLD) } else { scheme.base() };
+            let title = Paragraph::new(Span::styled(format!("#{} {} → {}", t.id, name, t.agent_id), style));
+            f.render_widget(title, Rect::new(inner.x, y, inner.width, 1));
+
+            let label = match transfer_eta(t, now) {
+                Some(eta) => format!("{:.0}% ETA {}", t.ratio() * 100.0, format_age(eta)),
+                None => format!("{:.0}% {}", t.ratio() * 100.0, t.status),
+            };
+            let gauge = Gauge::default()
+                .gauge_style(transfer_style(&t.status, scheme))
+                .ratio(t.ratio())
+                .label(label);
+            f.render_widget(gauge, Rect::new(inner.x, y + 1, inner.width, 1));
+        }
+    }
+
+    fn datasheet(&self, scheme: &ColorScheme) -> Vec<Line<'static>> {
+        let Some(t) = self.transfers.get(self.index) else {
+            return vec![Line::from("Aucun transfert sélectionné")];
+        };
+        vec![
+            Line::from(format!("ID: {}", t.id)),
+            Line::from(format!("Agent: {}", t.agent_id)),
+            Line::from(format!("Direction: {}", t.direction)),
+            Line::from(format!("File: {}", t.filename)),
+            Line::from(format!("Size: {} bytes", t.size_bytes)),
+            Line::from(format!("Transferred: {} bytes", t.transferred_bytes)),
+            Line::from(vec![
+                Span::raw("Status: "),
+                Span::styled(t.status.clone(), transfer_style(&t.status, scheme)),
+            ]),
+            Line::from(format!("Started: {}", t.started_at.as_deref().unwrap_or("-"))),
+        ]
+    }
+
+    // L'envoi remonte à l'appelant, qui ouvre le formulaire pour l'agent sélectionné
+    fn handle_action(&mut self, _db: &Db, action: Action) -> Result<Option<Action>> {
+        match action {
+            Action::Down => self.index = (self.index + 1).min(self.transfers.len().saturating_sub(1)),
+            Action::Up => self.index = self.index.saturating_sub(1),
+            action => return Ok(Some(action)),
+        }
+        Ok(None)
+    }
+}
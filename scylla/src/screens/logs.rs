@@ -0,0 +1,142 @@
+use std::collections::VecDeque;
+
+use ratatui::layout::Rect;
+use ratatui::style::Style;
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{List, ListItem, ListState};
+use ratatui::Frame;
+
+use crate::db::Db;
+use crate::error::Result;
+use crate::eventlog::{EventEntry, Level};
+use crate::keymap::Action;
+use crate::screens::{Screen, ScreenQuery, ScreenRows};
+use crate::theme::ColorScheme;
+
+// Au-delà, les plus anciens ne sont plus gardés en mémoire mais restent en base
+pub(crate) const MAX_ROWS: usize = 10_000;
+
+// Journal des événements en suivi continu, le plus récent en tête
+pub struct LogsScreen {
+    rows: VecDeque<EventEntry>,
+    // Dernier identifiant lu ; 0 force une relecture complète
+    last_id: i64,
+    min_level: Level,
+    state: ListState,
+}
+
+impl Default for LogsScreen {
+    fn default() -> LogsScreen {
+        LogsScreen {
+            rows: VecDeque::new(),
+            last_id: 0,
+            min_level: Level::Info,
+            state: ListState::default(),
+        }
+    }
+}
+
+pub fn level_style(level: Level, scheme: &ColorScheme) -> Style {
+    match level {
+        Level::Info => scheme.base(),
+        Level::Warn => scheme.warning(),
+        Level::Error => scheme.error(),
+    }
+}
+
+impl LogsScreen {
+    fn set_min_level(&mut self, level: Level) {
+        self.min_level = level;
+        self.rows.clear();
+        self.last_id = 0;
+        self.state.select(None);
+    }
+}
+
+impl Screen for LogsScreen {
+    fn query(&self) -> ScreenQuery {
+        ScreenQuery::Logs { after_id: self.last_id, min_level: self.min_level }
+    }
+
+    // Les nouvelles lignes arrivent en tête ; une sélection ailleurs qu'en tête reste
+    // sur la même ligne, en tête elle suit le journal. Une ligne déjà affichée n'est
+    // pas reprise, si deux lectures se croisent.
+    fn apply(&mut self, rows: ScreenRows) {
+        let ScreenRows::Logs(entries) = rows else {
+            return;
+        };
+        let added: Vec<EventEntry> = entries.into_iter().filter(|e| e.id > self.last_id).collect();
+        if let Some(selected) = self.state.selected().filter(|&s| s > 0) {
+            self.state.select(Some(selected + added.len()));
+        }
+        for entry in added {
+            self.last_id = self.last_id.max(entry.id);
+            self.rows.push_front(entry);
+        }
+        self.rows.truncate(MAX_ROWS);
+        let last = self.rows.len().checked_sub(1);
+        self.state.select(self.state.selected().or(Some(0)).zip(last).map(|(s, l)| s.min(l)));
+    }
+
+    fn draw(&mut self, f: &mut Frame, area: Rect, scheme: &ColorScheme) {
+        let items: Vec<ListItem> = self.rows.iter()
+            .map(|e| {
+                let mut spans = vec![
+                    Span::styled(format!("{} ", e.timestamp), scheme.dim()),
+                    Span::styled(format!("{:<5} ", e.level.as_str()), level_style(e.level, scheme)),
+                    Span::styled(format!("{} ", e.category), scheme.accent()),
+                ];
+                if let Some(agent_id) = &e.agent_id {
+                    spans.push(Span::raw(format!("{} ", agent_id)));
+                }
+                spans.push(Span::styled(e.message.clone(), level_style(e.level, scheme)));
+                ListItem::new(Line::from(spans))
+            })
+            .collect();
+        let title = format!("Logs [{}+] {}", self.min_level.as_str(), self.rows.len());
+        let list = List::new(items)
+            .block(scheme.block(title))
+            .highlight_style(scheme.selection());
+        f.render_stateful_widget(list, area, &mut self.state);
+    }
+
+    fn datasheet(&self, scheme: &ColorScheme) -> Vec<Line<'static>> {
+        let Some(e) = self.state.selected().and_then(|i| self.rows.get(i)) else {
+            return vec![Line::from("Aucun événement")];
+        };
+        vec![
+            Line::from(format!("ID: {}", e.id)),
+            Line::from(format!("Timestamp: {}", e.timestamp)),
+            Line::from(vec![
+                Span::raw("Level: "),
+                Span::styled(e.level.as_str(), level_style(e.level, scheme)),
+            ]),
+            Line::from(format!("Category: {}", e.category)),
+            Line::from(format!("Agent: {}", e.agent_id.as_deref().unwrap_or("-"))),
+            Line::from(format!("Message: {}", e.message)),
+        ]
+    }
+
+    fn handle_action(&mut self, db: &Db, action: Action) -> Result<Option<Action>> {
+        let last = self.rows.len().saturating_sub(1);
+        let selected = self.state.selected().unwrap_or(0);
+        let selected = match action {
+            Action::Down => selected.saturating_add(1),
+            Action::Up => selected.saturating_sub(1),
+            Action::PageDown => selected.saturating_add(10),
+            Action::PageUp => selected.saturating_sub(10),
+            Action::HalfPageDown => selected.saturating_add(5),
+            Action::HalfPageUp => selected.saturating_sub(5),
+            Action::First => 0,
+            Action::Last => last,
+            Action::MinLevel(level) => {
+                self.set_min_level(level);
+                self.reload(db)?;
+                return Ok(None);
+            }
+            action => return Ok(Some(action)),
+        };
+        self.state.select((!self.rows.is_empty()).then(|| selected.min(last)));
+        Ok(None)
+    }
+}
@@ -0,0 +1,97 @@
+use crossterm::event::KeyEvent;
+use ratatui::layout::Rect;
+use ratatui::text::Line;
+use ratatui::Frame;
+
+use crate::audit::{recent_audit_entries, AuditEntry};
+use crate::db::{Db, Task};
+use crate::error::Result;
+use crate::eventlog::{events_after, recent_events, EventEntry, Level};
+use crate::keymap::Action;
+use crate::scheduler::{load_scheduled_tasks, ScheduledTask};
+use crate::theme::ColorScheme;
+use crate::transfers::{load_transfers, FileTransfer};
+
+pub mod audit;
+pub mod files;
+pub mod logs;
+pub mod scheduler;
+pub mod tasks;
+
+// Écran autonome de la barre d'onglets : il garde sa sélection quand on le quitte.
+// Agents et Listeners restent portés par AppState, leurs fenêtres agissant sur tout l'état.
+pub trait Screen {
+    // Lecture du tick, faite par le worker tant que l'écran est affiché
+    fn query(&self) -> ScreenQuery;
+
+    // Lignes lues pour query ; celles d'un autre écran sont ignorées
+    fn apply(&mut self, rows: ScreenRows);
+
+    // Relecture immédiate, après une action de l'opérateur
+    fn reload(&mut self, db: &Db) -> Result<()> {
+        let rows = self.query().load(db)?;
+        self.apply(rows);
+        Ok(())
+    }
+
+    // Panneau de gauche
+    fn draw(&mut self, f: &mut Frame, area: Rect, scheme: &ColorScheme);
+
+    // Contenu du panneau Datasheet pour la ligne sélectionnée
+    fn datasheet(&self, scheme: &ColorScheme) -> Vec<Line<'static>>;
+
+    // Action tirée des touches de l'écran par l'appelant ; celles que l'écran
+    // ne sait pas traiter seul lui sont renvoyées
+    fn handle_action(&mut self, db: &Db, action: Action) -> Result<Option<Action>>;
+
+    // Vrai tant qu'une vue de l'écran doit recevoir toutes les touches, q compris
+    fn captures_input(&self) -> bool {
+        false
+    }
+
+    // Touches brutes, reçues seulement tant que captures_input est vrai
+    fn handle_key(&mut self, _db: &Db, _key: KeyEvent) -> Result<()> {
+        Ok(())
+    }
+
+    // Dessiné par-dessus toute l'interface, après les autres panneaux
+    fn draw_overlay(&mut self, _f: &mut Frame, _scheme: &ColorScheme) {}
+}
+
+// Ce qu'un écran relit, sans accès à l'écran lui-même : le worker s'en charge
+#[derive(Clone, Debug, PartialEq)]
+pub enum ScreenQuery {
+    Tasks,
+    Audit,
+    Files,
+    // Les événements après after_id ; 0 relit les plus récents
+    Logs { after_id: i64, min_level: Level },
+    Scheduler,
+}
+
+#[derive(Debug)]
+pub enum ScreenRows {
+    Tasks(Vec<Task>),
+    Audit(Vec<AuditEntry>),
+    Files(Vec<FileTransfer>),
+    // Du plus ancien au plus récent
+    Logs(Vec<EventEntry>),
+    Scheduler(Vec<ScheduledTask>),
+}
+
+impl ScreenQuery {
+    pub fn load(&self, db: &Db) -> Result<ScreenRows> {
+        Ok(match *self {
+            ScreenQuery::Tasks => ScreenRows::Tasks(db.all_tasks(tasks::TASK_LIMIT)?),
+            ScreenQuery::Audit => ScreenRows::Audit(recent_audit_entries(db.conn(), audit::AUDIT_LIMIT)?),
+            ScreenQuery::Files => ScreenRows::Files(load_transfers(db.conn())?),
+            ScreenQuery::Logs { after_id: 0, min_level } => {
+                let mut rows = recent_events(db.conn(), min_level, logs::MAX_ROWS)?;
+                rows.reverse();
+                ScreenRows::Logs(rows)
+            }
+            ScreenQuery::Logs { after_id, min_level } => ScreenRows::Logs(events_after(db.conn(), after_id, min_level, logs::MAX_ROWS)?),
+            ScreenQuery::Scheduler => ScreenRows::Scheduler(load_scheduled_tasks(db.conn())?),
+        })
+    }
+}
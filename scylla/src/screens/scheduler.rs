@@ -0,0 +1,112 @@
+use chrono::Utc;
+use ratatui::layout::Rect;
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{List, ListItem, ListState};
+use ratatui::Frame;
+
+use crate::db::Db;
+use crate::error::Result;
+use crate::keymap::Action;
+use crate::scheduler::ScheduledTask;
+use crate::screens::{Screen, ScreenQuery, ScreenRows};
+use crate::theme::ColorScheme;
+use crate::timefmt::format_relative;
+
+// Tâches récurrentes, par prochaine échéance ; activer, suspendre et supprimer passent
+// par l'appelant pour être journalisés au nom de l'opérateur
+#[derive(Default)]
+pub struct SchedulerScreen {
+    tasks: Vec<ScheduledTask>,
+    state: ListState,
+}
+
+impl SchedulerScreen {
+    pub fn selected(&self) -> Option<&ScheduledTask> {
+        self.state.selected().and_then(|i| self.tasks.get(i))
+    }
+}
+
+impl Screen for SchedulerScreen {
+    // L'ordre change à chaque exécution : la sélection suit la tâche
+    fn query(&self) -> ScreenQuery {
+        ScreenQuery::Scheduler
+    }
+
+    fn apply(&mut self, rows: ScreenRows) {
+        let ScreenRows::Scheduler(tasks) = rows else {
+            return;
+        };
+        let selected_id = self.selected().map(|t| t.id);
+        self.tasks = tasks;
+        let index = selected_id
+            .and_then(|id| self.tasks.iter().position(|t| t.id == id))
+            .or((!self.tasks.is_empty()).then_some(0));
+        self.state.select(index);
+    }
+
+    fn draw(&mut self, f: &mut Frame, area: Rect, scheme: &ColorScheme) {
+        let now = Utc::now();
+        let items: Vec<ListItem> = self.tasks.iter()
+            .map(|t| {
+                let (state, next) = if t.enabled {
+                    (Span::styled("● ", scheme.online()), format_relative(t.next_run.as_deref(), now))
+                } else {
+                    (Span::styled("○ ", scheme.dim()), "suspendue".to_string())
+                };
+                ListItem::new(Line::from(vec![
+                    state,
+                    Span::styled(format!("{} ", t.agent_id), scheme.accent()),
+                    Span::styled(format!("[{}] ", t.schedule), scheme.dim()),
+                    Span::raw(format!("{} ", t.command)),
+                    Span::styled(next, scheme.dim()),
+                ]))
+            })
+            .collect();
+        let list = List::new(items)
+            .block(scheme.block("Scheduler"))
+            .highlight_style(scheme.selection());
+        f.render_stateful_widget(list, area, &mut self.state);
+    }
+
+    fn datasheet(&self, _scheme: &ColorScheme) -> Vec<Line<'static>> {
+        let Some(t) = self.selected() else {
+            return vec![
+                Line::from("Aucune tâche planifiée"),
+                Line::from(""),
+                Line::from(":schedule <agent> <cron> -- <commande>"),
+            ];
+        };
+        let now = Utc::now();
+        vec![
+            Line::from(format!("ID: {}", t.id)),
+            Line::from(format!("Agent: {}", t.agent_id)),
+            Line::from(format!("Command: {}", t.command)),
+            Line::from(format!("Schedule: {}", t.schedule)),
+            Line::from(format!("Enabled: {}", if t.enabled { "oui" } else { "non" })),
+            Line::from(format!(
+                "Last run: {} ({})",
+                t.last_run.as_deref().unwrap_or("-"),
+                format_relative(t.last_run.as_deref(), now),
+            )),
+            Line::from(format!(
+                "Next run: {} ({})",
+                t.next_run.as_deref().unwrap_or("-"),
+                format_relative(t.next_run.as_deref(), now),
+            )),
+        ]
+    }
+
+    fn handle_action(&mut self, _db: &Db, action: Action) -> Result<Option<Action>> {
+        let last = self.tasks.len().saturating_sub(1);
+        let selected = self.state.selected().unwrap_or(0);
+        let selected = match action {
+            Action::Down => selected.saturating_add(1),
+            Action::Up => selected.saturating_sub(1),
+            Action::First => 0,
+            Action::Last => last,
+            action => return Ok(Some(action)),
+        };
+        self.state.select((!self.tasks.is_empty()).then(|| selected.min(last)));
+        Ok(None)
+    }
+}
@@ -0,0 +1,145 @@
+use crossterm::event::KeyEvent;
+use ratatui::layout::Rect;
+use ratatui::style::Style;
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{List, ListItem, ListState};
+use ratatui::Frame;
+
+use crate::db::{Db, Task};
+use crate::error::Result;
+use crate::keymap::Action;
+use crate::screens::{Screen, ScreenQuery, ScreenRows};
+use crate::theme::ColorScheme;
+use crate::viewer::{draw_output_viewer, OutputViewer};
+
+pub(crate) const TASK_LIMIT: usize = 200;
+
+// File de tous les agents ; Entrée ouvre la sortie d'une tâche terminée en plein écran
+#[derive(Default)]
+pub struct TasksScreen {
+    tasks: Vec<Task>,
+    state: ListState,
+    viewer: Option<OutputViewer>,
+}
+
+pub fn task_status_style(status: &str, scheme: &ColorScheme) -> Style {
+    match status {
+        "pending" => scheme.warning(),
+        "done" => scheme.online(),
+        "sent" => scheme.info(),
+        "failed" => scheme.error(),
+        _ => scheme.dim(),
+    }
+}
+
+// Aperçu du panneau de détail ; une sortie chiffrée ne s'ouvre que dans la visionneuse
+pub fn task_output_preview(task: &Task) -> &str {
+    match (&task.output, task.sealed) {
+        (_, true) => "(sortie chiffrée, Entrée pour la lire)",
+        (Some(output), false) => output,
+        (None, false) => "(pas encore de sortie)",
+    }
+}
+
+impl TasksScreen {
+    fn selected(&self) -> Option<&Task> {
+        self.state.selected().and_then(|i| self.tasks.get(i))
+    }
+}
+
+impl Screen for TasksScreen {
+    // La sélection suit la tâche, pas la ligne : les nouvelles arrivent en tête
+    fn query(&self) -> ScreenQuery {
+        ScreenQuery::Tasks
+    }
+
+    fn apply(&mut self, rows: ScreenRows) {
+        let ScreenRows::Tasks(tasks) = rows else {
+            return;
+        };
+        let selected_id = self.selected().map(|t| t.id);
+        self.tasks = tasks;
+        let index = selected_id
+            .and_then(|id| self.tasks.iter().position(|t| t.id == id))
+            .or((!self.tasks.is_empty()).then_some(0));
+        self.state.select(index);
+    }
+
+    fn draw(&mut self, f: &mut Frame, area: Rect, scheme: &ColorScheme) {
+        let items: Vec<ListItem> = self.tasks.iter()
+            .map(|t| ListItem::new(Line::from(vec![
+                Span::raw(format!("#{} ", t.id)),
+                Span::styled(format!("[{}] ", t.status), task_status_style(&t.status, scheme)),
+                Span::styled(format!("{} ", t.agent_id), scheme.accent()),
+                Span::raw(t.command.clone()),
+            ])))
+            .collect();
+        let list = List::new(items)
+            .block(scheme.block("Tasks"))
+            .highlight_style(scheme.selection());
+        f.render_stateful_widget(list, area, &mut self.state);
+    }
+
+    fn datasheet(&self, scheme: &ColorScheme) -> Vec<Line<'static>> {
+        let Some(t) = self.selected() else {
+            return vec![Line::from("Aucune tâche")];
+        };
+        let mut lines = vec![
+            Line::from(format!("ID: {}", t.id)),
+            Line::from(format!("Agent: {}", t.agent_id)),
+            Line::from(format!("Command: {}", t.command)),
+            Line::from(vec![
+                Span::raw("Status: "),
+                Span::styled(t.status.clone(), task_status_style(&t.status, scheme)),
+            ]),
+            Line::from(format!("Created: {}", t.created_at)),
+            Line::from(""),
+        ];
+        lines.extend(task_output_preview(t).lines().map(|l| Line::from(l.to_string())));
+        lines
+    }
+
+    fn handle_action(&mut self, db: &Db, action: Action) -> Result<Option<Action>> {
+        let last = self.tasks.len().saturating_sub(1);
+        let selected = self.state.selected().unwrap_or(0);
+        let selected = match action {
+            Action::Down => selected.saturating_add(1),
+            Action::Up => selected.saturating_sub(1),
+            Action::PageDown => selected.saturating_add(10),
+            Action::PageUp => selected.saturating_sub(10),
+            Action::HalfPageDown => selected.saturating_add(5),
+            Action::HalfPageUp => selected.saturating_sub(5),
+            Action::First => 0,
+            Action::Last => last,
+            // Une tâche en attente n'a pas de sortie à afficher
+            Action::Open => {
+                if let Some(task) = self.selected().filter(|t| matches!(t.status.as_str(), "done" | "failed")) {
+                    self.viewer = Some(OutputViewer::open(db, task)?);
+                }
+                return Ok(None);
+            }
+            action => return Ok(Some(action)),
+        };
+        self.state.select((!self.tasks.is_empty()).then(|| selected.min(last)));
+        Ok(None)
+    }
+
+    fn captures_input(&self) -> bool {
+        self.viewer.is_some()
+    }
+
+    fn handle_key(&mut self, db: &Db, key: KeyEvent) -> Result<()> {
+        if let Some(viewer) = self.viewer.as_mut()
+            && !viewer.handle_key(db, key)?
+        {
+            self.viewer = None;
+        }
+        Ok(())
+    }
+
+    fn draw_overlay(&mut self, f: &mut Frame, scheme: &ColorScheme) {
+        if let Some(viewer) = self.viewer.as_mut() {
+            draw_output_viewer(f, viewer, f.area(), scheme);
+        }
+    }
+}
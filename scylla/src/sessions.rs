@@ -0,0 +1,84 @@
+use std::collections::VecDeque;
+use std::net::SocketAddr;
+use std::sync::Arc;
+
+use chrono::{DateTime, Utc};
+use dashmap::DashMap;
+use tokio::sync::mpsc;
+use uuid::Uuid;
+
+// Connexion TCP ouverte par un agent, tant que le socket n'est pas fermé
+#[derive(Debug, Clone)]
+pub struct Session {
+    pub id: Uuid,
+    pub agent_id: String,
+    pub remote_addr: SocketAddr,
+    pub connected_at: DateTime<Utc>,
+    // Octets à écrire sur le socket ; retirer la session ferme la connexion
+    pub tx: mpsc::Sender<Vec<u8>>,
+}
+
+// Sessions actives par identifiant de session, partagées entre les listeners et l'interface
+pub type Sessions = Arc<DashMap<String, Session>>;
+
+// Sessions d'un agent, les plus anciennes d'abord
+pub fn sessions_for(sessions: &Sessions, agent_id: &str) -> Vec<Session> {
+    let mut found: Vec<Session> = sessions.iter()
+        .filter(|entry| entry.agent_id == agent_id)
+        .map(|entry| entry.value().clone())
+        .collect();
+    found.sort_by_key(|s| s.connected_at);
+    found
+}
+
+pub fn session_count(sessions: &Sessions, agent_id: &str) -> usize {
+    sessions.iter().filter(|entry| entry.agent_id == agent_id).count()
+}
+
+// Lignes reçues sur une session, les plus anciennes sont jetées au-delà de MAX_LINES
+#[derive(Debug, Default)]
+pub struct OutputBuffer {
+    pub lines: VecDeque<String>,
+    // La dernière ligne n'a pas encore reçu son saut de ligne
+    open_line: bool,
+}
+
+impl OutputBuffer {
+    const MAX_LINES: usize = 500;
+
+    pub fn push_bytes(&mut self, bytes: &[u8]) {
+        let text = String::from_utf8_lossy(bytes).replace('\r', "");
+        for (i, part) in text.split('\n').enumerate() {
+            // Chaque saut de ligne ferme la ligne en cours, éventuellement vide
+            if i > 0 {
+                if !self.open_line {
+                    self.lines.push_back(String::new());
+                }
+                self.open_line = false;
+            }
+            if part.is_empty() {
+                continue;
+            }
+            match self.lines.back_mut() {
+                Some(line) if self.open_line => line.push_str(part),
+                _ => {
+                    self.lines.push_back(part.to_string());
+                    self.open_line = true;
+                }
+            }
+        }
+        self.trim();
+    }
+
+    pub fn push_line(&mut self, line: String) {
+        self.lines.push_back(line);
+        self.open_line = false;
+        self.trim();
+    }
+
+    fn trim(&mut self) {
+        while self.lines.len() > Self::MAX_LINES {
+            self.lines.pop_front();
+        }
+    }
+}
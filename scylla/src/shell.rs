@@ -0,0 +1,91 @@
+use rusqlite::{Connection, OptionalExtension};
+
+use crate::crypto::open_payload_text;
+use crate::form::TextInput;
+use crate::sessions::OutputBuffer;
+
+// Réponse rendue au shell : l'id de la tâche, son statut et sa sortie, rien si elle a disparu
+pub type ShellReply = (i64, Option<(String, String)>);
+
+// Terminal du bas en mode shell sur les tâches : chaque ligne tapée part en tâche, la
+// réponse s'ajoute à l'historique dès que l'agent la rend. Un shell par agent, gardé tant
+// que l'application tourne ; Échap le quitte sans perdre la ligne en cours.
+pub struct TaskShell {
+    pub agent_id: String,
+    pub hostname: String,
+    pub input: TextInput,
+    pub scrollback: OutputBuffer,
+    // Lignes remontées depuis le bas de l'historique
+    pub scroll: usize,
+    // Tâches envoyées sans réponse, dans l'ordre d'envoi
+    pending: Vec<i64>,
+}
+
+impl TaskShell {
+    pub fn new(agent_id: &str, hostname: &str) -> TaskShell {
+        TaskShell {
+            agent_id: agent_id.to_string(),
+            hostname: hostname.to_string(),
+            input: TextInput::default(),
+            scrollback: OutputBuffer::default(),
+            scroll: 0,
+            pending: Vec::new(),
+        }
+    }
+
+    pub fn prompt(&self) -> String {
+        format!("{}$ ", self.hostname)
+    }
+
+    pub fn pending_count(&self) -> usize {
+        self.pending.len()
+    }
+
+    pub fn sent(&mut self, task_id: i64, command: &str) {
+        let line = format!("{}{}", self.prompt(), command);
+        self.scrollback.push_line(line);
+        self.pending.push(task_id);
+        self.scroll = 0;
+    }
+
+    pub fn pending(&self) -> &[i64] {
+        &self.pending
+    }
+
+    // Ajoute les réponses lues par shell_replies ; une réponse déjà affichée est ignorée
+    pub fn apply(&mut self, replies: Vec<ShellReply>) {
+        for (task_id, reply) in replies {
+            let Some(position) = self.pending.iter().position(|&id| id == task_id) else {
+                continue;
+            };
+            self.pending.remove(position);
+            let Some((status, output)) = reply else {
+                continue;
+            };
+            self.scrollback.push_line(format!("[#{} {}]", task_id, status));
+            for line in output.replace('\r', "").lines() {
+                self.scrollback.push_line(line.to_string());
+            }
+        }
+    }
+}
+
+// Tâches du shell qui ne sont plus en attente, dans l'ordre d'envoi : le statut et la
+// sortie d'une tâche terminée, rien pour une tâche supprimée entre-temps
+pub fn shell_replies(conn: &Connection, agent_id: &str, pending: &[i64]) -> rusqlite::Result<Vec<ShellReply>> {
+    let mut replies = Vec::new();
+    for &task_id in pending {
+        let task: Option<(String, Option<String>)> = conn
+            .query_row("SELECT status, output FROM tasks WHERE id = ?1", [task_id], |row| Ok((row.get(0)?, row.get(1)?)))
+            .optional()?;
+        match task {
+            None => replies.push((task_id, None)),
+            Some((status, output)) if matches!(status.as_str(), "done" | "failed") => {
+                let output = open_payload_text(conn, agent_id, output.unwrap_or_default())?;
+                replies.push((task_id, Some((status, output))));
+            }
+            Some(_) => {}
+        }
+    }
+    Ok(replies)
+}
@@ -0,0 +1,58 @@
+use chrono::{DateTime, TimeDelta, Utc};
+
+use crate::config::ScyllaConfig;
+use crate::agent::Agent;
+use crate::beacon::missed_after;
+use crate::timefmt::parse_timestamp;
+
+// Nombre de beacons manqués au-delà duquel un agent "online" est affiché stale puis dead ;
+// un beacon dure l'intervalle de l'agent plus sa gigue
+#[derive(Debug, Clone, Copy)]
+pub struct StalenessThresholds {
+    pub stale_after_beacons: u32,
+    pub dead_after_beacons: u32,
+}
+
+impl StalenessThresholds {
+    pub fn from_config(config: &ScyllaConfig) -> Result<StalenessThresholds, String> {
+        if config.stale_after_beacons == 0 {
+            return Err("le seuil stale doit être d'au moins un beacon manqué".to_string());
+        }
+        if config.dead_after_beacons < config.stale_after_beacons {
+            return Err("le seuil dead doit être supérieur ou égal au seuil stale".to_string());
+        }
+        Ok(StalenessThresholds {
+            stale_after_beacons: config.stale_after_beacons,
+            dead_after_beacons: config.dead_after_beacons,
+        })
+    }
+
+    pub fn stale_after(&self, agent: &Agent) -> TimeDelta {
+        missed_after(agent.beacon_interval_secs, agent.jitter_pct, self.stale_after_beacons)
+    }
+
+    pub fn dead_after(&self, agent: &Agent) -> TimeDelta {
+        missed_after(agent.beacon_interval_secs, agent.jitter_pct, self.dead_after_beacons)
+    }
+}
+
+// Statut affiché : un agent vivant d'après la base mais muet depuis trop longtemps est
+// déclassé, sans toucher à la ligne en base. Passé le seuil dead, le thread offline
+// l'écrit offline en base à son prochain passage.
+pub fn derived_status(agent: &Agent, now: DateTime<Utc>, thresholds: &StalenessThresholds) -> Option<&'static str> {
+    if !matches!(agent.status.to_lowercase().as_str(), "online" | "idle") {
+        return None;
+    }
+    let age = now - agent.last_seen.as_deref().and_then(parse_timestamp)?;
+    if age >= thresholds.dead_after(agent) {
+        Some("dead")
+    } else if age >= thresholds.stale_after(agent) {
+        Some("stale")
+    } else {
+        None
+    }
+}
+
+pub fn display_status<'a>(agent: &'a Agent, now: DateTime<Utc>, thresholds: &StalenessThresholds) -> &'a str {
+    derived_status(agent, now, thresholds).unwrap_or(&agent.status)
+}
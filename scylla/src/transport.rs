@@ -0,0 +1,135 @@
+use ring::aead::{Aad, LessSafeKey, Nonce, UnboundKey, CHACHA20_POLY1305, NONCE_LEN};
+use ring::agreement::{agree_ephemeral, EphemeralPrivateKey, UnparsedPublicKey, X25519};
+use ring::hkdf::{Salt, HKDF_SHA256};
+use ring::rand::SystemRandom;
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+
+use crate::error::{Error, Result};
+
+// Canal TCP des implants. À la connexion, le serveur envoie sa clé publique X25519
+// (32 octets), l'implant répond avec la sienne. La clé de session est tirée du secret
+// partagé par HKDF-SHA256, salé par les deux clés publiques dans cet ordre. Ensuite,
+// tout passe en trames : longueur sur 4 octets big-endian, puis texte chiffré
+// ChaCha20-Poly1305 et son tag. La première trame de l'implant est son enregistrement JSON.
+pub const PUBLIC_KEY_LEN: usize = 32;
+pub const SESSION_KEY_LEN: usize = 32;
+const SESSION_INFO: &[u8] = b"scylla tcp session";
+const TAG_LEN: usize = 16;
+// Au-delà, la connexion est jugée corrompue plutôt que de tout garder en mémoire
+pub const MAX_FRAME_LEN: usize = 1 << 20;
+
+// Premier octet du nonce, suivi de trois zéros et d'un compteur sur 8 octets : chaque
+// sens a sa suite de nonces sous la même clé
+const TO_AGENT: u8 = 0;
+const FROM_AGENT: u8 = 1;
+
+pub struct SessionCipher {
+    key: LessSafeKey,
+    raw: [u8; SESSION_KEY_LEN],
+    sent: u64,
+    received: u64,
+}
+
+fn nonce(direction: u8, counter: u64) -> Nonce {
+    let mut bytes = [0u8; NONCE_LEN];
+    bytes[0] = direction;
+    bytes[4..].copy_from_slice(&counter.to_be_bytes());
+    Nonce::assume_unique_for_key(bytes)
+}
+
+impl SessionCipher {
+    fn new(raw: [u8; SESSION_KEY_LEN]) -> Result<SessionCipher> {
+        let key = UnboundKey::new(&CHACHA20_POLY1305, &raw)
+            .map_err(|_| Error::Crypto("clé de session invalide".to_string()))?;
+        Ok(SessionCipher { key: LessSafeKey::new(key), raw, sent: 0, received: 0 })
+    }
+
+    pub fn key_bytes(&self) -> &[u8] {
+        &self.raw
+    }
+
+    // Trame complète, longueur comprise, prête à écrire sur le socket
+    pub fn seal(&mut self, plaintext: &[u8]) -> Result<Vec<u8>> {
+        let mut body = plaintext.to_vec();
+        self.key
+            .seal_in_place_append_tag(nonce(TO_AGENT, self.sent), Aad::empty(), &mut body)
+            .map_err(|_| Error::Crypto("chiffrement de la trame impossible".to_string()))?;
+        self.sent += 1;
+        let mut frame = (body.len() as u32).to_be_bytes().to_vec();
+        frame.append(&mut body);
+        Ok(frame)
+    }
+
+    // Une trame rejouée, réordonnée ou modifiée ne s'ouvre pas : le compteur attendu a avancé
+    pub fn open(&mut self, mut body: Vec<u8>) -> Result<Vec<u8>> {
+        let plaintext = self.key
+            .open_in_place(nonce(FROM_AGENT, self.received), Aad::empty(), &mut body)
+            .map_err(|_| Error::Crypto(format!("trame {} de l'implant illisible", self.received)))?;
+        let len = plaintext.len();
+        self.received += 1;
+        body.truncate(len);
+        Ok(body)
+    }
+}
+
+// Octets reçus, découpés en trames au fil des lectures
+#[derive(Debug, Default)]
+pub struct FrameBuffer {
+    buf: Vec<u8>,
+}
+
+impl FrameBuffer {
+    pub fn push(&mut self, bytes: &[u8]) {
+        self.buf.extend_from_slice(bytes);
+    }
+
+    // Corps de la prochaine trame complète, tag compris ; None tant qu'elle n'est pas arrivée
+    pub fn next_frame(&mut self, max_len: usize) -> Result<Option<Vec<u8>>> {
+        let Some(header) = self.buf.first_chunk::<4>() else {
+            return Ok(None);
+        };
+        let len = u32::from_be_bytes(*header) as usize;
+        if !(TAG_LEN..=max_len + TAG_LEN).contains(&len) {
+            return Err(Error::Crypto(format!("trame de {} octets refusée", len)));
+        }
+        if self.buf.len() < 4 + len {
+            return Ok(None);
+        }
+        let frame = self.buf[4..4 + len].to_vec();
+        self.buf.drain(..4 + len);
+        Ok(Some(frame))
+    }
+}
+
+pub struct Handshake {
+    pub cipher: SessionCipher,
+    // Clé publique éphémère de l'implant, gardée dans la table keys
+    pub agent_public: [u8; PUBLIC_KEY_LEN],
+}
+
+// Côté serveur, avant toute autre lecture ; le délai est à la charge de l'appelant
+pub async fn server_handshake<S: AsyncRead + AsyncWrite + Unpin>(stream: &mut S) -> Result<Handshake> {
+    let failed = |what: &str| Error::Crypto(format!("échange de clés : {}", what));
+    let rng = SystemRandom::new();
+    let private = EphemeralPrivateKey::generate(&X25519, &rng).map_err(|_| failed("génération de la clé"))?;
+    let public = private.compute_public_key().map_err(|_| failed("clé publique"))?;
+    let mut server_public = [0u8; PUBLIC_KEY_LEN];
+    server_public.copy_from_slice(public.as_ref());
+    stream.write_all(&server_public).await?;
+
+    let mut agent_public = [0u8; PUBLIC_KEY_LEN];
+    stream.read_exact(&mut agent_public).await?;
+    let raw = agree_ephemeral(private, &UnparsedPublicKey::new(&X25519, agent_public), |shared| {
+        let salt = [server_public, agent_public].concat();
+        let info = [SESSION_INFO];
+        let mut raw = [0u8; SESSION_KEY_LEN];
+        Salt::new(HKDF_SHA256, &salt)
+            .extract(shared)
+            .expand(&info, &CHACHA20_POLY1305)
+            .and_then(|okm| okm.fill(&mut raw))
+            .map(|_| raw)
+    })
+    .map_err(|_| failed("clé publique de l'implant invalide"))?
+    .map_err(|_| failed("dérivation de la clé de session"))?;
+    Ok(Handshake { cipher: SessionCipher::new(raw)?, agent_public })
+}
@@ -0,0 +1,71 @@
+use std::io::{self, Stdout};
+use std::ops::{Deref, DerefMut};
+use std::panic;
+
+use crossterm::cursor::Show;
+use crossterm::event::{DisableMouseCapture, EnableMouseCapture};
+use crossterm::execute;
+use crossterm::terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen, SetTitle};
+use ratatui::backend::CrosstermBackend;
+use ratatui::Terminal;
+
+// Terminal en mode brut sur l'écran alternatif, rendu dans son état normal quand
+// le garde est abandonné : sortie normale, retour anticipé sur ? ou panique
+pub struct TerminalGuard {
+    terminal: Terminal<CrosstermBackend<Stdout>>,
+}
+
+impl TerminalGuard {
+    pub fn enter(title: &str) -> io::Result<TerminalGuard> {
+        install_panic_hook();
+        enable_raw_mode()?;
+        // Un échec après le mode brut doit quand même le désactiver
+        let entered = execute!(io::stdout(), EnterAlternateScreen, EnableMouseCapture, SetTitle(title))
+            .and_then(|_| Terminal::new(CrosstermBackend::new(io::stdout())));
+        match entered {
+            Ok(terminal) => Ok(TerminalGuard { terminal }),
+            Err(e) => {
+                restore();
+                Err(e)
+            }
+        }
+    }
+}
+
+impl Deref for TerminalGuard {
+    type Target = Terminal<CrosstermBackend<Stdout>>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.terminal
+    }
+}
+
+impl DerefMut for TerminalGuard {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.terminal
+    }
+}
+
+impl Drop for TerminalGuard {
+    fn drop(&mut self) {
+        restore();
+    }
+}
+
+// Sans effet si le terminal est déjà restauré ; les erreurs sont ignorées
+// puisqu'il n'y a plus d'interface pour les afficher
+fn restore() {
+    let _ = disable_raw_mode();
+    let _ = execute!(io::stdout(), DisableMouseCapture, LeaveAlternateScreen, Show);
+}
+
+// Restaure le terminal avant le message de panique, sinon il s'affiche sur
+// l'écran alternatif et disparaît avec lui ; le journal en garde une copie
+fn install_panic_hook() {
+    let previous = panic::take_hook();
+    panic::set_hook(Box::new(move |info| {
+        tracing::error!("panique : {}", info);
+        restore();
+        previous(info);
+    }));
+}
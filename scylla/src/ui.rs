@@ -0,0 +1,1399 @@
+use std::collections::BTreeMap;
+
+use ratatui::Frame;
+use ratatui::layout::{Constraint, Direction, Layout, Position, Rect};
+use ratatui::widgets::{Block, Cell, Clear, List, ListItem, Paragraph, Row, Sparkline, Table, Wrap};
+use ratatui::text::{Span, Line};
+use ratatui::style::{Modifier, Style};
+use chrono::{DateTime, TimeDelta, Utc};
+
+use crate::agent::{Agent, AgentField};
+use crate::app::{
+    palette_matches, App, AppScreen, ContextMenu, CopyMenu, DetailView, EditDialog, GroupsPanel, ListRow, MapMode, TaskView,
+    TerminalFocus, CONTEXT_ACTIONS,
+};
+use crate::beacon::next_check_in;
+use crate::clipboard::{draw_clipboard_popup, COPY_FIELDS};
+use crate::config::LogLevel;
+use crate::editor::draw_note_editor;
+use crate::form::{draw_form, TextInput};
+use crate::keylog::draw_keylog_panel;
+use crate::loot::draw_loot_panel;
+use crate::screenshots::draw_gallery_panel;
+use crate::pivots::draw_pivots_panel;
+use crate::processes::draw_process_panel;
+use crate::selfdestruct::draw_self_destruct_confirm;
+use crate::topology::draw_topology_panel;
+use crate::keymap::{action_key, key_label, Action, Binding, GLOBAL_KEYS, KEYMAPS};
+use crate::listeners::Listener;
+use crate::logging::draw_log_viewer;
+use crate::map::{draw_map, parse_location};
+use crate::netinfo::{draw_netinfo_panel, netinfo_height};
+use crate::notes::Note;
+use crate::screens::logs::level_style;
+use crate::screens::tasks::{task_output_preview, task_status_style};
+use crate::sessions::{session_count, sessions_for};
+use crate::shell::TaskShell;
+use crate::status::{derived_status, display_status};
+use crate::theme::ColorScheme;
+use crate::timefmt::{format_age, format_clock, format_relative};
+use crate::viewer::draw_output_viewer;
+
+pub(crate) fn draw_context_menu(f: &mut Frame, menu: &mut ContextMenu, scheme: &ColorScheme) {
+    let area = menu.place(f.area());
+    let items: Vec<ListItem> = CONTEXT_ACTIONS.iter()
+        .enumerate()
+        .map(|(i, (label, _))| {
+            let style = if i == menu.selected { scheme.selection() } else { scheme.base() };
+            ListItem::new(Span::styled(*label, style))
+        })
+        .collect();
+    f.render_widget(Clear, area);
+    f.render_widget(List::new(items).block(scheme.block(menu.agent_id.as_str())), area);
+}
+
+pub(crate) fn draw_copy_menu(f: &mut Frame, menu: &CopyMenu, scheme: &ColorScheme) {
+    let width = 24.min(f.area().width);
+    let height = (COPY_FIELDS.len() as u16 + 2).min(f.area().height);
+    let area = Rect::new(
+        f.area().x + (f.area().width - width) / 2,
+        f.area().y + (f.area().height - height) / 2,
+        width,
+        height,
+    );
+    let items: Vec<ListItem> = COPY_FIELDS.iter()
+        .enumerate()
+        .map(|(i, field)| {
+            let style = if i == menu.selected { scheme.selection() } else { scheme.base() };
+            ListItem::new(Span::styled(field.label(), style))
+        })
+        .collect();
+    f.render_widget(Clear, area);
+    f.render_widget(List::new(items).block(scheme.block(format!("Copier — {}", menu.agent_id))), area);
+}
+
+// Suggestions de la palette, posées juste au-dessus du panneau terminal
+pub(crate) fn draw_palette_matches(f: &mut Frame, input: &TextInput, terminal_area: Rect, scheme: &ColorScheme) {
+    let matches = palette_matches(&input.content);
+    if matches.is_empty() {
+        return;
+    }
+    let height = (matches.len() as u16 + 2).min(terminal_area.y);
+    let area = Rect::new(terminal_area.x + 1, terminal_area.y - height, 30.min(terminal_area.width), height);
+    let items: Vec<ListItem> = matches.iter()
+        .enumerate()
+        .map(|(i, (name, _))| {
+            let style = if i == 0 { scheme.accent() } else { scheme.base() };
+            ListItem::new(Span::styled(format!(":{}", name), style))
+        })
+        .collect();
+    f.render_widget(Clear, area);
+    f.render_widget(List::new(items).block(scheme.block("Tab: compléter")), area);
+}
+
+// Couleur associée à un statut, partagée par la liste, le datasheet et la légende
+pub(crate) fn status_style(status: &str, scheme: &ColorScheme) -> Style {
+    match status.to_lowercase().as_str() {
+        "online" => scheme.online(),
+        "idle" | "stale" => scheme.warning(),
+        "dead" | "lost" | "offline" => scheme.offline(),
+        "terminating" => scheme.error(),
+        _ => scheme.dim(),
+    }
+}
+
+pub(crate) fn status_legend(scheme: &ColorScheme) -> Line<'static> {
+    // Libellés courts pour tenir dans la largeur du panneau de liste
+    let entries = [("online", "online"), ("stale", "stale"), ("dead", "dead"), ("other", "")];
+    let mut spans = Vec::new();
+    for (label, status) in entries {
+        spans.push(Span::styled("●", status_style(status, scheme)));
+        spans.push(Span::raw(format!("{} ", label)));
+    }
+    Line::from(spans)
+}
+
+// Rectangle centré occupant un pourcentage de la zone donnée
+pub fn centered_rect(percent_x: u16, percent_y: u16, area: Rect) -> Rect {
+    let vertical = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Percentage((100 - percent_y) / 2),
+            Constraint::Percentage(percent_y),
+            Constraint::Percentage((100 - percent_y) / 2),
+        ])
+        .split(area);
+    Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([
+            Constraint::Percentage((100 - percent_x) / 2),
+            Constraint::Percentage(percent_x),
+            Constraint::Percentage((100 - percent_x) / 2),
+        ])
+        .split(vertical[1])[1]
+}
+
+pub(crate) fn draw_listeners(f: &mut Frame, app: &mut App, area: Rect) {
+    let items: Vec<ListItem> = app.listeners.iter()
+        .enumerate()
+        .map(|(i, l)| {
+            let text = format!("{} | {} | {} | {}:{} | {}", l.id, l.name, l.protocol, l.bind_addr, l.port, l.status);
+            let style = listener_status_style(&l.status, &app.scheme);
+            if i == app.listener_index {
+                ListItem::new(Span::styled(text, style.patch(app.scheme.selection()).add_modifier(Modifier::BOLD)))
+            } else {
+                ListItem::new(Span::styled(text, style))
+            }
+        })
+        .collect();
+    let list = List::new(items)
+        .block(app.scheme.block("Listeners"));
+    f.render_stateful_widget(list, area, &mut app.listener_state);
+}
+
+pub(crate) fn listener_status_style(status: &str, scheme: &ColorScheme) -> Style {
+    match status {
+        "running" => scheme.online(),
+        "stopped" => scheme.dim(),
+        _ => scheme.error(),
+    }
+}
+
+pub(crate) fn listener_datasheet(listener: Option<&Listener>, scheme: &ColorScheme) -> Vec<Line<'static>> {
+    let Some(l) = listener else {
+        return vec![Line::from("Aucun listener sélectionné")];
+    };
+    vec![
+        Line::from(format!("ID: {}", l.id)),
+        Line::from(format!("Name: {}", l.name)),
+        Line::from(format!("Protocol: {}", l.protocol)),
+        Line::from(format!("Bind address: {}", l.bind_addr)),
+        Line::from(format!("Port: {}", l.port)),
+        Line::from(vec![
+            Span::raw("Status: "),
+            Span::styled(l.status.clone(), listener_status_style(&l.status, scheme)),
+        ]),
+        Line::from(format!("Started: {}", l.started_at.as_deref().unwrap_or("-"))),
+        Line::from(format!("Autostart: {}", if l.autostart { "yes" } else { "no" })),
+    ]
+}
+
+// Le panneau d'édition recouvre le datasheet
+pub(crate) fn draw_edit_dialog(f: &mut Frame, dialog: &EditDialog, area: Rect, scheme: &ColorScheme) {
+    let field = dialog.field();
+    let prompt = format!("{}: ", field.label());
+    let mut lines = vec![
+        Line::from(format!("Agent {} ({}/{})", dialog.agent_id, dialog.field_index + 1, AgentField::EDITABLE.len())),
+        Line::from(""),
+        Line::from(vec![
+            Span::styled(prompt.clone(), scheme.accent()),
+            Span::raw(dialog.input.content.clone()),
+        ]),
+        Line::from(""),
+    ];
+    match &dialog.error {
+        Some(e) => lines.push(Line::from(Span::styled(e.clone(), scheme.error()))),
+        None => lines.push(Line::from("Entrée: enregistrer et continuer, Échap: terminer")),
+    }
+
+    f.render_widget(Clear, area);
+    let paragraph = Paragraph::new(lines)
+        .block(scheme.block("Édition"));
+    f.render_widget(paragraph, area);
+
+    let col = (prompt.chars().count() + dialog.input.cursor) as u16;
+    f.set_cursor_position(Position::new(area.x + 1 + col, area.y + 3));
+}
+
+pub(crate) fn draw_confirm_delete(f: &mut Frame, ids: &[String], scheme: &ColorScheme) {
+    let area = centered_rect(40, 20, f.area());
+    f.render_widget(Clear, area);
+    let question = match ids {
+        [id] => format!("Delete agent {}? [y/N]", id),
+        _ => format!("Delete {} agents? [y/N]", ids.len()),
+    };
+    let paragraph = Paragraph::new(question)
+        .alignment(ratatui::layout::Alignment::Center)
+        .block(scheme.block("Confirmation"));
+    f.render_widget(paragraph, area);
+}
+
+pub(crate) fn draw_confirm_quit(f: &mut Frame, work: &[String], scheme: &ColorScheme) {
+    let area = centered_rect(40, 20, f.area());
+    f.render_widget(Clear, area);
+    let mut lines = vec![Line::from("Quit Scylla? [y/N]"), Line::from("")];
+    lines.extend(work.iter().map(|w| Line::from(Span::styled(w.clone(), scheme.warning()))));
+    let paragraph = Paragraph::new(lines)
+        .alignment(ratatui::layout::Alignment::Center)
+        .block(scheme.block("Confirmation"));
+    f.render_widget(paragraph, area);
+}
+
+pub(crate) fn draw_sessions(f: &mut Frame, app: &App, agent_id: &str) {
+    let area = centered_rect(70, 50, f.area());
+    f.render_widget(Clear, area);
+    let now = Utc::now();
+    let sessions = sessions_for(&app.sessions, agent_id);
+    let items: Vec<ListItem> = if sessions.is_empty() {
+        vec![ListItem::new("Aucune session active")]
+    } else {
+        sessions.iter()
+            .map(|s| {
+                ListItem::new(format!(
+                    "{} | {} | connecté {}",
+                    s.id,
+                    s.remote_addr,
+                    format_relative(Some(&s.connected_at.to_rfc3339()), now),
+                ))
+            })
+            .collect()
+    };
+    let title = format!("Sessions de {} (Échap pour fermer)", agent_id);
+    let list = List::new(items).block(app.scheme.block(title));
+    f.render_widget(list, area);
+}
+
+// File complète à gauche, commande et sortie de la tâche sélectionnée à droite
+pub(crate) fn draw_task_view(f: &mut Frame, view: &mut TaskView, scheme: &ColorScheme) {
+    let area = centered_rect(80, 60, f.area());
+    f.render_widget(Clear, area);
+    let chunks = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Percentage(50), Constraint::Percentage(50)])
+        .split(area);
+    let items: Vec<ListItem> = if view.tasks.is_empty() {
+        vec![ListItem::new("Aucune tâche")]
+    } else {
+        view.tasks.iter()
+            .map(|t| {
+                let mut spans = vec![
+                    Span::raw(format!("#{} ", t.id)),
+                    Span::styled(format!("[{}] ", t.status), task_status_style(&t.status, scheme)),
+                    Span::raw(t.command.clone()),
+                ];
+                if let Some((acked, size)) = view.uploads.get(&t.id) {
+                    spans.push(Span::styled(format!("  {}/{} octets", acked, size), scheme.dim()));
+                }
+                ListItem::new(Line::from(spans))
+            })
+            .collect()
+    };
+    let title = format!(
+        "Tâches de {} (c: nouvelle, f: download, F: fichiers, u: upload, r: reprendre, Entrée: sortie, Échap)",
+        view.agent_id,
+    );
+    let list = List::new(items)
+        .block(scheme.block(title))
+        .highlight_style(scheme.selection());
+    f.render_stateful_widget(list, chunks[0], &mut view.state);
+
+    let selected = view.state.selected().and_then(|i| view.tasks.get(i));
+    let lines: Vec<Line> = match selected {
+        Some(t) => {
+            let mut lines = vec![
+                Line::from(format!("$ {}", t.command)),
+                Line::from(Span::styled(format!("{} — {}", t.status, t.created_at), task_status_style(&t.status, scheme))),
+                Line::from(""),
+            ];
+            lines.extend(task_output_preview(t).lines().map(|l| Line::from(l.to_string())));
+            lines
+        }
+        None => Vec::new(),
+    };
+    let output = Paragraph::new(lines)
+        .wrap(ratatui::widgets::Wrap { trim: false })
+        .block(scheme.block("Sortie"));
+    f.render_widget(output, chunks[1]);
+}
+
+// Panneau du bas : erreur éventuelle, dernières commandes de l'agent, puis la ligne de saisie
+pub(crate) fn prompt_label(agent_ids: &[String], suffix: &str) -> String {
+    match agent_ids {
+        [id] => format!("{} {} ", id, suffix),
+        _ => format!("({} agents) {} ", agent_ids.len(), suffix),
+    }
+}
+
+// Mode pseudo-shell : sortie de la session puis la ligne de commande en cours
+pub(crate) fn draw_session_terminal(f: &mut Frame, app: &App, terminal: &TerminalFocus, area: Rect) {
+    let closed = !app.sessions.contains_key(&terminal.session_id);
+    let title = format!(
+        "Terminal connecté — {} ({}){}",
+        terminal.agent_id,
+        &terminal.session_id[..8],
+        if closed { " [fermée]" } else { "" },
+    );
+    let block = app.scheme.block(title).border_style(app.scheme.accent());
+    let inner = block.inner(area);
+    f.render_widget(block, area);
+    if inner.height == 0 {
+        return;
+    }
+
+    let mut lines: Vec<Line> = Vec::new();
+    if let Some(e) = &app.error {
+        lines.push(Line::from(Span::styled(e.clone(), app.scheme.error())));
+    }
+    if let Some(output) = app.session_output.get(&terminal.session_id) {
+        lines.extend(output.lines.iter().map(|l| Line::from(l.clone())));
+    }
+    let rows = inner.height as usize - 1;
+    let end = lines.len().saturating_sub(terminal.scroll);
+    let start = end.saturating_sub(rows);
+    let history = Paragraph::new(lines[start..end].to_vec());
+    f.render_widget(history, Rect::new(inner.x, inner.y, inner.width, inner.height - 1));
+
+    let y = inner.y + inner.height - 1;
+    let prompt = Paragraph::new(Line::from(vec![
+        Span::styled("$ ", app.scheme.accent()),
+        Span::raw(terminal.command.clone()),
+    ]));
+    f.render_widget(prompt, Rect::new(inner.x, y, inner.width, 1));
+    f.set_cursor_position(Position::new(inner.x + 2 + terminal.command.chars().count() as u16, y));
+}
+
+// Mode shell sur les tâches : commandes et réponses, puis la ligne en cours
+pub(crate) fn draw_shell_terminal(f: &mut Frame, app: &App, shell: &TaskShell, area: Rect) {
+    let title = match shell.pending_count() {
+        0 => format!("Shell — {}", shell.agent_id),
+        n => format!("Shell — {} ({} en attente)", shell.agent_id, n),
+    };
+    let block = app.scheme.block(title).border_style(app.scheme.accent());
+    let inner = block.inner(area);
+    f.render_widget(block, area);
+    if inner.height == 0 {
+        return;
+    }
+
+    let mut lines: Vec<Line> = Vec::new();
+    if let Some(e) = &app.error {
+        lines.push(Line::from(Span::styled(e.clone(), app.scheme.error())));
+    }
+    lines.extend(shell.scrollback.lines.iter().map(|l| Line::from(l.clone())));
+    let rows = inner.height as usize - 1;
+    let end = lines.len().saturating_sub(shell.scroll);
+    let start = end.saturating_sub(rows);
+    let history = Paragraph::new(lines[start..end].to_vec());
+    f.render_widget(history, Rect::new(inner.x, inner.y, inner.width, inner.height - 1));
+
+    let y = inner.y + inner.height - 1;
+    let label = shell.prompt();
+    let col = (label.chars().count() + shell.input.cursor) as u16;
+    let prompt = Paragraph::new(Line::from(vec![
+        Span::styled(label, app.scheme.accent()),
+        Span::raw(shell.input.content.clone()),
+    ]));
+    f.render_widget(prompt, Rect::new(inner.x, y, inner.width, 1));
+    f.set_cursor_position(Position::new(inner.x + col, y));
+}
+
+pub(crate) fn draw_terminal(f: &mut Frame, app: &mut App, area: Rect) {
+    if let Some(panel) = app.process_panel.as_mut() {
+        draw_process_panel(f, panel, area, &app.scheme);
+        return;
+    }
+    if let Some(terminal) = &app.terminal {
+        draw_session_terminal(f, app, terminal, area);
+        return;
+    }
+    if let Some(shell) = app.shell.as_ref().and_then(|id| app.shells.get(id)) {
+        draw_shell_terminal(f, app, shell, area);
+        return;
+    }
+    // Cadre en couleur d'accent tant qu'une saisie a le focus
+    let focused = app.palette.is_some() || app.command_prompt.is_some()
+        || app.tag_prompt.is_some() || app.upload_prompt.is_some();
+    let mut block = app.scheme.block("Terminal connecté");
+    if focused {
+        block = block.border_style(app.scheme.accent());
+    }
+    let inner = block.inner(area);
+    f.render_widget(block, area);
+
+    let mut lines = Vec::new();
+    if let Some(e) = &app.error {
+        lines.push(Line::from(Span::styled(e.clone(), app.scheme.error())));
+    }
+    if let Some(notice) = app.notice.as_ref().filter(|_| app.config.log_level >= LogLevel::Info) {
+        lines.push(Line::from(Span::styled(notice.clone(), app.scheme.accent())));
+    }
+    for task in &app.recent_tasks {
+        let mut spans = vec![
+            Span::raw(format!("#{} ", task.id)),
+            Span::styled(format!("[{}] ", task.status), task_status_style(&task.status, &app.scheme)),
+            Span::raw(format!("{}  {}", task.created_at, task.command)),
+        ];
+        // Première ligne de la sortie seulement, le panneau est étroit
+        if task.sealed {
+            spans.push(Span::styled("  → (chiffrée)", app.scheme.dim()));
+        } else if let Some(first) = task.output.as_deref().and_then(|o| o.lines().next()) {
+            spans.push(Span::styled(format!("  → {}", first), app.scheme.dim()));
+        }
+        lines.push(Line::from(spans));
+    }
+    // Une seule invite à la fois : palette, commande, étiquettes ou envoi
+    let upload = app.upload_prompt.as_ref().map(|prompt| {
+        let suffix = match &prompt.local_path {
+            None => "upload, fichier local (Tab: compléter) >".to_string(),
+            Some(local) => format!("upload {} vers >", local),
+        };
+        (prompt_label(std::slice::from_ref(&prompt.agent_id), &suffix), &prompt.input)
+    });
+    let prompt = if let Some(input) = &app.palette {
+        Some((":".to_string(), input))
+    } else if let Some(prompt) = &app.command_prompt {
+        Some((prompt_label(&prompt.agent_ids, ">"), &prompt.input))
+    } else if let Some((agent_ids, input)) = &app.tag_prompt {
+        Some((prompt_label(agent_ids, "tags (a, b, -c) >"), input))
+    } else {
+        upload
+    };
+    let rows = inner.height as usize - usize::from(prompt.is_some()).min(inner.height as usize);
+    let skip = lines.len().saturating_sub(rows);
+    let history = Paragraph::new(lines.into_iter().skip(skip).collect::<Vec<_>>());
+    f.render_widget(history, inner);
+
+    if let Some((label, input)) = prompt
+        && inner.height > 0
+    {
+        let y = inner.y + inner.height - 1;
+        let prompt = Paragraph::new(Line::from(vec![
+            Span::styled(label.clone(), app.scheme.accent()),
+            Span::raw(input.content.clone()),
+        ]));
+        f.render_widget(Clear, Rect::new(inner.x, y, inner.width, 1));
+        f.render_widget(prompt, Rect::new(inner.x, y, inner.width, 1));
+        let col = (label.chars().count() + input.cursor) as u16;
+        f.set_cursor_position(Position::new(inner.x + col, y));
+    }
+}
+
+// Colonnes de la liste des agents, de gauche à droite
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum AgentColumn {
+    Id,
+    Hostname,
+    Ip,
+    Os,
+    Status,
+    LastSeen,
+}
+
+const AGENT_COLUMNS: [AgentColumn; 6] =
+    [AgentColumn::Id, AgentColumn::Hostname, AgentColumn::Ip, AgentColumn::Os, AgentColumn::Status, AgentColumn::LastSeen];
+
+// Colonnes retirées dans cet ordre quand le panneau est trop étroit ; l'id et le nom
+// d'hôte restent toujours
+const DROPPED_FIRST: [AgentColumn; 4] = [AgentColumn::Os, AgentColumn::LastSeen, AgentColumn::Ip, AgentColumn::Status];
+const MIN_HOSTNAME_WIDTH: usize = 8;
+
+impl AgentColumn {
+    fn header(self) -> &'static str {
+        match self {
+            AgentColumn::Id => "ID",
+            AgentColumn::Hostname => "Hostname",
+            AgentColumn::Ip => "IP",
+            AgentColumn::Os => "OS",
+            AgentColumn::Status => "Status",
+            AgentColumn::LastSeen => "Last seen",
+        }
+    }
+
+    // Au-delà, le texte est coupé ; le nom d'hôte prend toute la place restante
+    fn max_width(self) -> usize {
+        match self {
+            // Dix caractères d'id après les marques
+            AgentColumn::Id => 16,
+            AgentColumn::Hostname => usize::MAX,
+            AgentColumn::Ip => 15,
+            AgentColumn::Os => 14,
+            AgentColumn::Status => 8,
+            AgentColumn::LastSeen => 12,
+        }
+    }
+}
+
+// Coupe au dernier caractère qui tient et marque la coupe d'un …
+pub(crate) fn ellipsize(text: &str, width: usize) -> String {
+    if text.chars().count() <= width {
+        return text.to_string();
+    }
+    if width == 0 {
+        return String::new();
+    }
+    let mut cut: String = text.chars().take(width - 1).collect();
+    cut.push('…');
+    cut
+}
+
+// Largeur de chaque colonne affichée, à la mesure de son contenu et dans sa limite.
+// Les colonnes optionnelles tombent tant que le nom d'hôte n'a pas sa largeur minimale.
+fn agent_column_widths(cells: &[Vec<String>], width: usize) -> Vec<(AgentColumn, usize)> {
+    let mut columns: Vec<(AgentColumn, usize)> = AGENT_COLUMNS.iter()
+        .enumerate()
+        .map(|(n, &column)| {
+            let content = cells.iter().map(|row| row[n].chars().count()).max().unwrap_or(0);
+            (column, content.max(column.header().len()).min(column.max_width()))
+        })
+        .collect();
+    let fixed = |columns: &[(AgentColumn, usize)]| -> usize {
+        columns.iter().filter(|(c, _)| *c != AgentColumn::Hostname).map(|(_, w)| w + 1).sum()
+    };
+    for dropped in DROPPED_FIRST {
+        if fixed(&columns) + MIN_HOSTNAME_WIDTH <= width {
+            break;
+        }
+        columns.retain(|(c, _)| *c != dropped);
+    }
+    let rest = width.saturating_sub(fixed(&columns));
+    for (column, w) in columns.iter_mut() {
+        if *column == AgentColumn::Hostname {
+            *w = rest;
+        }
+    }
+    columns
+}
+
+pub(crate) fn draw_agent_list(f: &mut Frame, app: &mut App, area: Rect, now: DateTime<Utc>) {
+    // Marques avant l'id : agent nouveau, puis case tant qu'une sélection multiple est en cours
+    let mark = |a: &Agent| {
+        let unseen = if app.unseen.contains(&a.id) { "* " } else { "" };
+        let checkbox = match (app.multi_selection.is_empty(), app.multi_selection.contains(&a.id)) {
+            (true, _) => "",
+            (false, true) => "[x] ",
+            (false, false) => "[ ] ",
+        };
+        format!("{}{}", unseen, checkbox)
+    };
+    let cells: Vec<Vec<String>> = app.rows.iter()
+        .filter_map(|row| match row {
+            ListRow::Agent(index) => Some(&app.agents[*index]),
+            ListRow::Header { .. } => None,
+        })
+        .map(|a| AGENT_COLUMNS.iter()
+            .map(|column| match column {
+                AgentColumn::Id => format!("{}{}", mark(a), a.id),
+                // Badge [!] : la dernière fiche sysinfo rapporte des droits élevés
+                AgentColumn::Hostname if a.is_elevated => format!("[!] {}", a.hostname),
+                AgentColumn::Hostname => a.hostname.clone(),
+                AgentColumn::Ip => a.ip.clone(),
+                AgentColumn::Os => a.os.clone().unwrap_or_else(|| "-".to_string()),
+                // Visibles seulement après :show-archived
+                AgentColumn::Status if a.archived => "archived".to_string(),
+                AgentColumn::Status => display_status(a, now, &app.thresholds).to_string(),
+                AgentColumn::LastSeen => format_relative(a.last_seen.as_deref(), now),
+            })
+            .collect())
+        .collect();
+
+    let list_title = if app.filter.is_empty() || app.search.is_some() {
+        "Agent list".to_string()
+    } else {
+        format!("Agent list [{}] {}/{}", app.filter, app.filtered_indices.len(), app.agents.len())
+    };
+    let list_title = match app.sort_key {
+        Some(key) => format!("{} [{} {}]", list_title, key.label(), if app.sort_descending { "▼" } else { "▲" }),
+        None => list_title,
+    };
+    let list_title = match app.group_by {
+        Some(group_by) => format!("{} [by {}]", list_title, group_by.label()),
+        None => list_title,
+    };
+    let list_title = if app.show_archived { format!("{} [+archived]", list_title) } else { list_title };
+    let list_title = if app.multi_selection.is_empty() {
+        list_title
+    } else {
+        format!("{} ({} selected)", list_title, app.multi_selection.len())
+    };
+    let list_block = app.scheme.block(list_title);
+    let mut list_area = list_block.inner(area);
+    f.render_widget(list_block, area);
+
+    // La barre de recherche occupe la première ligne du panneau quand elle est ouverte
+    if let Some(search) = &app.search {
+        let bar_chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Length(1), Constraint::Min(0)])
+            .split(list_area);
+        let bar = Paragraph::new(Line::from(vec![
+            Span::styled("/", app.scheme.accent()),
+            Span::raw(search.content.clone()),
+        ]));
+        f.render_widget(bar, bar_chunks[0]);
+        f.set_cursor_position(Position::new(bar_chunks[0].x + 1 + search.cursor as u16, bar_chunks[0].y));
+        list_area = bar_chunks[1];
+    }
+
+    // La légende des couleurs occupe la dernière ligne du panneau
+    let legend_chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Min(0), Constraint::Length(1)])
+        .split(list_area);
+    f.render_widget(Paragraph::new(status_legend(&app.scheme)), legend_chunks[1]);
+    list_area = legend_chunks[0];
+    // L'en-tête du tableau prend la première ligne : la souris et PageUp/PageDown ne
+    // comptent que les lignes en dessous
+    app.list_area = Rect {
+        y: list_area.y + 1.min(list_area.height),
+        height: list_area.height.saturating_sub(1),
+        ..list_area
+    };
+
+    // Hauteur utile de la liste, pour PageUp/PageDown
+    let list_height = app.list_area.height as usize;
+    if list_height != app.list_height {
+        app.list_height = list_height;
+        app.sync_selection();
+    }
+
+    let widths = agent_column_widths(&cells, list_area.width as usize);
+    let header = Row::new(widths.iter().map(|(column, _)| column.header()))
+        .style(app.scheme.accent().add_modifier(Modifier::BOLD));
+    let mut agent_cells = cells.into_iter();
+    let rows: Vec<Row> = app.rows.iter()
+        .enumerate()
+        .map(|(i, row)| {
+            let selected = i == app.selected_index;
+            let a = match row {
+                ListRow::Agent(index) => &app.agents[*index],
+                ListRow::Header { name, count } => {
+                    let marker = if app.is_collapsed(name) { "▶" } else { "▼" };
+                    let style = app.scheme.base().add_modifier(Modifier::BOLD);
+                    let style = if selected { style.patch(app.scheme.selection()) } else { style };
+                    let label = ellipsize(&format!("{} ({})", name, count), widths[1].1);
+                    return Row::new(vec![Cell::from(marker), Cell::from(label)]).style(style);
+                }
+            };
+            let Some(texts) = agent_cells.next() else {
+                return Row::default();
+            };
+            let status = display_status(a, now, &app.thresholds);
+            let style = status_style(status, &app.scheme);
+            let style = if app.unseen.contains(&a.id) { style.add_modifier(Modifier::BOLD) } else { style };
+            let style = if selected { style.patch(app.scheme.selection()).add_modifier(Modifier::BOLD) } else { style };
+            let row: Vec<Cell> = widths.iter()
+                .map(|&(column, width)| {
+                    let n = AGENT_COLUMNS.iter().position(|&c| c == column).unwrap_or(0);
+                    let text = ellipsize(&texts[n], width);
+                    if column != AgentColumn::Hostname {
+                        return Cell::from(text);
+                    }
+                    // Les étiquettes suivent le nom d'hôte tant qu'il reste de la place
+                    let mut spans = vec![Span::raw(text)];
+                    for tag in app.tags_of(&a.id) {
+                        spans.push(Span::raw(" "));
+                        spans.push(Span::styled(format!("#{}", tag), app.scheme.tag(tag)));
+                    }
+                    Cell::from(Line::from(spans))
+                })
+                .collect();
+            Row::new(row).style(style)
+        })
+        .collect();
+    let constraints: Vec<Constraint> = widths.iter().map(|&(_, w)| Constraint::Length(w as u16)).collect();
+    let table = Table::new(rows, constraints).header(header).column_spacing(1);
+    f.render_stateful_widget(table, list_area, &mut app.list_state);
+}
+
+pub(crate) fn agent_datasheet(app: &App, now: DateTime<Utc>) -> Vec<Line<'static>> {
+    let Some(agent) = app.selected_agent() else {
+        return vec![Line::from("Aucun agent sélectionné")];
+    };
+    let mut status_spans = vec![Span::raw("Status: ")];
+    status_spans.extend(agent_status_spans(app, agent, now));
+    let mut lines = vec![
+        Line::from(format!("ID: {}", agent.id)),
+        Line::from(format!("Hostname: {}", agent.hostname)),
+        Line::from(format!("IP: {}", agent.ip)),
+        Line::from(format!("OS: {}", agent.os.as_deref().unwrap_or("-"))),
+        Line::from(status_spans),
+        Line::from(format!(
+            "Last seen: {} ({})",
+            format_relative(agent.last_seen.as_deref(), now),
+            agent.last_seen.as_deref().unwrap_or("-"),
+        )),
+        Line::from(format!("Location: {}", agent.location.as_deref().unwrap_or("-"))),
+        Line::from(format!("User: {}", user_summary(agent))),
+        Line::from(beacon_summary(agent, now)),
+        Line::from(note_summary(app.notes_of(&agent.id))),
+        Line::from(tag_spans(app.tags_of(&agent.id), &app.scheme)),
+        Line::from(format!("Sessions: {}", session_count(&app.sessions, &agent.id))),
+        Line::from(format!("Pending tasks: {}", app.pending_tasks)),
+    ];
+    if let Some(secs) = agent.screenshot_interval_secs {
+        lines.push(Line::from(format!("Screenshots: every {}", format_age(TimeDelta::seconds(secs as i64)))));
+    }
+    if let Some(path) = &app.last_screenshot {
+        lines.push(Line::from(format!("Last screenshot: {}", path)));
+    }
+    lines
+}
+
+// Une barre par tranche, les plus récentes à droite quand la largeur manque
+fn draw_checkin_history(f: &mut Frame, app: &App, area: Rect) {
+    let [label, bars] = Layout::vertical([Constraint::Length(1), Constraint::Min(0)]).areas(area);
+    let title = format!(
+        "Check-ins (last {}, {} buckets): {}",
+        format_age(TimeDelta::seconds(app.config.checkin_window_secs)),
+        format_age(TimeDelta::seconds(app.config.checkin_bucket_secs)),
+        app.checkin_history.iter().sum::<u64>(),
+    );
+    f.render_widget(Paragraph::new(Span::styled(title, app.scheme.dim())), label);
+    let skip = app.checkin_history.len().saturating_sub(bars.width as usize);
+    let sparkline = Sparkline::default().data(&app.checkin_history[skip..]).style(app.scheme.accent());
+    f.render_widget(sparkline, bars);
+}
+
+// "bob [!] elevated, up 3d" ; "-" tant qu'aucune fiche sysinfo n'est arrivée
+fn user_summary(agent: &Agent) -> String {
+    let mut summary = agent.username.as_deref().unwrap_or("-").to_string();
+    if agent.is_elevated {
+        summary.push_str(" [!] elevated");
+    }
+    if let Some(secs) = agent.uptime_secs {
+        summary.push_str(&format!(", up {}", format_age(TimeDelta::seconds(secs.min(i64::MAX as u64) as i64))));
+    }
+    summary
+}
+
+// "Beacon: 60s ±20%, next in 23s", ou le retard une fois l'heure passée
+fn beacon_summary(agent: &Agent, now: DateTime<Utc>) -> String {
+    let every = match agent.jitter_pct {
+        0 => format!("Beacon: {}s", agent.beacon_interval_secs),
+        jitter => format!("Beacon: {}s ±{}%", agent.beacon_interval_secs, jitter),
+    };
+    match next_check_in(agent, now) {
+        Some(left) if left >= TimeDelta::zero() => format!("{}, next in {}", every, format_age(left)),
+        Some(late) => format!("{}, overdue by {}", every, format_age(late)),
+        None => every,
+    }
+}
+
+// Statut brut de la base, suivi du statut déduit de last_seen s'il diffère
+pub(crate) fn agent_status_spans(app: &App, agent: &Agent, now: DateTime<Utc>) -> Vec<Span<'static>> {
+    let mut spans = vec![Span::styled(agent.status.clone(), status_style(&agent.status, &app.scheme))];
+    if let Some(derived) = derived_status(agent, now, &app.thresholds) {
+        spans.push(Span::raw(" ("));
+        spans.push(Span::styled(derived, status_style(derived, &app.scheme)));
+        spans.push(Span::raw(format!(", last seen {})", format_relative(agent.last_seen.as_deref(), now))));
+    }
+    spans
+}
+
+const DETAIL_LABEL_WIDTH: usize = 18;
+
+// Libellé aligné puis valeur ; les lignes suivantes d'une valeur multiligne restent
+// sous la première
+fn detail_field(lines: &mut Vec<Line<'static>>, label: &str, value: &str, scheme: &ColorScheme) {
+    for (i, part) in value.split('\n').enumerate() {
+        let label = if i == 0 {
+            format!("{:<DETAIL_LABEL_WIDTH$}", format!("{}:", label))
+        } else {
+            " ".repeat(DETAIL_LABEL_WIDTH)
+        };
+        lines.push(Line::from(vec![Span::styled(label, scheme.accent()), Span::raw(part.to_string())]));
+    }
+}
+
+fn detail_section(lines: &mut Vec<Line<'static>>, title: String, scheme: &ColorScheme) {
+    lines.push(Line::from(""));
+    lines.push(Line::from(Span::styled(title, scheme.accent().add_modifier(Modifier::BOLD))));
+}
+
+pub(crate) fn detail_lines(app: &App, view: &DetailView, agent: &Agent, now: DateTime<Utc>) -> Vec<Line<'static>> {
+    let scheme = &app.scheme;
+    let mut lines = Vec::new();
+    detail_field(&mut lines, "ID", &agent.id, scheme);
+    detail_field(&mut lines, "Hostname", &agent.hostname, scheme);
+    detail_field(&mut lines, "IP", &agent.ip, scheme);
+    detail_field(&mut lines, "OS", agent.os.as_deref().unwrap_or("-"), scheme);
+    detail_field(&mut lines, "User", &user_summary(agent), scheme);
+    let mut status = vec![Span::styled(format!("{:<DETAIL_LABEL_WIDTH$}", "Status:"), scheme.accent())];
+    status.extend(agent_status_spans(app, agent, now));
+    lines.push(Line::from(status));
+    let last_seen = match &agent.last_seen {
+        Some(at) => format!("{} ({})", at, format_relative(Some(at), now)),
+        None => "-".to_string(),
+    };
+    detail_field(&mut lines, "Last seen", &last_seen, scheme);
+    detail_field(&mut lines, "Location", agent.location.as_deref().unwrap_or("-"), scheme);
+    lines.push(Line::from(tag_spans(app.tags_of(&agent.id), scheme)));
+    detail_field(&mut lines, "Sessions", &session_count(&app.sessions, &agent.id).to_string(), scheme);
+    detail_field(
+        &mut lines,
+        "Tasks",
+        &format!("{} pending, {} completed", view.pending_tasks, view.completed_tasks),
+        scheme,
+    );
+    if let Some(path) = &app.last_screenshot {
+        detail_field(&mut lines, "Last screenshot", path, scheme);
+    }
+
+    let notes = app.notes_of(&agent.id);
+    detail_section(&mut lines, format!("Notes ({})", notes.len()), scheme);
+    if notes.is_empty() {
+        lines.push(Line::from(Span::styled("Aucune note — e pour en écrire une", scheme.dim())));
+    }
+    for (i, note) in notes.iter().enumerate() {
+        if i > 0 {
+            lines.push(Line::from(""));
+        }
+        lines.push(Line::from(Span::styled(format!("── {} ──", note.updated_at), scheme.dim())));
+        lines.extend(note.content.lines().map(|l| Line::from(l.to_string())));
+    }
+
+    detail_section(&mut lines, format!("Recent events ({})", view.events.len()), scheme);
+    if view.events.is_empty() {
+        lines.push(Line::from(Span::styled("Aucun événement", scheme.dim())));
+    }
+    for e in &view.events {
+        lines.push(Line::from(vec![
+            Span::styled(format!("{} ", e.timestamp), scheme.dim()),
+            Span::styled(format!("{:<5} ", e.level.as_str()), level_style(e.level, scheme)),
+            Span::styled(format!("{} ", e.category), scheme.accent()),
+            Span::styled(e.message.clone(), level_style(e.level, scheme)),
+        ]));
+    }
+    lines
+}
+
+// Toute la surface du terminal ; le texte est replié à la largeur, mots trop longs compris
+pub(crate) fn draw_detail_view(f: &mut Frame, app: &mut App, now: DateTime<Utc>) {
+    let Some(view) = app.detail_view.as_ref() else {
+        return;
+    };
+    let Some(agent) = app.agents.iter().find(|a| a.id == view.agent_id) else {
+        return;
+    };
+    let lines = detail_lines(app, view, agent, now);
+    let title = format!("Agent {} — j/k, PgUp/PgDn, e: note, Échap", agent.id);
+    let area = f.area();
+    let block = app.scheme.block(title);
+    let inner = block.inner(area);
+    let paragraph = Paragraph::new(lines).wrap(Wrap { trim: false });
+    let content_height = paragraph.line_count(inner.width).min(u16::MAX as usize) as u16;
+    let Some(view) = app.detail_view.as_mut() else {
+        return;
+    };
+    view.height = inner.height;
+    view.content_height = content_height;
+    view.scroll = view.scroll.min(view.max_scroll());
+    f.render_widget(Clear, area);
+    f.render_widget(block, area);
+    f.render_widget(paragraph.scroll((view.scroll, 0)), inner);
+}
+
+// Première ligne de la note la plus récente, suivie du nombre d'autres notes
+pub(crate) fn note_summary(notes: &[Note]) -> String {
+    let Some(latest) = notes.first() else {
+        return "Note: -".to_string();
+    };
+    let first_line = latest.content.lines().next().unwrap_or("");
+    let mut summary = format!("Note: {} ({})", first_line, latest.updated_at);
+    if notes.len() > 1 {
+        summary.push_str(&format!(" ({} more)", notes.len() - 1));
+    }
+    summary
+}
+
+pub(crate) fn tag_spans(tags: &[String], scheme: &ColorScheme) -> Vec<Span<'static>> {
+    let mut spans = vec![Span::raw("Tags:")];
+    if tags.is_empty() {
+        spans.push(Span::raw(" -"));
+    }
+    for tag in tags {
+        spans.push(Span::raw(" "));
+        spans.push(Span::styled(format!("#{}", tag), scheme.tag(tag)));
+    }
+    spans
+}
+
+pub fn draw(f: &mut Frame, app: &mut App) {
+    let size = f.area();
+    f.render_widget(Block::default().style(app.scheme.base()), size);
+    if size.width < app.config.min_width || size.height < app.config.min_height {
+        draw_too_small(f, app);
+        return;
+    }
+
+    let vertical_chunks = ratatui::layout::Layout::default()
+        .direction(ratatui::layout::Direction::Vertical)
+        .margin(1)
+        .constraints([
+            ratatui::layout::Constraint::Length(4),
+            ratatui::layout::Constraint::Min(10),
+            // Agrandi pour le tableau des processus
+            ratatui::layout::Constraint::Length(if app.process_panel.is_some() { 14 } else { 7 }),
+            ratatui::layout::Constraint::Length(1),
+        ])
+        .split(size);
+
+    let top_chunks = ratatui::layout::Layout::default()
+        .direction(ratatui::layout::Direction::Horizontal)
+        .constraints([
+            ratatui::layout::Constraint::Length(20),
+            ratatui::layout::Constraint::Min(10),
+        ])
+        .split(vertical_chunks[0]);
+
+    let middle_chunks = ratatui::layout::Layout::default()
+        .direction(ratatui::layout::Direction::Horizontal)
+        .constraints([
+            // Assez large pour les colonnes du tableau, la place restante au datasheet
+            ratatui::layout::Constraint::Percentage(45),
+            ratatui::layout::Constraint::Min(10),
+        ])
+        .split(vertical_chunks[1]);
+
+    let now = Utc::now();
+    let logo_block = app.scheme.block("Logo RAT");
+    f.render_widget(logo_block, top_chunks[0]);
+
+    // Barre d'onglets : l'écran actif est surligné, le chiffre y mène directement
+    let menu_spans: Vec<Span> = AppScreen::ALL.iter()
+        .enumerate()
+        .map(|(i, &screen)| {
+            let label = format!("{}:{}", i + 1, screen.label());
+            if app.active_screen == screen {
+                Span::styled(format!(" [{}] ", label), app.scheme.accent().add_modifier(Modifier::BOLD))
+            } else {
+                Span::raw(format!("  {}  ", label))
+            }
+        })
+        .collect();
+    let menu = Paragraph::new(vec![Line::from(menu_spans), Line::from(agent_summary(app, now))])
+    .block(app.scheme.block(format!("Menu — {} @ {}", app.operator, app.db.path())));
+    f.render_widget(menu, top_chunks[1]);
+
+    match app.active_screen {
+        AppScreen::Agents => draw_agent_list(f, app, middle_chunks[0], now),
+        AppScreen::Listeners => draw_listeners(f, app, middle_chunks[0]),
+        screen => {
+            if let Some(screen) = app.screens.get_mut(screen) {
+                screen.draw(f, middle_chunks[0], &app.scheme);
+            }
+        }
+    }
+
+    let mode = if app.active_screen == AppScreen::Agents { app.map_mode } else { MapMode::Datasheet };
+    let [datasheet_area, map_area] = match mode {
+        MapMode::Datasheet => [middle_chunks[1], Rect::default()],
+        MapMode::Split => Layout::horizontal([Constraint::Percentage(50), Constraint::Percentage(50)]).areas(middle_chunks[1]),
+        MapMode::Map => [Rect::default(), middle_chunks[1]],
+    };
+    if mode != MapMode::Datasheet {
+        draw_agent_map(f, app, map_area, now, mode == MapMode::Split);
+    }
+    if mode != MapMode::Map {
+        let datasheet_text = match app.active_screen {
+            AppScreen::Agents => agent_datasheet(app, now),
+            AppScreen::Listeners => listener_datasheet(app.listeners.get(app.listener_index), &app.scheme),
+            screen => app.screens.get(screen).map(|s| s.datasheet(&app.scheme)).unwrap_or_default(),
+        };
+
+        // Défilement à la molette, borné pour que la dernière ligne reste visible
+        app.datasheet_area = datasheet_area;
+        app.datasheet_scroll = app.datasheet_scroll.min(datasheet_text.len().saturating_sub(1) as u16);
+        let block = app.scheme.block("Datasheet / Map");
+        let inner = block.inner(datasheet_area);
+        f.render_widget(block, datasheet_area);
+        // Historique des check-ins en bas du datasheet, s'il reste de la place au texte
+        let mut text_area = inner;
+        if app.active_screen == AppScreen::Agents && app.selected_agent().is_some() && inner.height >= 8 {
+            let [text, history] = Layout::vertical([Constraint::Min(0), Constraint::Length(3)]).areas(inner);
+            draw_checkin_history(f, app, history);
+            text_area = text;
+        }
+        // Interfaces réseau au-dessus, sur la moitié du texte au plus
+        if app.active_screen == AppScreen::Agents && app.show_interfaces && app.selected_agent().is_some() {
+            let height = netinfo_height(&app.interfaces).min(text_area.height / 2);
+            let [text, interfaces] = Layout::vertical([Constraint::Min(0), Constraint::Length(height)]).areas(text_area);
+            draw_netinfo_panel(f, &app.interfaces, interfaces, &app.scheme);
+            text_area = text;
+        }
+        let datasheet = Paragraph::new(datasheet_text).scroll((app.datasheet_scroll, 0));
+        f.render_widget(datasheet, text_area);
+    }
+
+    draw_terminal(f, app, vertical_chunks[2]);
+    render_status_bar(f, vertical_chunks[3], app);
+    if let Some(input) = &app.palette {
+        draw_palette_matches(f, input, vertical_chunks[2], &app.scheme);
+    }
+
+    draw_detail_view(f, app, now);
+    if let Some(dialog) = &app.edit_dialog {
+        draw_edit_dialog(f, dialog, middle_chunks[1], &app.scheme);
+    }
+    if let Some(editor) = app.note_editor.as_mut() {
+        draw_note_editor(f, editor, vertical_chunks[1], &app.scheme);
+    }
+    let beacon = app.beacon_dialog.as_ref().map(|(_, form)| form);
+    if let Some(form) = app.add_dialog.as_ref().or(app.listener_dialog.as_ref()).or(app.upload_dialog.as_ref()).or(beacon) {
+        draw_form(f, form, centered_rect(60, 40, f.area()), &app.scheme);
+    }
+    if let Some(ids) = &app.confirm_delete {
+        draw_confirm_delete(f, ids, &app.scheme);
+    }
+    if let Some(confirm) = &app.confirm_self_destruct {
+        draw_self_destruct_confirm(f, confirm, &app.scheme);
+    }
+    if let Some(work) = &app.confirm_quit {
+        draw_confirm_quit(f, work, &app.scheme);
+    }
+    if let Some(agent_id) = &app.session_view {
+        draw_sessions(f, app, agent_id);
+    }
+    if let Some(view) = app.task_view.as_mut() {
+        match view.viewer.as_mut() {
+            Some(viewer) => draw_output_viewer(f, viewer, f.area(), &app.scheme),
+            None => draw_task_view(f, view, &app.scheme),
+        }
+    }
+    if let Some(popup) = &app.clipboard_popup {
+        draw_clipboard_popup(f, popup, &app.scheme);
+    }
+    if let Some(panel) = app.gallery_panel.as_mut() {
+        draw_gallery_panel(f, panel, centered_rect(70, 60, f.area()), &app.scheme);
+    }
+    if let Some(panel) = app.loot_panel.as_mut() {
+        draw_loot_panel(f, panel, centered_rect(85, 60, f.area()), &app.scheme);
+    }
+    if let Some(panel) = app.keylog_panel.as_mut() {
+        draw_keylog_panel(f, panel, centered_rect(80, 70, f.area()), &app.scheme);
+    }
+    if let Some(panel) = &app.pivots_panel {
+        draw_pivots_panel(f, panel, centered_rect(80, 70, f.area()), &app.scheme);
+    }
+    if let Some(panel) = &app.topology_panel {
+        draw_topology_panel(f, panel, centered_rect(80, 70, f.area()), &app.scheme);
+    }
+    if let Some(viewer) = app.log_viewer.as_mut() {
+        draw_log_viewer(f, viewer, centered_rect(90, 80, f.area()), &app.scheme);
+    }
+    let scheme = app.scheme;
+    if let Some(screen) = app.screens.get_mut(app.active_screen) {
+        screen.draw_overlay(f, &scheme);
+    }
+    if let Some(menu) = app.context_menu.as_mut() {
+        draw_context_menu(f, menu, &scheme);
+    }
+    if let Some(menu) = &app.copy_menu {
+        draw_copy_menu(f, menu, &scheme);
+    }
+    if let Some(scroll) = app.help_scroll.as_mut() {
+        draw_help(f, scroll, &app.scheme);
+    }
+    if let Some(panel) = &app.groups_panel {
+        draw_groups_panel(f, panel, &app.scheme);
+    }
+    if let Some(errors) = &app.import_errors {
+        draw_import_errors(f, errors, &app.scheme);
+    }
+}
+
+// Agents filtrés dont la location se lit comme une position ; le sélectionné est tracé
+// en dernier pour rester visible quand plusieurs agents partagent une case. Avec
+// selected_only, seul l'agent sélectionné est placé, ou tous si c'est un en-tête de groupe.
+pub(crate) fn draw_agent_map(f: &mut Frame, app: &App, area: Rect, now: DateTime<Utc>, selected_only: bool) {
+    let selected = app.selected_agent().map(|a| a.id.as_str());
+    let mut points = Vec::new();
+    let mut unplaced = 0;
+    let others = app.filtered_indices.iter()
+        .map(|&i| &app.agents[i])
+        .filter(|a| Some(a.id.as_str()) != selected && !(selected_only && selected.is_some()));
+    for agent in others.chain(app.selected_agent()) {
+        match agent.location.as_deref().and_then(parse_location) {
+            Some(position) => {
+                let mut style = status_style(display_status(agent, now, &app.thresholds), &app.scheme);
+                if Some(agent.id.as_str()) == selected {
+                    style = style.add_modifier(Modifier::REVERSED);
+                }
+                points.push((position, style));
+            }
+            None => unplaced += 1,
+        }
+    }
+    let title = match unplaced {
+        0 => "Map".to_string(),
+        n => format!("Map — {} sans position", n),
+    };
+    draw_map(f, area, &points, title, &app.scheme);
+}
+
+pub(crate) fn draw_groups_panel(f: &mut Frame, panel: &GroupsPanel, scheme: &ColorScheme) {
+    let area = centered_rect(70, 60, f.area());
+    f.render_widget(Clear, area);
+    let title = format!("Groupes — cible : {} agent(s)", panel.targets.len());
+    let block = scheme.block(title);
+    let inner = block.inner(area);
+    f.render_widget(block, area);
+    let [body, footer] = Layout::vertical([Constraint::Min(1), Constraint::Length(1)]).areas(inner);
+    let [list_area, members_area] = Layout::horizontal([Constraint::Percentage(60), Constraint::Percentage(40)]).areas(body);
+
+    let items: Vec<ListItem> = if panel.groups.is_empty() {
+        vec![ListItem::new(Span::styled("Aucun groupe : + pour en créer un", scheme.dim()))]
+    } else {
+        panel.groups.iter().enumerate().map(|(i, group)| {
+            let mut spans = vec![
+                Span::styled("■ ", scheme.swatch(group.color.as_deref())),
+                Span::raw(format!("{} ({})", group.name, group.members)),
+            ];
+            if let Some(description) = &group.description {
+                spans.push(Span::styled(format!(" — {}", description), scheme.dim()));
+            }
+            let line = Line::from(spans);
+            let line = if i == panel.index { line.style(scheme.selection()) } else { line };
+            ListItem::new(line)
+        }).collect()
+    };
+    f.render_widget(List::new(items), list_area);
+
+    let members: Vec<ListItem> = panel.members.iter().map(|id| ListItem::new(id.as_str())).collect();
+    f.render_widget(List::new(members).block(Block::default().borders(ratatui::widgets::Borders::LEFT).title("Membres")), members_area);
+
+    let footer_line = match &panel.input {
+        Some(input) => Line::from(format!("nom [description] > {}", input.content)),
+        None => Line::from(Span::styled(
+            "+ nouveau  a ajouter la cible  x retirer la cible  Entrée filtrer  Échap fermer",
+            scheme.dim(),
+        )),
+    };
+    f.render_widget(Paragraph::new(footer_line), footer);
+}
+
+pub(crate) fn draw_import_errors(f: &mut Frame, errors: &[String], scheme: &ColorScheme) {
+    let area = centered_rect(70, 60, f.area());
+    f.render_widget(Clear, area);
+    let lines: Vec<Line> = errors.iter()
+        .map(|e| Line::from(Span::styled(e.as_str(), scheme.error())))
+        .collect();
+    let title = format!("Import : {} ligne(s) rejetée(s) — une touche pour fermer", errors.len());
+    let paragraph = Paragraph::new(lines)
+        .wrap(ratatui::widgets::Wrap { trim: false })
+        .block(scheme.block(title));
+    f.render_widget(paragraph, area);
+}
+
+// Lignes d'une section de l'aide ; deux touches voisines pour la même action partagent une ligne
+pub(crate) fn help_lines<'a>(title: &'a str, bindings: &'a [Binding], scheme: &ColorScheme) -> Vec<Line<'a>> {
+    let mut lines = vec![Line::from(Span::styled(title, scheme.accent().add_modifier(Modifier::BOLD)))];
+    let mut rows: Vec<(String, &Binding)> = Vec::new();
+    for binding in bindings {
+        let label = key_label(binding);
+        match rows.last_mut() {
+            // Une touche redéfinie dans [keys] remplace toutes celles de l'action
+            Some((keys, last)) if last.action == binding.action && keys.split(", ").any(|k| k == label) => {}
+            Some((keys, last)) if last.action == binding.action => {
+                keys.push_str(", ");
+                keys.push_str(&label);
+            }
+            _ => rows.push((label, binding)),
+        }
+    }
+    for (keys, binding) in rows {
+        lines.push(Line::from(vec![
+            Span::styled(format!("  {:<12}", keys), scheme.accent()),
+            Span::raw(binding.description),
+        ]));
+    }
+    lines.push(Line::from(""));
+    lines
+}
+
+// Aide générée depuis les tables de keymap, une section par écran ; le défilement est
+// borné ici, la hauteur visible n'étant connue qu'au dessin
+pub(crate) fn draw_help(f: &mut Frame, scroll: &mut u16, scheme: &ColorScheme) {
+    let area = centered_rect(80, 90, f.area());
+    f.render_widget(Clear, area);
+    let lines: Vec<Line> = KEYMAPS.iter()
+        .flat_map(|(title, bindings)| help_lines(title, bindings, scheme))
+        .collect();
+    let height = area.height.saturating_sub(2) as usize;
+    let max_scroll = lines.len().saturating_sub(height) as u16;
+    *scroll = (*scroll).min(max_scroll);
+    let title = if max_scroll > 0 {
+        format!("Aide — j/k pour défiler ({}/{}), Échap, ? ou q pour fermer", *scroll, max_scroll)
+    } else {
+        "Aide — Échap, ? ou q pour fermer".to_string()
+    };
+    let help = Paragraph::new(lines)
+        .scroll((*scroll, 0))
+        .block(scheme.block(title));
+    f.render_widget(help, area);
+}
+
+// Raccourcis rappelés dans la barre d'état, lus dans la table des touches pour suivre
+// la section [keys] ; seules les actions de l'écran actif et les globales apparaissent
+pub(crate) const SCREEN_HINTS: &[(Action, &str)] = &[
+    (Action::Search, "filtrer"),
+    (Action::Command, "tâche"),
+    (Action::Mark, "marquer"),
+    (Action::NewListener, "nouveau"),
+    (Action::Open, "ouvrir"),
+    (Action::Upload, "envoyer"),
+    (Action::ToggleScheduledTask, "activer/suspendre"),
+    (Action::OpenPalette, "palette"),
+    (Action::Help, "aide"),
+    (Action::Quit, "quitter"),
+];
+
+// Raccourcis du mode de saisie en cours, sinon ceux de l'écran actif, du plus utile au
+// moins utile pour que la barre d'état puisse tronquer par la fin
+pub(crate) fn key_hints(state: &App) -> Vec<String> {
+    let fixed = if state.note_editor.is_some() {
+        Some("Ctrl-S enregistrer  Échap annuler")
+    } else if state.palette.is_some() {
+        Some("Tab compléter  Entrée exécuter  Échap fermer")
+    } else if state.search.is_some() {
+        Some("Entrée appliquer  Échap annuler")
+    } else if state.terminal.is_some() {
+        Some("Entrée envoyer  Échap quitter le terminal")
+    } else if state.shell.is_some() {
+        Some("Entrée envoyer  PgUp/PgDn défiler  Échap quitter le shell")
+    } else if state.process_panel.is_some() {
+        Some("s trier  I inverser  r relancer ps  Échap fermer")
+    } else if state.modal_open() {
+        Some("Entrée valider  Échap fermer")
+    } else {
+        None
+    };
+    if let Some(fixed) = fixed {
+        return fixed.split("  ").map(str::to_string).collect();
+    }
+    let tables = [state.active_screen.bindings(), GLOBAL_KEYS];
+    let mut hints = Vec::new();
+    if let (Some(up), Some(down)) = (action_key(&tables, Action::Up), action_key(&tables, Action::Down)) {
+        hints.push(format!("{}{} naviguer", up, down));
+    }
+    hints.extend(SCREEN_HINTS.iter().filter_map(|&(action, label)| {
+        Some(format!("{} {}", action_key(&tables, action)?, label))
+    }));
+    hints
+}
+
+// Autant de raccourcis que la largeur en laisse, séparés du reste de la barre
+pub(crate) fn fit_hints(hints: Vec<String>, width: u16) -> String {
+    let mut line = String::new();
+    for hint in hints {
+        let candidate = if line.is_empty() { hint } else { format!("{}  {}", line, hint) };
+        if candidate.chars().count() + 2 > width as usize {
+            break;
+        }
+        line = candidate;
+    }
+    line
+}
+
+pub(crate) fn render_status_bar(f: &mut Frame, rect: Rect, state: &App) {
+    let now = Utc::now();
+    let online = state.agents.iter()
+        .filter(|a| display_status(a, now, &state.thresholds) == "online")
+        .count();
+    let mut spans = vec![
+        Span::styled(format!("● {} online", online), state.scheme.online()),
+        Span::raw("  "),
+        Span::styled(format!("● {} offline", state.agents.len() - online), state.scheme.error()),
+        Span::raw(format!("  │ {}  │ {}", format_clock(now, state.config.ui.clock), state.operator)),
+    ];
+    if let Some(key) = state.sort_key {
+        let direction = if state.sort_descending { "▼" } else { "▲" };
+        spans.push(Span::raw(format!("  │ tri {} {}", key.label(), direction)));
+    }
+    if !state.filter.is_empty() {
+        spans.push(Span::raw(format!("  │ filtre {}", state.filter)));
+    }
+    // Rappel hors de l'écran Agents : c, T et d visent alors toute la sélection
+    if !state.multi_selection.is_empty() {
+        spans.push(Span::styled(format!("  │ {} sélectionné(s)", state.multi_selection.len()), state.scheme.accent()));
+    }
+    let status = Line::from(spans);
+    let [left, right] = Layout::horizontal([Constraint::Length(status.width() as u16), Constraint::Min(0)]).areas(rect);
+    f.render_widget(Paragraph::new(status).style(state.scheme.dim()), left);
+    // Le message en tête de file masque les raccourcis le temps de son affichage
+    let hint = match state.status_messages.front() {
+        Some(message) => {
+            let style = if message.error { state.scheme.error() } else { state.scheme.base() };
+            Span::styled(message.text.clone(), style)
+        }
+        None => Span::styled(fit_hints(key_hints(state), right.width), state.scheme.dim()),
+    };
+    let hint = Paragraph::new(Line::from(hint)).alignment(ratatui::layout::Alignment::Right);
+    f.render_widget(hint, right);
+}
+
+// Ordre des statuts dans le résumé du menu ; les autres suivent par ordre alphabétique
+const SUMMARY_STATUSES: &[&str] = &["online", "idle", "stale", "dead", "offline"];
+
+// Seconde ligne du menu : agents par statut affiché (staleness comprise), écoute, file
+// de tâches, et part de la liste visible quand un filtre est actif
+pub(crate) fn agent_summary(app: &App, now: DateTime<Utc>) -> Vec<Span<'static>> {
+    let mut counts: BTreeMap<String, usize> = BTreeMap::new();
+    for agent in &app.agents {
+        *counts.entry(display_status(agent, now, &app.thresholds).to_lowercase()).or_default() += 1;
+    }
+    let mut statuses: Vec<(String, usize)> = SUMMARY_STATUSES.iter()
+        .filter_map(|&s| counts.remove(s).map(|count| (s.to_string(), count)))
+        .collect();
+    statuses.extend(counts);
+
+    let mut spans = vec![Span::raw(format!(" {} agents", app.agents.len()))];
+    for (i, (status, count)) in statuses.into_iter().enumerate() {
+        spans.push(Span::raw(if i == 0 { " : " } else { " · " }));
+        let style = status_style(&status, &app.scheme);
+        spans.push(Span::styled(format!("{} {}", count, status), style));
+    }
+    spans.push(Span::raw(" │ "));
+    spans.extend(listener_summary(app));
+    spans.push(Span::raw(format!(" │ {} tâche(s) en attente", app.queued_tasks)));
+    if !app.active_query().is_empty() {
+        let shown = format!(" │ affichés {}/{}", app.filtered_indices.len(), app.agents.len());
+        spans.push(Span::styled(shown, app.scheme.accent()));
+    }
+    spans
+}
+
+// État d'écoute pour le menu : adresses des listeners démarrés et check-ins reçus
+pub(crate) fn listener_summary(app: &App) -> Vec<Span<'static>> {
+    let running: Vec<String> = app.listeners.iter()
+        .filter(|l| l.status == "running")
+        .map(|l| format!("{} {}:{}", l.protocol, l.bind_addr, l.port))
+        .collect();
+    let state = if running.is_empty() {
+        Span::styled("aucun listener", app.scheme.dim())
+    } else {
+        Span::styled(format!("écoute sur {}", running.join(", ")), app.scheme.online())
+    };
+    vec![state, Span::raw(format!(" · {} check-ins", app.checkins))]
+}
+
+// Remplace l'interface tant que le terminal est trop petit pour la disposition
+pub(crate) fn draw_too_small(f: &mut Frame, app: &App) {
+    let area = f.area();
+    let message = format!(
+        "Terminal trop petit (minimum {}×{}, actuel {}×{})",
+        app.config.min_width, app.config.min_height, area.width, area.height,
+    );
+    // Un seul message centré, replié si la largeur ne suffit pas
+    let paragraph = Paragraph::new(Span::styled(message, app.scheme.error()))
+        .alignment(ratatui::layout::Alignment::Center)
+        .wrap(ratatui::widgets::Wrap { trim: true });
+    let height = (paragraph.line_count(area.width) as u16).min(area.height);
+    let top = area.height.saturating_sub(height) / 2;
+    f.render_widget(paragraph, Rect::new(area.x, area.y + top, area.width, area.height - top));
+}
+
+// Écran affiché à la place de la liste quand la base ne peut pas être ouverte
+pub fn draw_startup_error(f: &mut Frame, path: &str, message: &str, scheme: &ColorScheme) {
+    let area = centered_rect(70, 30, f.area());
+    let lines = vec![
+        Line::from(format!("Base : {}", path)),
+        Line::from(Span::styled(message.to_string(), scheme.error())),
+        Line::from(""),
+        Line::from("q, Échap ou Entrée pour quitter"),
+    ];
+    let paragraph = Paragraph::new(lines)
+        .wrap(ratatui::widgets::Wrap { trim: false })
+        .block(scheme.block("Erreur au démarrage"));
+    f.render_widget(paragraph, area);
+}
@@ -0,0 +1,219 @@
+use crossterm::event::{KeyCode, KeyEvent};
+use ratatui::layout::Rect;
+use ratatui::text::Line;
+use ratatui::widgets::{Clear, Paragraph, Wrap};
+use ratatui::Frame;
+
+use crate::db::{Db, Task};
+use crate::error::Result;
+use crate::theme::ColorScheme;
+
+// Taille des tranches lues dans tasks.output
+const CHUNK_BYTES: i64 = 64 * 1024;
+pub(crate) const HEX_ROW: usize = 16;
+
+// Sortie complète d'une tâche en plein écran. Les tranches sont lues à mesure que
+// l'opérateur descend, une sortie de plusieurs Mo n'est jamais chargée d'un coup ; une
+// sortie chiffrée, elle, l'est forcément, déchiffrée une fois à l'ouverture.
+pub struct OutputViewer {
+    pub task_id: i64,
+    command: String,
+    // Sortie chiffrée déchiffrée, où les tranches sont prises au lieu de la base
+    sealed: Option<Vec<u8>>,
+    total: i64,
+    offset: i64,
+    // Fin de la dernière tranche, en attente d'un retour à la ligne ou d'une ligne hex complète
+    pending: Vec<u8>,
+    // Sortie non UTF-8 ou contenant des octets nuls, affichée en hex/ASCII
+    binary: bool,
+    lines: Vec<String>,
+    scroll: usize,
+    hscroll: u16,
+    wrap: bool,
+    // Zone utile relevée au dernier affichage, pour PageUp/PageDown et le renvoi à la ligne
+    height: usize,
+    width: usize,
+}
+
+pub(crate) fn looks_binary(bytes: &[u8]) -> bool {
+    if bytes.contains(&0) {
+        return true;
+    }
+    // Une tranche peut couper un caractère multi-octets : seule une séquence invalide compte
+    match std::str::from_utf8(bytes) {
+        Ok(_) => false,
+        Err(e) => e.error_len().is_some(),
+    }
+}
+
+pub(crate) fn hex_line(offset: usize, row: &[u8]) -> String {
+    let hex: Vec<String> = row.iter().map(|b| format!("{:02x}", b)).collect();
+    let ascii: String = row.iter()
+        .map(|&b| if b.is_ascii_graphic() || b == b' ' { b as char } else { '.' })
+        .collect();
+    format!("{:08x}  {:<47}  |{}|", offset, hex.join(" "), ascii)
+}
+
+impl OutputViewer {
+    pub fn open(db: &Db, task: &Task) -> Result<OutputViewer> {
+        let sealed = db.sealed_output(task.id)?;
+        let total = match &sealed {
+            Some(output) => output.len() as i64,
+            None => db.task_output_len(task.id)?,
+        };
+        let mut viewer = OutputViewer {
+            task_id: task.id,
+            command: task.command.clone(),
+            sealed,
+            total,
+            offset: 0,
+            pending: Vec::new(),
+            binary: false,
+            lines: Vec::new(),
+            scroll: 0,
+            hscroll: 0,
+            wrap: true,
+            height: 0,
+            width: 0,
+        };
+        viewer.load_chunk(db)?;
+        Ok(viewer)
+    }
+
+    fn complete(&self) -> bool {
+        self.offset >= self.total
+    }
+
+    fn load_chunk(&mut self, db: &Db) -> Result<()> {
+        if self.complete() {
+            return Ok(());
+        }
+        let chunk = match &self.sealed {
+            Some(output) => {
+                let start = (self.offset as usize).min(output.len());
+                output[start..(start + CHUNK_BYTES as usize).min(output.len())].to_vec()
+            }
+            None => db.task_output_chunk(self.task_id, self.offset, CHUNK_BYTES)?,
+        };
+        if chunk.is_empty() {
+            // La sortie a raccourci depuis l'ouverture
+            self.total = self.offset;
+        }
+        if self.offset == 0 {
+            self.binary = looks_binary(&chunk);
+        }
+        self.offset += chunk.len() as i64;
+        self.pending.extend(chunk);
+        if self.binary {
+            let keep = if self.complete() { 0 } else { self.pending.len() % HEX_ROW };
+            let start = self.lines.len() * HEX_ROW;
+            let full = self.pending.len() - keep;
+            for (i, row) in self.pending[..full].chunks(HEX_ROW).enumerate() {
+                self.lines.push(hex_line(start + i * HEX_ROW, row));
+            }
+            self.pending.drain(..full);
+        } else {
+            while let Some(end) = self.pending.iter().position(|&b| b == b'\n') {
+                let line: Vec<u8> = self.pending.drain(..=end).collect();
+                let line = String::from_utf8_lossy(&line);
+                self.lines.push(line.trim_end_matches(['\n', '\r']).to_string());
+            }
+            if self.complete() && !self.pending.is_empty() {
+                self.lines.push(String::from_utf8_lossy(&self.pending).into_owned());
+                self.pending.clear();
+            }
+        }
+        Ok(())
+    }
+
+    // Lit des tranches jusqu'à disposer de `rows` lignes ou de toute la sortie
+    fn ensure_lines(&mut self, db: &Db, rows: usize) -> Result<()> {
+        while self.lines.len() < rows && !self.complete() {
+            self.load_chunk(db)?;
+        }
+        Ok(())
+    }
+
+    // Première ligne affichable telle que la fin de la sortie remplisse l'écran,
+    // en comptant les lignes repliées quand le renvoi est actif
+    fn max_scroll(&self) -> usize {
+        let height = self.height.max(1);
+        if !self.wrap || self.binary || self.width == 0 {
+            return self.lines.len().saturating_sub(height);
+        }
+        let mut rows = 0;
+        for (i, line) in self.lines.iter().enumerate().rev() {
+            let wrapped = Paragraph::new(line.as_str()).wrap(Wrap { trim: false });
+            rows += wrapped.line_count(self.width as u16).max(1);
+            if rows > height {
+                return i + 1;
+            }
+        }
+        0
+    }
+
+    // Renvoie false quand l'opérateur referme la visionneuse
+    pub fn handle_key(&mut self, db: &Db, key: KeyEvent) -> Result<bool> {
+        let page = self.height.max(1);
+        let target = match key.code {
+            KeyCode::Esc | KeyCode::Char('q') => return Ok(false),
+            KeyCode::Char('w') => {
+                self.wrap = !self.wrap;
+                self.hscroll = 0;
+                return Ok(true);
+            }
+            KeyCode::Left if !self.wrap => {
+                self.hscroll = self.hscroll.saturating_sub(8);
+                return Ok(true);
+            }
+            KeyCode::Right if !self.wrap => {
+                self.hscroll = self.hscroll.saturating_add(8);
+                return Ok(true);
+            }
+            KeyCode::Down => self.scroll + 1,
+            KeyCode::Up => self.scroll.saturating_sub(1),
+            KeyCode::PageDown => self.scroll + page,
+            KeyCode::PageUp => self.scroll.saturating_sub(page),
+            KeyCode::Char('g') | KeyCode::Home => 0,
+            // Aller à la fin oblige à tout lire
+            KeyCode::Char('G') | KeyCode::End => {
+                self.ensure_lines(db, usize::MAX)?;
+                usize::MAX
+            }
+            _ => return Ok(true),
+        };
+        // Une page d'avance pour que la descente ne bute pas sur la fin d'une tranche
+        self.ensure_lines(db, target.saturating_add(2 * page))?;
+        self.scroll = target.min(self.max_scroll());
+        Ok(true)
+    }
+}
+
+pub fn draw_output_viewer(f: &mut Frame, viewer: &mut OutputViewer, area: Rect, scheme: &ColorScheme) {
+    f.render_widget(Clear, area);
+    let loaded = if viewer.complete() { String::new() } else { format!(", {} Ko lus", viewer.offset / 1024) };
+    let mode = match (viewer.binary, viewer.wrap) {
+        (true, _) => "hex",
+        (false, true) => "wrap",
+        (false, false) => "défilement",
+    };
+    let title = format!(
+        "Sortie de #{} — {} [{}] ({} lignes{}) — g/G, PgUp/PgDn, w, Échap",
+        viewer.task_id, viewer.command, mode, viewer.lines.len(), loaded,
+    );
+    let block = scheme.block(title);
+    let inner = block.inner(area);
+    viewer.height = inner.height as usize;
+    viewer.width = inner.width as usize;
+    viewer.scroll = viewer.scroll.min(viewer.max_scroll());
+
+    let end = (viewer.scroll + viewer.height).min(viewer.lines.len());
+    let lines: Vec<Line> = viewer.lines[viewer.scroll..end].iter().map(|l| Line::from(l.as_str())).collect();
+    let mut paragraph = Paragraph::new(lines).block(block);
+    if viewer.wrap && !viewer.binary {
+        paragraph = paragraph.wrap(Wrap { trim: false });
+    } else {
+        paragraph = paragraph.scroll((0, viewer.hscroll));
+    }
+    f.render_widget(paragraph, area);
+}
@@ -0,0 +1,54 @@
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::mpsc::{Receiver, Sender};
+use std::thread::{self, JoinHandle};
+
+use crate::cli::FileFormat;
+use crate::agent::Agent;
+use crate::db::Db;
+use crate::events::AppEvent;
+use crate::export::{export_agents_csv, export_agents_json};
+use crate::import::{import_agents_csv, import_agents_json, OnConflict};
+use crate::notes::Note;
+use crate::refresh::RefreshQuery;
+
+// Travaux trop longs pour la boucle de l'interface, exécutés dans l'ordre de la file
+pub enum DbRequest {
+    // Génération fournie par l'appelant, renvoyée avec la liste
+    LoadAgents(u64),
+    // Ce qui est affiché au tick, avec sa génération
+    Refresh(u64, RefreshQuery),
+    Export { agents: Vec<Agent>, notes: HashMap<String, Vec<Note>>, format: FileFormat, path: PathBuf },
+    Import { format: FileFormat, path: PathBuf, on_conflict: OnConflict },
+}
+
+// Connexion propre au worker ; il s'arrête quand l'interface abandonne la file, après
+// la requête en cours
+pub fn spawn_db_worker(db: Db, requests: Receiver<DbRequest>, tx: Sender<AppEvent>) -> JoinHandle<()> {
+    thread::spawn(move || {
+        for request in requests {
+            let event = match request {
+                DbRequest::LoadAgents(generation) => AppEvent::AgentsLoaded(generation, db.load_agents()),
+                DbRequest::Refresh(generation, query) => AppEvent::Refreshed(generation, Box::new(query.load(&db))),
+                DbRequest::Export { agents, notes, format, path } => {
+                    let agents: Vec<&Agent> = agents.iter().collect();
+                    let result = match format {
+                        FileFormat::Csv => export_agents_csv(&agents, &notes, &path),
+                        FileFormat::Json => export_agents_json(&agents, &notes, &path),
+                    };
+                    AppEvent::ExportFinished { format, path, result }
+                }
+                DbRequest::Import { format, path, on_conflict } => {
+                    let result = match format {
+                        FileFormat::Csv => import_agents_csv(&db, &path, on_conflict),
+                        FileFormat::Json => import_agents_json(&db, &path, on_conflict),
+                    };
+                    AppEvent::ImportFinished { path, result }
+                }
+            };
+            if tx.send(event).is_err() {
+                return;
+            }
+        }
+    })
+}
@@ -0,0 +1,676 @@
+use std::path::PathBuf;
+use std::sync::mpsc;
+use std::time::Duration;
+
+use chrono::{TimeDelta, Utc};
+use crossterm::event::{KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use ratatui::backend::TestBackend;
+use ratatui::Terminal;
+
+use scylla::agent::Agent;
+use scylla::app::{App, Effect};
+use scylla::beacon::next_check_in;
+use scylla::config::ScyllaConfig;
+use scylla::db::{complete_task, Db};
+use scylla::eventlog::events_for_agent;
+use scylla::events::AppEvent;
+use scylla::groups::{add_agent_to_group, create_group};
+use scylla::keymap::Action;
+use scylla::netinfo::format_mac;
+use scylla::notes::add_note;
+use scylla::processes::parse_process_list;
+use scylla::scheduler::run_auto_screenshots;
+use scylla::selfdestruct::{settle_self_destructs, SelfDestructOutcome};
+use scylla::status::{display_status, StalenessThresholds};
+use scylla::sysinfo::record_sysinfo;
+use scylla::tags::add_tag;
+use scylla::topology::record_probe_results;
+use scylla::ui::draw;
+use scylla::worker::spawn_db_worker;
+
+// Base jetable, supprimée avec ses fichiers WAL en fin de test
+struct TempDb(PathBuf);
+
+impl Drop for TempDb {
+    fn drop(&mut self) {
+        for suffix in ["", "-wal", "-shm"] {
+            let _ = std::fs::remove_file(format!("{}{}", self.0.display(), suffix));
+        }
+    }
+}
+
+fn agent(id: &str, hostname: &str) -> Agent {
+    Agent {
+        id: id.to_string(),
+        hostname: hostname.to_string(),
+        ip: "10.0.0.1".to_string(),
+        os: Some("Linux".to_string()),
+        status: "online".to_string(),
+        last_seen: None,
+        location: None,
+        beacon_interval_secs: 60,
+        jitter_pct: 0,
+        screenshot_interval_secs: None,
+        username: None,
+        is_elevated: false,
+        uptime_secs: None,
+        archived: false,
+    }
+}
+
+fn app_with(name: &str, agents: &[Agent]) -> (App, TempDb) {
+    app_seeded(name, agents, ScyllaConfig::default(), |_| {})
+}
+
+// Base remplie par seed avant l'ouverture de l'application
+fn app_seeded(name: &str, agents: &[Agent], config: ScyllaConfig, seed: impl FnOnce(&Db)) -> (App, TempDb) {
+    let path = std::env::temp_dir().join(format!("scylla-{}-{}.db", name, std::process::id()));
+    let temp = TempDb(path);
+    let db = Db::create(&temp.0.display().to_string()).expect("création de la base");
+    for a in agents {
+        db.save_agent(a).expect("enregistrement de l'agent");
+    }
+    seed(&db);
+    let thresholds = StalenessThresholds::from_config(&config).expect("seuils par défaut");
+    let (events, _) = mpsc::channel();
+    let (db_requests, _) = mpsc::channel();
+    let app = App::new(db, "alice".to_string(), config, thresholds, events, db_requests).expect("état initial");
+    (app, temp)
+}
+
+fn three_agents() -> Vec<Agent> {
+    vec![agent("a1", "alpha"), agent("a2", "bravo"), agent("a3", "charlie")]
+}
+
+fn selected_id(app: &App) -> Option<String> {
+    app.selected_agent().map(|a| a.id.clone())
+}
+
+// Touches tapées une à une, puis Entrée
+fn type_keys(app: &mut App, text: &str) {
+    for c in text.chars() {
+        app.handle_key(KeyEvent::new(KeyCode::Char(c), KeyModifiers::NONE));
+    }
+    app.handle_key(KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE));
+}
+
+#[test]
+fn navigation_moves_the_selection() {
+    let (mut app, _db) = app_with("navigation", &three_agents());
+    assert_eq!(selected_id(&app).as_deref(), Some("a1"));
+    app.handle_action(Action::Down);
+    assert_eq!(selected_id(&app).as_deref(), Some("a2"));
+    app.handle_action(Action::Last);
+    assert_eq!(selected_id(&app).as_deref(), Some("a3"));
+    app.handle_action(Action::Down);
+    assert_eq!(selected_id(&app).as_deref(), Some("a3"));
+    app.handle_action(Action::Up);
+    app.handle_action(Action::First);
+    assert_eq!(selected_id(&app).as_deref(), Some("a1"));
+}
+
+#[test]
+fn keys_go_through_the_keymap() {
+    let (mut app, _db) = app_with("keys", &three_agents());
+    let effects = app.handle_key(KeyEvent::new(KeyCode::Char('j'), KeyModifiers::NONE));
+    assert!(effects.is_empty());
+    assert_eq!(selected_id(&app).as_deref(), Some("a2"));
+}
+
+// Certains terminaux envoient aussi Release et Repeat : seul Press compte
+#[test]
+fn only_key_presses_are_handled() {
+    let (mut app, _db) = app_with("presses", &three_agents());
+    for kind in [KeyEventKind::Release, KeyEventKind::Repeat] {
+        let effects = app.handle_key(KeyEvent::new_with_kind(KeyCode::Char('q'), KeyModifiers::NONE, kind));
+        assert!(effects.is_empty());
+        app.handle_key(KeyEvent::new_with_kind(KeyCode::Down, KeyModifiers::NONE, kind));
+    }
+    assert_eq!(selected_id(&app).as_deref(), Some("a1"));
+    app.handle_key(KeyEvent::new(KeyCode::Down, KeyModifiers::NONE));
+    assert_eq!(selected_id(&app).as_deref(), Some("a2"));
+}
+
+#[test]
+fn selection_survives_a_reload() {
+    let (mut app, _db) = app_with("reload", &three_agents());
+    app.handle_action(Action::Down);
+    app.handle_action(Action::Reload);
+    assert_eq!(selected_id(&app).as_deref(), Some("a2"));
+}
+
+#[test]
+fn empty_list_has_no_selection() {
+    let (mut app, _db) = app_with("empty", &[]);
+    app.handle_action(Action::Down);
+    assert!(app.selected_agent().is_none());
+}
+
+#[test]
+fn quit_is_returned_as_an_effect() {
+    let (mut app, _db) = app_with("quit", &three_agents());
+    assert_eq!(app.handle_action(Action::Quit), vec![Effect::Quit]);
+    let effects = app.handle_key(KeyEvent::new(KeyCode::Char('q'), KeyModifiers::NONE));
+    assert_eq!(effects, vec![Effect::Quit]);
+}
+
+#[test]
+fn quit_waits_for_a_running_export() {
+    let path = std::env::temp_dir().join(format!("scylla-confirm-quit-{}.db", std::process::id()));
+    let temp = TempDb(path);
+    let db = Db::create(&temp.0.display().to_string()).expect("création de la base");
+    let config = ScyllaConfig::default();
+    let thresholds = StalenessThresholds::from_config(&config).expect("seuils par défaut");
+    let (events, _) = mpsc::channel();
+    // File du worker gardée ouverte : l'export reste en cours
+    let (db_requests, _queue) = mpsc::channel();
+    let mut app = App::new(db, "alice".to_string(), config, thresholds, events, db_requests).expect("état initial");
+    type_keys(&mut app, ":export csv /tmp/agents.csv");
+
+    let ctrl_c = KeyEvent::new(KeyCode::Char('c'), KeyModifiers::CONTROL);
+    assert!(app.handle_key(KeyEvent::new(KeyCode::Char('q'), KeyModifiers::NONE)).is_empty());
+    let mut terminal = Terminal::new(TestBackend::new(160, 45)).expect("terminal de test");
+    terminal.draw(|f| draw(f, &mut app)).expect("rendu");
+    let screen: String = terminal.backend().buffer().content().iter().map(|c| c.symbol()).collect();
+    assert!(screen.contains("Quit Scylla? [y/N]"));
+    assert!(screen.contains("1 export(s)/import(s) en cours"));
+    // Toute autre touche annule, y confirme, un second Ctrl-C sort aussitôt
+    assert!(app.handle_key(KeyEvent::new(KeyCode::Char('n'), KeyModifiers::NONE)).is_empty());
+    assert!(app.handle_key(ctrl_c).is_empty());
+    assert_eq!(app.handle_key(ctrl_c), vec![Effect::Quit]);
+    assert!(app.handle_key(KeyEvent::new(KeyCode::Char('q'), KeyModifiers::NONE)).is_empty());
+    assert_eq!(app.handle_key(KeyEvent::new(KeyCode::Char('y'), KeyModifiers::NONE)), vec![Effect::Quit]);
+}
+
+// Relecture du tick par le vrai worker, sur sa propre connexion
+#[test]
+fn the_tick_is_read_by_the_worker() {
+    let path = std::env::temp_dir().join(format!("scylla-tick-worker-{}.db", std::process::id()));
+    let temp = TempDb(path);
+    let path = temp.0.display().to_string();
+    let db = Db::create(&path).expect("création de la base");
+    for a in three_agents() {
+        db.save_agent(&a).expect("enregistrement de l'agent");
+    }
+    db.enqueue_task("a1", "whoami").expect("tâche de a1");
+    let config = ScyllaConfig::default();
+    let thresholds = StalenessThresholds::from_config(&config).expect("seuils par défaut");
+    let (events, rx) = mpsc::channel();
+    let (db_requests, queue) = mpsc::channel();
+    spawn_db_worker(Db::open(&path).expect("connexion du worker"), queue, events.clone());
+    let mut app = App::new(db, "alice".to_string(), config, thresholds, events, db_requests).expect("état initial");
+    let draw_screen = |app: &mut App| {
+        let mut terminal = Terminal::new(TestBackend::new(160, 45)).expect("terminal de test");
+        terminal.draw(|f| draw(f, app)).expect("rendu");
+        terminal.backend().buffer().content().iter().map(|c| c.symbol()).collect::<String>()
+    };
+    let refreshed = |rx: &mpsc::Receiver<AppEvent>| loop {
+        match rx.recv_timeout(Duration::from_secs(5)).expect("réponse du worker") {
+            event @ AppEvent::Refreshed(..) => return event,
+            _ => continue,
+        }
+    };
+
+    // Sélection changée pendant la lecture : les tâches de a1 ne sont pas montrées sous a2
+    app.on_tick();
+    app.handle_action(Action::Down);
+    app.handle_event(refreshed(&rx));
+    assert!(!draw_screen(&mut app).contains("#1 [pending]"));
+
+    // Une seule relecture à la fois : le second tick n'envoie rien
+    app.handle_action(Action::Up);
+    app.on_tick();
+    app.on_tick();
+    app.handle_event(refreshed(&rx));
+    let screen = draw_screen(&mut app);
+    assert!(screen.contains("#1 [pending]"));
+    assert!(screen.contains("1 tâche(s) en attente"));
+    assert!(rx.recv_timeout(Duration::from_millis(200)).is_err());
+}
+
+#[test]
+fn copy_goes_through_an_effect() {
+    let (mut app, _db) = app_with("copy", &three_agents());
+    let effects = app.handle_key(KeyEvent::new(KeyCode::Char('y'), KeyModifiers::NONE));
+    assert_eq!(effects, vec![Effect::Copy { label: "IP de a1".to_string(), text: "10.0.0.1".to_string() }]);
+    // Y ouvre le choix : ID, Hostname, IP, ligne TSV
+    assert!(app.handle_key(KeyEvent::new(KeyCode::Char('Y'), KeyModifiers::NONE)).is_empty());
+    app.handle_key(KeyEvent::new(KeyCode::Char('j'), KeyModifiers::NONE));
+    let effects = app.handle_key(KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE));
+    assert_eq!(effects, vec![Effect::Copy { label: "Hostname de a1".to_string(), text: "alpha".to_string() }]);
+    assert_eq!(selected_id(&app).as_deref(), Some("a1"));
+}
+
+#[test]
+fn detail_screen_keeps_the_selection() {
+    let (mut app, _db) = app_with("detail", &three_agents());
+    app.handle_action(Action::Down);
+    app.handle_key(KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE));
+    let mut terminal = Terminal::new(TestBackend::new(100, 30)).expect("terminal de test");
+    terminal.draw(|f| draw(f, &mut app)).expect("rendu");
+    let screen: String = terminal.backend().buffer().content().iter().map(|c| c.symbol()).collect();
+    assert!(screen.contains("Agent a2"));
+    // j fait défiler la fiche, pas la liste
+    app.handle_key(KeyEvent::new(KeyCode::Char('j'), KeyModifiers::NONE));
+    app.handle_key(KeyEvent::new(KeyCode::Esc, KeyModifiers::NONE));
+    assert_eq!(selected_id(&app).as_deref(), Some("a2"));
+}
+
+// L'identifiant ne se modifie pas : tâches, tags et clés de l'agent y restent rattachés
+#[test]
+fn edit_walks_the_fields_but_not_the_id() {
+    let (mut app, db) = app_with("edit", &three_agents());
+    app.handle_key(KeyEvent::new(KeyCode::Char('e'), KeyModifiers::NONE));
+    let mut terminal = Terminal::new(TestBackend::new(160, 45)).expect("terminal de test");
+    terminal.draw(|f| draw(f, &mut app)).expect("rendu");
+    let screen: String = terminal.backend().buffer().content().iter().map(|c| c.symbol()).collect();
+    assert!(screen.contains("Agent a1 (1/6)") && screen.contains("Hostname: alpha"));
+    for _ in "alpha".chars() {
+        app.handle_key(KeyEvent::new(KeyCode::Backspace, KeyModifiers::NONE));
+    }
+    type_keys(&mut app, "delta");
+    app.handle_key(KeyEvent::new(KeyCode::Esc, KeyModifiers::NONE));
+    let other = Db::open(&db.0.display().to_string()).expect("seconde connexion");
+    let edited = other.get_agent("a1").expect("lecture").expect("agent a1");
+    assert_eq!(edited.hostname, "delta");
+    assert_eq!(selected_id(&app).as_deref(), Some("a1"));
+}
+
+#[test]
+fn menu_counts_the_filtered_agents() {
+    let (mut app, _db) = app_with("summary", &three_agents());
+    type_keys(&mut app, "/bravo");
+    let mut terminal = Terminal::new(TestBackend::new(160, 45)).expect("terminal de test");
+    terminal.draw(|f| draw(f, &mut app)).expect("rendu");
+    let screen: String = terminal.backend().buffer().content().iter().map(|c| c.symbol()).collect();
+    // Sans check-in, les trois agents online restent online
+    assert!(screen.contains("3 agents : 3 online"));
+    assert!(screen.contains("affichés 1/3"));
+}
+
+#[test]
+fn pivots_are_drawn_under_their_relay() {
+    let (mut app, _db) = app_with("pivots", &three_agents());
+    type_keys(&mut app, ":pivot a2 172.16.0.9:3389");
+    app.handle_key(KeyEvent::new(KeyCode::Char('V'), KeyModifiers::NONE));
+    let mut terminal = Terminal::new(TestBackend::new(160, 45)).expect("terminal de test");
+    terminal.draw(|f| draw(f, &mut app)).expect("rendu");
+    let screen: String = terminal.backend().buffer().content().iter().map(|c| c.symbol()).collect();
+    assert!(screen.contains("└─ a2"));
+    assert!(screen.contains("└─ #1 172.16.0.9:3389 [pending]"));
+    // La commande pivot attend dans la file du relais
+    app.handle_key(KeyEvent::new(KeyCode::Esc, KeyModifiers::NONE));
+    app.handle_action(Action::Down);
+    app.handle_action(Action::Tasks);
+    terminal.draw(|f| draw(f, &mut app)).expect("rendu");
+    let screen: String = terminal.backend().buffer().content().iter().map(|c| c.symbol()).collect();
+    assert!(screen.contains("pivot 1 172.16.0.9:3389"));
+}
+
+#[test]
+fn process_list_answers_are_tabulated() {
+    let (mut app, db) = app_with("processes", &three_agents());
+    app.handle_key(KeyEvent::new(KeyCode::Char('p'), KeyModifiers::CONTROL));
+    app.handle_key(KeyEvent::new(KeyCode::Char('r'), KeyModifiers::NONE));
+    // Réponse de l'agent, arrivée par un listener
+    let other = Db::open(&db.0.display().to_string()).expect("seconde connexion");
+    let output = r#"{"entries": [
+        {"pid": 1, "name": "init", "user": "root", "cpu_pct": 0.1, "mem_kb": 1200},
+        {"pid": 4242, "name": "backup", "user": "svc", "cpu_pct": 87.5, "mem_kb": 52000},
+        {"pid": 300, "name": "sshd", "user": "root", "cpu_pct": 1.5, "mem_kb": 8000}
+    ]}"#;
+    assert!(complete_task(other.conn(), 1, output, false).expect("résultat"));
+    app.refresh_visible();
+    let mut terminal = Terminal::new(TestBackend::new(160, 45)).expect("terminal de test");
+    terminal.draw(|f| draw(f, &mut app)).expect("rendu");
+    let screen: String = terminal.backend().buffer().content().iter().map(|c| c.symbol()).collect();
+    assert!(screen.contains("Processus de a1 (3)"));
+    let rows: Vec<usize> = ["4242", "sshd", "init"].iter().map(|t| screen.find(t).expect("ligne affichée")).collect();
+    assert!(rows.windows(2).all(|w| w[0] < w[1]), "tri par CPU décroissant");
+    assert!(parse_process_list("uid=0(root)").is_err());
+}
+
+#[test]
+fn narrow_lists_drop_columns_and_cut_hostnames() {
+    let agents = vec![agent("a1", "alpha"), agent("a2", "build-server-eu-west-01")];
+    let (mut app, _db) = app_with("columns", &agents);
+    let mut terminal = Terminal::new(TestBackend::new(160, 45)).expect("terminal de test");
+    terminal.draw(|f| draw(f, &mut app)).expect("rendu");
+    let screen: String = terminal.backend().buffer().content().iter().map(|c| c.symbol()).collect();
+    assert!(screen.contains("Last seen"));
+    assert!(screen.contains("build-server-eu-west-01"));
+
+    let mut terminal = Terminal::new(TestBackend::new(80, 30)).expect("terminal de test");
+    terminal.draw(|f| draw(f, &mut app)).expect("rendu");
+    let screen: Vec<String> = terminal.backend().buffer().content().chunks(80)
+        .map(|line| line.iter().map(|c| c.symbol()).collect())
+        .collect();
+    let header = screen.iter().find(|line| line.contains("Hostname")).expect("en-tête du tableau");
+    assert!(header.contains("Status"));
+    assert!(!header.contains("Last seen") && !header.contains("OS"), "{}", header);
+    assert!(screen.iter().any(|line| line.contains("build-server-…")));
+}
+
+#[test]
+fn reachability_probes_fill_the_topology() {
+    let (mut app, db) = app_with("topology", &three_agents());
+    type_keys(&mut app, ":scan-reachability");
+    let other = Db::open(&db.0.display().to_string()).expect("seconde connexion");
+    let probes = other.tasks_for_agent("a1").expect("tâches de a1");
+    assert_eq!(probes[0].command, "reach a2=10.0.0.1 a3=10.0.0.1");
+    let output = r#"[{"agent_id": "a2", "latency_ms": 12.5}, {"agent_id": "a3", "latency_ms": null}]"#;
+    assert!(complete_task(other.conn(), probes[0].id, output, false).expect("résultat"));
+    assert_eq!(record_probe_results(other.conn(), probes[0].id).expect("arêtes"), 2);
+
+    app.handle_key(KeyEvent::new(KeyCode::Char('O'), KeyModifiers::NONE));
+    let mut terminal = Terminal::new(TestBackend::new(160, 45)).expect("terminal de test");
+    terminal.draw(|f| draw(f, &mut app)).expect("rendu");
+    let screen: String = terminal.backend().buffer().content().iter().map(|c| c.symbol()).collect();
+    assert!(screen.contains("Topologie (3 agents)"));
+    assert!(screen.contains(" 1 a1 │ ╲  ●  ✗ "), "{}", screen);
+    assert!(screen.contains(" 2 a2 │ ·  ╲  · "));
+}
+
+#[test]
+fn beacon_interval_sets_the_offline_delay() {
+    let (mut app, db) = app_with("beacon", &three_agents());
+    app.handle_key(KeyEvent::new(KeyCode::Char('B'), KeyModifiers::NONE));
+    for _ in 0..2 {
+        app.handle_key(KeyEvent::new(KeyCode::Backspace, KeyModifiers::NONE));
+    }
+    for c in "5m".chars() {
+        app.handle_key(KeyEvent::new(KeyCode::Char(c), KeyModifiers::NONE));
+    }
+    // Une gigue au-delà de 100 % est refusée, le formulaire reste ouvert
+    app.handle_key(KeyEvent::new(KeyCode::Tab, KeyModifiers::NONE));
+    app.handle_key(KeyEvent::new(KeyCode::Backspace, KeyModifiers::NONE));
+    type_keys(&mut app, "150");
+    let other = Db::open(&db.0.display().to_string()).expect("seconde connexion");
+    assert!(other.tasks_for_agent("a1").expect("tâches de a1").is_empty());
+    for _ in 0..3 {
+        app.handle_key(KeyEvent::new(KeyCode::Backspace, KeyModifiers::NONE));
+    }
+    type_keys(&mut app, "10%");
+    assert_eq!(other.tasks_for_agent("a1").expect("tâches de a1")[0].command, r#"set_beacon {"interval":300,"jitter":10}"#);
+    assert_eq!(app.selected_agent().map(|a| (a.beacon_interval_secs, a.jitter_pct)), Some((300, 10)));
+
+    // Muet depuis vingt minutes : trois beacons de 300s ±10 % manqués pour a1, six de 60s
+    // pour a2, aucun pour a3
+    let thresholds = StalenessThresholds::from_config(&ScyllaConfig::default()).expect("seuils par défaut");
+    let now = Utc::now();
+    let seen = (now - TimeDelta::minutes(20)).to_rfc3339();
+    let a1 = Agent { last_seen: Some(seen.clone()), beacon_interval_secs: 300, jitter_pct: 10, ..agent("a1", "alpha") };
+    let a2 = Agent { last_seen: Some(seen.clone()), ..agent("a2", "bravo") };
+    let a3 = Agent { last_seen: Some(seen), beacon_interval_secs: 1500, ..agent("a3", "charlie") };
+    assert_eq!(display_status(&a1, now, &thresholds), "stale");
+    assert_eq!(display_status(&a2, now, &thresholds), "dead");
+    assert_eq!(display_status(&a3, now, &thresholds), "online");
+    assert_eq!(next_check_in(&a3, now).map(|left| left.num_minutes()), Some(5));
+}
+
+// Un agent supprimé entre-temps arrête la boucle : le bilan le dit, en erreur
+#[test]
+fn beacon_reports_the_agents_really_updated() {
+    let (mut app, db) = app_with("beacon-partial", &three_agents());
+    app.handle_action(Action::SelectAll);
+    let other = Db::open(&db.0.display().to_string()).expect("seconde connexion");
+    other.delete_agents(&["a2".to_string()], "bob").expect("suppression de a2");
+    app.handle_key(KeyEvent::new(KeyCode::Char('B'), KeyModifiers::NONE));
+    app.handle_key(KeyEvent::new(KeyCode::Tab, KeyModifiers::NONE));
+    type_keys(&mut app, "");
+    assert_eq!(other.tasks_for_agent("a1").expect("tâches de a1").len(), 1);
+    assert!(other.tasks_for_agent("a3").expect("tâches de a3").is_empty());
+
+    app.on_tick();
+    let mut terminal = Terminal::new(TestBackend::new(160, 45)).expect("terminal de test");
+    terminal.draw(|f| draw(f, &mut app)).expect("rendu");
+    let screen: String = terminal.backend().buffer().content().iter().map(|c| c.symbol()).collect();
+    assert!(screen.contains("pour 1 agent(s) sur 3"), "bilan absent");
+}
+
+#[test]
+fn self_destruct_needs_the_hostname_and_archives_the_agent() {
+    let (mut app, db) = app_with("self-destruct", &three_agents());
+    app.handle_key(KeyEvent::new(KeyCode::Char('X'), KeyModifiers::NONE));
+    app.handle_key(KeyEvent::new(KeyCode::Char('y'), KeyModifiers::NONE));
+    type_keys(&mut app, "a1");
+    let other = Db::open(&db.0.display().to_string()).expect("seconde connexion");
+    assert!(other.tasks_for_agent("a1").expect("tâches de a1").is_empty());
+    for _ in 0..2 {
+        app.handle_key(KeyEvent::new(KeyCode::Backspace, KeyModifiers::NONE));
+    }
+    type_keys(&mut app, "alpha");
+    let tasks = other.tasks_for_agent("a1").expect("tâches de a1");
+    assert_eq!(tasks[0].command, "self_destruct");
+    assert_eq!(other.get_agent("a1").expect("a1").map(|a| a.status).as_deref(), Some("terminating"));
+    let events = events_for_agent(other.conn(), "a1", 10).expect("événements de a1");
+    assert!(events.iter().any(|e| e.message.contains("par alice")));
+
+    // a1 répond et passe dead sans attendre l'échéance, a3 se tait jusqu'à elle
+    app.handle_key(KeyEvent::new(KeyCode::Char('G'), KeyModifiers::NONE));
+    app.handle_key(KeyEvent::new(KeyCode::Char('X'), KeyModifiers::NONE));
+    app.handle_key(KeyEvent::new(KeyCode::Char('y'), KeyModifiers::NONE));
+    type_keys(&mut app, "charlie");
+    assert!(complete_task(other.conn(), tasks[0].id, "removed", false).expect("résultat"));
+    let outcomes = settle_self_destructs(other.conn(), Utc::now(), true).expect("réponse");
+    assert_eq!(outcomes, [SelfDestructOutcome::Confirmed("a1".to_string())]);
+    let later = Utc::now() + TimeDelta::seconds(31);
+    let outcomes = settle_self_destructs(other.conn(), later, true).expect("échéance");
+    assert_eq!(outcomes, [SelfDestructOutcome::TimedOut("a3".to_string())]);
+    for id in ["a1", "a3"] {
+        let agent = other.get_agent(id).expect("agent").expect("agent gardé en base");
+        assert_eq!((agent.status.as_str(), agent.archived), ("dead", true));
+    }
+
+    // Archivés : hors de la liste jusqu'à :show-archived
+    type_keys(&mut app, ":reload");
+    let mut terminal = Terminal::new(TestBackend::new(160, 45)).expect("terminal de test");
+    terminal.draw(|f| draw(f, &mut app)).expect("rendu");
+    let hidden: String = terminal.backend().buffer().content().iter().map(|c| c.symbol()).collect();
+    assert!(hidden.contains("bravo") && !hidden.contains("charlie"));
+    type_keys(&mut app, ":show-archived");
+    terminal.draw(|f| draw(f, &mut app)).expect("rendu");
+    let shown: String = terminal.backend().buffer().content().iter().map(|c| c.symbol()).collect();
+    assert!(shown.contains("charlie") && shown.contains("archived"));
+}
+
+// Étiquette, note et groupe pour a1
+fn seed_labels(db: &Db) {
+    add_tag(db.conn(), "a1", "dc").expect("étiquette");
+    add_note(db.conn(), "a1", "contrôleur de domaine").expect("note");
+    let group = create_group(db.conn(), "serveurs", None).expect("groupe");
+    add_agent_to_group(db.conn(), group, "a1").expect("appartenance");
+}
+
+// X, y puis le hostname sur l'agent sélectionné
+fn confirm_self_destruct(app: &mut App, hostname: &str) {
+    app.handle_key(KeyEvent::new(KeyCode::Char('X'), KeyModifiers::NONE));
+    app.handle_key(KeyEvent::new(KeyCode::Char('y'), KeyModifiers::NONE));
+    type_keys(app, hostname);
+}
+
+#[test]
+fn an_archived_agent_keeps_its_labels_out_of_the_filters() {
+    let (mut app, db) = app_seeded("archive-labels", &three_agents(), ScyllaConfig::default(), seed_labels);
+    assert_eq!(app.tags_of("a1"), ["dc"]);
+    confirm_self_destruct(&mut app, "alpha");
+    let other = Db::open(&db.0.display().to_string()).expect("seconde connexion");
+    let task = other.tasks_for_agent("a1").expect("tâches de a1").remove(0);
+    assert!(complete_task(other.conn(), task.id, "removed", false).expect("résultat"));
+
+    // Le tick règle le self-destruct : a1 archivé, masqué, sans étiquettes ni groupes en mémoire
+    app.on_tick();
+    let a1 = other.get_agent("a1").expect("lecture").expect("a1 gardé en base");
+    assert_eq!((a1.status.as_str(), a1.archived), ("dead", true));
+    assert!(app.tags_of("a1").is_empty() && app.notes_of("a1").is_empty() && app.groups_of("a1").is_empty());
+    assert_ne!(selected_id(&app).as_deref(), Some("a1"));
+
+    // Gardés en base : :show-archived les rend, le masquer les retire de nouveau
+    type_keys(&mut app, ":show-archived");
+    assert_eq!(app.tags_of("a1"), ["dc"]);
+    assert_eq!(app.groups_of("a1"), ["serveurs"]);
+    assert_eq!(app.notes_of("a1").len(), 1);
+    type_keys(&mut app, ":show-archived");
+    assert!(app.tags_of("a1").is_empty());
+}
+
+#[test]
+fn a_silent_agent_turns_dead_after_the_grace_delay() {
+    let config = ScyllaConfig { archive_destroyed_agents: false, ..ScyllaConfig::default() };
+    let (mut app, db) = app_seeded("grace", &three_agents(), config, seed_labels);
+    confirm_self_destruct(&mut app, "alpha");
+    app.handle_action(Action::Last);
+    confirm_self_destruct(&mut app, "charlie");
+    let other = Db::open(&db.0.display().to_string()).expect("seconde connexion");
+    let task = other.tasks_for_agent("a1").expect("tâches de a1").remove(0);
+    assert!(complete_task(other.conn(), task.id, "removed", false).expect("résultat"));
+
+    // Sans archivage, a1 qui a répondu est supprimé avec ses étiquettes ; a3 attend l'échéance
+    app.on_tick();
+    assert_eq!(other.get_agent("a1").expect("lecture"), None);
+    assert!(app.tags_of("a1").is_empty() && app.groups_of("a1").is_empty());
+    assert_eq!(other.get_agent("a3").expect("lecture").map(|a| a.status).as_deref(), Some("terminating"));
+
+    other.conn()
+        .execute("UPDATE self_destructs SET deadline = '2000-01-01T00:00:00Z' WHERE agent_id = 'a3'", [])
+        .expect("échéance passée");
+    app.on_tick();
+    let a3 = other.get_agent("a3").expect("lecture").expect("a3 gardé en base");
+    assert_eq!((a3.status.as_str(), a3.archived), ("dead", false));
+    let mut terminal = Terminal::new(TestBackend::new(160, 45)).expect("terminal de test");
+    terminal.draw(|f| draw(f, &mut app)).expect("rendu");
+    let screen: String = terminal.backend().buffer().content().iter().map(|c| c.symbol()).collect();
+    assert!(screen.contains("charlie") && !screen.contains("alpha"));
+    let events = events_for_agent(other.conn(), "a3", 10).expect("événements de a3");
+    assert!(events.iter().any(|e| e.message == "Self-destruct sans réponse, agent dead"));
+}
+
+#[test]
+fn clipboard_is_read_and_written_through_tasks() {
+    let (mut app, db) = app_with("clipboard", &three_agents());
+    assert!(app.handle_key(KeyEvent::new(KeyCode::Char('v'), KeyModifiers::CONTROL)).is_empty());
+    let other = Db::open(&db.0.display().to_string()).expect("seconde connexion");
+    let task = other.tasks_for_agent("a1").expect("tâches de a1").remove(0);
+    assert_eq!(task.command, "clipboard_read");
+    // Rien à copier tant que l'agent n'a pas répondu
+    assert!(app.handle_key(KeyEvent::new(KeyCode::Char('y'), KeyModifiers::NONE)).is_empty());
+    assert!(complete_task(other.conn(), task.id, "mot de passe", false).expect("résultat"));
+    app.refresh_visible();
+    let mut terminal = Terminal::new(TestBackend::new(160, 45)).expect("terminal de test");
+    terminal.draw(|f| draw(f, &mut app)).expect("rendu");
+    let screen: String = terminal.backend().buffer().content().iter().map(|c| c.symbol()).collect();
+    assert!(screen.contains("mot de passe"));
+    let effects = app.handle_key(KeyEvent::new(KeyCode::Char('y'), KeyModifiers::NONE));
+    assert!(matches!(effects.as_slice(), [Effect::Copy { text, .. }] if text == "mot de passe"));
+    app.handle_key(KeyEvent::new(KeyCode::Esc, KeyModifiers::NONE));
+
+    type_keys(&mut app, ":clipboard-write bonjour");
+    assert_eq!(other.tasks_for_agent("a1").expect("tâches de a1")[1].command, "clipboard_write bonjour");
+}
+
+#[test]
+fn shell_lines_become_tasks_and_keep_their_answers() {
+    let (mut app, db) = app_with("shell", &three_agents());
+    app.handle_key(KeyEvent::new(KeyCode::Char('!'), KeyModifiers::NONE));
+    type_keys(&mut app, "whoami");
+    let other = Db::open(&db.0.display().to_string()).expect("seconde connexion");
+    let task = other.tasks_for_agent("a1").expect("tâches de a1").remove(0);
+    assert_eq!(task.command, "whoami");
+    // Échap garde la ligne commencée, le shell la rend au retour
+    for c in "hostn".chars() {
+        app.handle_key(KeyEvent::new(KeyCode::Char(c), KeyModifiers::NONE));
+    }
+    app.handle_key(KeyEvent::new(KeyCode::Esc, KeyModifiers::NONE));
+    assert!(complete_task(other.conn(), task.id, "root\n", false).expect("résultat"));
+    app.refresh_visible();
+    app.handle_key(KeyEvent::new(KeyCode::Char('!'), KeyModifiers::NONE));
+    type_keys(&mut app, "ame");
+    assert_eq!(other.tasks_for_agent("a1").expect("tâches de a1")[1].command, "hostname");
+
+    let mut terminal = Terminal::new(TestBackend::new(160, 45)).expect("terminal de test");
+    terminal.draw(|f| draw(f, &mut app)).expect("rendu");
+    let screen: String = terminal.backend().buffer().content().iter().map(|c| c.symbol()).collect();
+    for expected in ["alpha$ whoami", "root", "alpha$ hostname", "1 en attente"] {
+        assert!(screen.contains(expected), "{} absent de l'écran", expected);
+    }
+}
+
+#[test]
+fn sysinfo_answers_rewrite_the_agent() {
+    let (mut app, db) = app_with("sysinfo", &three_agents());
+    type_keys(&mut app, ":sysinfo");
+    let other = Db::open(&db.0.display().to_string()).expect("seconde connexion");
+    let task = other.tasks_for_agent("a1").expect("tâches de a1").remove(0);
+    assert_eq!(task.command, "sysinfo");
+    let answer = r#"{"os": "Windows 11", "os_version": "22H2", "arch": "x86_64", "hostname": "WS01",
+        "username": "bob", "is_elevated": true, "uptime_secs": 7200}"#;
+    assert!(complete_task(other.conn(), task.id, answer, false).expect("résultat"));
+    assert!(record_sysinfo(other.conn(), task.id).expect("fiche appliquée"));
+    let a1 = other.get_agent("a1").expect("lecture").expect("agent a1");
+    assert_eq!((a1.hostname.as_str(), a1.os.as_deref()), ("WS01", Some("Windows 11 22H2 (x86_64)")));
+    assert_eq!((a1.username.as_deref(), a1.is_elevated, a1.uptime_secs), (Some("bob"), true, Some(7200)));
+
+    type_keys(&mut app, ":reload");
+    let mut terminal = Terminal::new(TestBackend::new(160, 45)).expect("terminal de test");
+    terminal.draw(|f| draw(f, &mut app)).expect("rendu");
+    let screen: String = terminal.backend().buffer().content().iter().map(|c| c.symbol()).collect();
+    for expected in ["[!] WS01", "Windows 11 22H2 (x86_64)", "User: bob [!] elevated, up 2h"] {
+        assert!(screen.contains(expected), "{} absent de l'écran", expected);
+    }
+    // Une tâche ordinaire ne touche pas à l'agent
+    let plain = other.enqueue_task("a2", "whoami").expect("tâche");
+    assert!(complete_task(other.conn(), plain, answer, false).expect("résultat"));
+    assert!(!record_sysinfo(other.conn(), plain).expect("ignorée"));
+}
+
+#[test]
+fn netinfo_answers_fill_the_interfaces_panel() {
+    let (mut app, db) = app_with("netinfo", &three_agents());
+    type_keys(&mut app, ":netinfo");
+    let other = Db::open(&db.0.display().to_string()).expect("seconde connexion");
+    let task = other.tasks_for_agent("a1").expect("tâches de a1").remove(0);
+    assert_eq!(task.command, "netinfo");
+    let answer = r#"[{"name": "eth0", "mac": "AA-BB-CC-DD-EE-0F", "ipv4": ["10.0.0.5/24"], "ipv6": ["fe80::1/64"], "is_up": true},
+        {"name": "eth1", "mac": "a0b1.c2d3.e4f5", "ipv4": ["172.16.8.2/16"], "is_up": false}]"#;
+    assert!(complete_task(other.conn(), task.id, answer, false).expect("résultat"));
+    app.handle_key(KeyEvent::new(KeyCode::Char('W'), KeyModifiers::NONE));
+
+    let mut terminal = Terminal::new(TestBackend::new(160, 45)).expect("terminal de test");
+    terminal.draw(|f| draw(f, &mut app)).expect("rendu");
+    let screen: String = terminal.backend().buffer().content().iter().map(|c| c.symbol()).collect();
+    for expected in ["Interfaces (2)", "aa:bb:cc:dd:ee:0f", "10.0.0.5/24", "a0:b1:c2:d3:e4:f5", "172.16.8.2/16"] {
+        assert!(screen.contains(expected), "{} absent de l'écran", expected);
+    }
+    assert_eq!(format_mac("not-a-mac"), "not-a-mac");
+}
+
+#[test]
+fn agents_are_drawn() {
+    let (mut app, _db) = app_with("draw", &three_agents());
+    let mut terminal = Terminal::new(TestBackend::new(160, 45)).expect("terminal de test");
+    terminal.draw(|f| draw(f, &mut app)).expect("rendu");
+    let screen: String = terminal.backend().buffer().content().iter().map(|c| c.symbol()).collect();
+    for hostname in ["alpha", "bravo", "charlie"] {
+        assert!(screen.contains(hostname), "{} absent de l'écran", hostname);
+    }
+}
+
+#[test]
+fn auto_screenshots_follow_the_interval() {
+    let (mut app, db) = app_with("auto-screenshot", &three_agents());
+    type_keys(&mut app, ":screenshot-interval a2 10m");
+    let conn = Db::open(&db.0.display().to_string()).expect("seconde connexion");
+    let a2 = conn.get_agent("a2").expect("lecture").expect("agent a2");
+    assert_eq!(a2.screenshot_interval_secs, Some(600));
+    let now = Utc::now();
+    let runs = run_auto_screenshots(conn.conn(), now).expect("première échéance");
+    assert_eq!(runs.iter().map(|r| (r.agent_id.as_str(), r.schedule_id)).collect::<Vec<_>>(), [("a2", None)]);
+    // Toujours en attente de l'agent : pas de seconde capture, même l'intervalle passé
+    assert!(run_auto_screenshots(conn.conn(), now + TimeDelta::minutes(11)).expect("en attente").is_empty());
+    complete_task(conn.conn(), runs[0].task_id, "/tmp/capture.png", false).expect("capture reçue");
+    assert!(run_auto_screenshots(conn.conn(), now + TimeDelta::minutes(5)).expect("trop tôt").is_empty());
+    assert_eq!(run_auto_screenshots(conn.conn(), now + TimeDelta::minutes(11)).expect("échéance").len(), 1);
+
+    type_keys(&mut app, ":screenshot-interval a2 off");
+    assert!(run_auto_screenshots(conn.conn(), now + TimeDelta::hours(1)).expect("désactivées").is_empty());
+}
@@ -0,0 +1,52 @@
+use chrono::{DateTime, TimeDelta, Utc};
+
+use scylla::checkins::{bucket_checkins, checkin_history, prune_checkins, record_checkin};
+use scylla::db::Db;
+
+fn at(timestamp: &str) -> DateTime<Utc> {
+    DateTime::parse_from_rfc3339(timestamp).expect("horodatage").with_timezone(&Utc)
+}
+
+#[test]
+fn checkins_fall_in_their_bucket() {
+    let now = at("2025-07-25T12:00:00Z");
+    let times = [
+        // Dernière tranche, bornes comprises côté récent
+        at("2025-07-25T12:00:00Z"),
+        at("2025-07-25T11:30:01Z"),
+        // Avant-dernière, dont la limite exacte des 30 minutes
+        at("2025-07-25T11:30:00Z"),
+        at("2025-07-25T11:10:00Z"),
+        // Première tranche, puis la limite de la fenêtre, hors fenêtre, et dans le futur
+        at("2025-07-25T10:00:01Z"),
+        at("2025-07-25T10:00:00Z"),
+        at("2025-07-25T12:00:01Z"),
+    ];
+    let buckets = bucket_checkins(&times, now, TimeDelta::hours(2), TimeDelta::minutes(30));
+    assert_eq!(buckets, [1, 0, 2, 2]);
+    // Une fenêtre qui n'est pas un multiple : la plus ancienne tranche est tronquée
+    let partial = bucket_checkins(&times, now, TimeDelta::minutes(45), TimeDelta::minutes(30));
+    assert_eq!(partial, [1, 2]);
+    assert!(bucket_checkins(&times, now, TimeDelta::zero(), TimeDelta::minutes(30)).is_empty());
+}
+
+#[test]
+fn old_checkins_are_pruned() {
+    let path = std::env::temp_dir().join(format!("scylla-checkins-{}.db", std::process::id()));
+    let db = Db::create(&path.display().to_string()).expect("création de la base");
+    db.conn()
+        .execute("INSERT INTO agents (id, hostname, ip) VALUES ('a1', 'alpha', '10.0.0.1')", [])
+        .expect("agent");
+    record_checkin(db.conn(), "a1").expect("check-in récent");
+    db.conn()
+        .execute("INSERT INTO checkins (agent_id, at) VALUES ('a1', '2020-01-01T00:00:00Z')", [])
+        .expect("check-in ancien");
+    assert_eq!(prune_checkins(db.conn(), TimeDelta::days(7)).expect("purge"), 1);
+    let history = checkin_history(db.conn(), "a1", Utc::now(), TimeDelta::hours(1), TimeDelta::minutes(30))
+        .expect("historique");
+    assert_eq!(history, [0, 1]);
+    drop(db);
+    for suffix in ["", "-wal", "-shm"] {
+        let _ = std::fs::remove_file(format!("{}{}", path.display(), suffix));
+    }
+}
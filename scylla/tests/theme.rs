@@ -0,0 +1,69 @@
+use std::path::Path;
+
+use ratatui::style::Color;
+
+use scylla::config::ScyllaConfig;
+use scylla::theme::ColorScheme;
+
+// Fichiers .rs de src/, en dehors de theme.rs
+fn sources(dir: &Path, found: &mut Vec<std::path::PathBuf>) {
+    for entry in std::fs::read_dir(dir).expect("lecture de src") {
+        let path = entry.expect("entrée").path();
+        if path.is_dir() {
+            sources(&path, found);
+        } else if path.extension().is_some_and(|e| e == "rs") && !path.ends_with("theme.rs") {
+            found.push(path);
+        }
+    }
+}
+
+// Une couleur écrite en dur échappe aux schémas et aux retouches de [ui.colors]
+#[test]
+fn colors_only_come_from_the_scheme() {
+    let mut files = Vec::new();
+    sources(&Path::new(env!("CARGO_MANIFEST_DIR")).join("src"), &mut files);
+    let mut offenders = Vec::new();
+    for path in files {
+        let text = std::fs::read_to_string(&path).expect("lecture du fichier");
+        for (n, line) in text.lines().enumerate() {
+            let imported = line.starts_with("use ratatui") && line.contains("Color");
+            if imported || line.contains("Color::") {
+                offenders.push(format!("{}:{}: {}", path.display(), n + 1, line.trim()));
+            }
+        }
+    }
+    assert!(offenders.is_empty(), "couleurs hors de theme.rs :\n{}", offenders.join("\n"));
+}
+
+#[test]
+fn overrides_apply_to_every_preset() {
+    let config: ScyllaConfig = toml::from_str(
+        r##"
+        [ui]
+        color_scheme = "light"
+        [ui.colors]
+        selection_bg = "#1e1e2e"
+        error = "magenta"
+        "##,
+    )
+    .expect("configuration");
+    let scheme = config.ui.scheme();
+    assert_eq!(scheme.name, "light");
+    assert_eq!(scheme.selection_bg, Color::Rgb(0x1e, 0x1e, 0x2e));
+    assert_eq!(scheme.error, Color::Magenta);
+    assert_eq!(scheme.border, ColorScheme::from_name("light").expect("light").border);
+
+    // F2 passe au schéma suivant en gardant les retouches
+    let next = config.ui.colors.apply(ColorScheme::from_name(scheme.next_name()).expect("suivant"));
+    assert_eq!(next.name, "nord");
+    assert_eq!(next.error, Color::Magenta);
+    assert_eq!(ColorScheme::from_name("solarized").expect("solarized").next_name(), "dark");
+
+    let dumped = toml::to_string(&config).expect("écriture");
+    assert!(dumped.contains("color_scheme = \"light\""), "{}", dumped);
+    let reread: ScyllaConfig = toml::from_str(&dumped).expect("relecture");
+    assert_eq!(reread.ui.scheme(), scheme);
+
+    let bad = toml::from_str::<ScyllaConfig>("[ui.colors]\nborder = \"mauve-ish\"\n");
+    assert!(bad.expect_err("couleur inconnue").to_string().contains("mauve-ish"));
+}
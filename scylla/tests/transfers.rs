@@ -0,0 +1,44 @@
+use std::fs;
+
+use scylla::db::{complete_task, Db};
+use scylla::transfers::{ack_upload_chunk, check_upload, enqueue_upload, resume_upload, upload_chunk, upload_manifest};
+
+#[test]
+fn an_interrupted_upload_resumes_after_the_last_ack() {
+    let dir = std::env::temp_dir().join(format!("scylla-upload-{}", std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).expect("répertoire de test");
+    let db = Db::create(&dir.join("c2.db").display().to_string()).expect("création de la base");
+    db.conn()
+        .execute("INSERT INTO agents (id, hostname, ip) VALUES ('a1', 'alpha', '10.0.0.1')", [])
+        .expect("agent");
+    let local = dir.join("tool.bin");
+    fs::write(&local, b"0123456789").expect("fichier local");
+    let local = local.display().to_string();
+
+    // Refusé avant la mise en file
+    assert!(check_upload(&dir.join("absent").display().to_string(), 1024).is_err());
+    assert!(enqueue_upload(db.conn(), "a1", &local, "/tmp/tool", 4, 9).is_err());
+    assert!(db.tasks_for_agent("a1").expect("tâches").is_empty());
+
+    let task = enqueue_upload(db.conn(), "a1", &local, "/tmp/tool", 4, 1024).expect("mise en file");
+    let manifest = upload_manifest(db.conn(), task).expect("lecture").expect("manifeste");
+    assert_eq!((manifest.size, manifest.chunks, manifest.next_seq), (10, 3, 0));
+    assert_eq!(upload_chunk(db.conn(), task, 2).expect("lecture").as_deref(), Some(&b"89"[..]));
+    assert!(!ack_upload_chunk(db.conn(), task, 0).expect("tranche 0"));
+    assert!(ack_upload_chunk(db.conn(), task, 2).is_err());
+
+    // L'implant échoue : la tâche remise en file reprend à la tranche 1
+    assert!(resume_upload(db.conn(), task).is_err());
+    complete_task(db.conn(), task, "connexion perdue", true).expect("échec");
+    resume_upload(db.conn(), task).expect("reprise");
+    assert_eq!(db.tasks_for_agent("a1").expect("tâches")[0].status, "pending");
+    assert_eq!(upload_manifest(db.conn(), task).expect("lecture").expect("manifeste").next_seq, 1);
+    assert!(!ack_upload_chunk(db.conn(), task, 0).expect("doublon ignoré"));
+    assert!(!ack_upload_chunk(db.conn(), task, 1).expect("tranche 1"));
+    assert!(ack_upload_chunk(db.conn(), task, 2).expect("tranche 2"));
+
+    let done = &db.tasks_for_agent("a1").expect("tâches")[0];
+    assert_eq!((done.status.as_str(), done.output.as_deref()), ("done", Some("10 octets envoyés vers /tmp/tool")));
+    assert!(upload_chunk(db.conn(), task, 0).expect("lecture").is_none());
+}
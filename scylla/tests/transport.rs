@@ -0,0 +1,97 @@
+use ring::aead::{Aad, LessSafeKey, Nonce, UnboundKey, CHACHA20_POLY1305, NONCE_LEN};
+use ring::agreement::{agree_ephemeral, EphemeralPrivateKey, UnparsedPublicKey, X25519};
+use ring::hkdf::{Salt, HKDF_SHA256};
+use ring::rand::SystemRandom;
+use tokio::io::{AsyncReadExt, AsyncWriteExt, DuplexStream};
+
+use scylla::transport::{server_handshake, FrameBuffer, MAX_FRAME_LEN};
+
+// Côté implant du protocole, écrit d'après la description de transport.rs
+struct Implant {
+    key: LessSafeKey,
+    sent: u64,
+    received: u64,
+}
+
+fn nonce(direction: u8, counter: u64) -> Nonce {
+    let mut bytes = [0u8; NONCE_LEN];
+    bytes[0] = direction;
+    bytes[4..].copy_from_slice(&counter.to_be_bytes());
+    Nonce::assume_unique_for_key(bytes)
+}
+
+impl Implant {
+    async fn connect(stream: &mut DuplexStream) -> (Implant, Vec<u8>) {
+        let mut server_public = [0u8; 32];
+        stream.read_exact(&mut server_public).await.expect("clé du serveur");
+        let private = EphemeralPrivateKey::generate(&X25519, &SystemRandom::new()).expect("clé de l'implant");
+        let public = private.compute_public_key().expect("clé publique");
+        stream.write_all(public.as_ref()).await.expect("envoi de la clé");
+        let raw = agree_ephemeral(private, &UnparsedPublicKey::new(&X25519, server_public), |shared| {
+            let salt = [&server_public[..], public.as_ref()].concat();
+            let mut raw = [0u8; 32];
+            Salt::new(HKDF_SHA256, &salt)
+                .extract(shared)
+                .expand(&[b"scylla tcp session"], &CHACHA20_POLY1305)
+                .and_then(|okm| okm.fill(&mut raw))
+                .expect("dérivation");
+            raw
+        })
+        .expect("accord");
+        let key = LessSafeKey::new(UnboundKey::new(&CHACHA20_POLY1305, &raw).expect("clé"));
+        (Implant { key, sent: 0, received: 0 }, raw.to_vec())
+    }
+
+    fn seal(&mut self, plaintext: &[u8]) -> Vec<u8> {
+        let mut body = plaintext.to_vec();
+        self.key.seal_in_place_append_tag(nonce(1, self.sent), Aad::empty(), &mut body).expect("chiffrement");
+        self.sent += 1;
+        [(body.len() as u32).to_be_bytes().to_vec(), body].concat()
+    }
+
+    fn open(&mut self, frame: &[u8]) -> Vec<u8> {
+        let mut body = frame[4..].to_vec();
+        let plaintext = self.key.open_in_place(nonce(0, self.received), Aad::empty(), &mut body).expect("déchiffrement");
+        self.received += 1;
+        plaintext.to_vec()
+    }
+}
+
+#[tokio::test]
+async fn both_sides_derive_the_same_session() {
+    let (mut server, mut agent) = tokio::io::duplex(4096);
+    let (handshake, (mut implant, implant_key)) = tokio::join!(
+        async { server_handshake(&mut server).await.expect("échange côté serveur") },
+        Implant::connect(&mut agent),
+    );
+    let mut cipher = handshake.cipher;
+    assert_eq!(cipher.key_bytes(), implant_key.as_slice());
+
+    // Deux trames de l'implant arrivées d'un bloc, la seconde coupée en deux lectures
+    let first = implant.seal(br#"{"id":"a1","hostname":"alpha"}"#);
+    let second = implant.seal(b"uid=0(root)\n");
+    let mut frames = FrameBuffer::default();
+    frames.push(&first);
+    frames.push(&second[..5]);
+    let frame = frames.next_frame(MAX_FRAME_LEN).expect("trame").expect("première trame complète");
+    assert_eq!(cipher.open(frame).expect("registration"), br#"{"id":"a1","hostname":"alpha"}"#);
+    assert!(frames.next_frame(MAX_FRAME_LEN).expect("trame").is_none());
+    frames.push(&second[5..]);
+    let frame = frames.next_frame(MAX_FRAME_LEN).expect("trame").expect("seconde trame complète");
+    assert_eq!(cipher.open(frame).expect("sortie"), b"uid=0(root)\n");
+
+    let outgoing = cipher.seal(b"id\n").expect("commande");
+    assert_eq!(implant.open(&outgoing), b"id\n");
+
+    // Rejouée, une trame ne s'ouvre plus
+    frames.push(&second);
+    let frame = frames.next_frame(MAX_FRAME_LEN).expect("trame").expect("trame rejouée");
+    assert!(cipher.open(frame).is_err());
+}
+
+#[test]
+fn oversized_frames_are_refused() {
+    let mut frames = FrameBuffer::default();
+    frames.push(&(MAX_FRAME_LEN as u32 + 17).to_be_bytes());
+    assert!(frames.next_frame(MAX_FRAME_LEN).is_err());
+}